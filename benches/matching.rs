@@ -0,0 +1,78 @@
+//! Per-message cost of matching a transaction's account keys against the watched accounts, as the
+//! yellowstone handler does for every update. Run with `cargo bench --bench matching`.
+
+use std::{collections::HashSet, hint::black_box, time::Instant};
+
+use geyserbench::{
+    bench_support::{allocation_count, count_allocations, WatchedKeys},
+    CountingAllocator,
+};
+
+#[global_allocator]
+static ALLOCATOR: CountingAllocator = CountingAllocator;
+
+const MESSAGES: usize = 200_000;
+const KEYS_PER_MESSAGE: usize = 24;
+const WATCHED_ACCOUNTS: u64 = 8;
+// One message in this many touches a watched account
+const MATCH_EVERY: usize = 100;
+
+/// Deterministic pseudo-random pubkey (splitmix64 over the seed).
+fn pubkey(seed: u64) -> Vec<u8> {
+    let mut state = seed.wrapping_mul(0x9e37_79b9_7f4a_7c15);
+    (0..4)
+        .flat_map(|_| {
+            state = state.wrapping_add(0x9e37_79b9_7f4a_7c15);
+            let mut z = state;
+            z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+            z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+            (z ^ (z >> 31)).to_le_bytes()
+        })
+        .collect()
+}
+
+fn run(label: &str, messages: &[Vec<Vec<u8>>], matches: impl Fn(&Vec<Vec<u8>>) -> bool) {
+    let allocations = allocation_count();
+    let started = Instant::now();
+    let matched = messages.iter().filter(|keys| matches(black_box(*keys))).count();
+    let elapsed = started.elapsed();
+    let allocations = allocation_count() - allocations;
+    println!(
+        "{:<28} {:>8.1} ns/message {:>8.2} allocations/message ({} matched)",
+        label,
+        elapsed.as_nanos() as f64 / messages.len() as f64,
+        allocations as f64 / messages.len() as f64,
+        matched
+    );
+}
+
+fn main() {
+    count_allocations(true);
+    let watched: Vec<String> = (0..WATCHED_ACCOUNTS)
+        .map(|seed| bs58::encode(pubkey(seed)).into_string())
+        .collect();
+    let messages: Vec<Vec<Vec<u8>>> = (0..MESSAGES)
+        .map(|message| {
+            (0..KEYS_PER_MESSAGE)
+                .map(|index| {
+                    if index == 0 && message % MATCH_EVERY == 0 {
+                        pubkey((message / MATCH_EVERY) as u64 % WATCHED_ACCOUNTS)
+                    } else {
+                        pubkey(WATCHED_ACCOUNTS + (message * KEYS_PER_MESSAGE + index) as u64)
+                    }
+                })
+                .collect()
+        })
+        .collect();
+    println!("{} messages of {} account keys, {} watched accounts", MESSAGES, KEYS_PER_MESSAGE, WATCHED_ACCOUNTS);
+
+    // The previous handler: clone the message, base58-encode every key, look the strings up
+    let encoded: HashSet<String> = watched.iter().cloned().collect();
+    run("clone + encode every key", &messages, |keys| {
+        let accounts: Vec<String> = keys.clone().iter().map(|key| bs58::encode(key).into_string()).collect();
+        accounts.iter().any(|account| encoded.contains(account))
+    });
+
+    let decoded = WatchedKeys::from_base58(&watched);
+    run("raw 32-byte keys", &messages, |keys| !decoded.matching(keys).is_empty());
+}
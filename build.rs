@@ -1,4 +1,4 @@
-use ::{ std::{ env, path::PathBuf }, tonic_build::manual::{ Builder, Method, Service } };
+use ::{ std::{ env, fs, path::PathBuf, process::Command }, tonic_build::manual::{ Builder, Method, Service } };
 
 const PROTOC_ENVAR: &str = "PROTOC";
 #[inline]
@@ -11,7 +11,38 @@ pub fn mpath(path: &str) -> String {
     path.to_string()
 }
 
+// AIDEV-NOTE: Feeds `RunMetadata` (src/metadata.rs); both values are optional so builds from a
+// source tarball without git or a lockfile still succeed
+fn emit_build_metadata() {
+    println!("cargo:rerun-if-changed=.git/HEAD");
+    // New commits move the branch ref, not HEAD
+    let branch = fs::read_to_string(".git/HEAD")
+        .ok()
+        .and_then(|head| head.strip_prefix("ref: ").map(|r| r.trim().to_string()));
+    if let Some(branch) = branch {
+        println!("cargo:rerun-if-changed=.git/{}", branch);
+    }
+    println!("cargo:rerun-if-changed=Cargo.lock");
+    let git_rev = Command::new("git")
+        .args(["rev-parse", "--short", "HEAD"])
+        .output()
+        .ok()
+        .filter(|output| output.status.success())
+        .map(|output| String::from_utf8_lossy(&output.stdout).trim().to_string());
+    if let Some(rev) = git_rev {
+        println!("cargo:rustc-env=GEYSERBENCH_GIT_REV={}", rev);
+    }
+    let proto_version = fs::read_to_string("Cargo.lock").ok().and_then(|lock| {
+        let mut lines = lock.lines().skip_while(|line| *line != "name = \"yellowstone-grpc-proto\"");
+        lines.nth(1)?.strip_prefix("version = ").map(|version| version.trim_matches('"').to_string())
+    });
+    if let Some(version) = proto_version {
+        println!("cargo:rustc-env=GEYSERBENCH_YELLOWSTONE_PROTO_VERSION={}", version);
+    }
+}
+
 fn main() -> anyhow::Result<()> {
+    emit_build_metadata();
     if env::var(PROTOC_ENVAR).is_err() {
         println!("protoc not found in PATH, attempting to fix");
         env::set_var(PROTOC_ENVAR, protoc());
@@ -24,10 +55,12 @@ fn main() -> anyhow::Result<()> {
         mpath("proto/publisher.proto"),
         mpath("proto/shredstream.proto"),
         mpath("proto/jetstream.proto"),
+        mpath("proto/fumarole.proto"),
+        mpath("proto/coordinator.proto"),
     ];
 
     // Compile all proto files
-    tonic_build
+    tonic_prost_build
         ::configure()
         .file_descriptor_set_path(
             PathBuf::from(env::var("OUT_DIR").unwrap()).join("proto_descriptors.bin")
@@ -47,7 +80,7 @@ fn main() -> anyhow::Result<()> {
                         .server_streaming()
                         .input_type("crate::providers::arpc::proto::SubscribeRequest")
                         .output_type("crate::providers::arpc::proto::SubscribeResponse")
-                        .codec_path("tonic::codec::ProstCodec")
+                        .codec_path("tonic_prost::ProstCodec")
                         .build()
                 )
                 .build(),
@@ -67,7 +100,7 @@ fn main() -> anyhow::Result<()> {
                         .server_streaming()
                         .input_type("crate::publisher::Empty")
                         .output_type("crate::publisher::StreamResponse")
-                        .codec_path("tonic::codec::ProstCodec")
+                        .codec_path("tonic_prost::ProstCodec")
                         .build()
                 )
                 .method(
@@ -77,7 +110,7 @@ fn main() -> anyhow::Result<()> {
                         .server_streaming()
                         .input_type("crate::publisher::Empty")
                         .output_type("crate::publisher::StreamResponse")
-                        .codec_path("tonic::codec::ProstCodec")
+                        .codec_path("tonic_prost::ProstCodec")
                         .build()
                 )
                 .method(
@@ -87,7 +120,7 @@ fn main() -> anyhow::Result<()> {
                         .server_streaming()
                         .input_type("crate::publisher::Empty")
                         .output_type("crate::publisher::StreamResponse")
-                        .codec_path("tonic::codec::ProstCodec")
+                        .codec_path("tonic_prost::ProstCodec")
                         .build()
                 )
                 .method(
@@ -97,7 +130,7 @@ fn main() -> anyhow::Result<()> {
                         .server_streaming()
                         .input_type("crate::publisher::SubscribeWalletRequest")
                         .output_type("crate::publisher::StreamResponse")
-                        .codec_path("tonic::codec::ProstCodec")
+                        .codec_path("tonic_prost::ProstCodec")
                         .build()
                 )
                 .build(),
@@ -116,7 +149,7 @@ fn main() -> anyhow::Result<()> {
                         .server_streaming()
                         .input_type("crate::shredstream::SubscribeTransactionsRequest")
                         .output_type("crate::shredstream::SubscribeTransactionsResponse")
-                        .codec_path("tonic::codec::ProstCodec")
+                        .codec_path("tonic_prost::ProstCodec")
                         .build()
                 )
                 .build(),
@@ -135,7 +168,7 @@ fn main() -> anyhow::Result<()> {
                         .server_streaming()
                         .input_type("crate::jetstream::SubscribeRequest")
                         .output_type("crate::jetstream::SubscribeUpdate")
-                        .codec_path("tonic::codec::ProstCodec")
+                        .codec_path("tonic_prost::ProstCodec")
                         .build()
                 )
                 .method(
@@ -146,7 +179,7 @@ fn main() -> anyhow::Result<()> {
                         .server_streaming()
                         .input_type("crate::jetstream::SubscribeParsedRequest")
                         .output_type("crate::jetstream::SubscribeUpdateParsedTransaction")
-                        .codec_path("tonic::codec::ProstCodec")
+                        .codec_path("tonic_prost::ProstCodec")
                         .build()
                 )
                 .method(
@@ -155,7 +188,7 @@ fn main() -> anyhow::Result<()> {
                         .route_name("Ping")
                         .input_type("crate::jetstream::PingRequest")
                         .output_type("crate::jetstream::PongResponse")
-                        .codec_path("tonic::codec::ProstCodec")
+                        .codec_path("tonic_prost::ProstCodec")
                         .build()
                 )
                 .method(
@@ -164,7 +197,7 @@ fn main() -> anyhow::Result<()> {
                         .route_name("GetVersion")
                         .input_type("crate::jetstream::GetVersionRequest")
                         .output_type("crate::jetstream::GetVersionResponse")
-                        .codec_path("tonic::codec::ProstCodec")
+                        .codec_path("tonic_prost::ProstCodec")
                         .build()
                 )
                 .build(),
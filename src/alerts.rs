@@ -0,0 +1,214 @@
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    clock::Clock,
+    config::{AlertConfig, AlertThresholds, ConfigToml, NotifyConfig},
+    notifier,
+    utils::percentile,
+};
+
+/// One threshold an endpoint broke during one alert window.
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+pub struct AlertViolation {
+    pub endpoint: String,
+    /// `p95_ms` or `max_gap_secs`
+    pub metric: String,
+    pub threshold: f64,
+    pub value: f64,
+    pub window_start: f64,
+    pub window_end: f64,
+}
+
+impl AlertViolation {
+    pub fn describe(&self) -> String {
+        match self.metric.as_str() {
+            "max_gap_secs" => format!(
+                "{}: no transaction for {:.1}s (budget {:.1}s)",
+                self.endpoint, self.value, self.threshold
+            ),
+            metric => format!(
+                "{}: {} {:.2} ms behind the first detection (budget {:.2} ms)",
+                self.endpoint, metric, self.value, self.threshold
+            ),
+        }
+    }
+}
+
+#[derive(Debug)]
+struct EndpointWindow {
+    thresholds: AlertThresholds,
+    delays_ms: Vec<f64>,
+    /// Last sighting, or when the endpoint joined the run before its first one
+    last_seen: f64,
+    max_gap_secs: f64,
+}
+
+#[derive(Debug)]
+struct PendingWindow {
+    start: f64,
+    endpoints: HashMap<String, EndpointWindow>,
+}
+
+// AIDEV-NOTE: Fed from `Comparator::insert` like the history store; gaps are measured between an
+// endpoint's matched sightings, so on a quiet account `max_gap_secs` has to allow for its pace.
+// A stretch that is still open when a window closes counts in that window (and again in the next).
+/// Per-window latency budget of each endpoint, checked while the run goes on.
+#[derive(Debug)]
+pub struct AlertMonitor {
+    pending: Mutex<PendingWindow>,
+    violations: Mutex<Vec<AlertViolation>>,
+    min_samples: usize,
+    clock: Arc<dyn Clock>,
+}
+
+impl AlertMonitor {
+    /// Watches the endpoints that take part in the run from `start_time`, honouring their start delays.
+    pub fn new(
+        config: &ConfigToml,
+        alerts: &AlertConfig,
+        endpoint_names: &[String],
+        start_time: f64,
+        clock: Arc<dyn Clock>,
+    ) -> Self {
+        let endpoints = config
+            .endpoint
+            .iter()
+            .filter(|endpoint| endpoint_names.contains(&endpoint.name))
+            .map(|endpoint| {
+                let thresholds = endpoint
+                    .alerts
+                    .as_ref()
+                    .map_or_else(|| alerts.thresholds.clone(), |own| own.or(&alerts.thresholds));
+                let window = EndpointWindow {
+                    thresholds,
+                    delays_ms: Vec::new(),
+                    last_seen: start_time + endpoint.start_delay_secs as f64,
+                    max_gap_secs: 0.0,
+                };
+                (endpoint.name.clone(), window)
+            })
+            .collect();
+        Self {
+            pending: Mutex::new(PendingWindow {
+                start: start_time,
+                endpoints,
+            }),
+            violations: Mutex::new(Vec::new()),
+            min_samples: alerts.min_samples,
+            clock,
+        }
+    }
+
+    pub fn record_observation(&self, endpoint: &str, timestamp: f64, delay_ms: f64) {
+        let mut pending = self.pending.lock().unwrap();
+        if let Some(window) = pending.endpoints.get_mut(endpoint) {
+            window.delays_ms.push(delay_ms.max(0.0));
+            window.max_gap_secs = window.max_gap_secs.max(timestamp - window.last_seen);
+            window.last_seen = window.last_seen.max(timestamp);
+        }
+    }
+
+    /// Closes the current window, logging and keeping every threshold it broke.
+    pub fn check_window(&self) -> Vec<AlertViolation> {
+        let window_end = self.clock.now();
+        let mut violations = Vec::new();
+        {
+            let mut pending = self.pending.lock().unwrap();
+            let window_start = std::mem::replace(&mut pending.start, window_end);
+            let mut names: Vec<&String> = pending.endpoints.keys().collect();
+            names.sort();
+            for name in names {
+                let window = &pending.endpoints[name];
+                let violation = |metric: &str, threshold: f64, value: f64| AlertViolation {
+                    endpoint: name.clone(),
+                    metric: metric.to_string(),
+                    threshold,
+                    value,
+                    window_start,
+                    window_end,
+                };
+                if let Some(threshold) = window.thresholds.p95_ms {
+                    if window.delays_ms.len() >= self.min_samples.max(1) {
+                        let mut sorted = window.delays_ms.clone();
+                        sorted.sort_by(|a, b| a.total_cmp(b));
+                        let p95 = percentile(&sorted, 0.95);
+                        if p95 > threshold {
+                            violations.push(violation("p95_ms", threshold, p95));
+                        }
+                    }
+                }
+                if let Some(threshold) = window.thresholds.max_gap_secs {
+                    let gap = window.max_gap_secs.max(window_end - window.last_seen);
+                    if gap > threshold {
+                        violations.push(violation("max_gap_secs", threshold, gap));
+                    }
+                }
+            }
+            for window in pending.endpoints.values_mut() {
+                window.delays_ms.clear();
+                window.max_gap_secs = 0.0;
+            }
+        }
+
+        for violation in &violations {
+            log::warn!("Latency budget violated: {}", violation.describe());
+        }
+        self.violations.lock().unwrap().extend(violations.iter().cloned());
+        violations
+    }
+
+    /// Every violation so far, oldest window first.
+    pub fn violations(&self) -> Vec<AlertViolation> {
+        self.violations.lock().unwrap().clone()
+    }
+}
+
+/// Checks a window every `window`; with `notify` set, each window with violations is posted there too.
+pub fn spawn_window_checker(
+    monitor: Arc<AlertMonitor>,
+    window: Duration,
+    notify: Option<NotifyConfig>,
+) -> tokio::task::JoinHandle<()> {
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(window);
+        interval.tick().await;
+        loop {
+            interval.tick().await;
+            let violations = monitor.check_window();
+            let Some(notify) = &notify else {
+                continue;
+            };
+            if violations.is_empty() {
+                continue;
+            }
+            let mut lines = vec!["geyserbench latency budget violated".to_string()];
+            lines.extend(violations.iter().map(AlertViolation::describe));
+            // A failed notification must not stop the monitoring, so it is only logged
+            if let Err(e) = notifier::send_text(notify, &lines.join("\n")).await {
+                log::error!("Failed to send alert webhook: {}", e);
+            }
+        }
+    })
+}
+
+pub fn print_alerts(violations: &[AlertViolation], start_time: f64) {
+    if violations.is_empty() {
+        return;
+    }
+    println!("\nLatency budget alerts");
+    println!("--------------------------------------------");
+    for violation in violations {
+        println!(
+            "[{:.0}s-{:.0}s] {}",
+            violation.window_start - start_time,
+            violation.window_end - start_time,
+            violation.describe()
+        );
+    }
+}
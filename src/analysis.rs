@@ -1,24 +1,78 @@
-use std::collections::HashMap;
-use crate::utils::{Comparator, percentile};
+use std::collections::{BTreeMap, HashMap};
+use crate::{
+    config::{AccountsFilterRule, Config, Endpoint, ExecutionStage, ScoreWeights, TagGrouping, TiePolicy},
+    events,
+    markets::MarketTag,
+    report::{
+        write_account_diff, write_pair_windows, write_race_table, BenchmarkReport, DelaySummary, EndpointReport, StatusDisagreement,
+        AccountDiffRow, AccountIntegrityReport, DataSliceReport, AccountMismatch, GapReport, HandicapReport, ProviderTimingReport, RttReport, AccountTargetReport, FilterGroupReport, ReplayEndpointReport, ReplayReport, AnomalyAnnotation, BackpressureReport, ConnectionPoolReport, PoolConnectionReport, SlotLagReport, SlotLagWindow, ExecutionGapReport, MarketEndpointReport, MarketReport, PairReport, PairWindow, SamplingReport, ScoreboardEntry, SlotReport, StatusPairReport, TagGroupReport, TagGroupingReport, TagPartitionReport,
+    },
+    slo,
+    timefmt::utc_time,
+    utils::{
+        Comparator, RaceResult, ReplayMetrics, StreamEventKind, TransactionData, median_absolute_deviation, median_confidence_interval, percentile, trimmed_mean,
+        wilcoxon_signed_rank, SIGNIFICANCE_LEVEL,
+    },
+};
 
 #[derive(Default)]
 pub struct EndpointStats {
     pub first_detections: usize,
+    pub win_credit: f64,
+    pub ties: usize,
     pub total_valid_transactions: usize,
     pub delays: Vec<f64>,
     pub old_transactions: usize,
+    pub with_meta: usize,
 }
 
-pub fn analyze_delays(comparator: &Comparator, endpoint_names: Vec<String>) {
+impl EndpointStats {
+    pub fn win_rate(&self) -> f64 {
+        if self.total_valid_transactions == 0 {
+            return 0.0;
+        }
+        self.win_credit / self.total_valid_transactions as f64 * 100.0
+    }
+}
+
+pub fn summarize_delays(delays: &[f64], trim_percent: f64) -> Option<DelaySummary> {
+    if delays.is_empty() {
+        return None;
+    }
+
+    let mut sorted_delays = delays.to_vec();
+    sorted_delays.sort_by(|a, b| a.partial_cmp(b).unwrap());
+
+    Some(DelaySummary {
+        average_ms: delays.iter().sum::<f64>() / delays.len() as f64,
+        median_ms: percentile(&sorted_delays, 0.5),
+        p95_ms: percentile(&sorted_delays, 0.95),
+        min_ms: sorted_delays[0],
+        max_ms: sorted_delays[sorted_delays.len() - 1],
+        trimmed_mean_ms: trimmed_mean(&sorted_delays, trim_percent / 100.0),
+        mad_ms: median_absolute_deviation(&sorted_delays),
+    })
+}
+
+pub fn analyze_delays(
+    comparator: &Comparator,
+    endpoint_names: Vec<String>,
+    config: &Config,
+) -> BenchmarkReport {
+    let trim_percent = config.trim_percent;
+    let tie_policy = config.tie_policy;
     let all_signatures = &comparator.data;
     let mut endpoint_stats: HashMap<String, EndpointStats> = HashMap::new();
 
-    for endpoint_name in endpoint_names {
-        endpoint_stats.insert(endpoint_name, EndpointStats::default());
+    for endpoint_name in &endpoint_names {
+        endpoint_stats.insert(endpoint_name.clone(), EndpointStats::default());
     }
 
     let mut fastest_endpoint = None;
     let mut highest_first_detection_rate = 0.0;
+    let mut tied_signatures = 0;
+    let mut excluded_signatures = 0;
+    let mut before_overlap = 0;
 
     for sig_data in all_signatures.values() {
         let mut is_historical = false;
@@ -30,7 +84,7 @@ pub fn analyze_delays(comparator: &Comparator, endpoint_names: Vec<String>) {
         }
 
         if is_historical {
-            for (endpoint, _) in sig_data {
+            for endpoint in sig_data.keys() {
                 if let Some(stats) = endpoint_stats.get_mut(endpoint) {
                     stats.old_transactions += 1;
                 }
@@ -38,23 +92,53 @@ pub fn analyze_delays(comparator: &Comparator, endpoint_names: Vec<String>) {
             continue;
         }
 
-        if let Some((first_endpoint, first_tx)) = sig_data
+        if comparator.before_overlap(sig_data) {
+            before_overlap += 1;
+            continue;
+        }
+
+        let first_timestamp = sig_data
+            .values()
+            .map(|tx| tx.precise_timestamp())
+            .fold(f64::INFINITY, f64::min);
+        // AIDEV-NOTE: Exact timestamp equality is a tie; resolved by `tie_policy` instead of map order
+        let first_endpoints: Vec<&String> = sig_data
             .iter()
-            .min_by(|a, b| a.1.timestamp.partial_cmp(&b.1.timestamp).unwrap())
-        {
-            if let Some(stats) = endpoint_stats.get_mut(first_endpoint) {
-                stats.first_detections += 1;
-                stats.total_valid_transactions += 1;
+            .filter(|(_, tx)| tx.precise_timestamp() == first_timestamp)
+            .map(|(endpoint, _)| endpoint)
+            .collect();
+
+        if first_endpoints.len() > 1 {
+            tied_signatures += 1;
+            for endpoint in &first_endpoints {
+                if let Some(stats) = endpoint_stats.get_mut(*endpoint) {
+                    stats.ties += 1;
+                }
+            }
+            if tie_policy == TiePolicy::Exclude {
+                excluded_signatures += 1;
+                continue;
             }
+        }
+
+        let credit = match tie_policy {
+            TiePolicy::Half => 1.0 / first_endpoints.len() as f64,
+            TiePolicy::Shared | TiePolicy::Exclude => 1.0,
+        };
 
-            for (endpoint, tx) in sig_data {
-                if endpoint != first_endpoint {
-                    if let Some(stats) = endpoint_stats.get_mut(endpoint) {
-                        stats
-                            .delays
-                            .push((tx.timestamp - first_tx.timestamp) * 1000.0);
-                        stats.total_valid_transactions += 1;
-                    }
+        for (endpoint, tx) in sig_data {
+            if let Some(stats) = endpoint_stats.get_mut(endpoint) {
+                stats.total_valid_transactions += 1;
+                if tx.status.as_ref().is_some_and(|status| status.has_meta()) {
+                    stats.with_meta += 1;
+                }
+                if first_endpoints.contains(&endpoint) {
+                    stats.first_detections += 1;
+                    stats.win_credit += credit;
+                } else {
+                    stats
+                        .delays
+                        .push((tx.precise_timestamp() - first_timestamp) * 1000.0);
                 }
             }
         }
@@ -62,8 +146,7 @@ pub fn analyze_delays(comparator: &Comparator, endpoint_names: Vec<String>) {
 
     for (endpoint, stats) in &endpoint_stats {
         if stats.total_valid_transactions > 0 {
-            let detection_rate =
-                stats.first_detections as f64 / stats.total_valid_transactions as f64;
+            let detection_rate = stats.win_rate();
             if detection_rate > highest_first_detection_rate {
                 highest_first_detection_rate = detection_rate;
                 fastest_endpoint = Some(endpoint.clone());
@@ -76,9 +159,7 @@ pub fn analyze_delays(comparator: &Comparator, endpoint_names: Vec<String>) {
 
     if let Some(fastest) = fastest_endpoint.as_ref() {
         let fastest_stats = &endpoint_stats[fastest];
-        let win_rate = (fastest_stats.first_detections as f64
-            / fastest_stats.total_valid_transactions as f64)
-            * 100.0;
+        let win_rate = fastest_stats.win_rate();
         println!(
             "{}: Win rate {:.2}%, avg delay 0.00ms (fastest)",
             fastest, win_rate
@@ -86,8 +167,7 @@ pub fn analyze_delays(comparator: &Comparator, endpoint_names: Vec<String>) {
 
         for (endpoint, stats) in &endpoint_stats {
             if endpoint != fastest && stats.total_valid_transactions > 0 {
-                let win_rate =
-                    (stats.first_detections as f64 / stats.total_valid_transactions as f64) * 100.0;
+                let win_rate = stats.win_rate();
                 let avg_delay = if stats.delays.is_empty() {
                     0.0
                 } else {
@@ -107,16 +187,18 @@ pub fn analyze_delays(comparator: &Comparator, endpoint_names: Vec<String>) {
     println!("\nDetailed test results");
     println!("--------------------------------------------");
 
-    if let Some(fastest) = fastest_endpoint {
+    if let Some(fastest) = fastest_endpoint.clone() {
         println!("\nFastest Endpoint: {}", fastest);
         let fastest_stats = &endpoint_stats[&fastest];
         println!(
             "  First detections: {} out of {} valid transactions ({:.2}%)",
             fastest_stats.first_detections,
             fastest_stats.total_valid_transactions,
-            (fastest_stats.first_detections as f64 / fastest_stats.total_valid_transactions as f64)
-                * 100.0
+            fastest_stats.win_rate()
         );
+        if fastest_stats.ties > 0 {
+            println!("  Tied first detections: {}", fastest_stats.ties);
+        }
         if fastest_stats.old_transactions > 0 {
             println!(
                 "  Historical transactions detected: {}",
@@ -124,26 +206,37 @@ pub fn analyze_delays(comparator: &Comparator, endpoint_names: Vec<String>) {
             );
         }
 
+        if tied_signatures > 0 {
+            println!(
+                "\nTied signatures: {} (policy: {:?}, {} excluded)",
+                tied_signatures, tie_policy, excluded_signatures
+            );
+        }
+
+        if before_overlap > 0 {
+            println!(
+                "\nSignatures before all endpoints joined (excluded): {}",
+                before_overlap
+            );
+        }
+
         println!("\nDelays relative to fastest endpoint:");
         for (endpoint, stats) in &endpoint_stats {
             if endpoint != &fastest && !stats.delays.is_empty() {
-                let avg_delay = stats.delays.iter().sum::<f64>() / stats.delays.len() as f64;
-                let max_delay = stats
-                    .delays
-                    .iter()
-                    .fold(f64::NEG_INFINITY, |a, &b| a.max(b));
-                let min_delay = stats.delays.iter().fold(f64::INFINITY, |a, &b| a.min(b));
-
-                let mut sorted_delays = stats.delays.clone();
-                sorted_delays.sort_by(|a, b| a.partial_cmp(b).unwrap());
-                let p50 = percentile(&sorted_delays, 0.5);
-                let p95 = percentile(&sorted_delays, 0.95);
+                let summary = summarize_delays(&stats.delays, trim_percent).unwrap();
 
                 println!("\n{}:", endpoint);
-                println!("  Average delay: {:.2} ms", avg_delay);
-                println!("  Median delay: {:.2} ms", p50);
-                println!("  95th percentile: {:.2} ms", p95);
-                println!("  Min/Max delay: {:.2}/{:.2} ms", min_delay, max_delay);
+                println!("  Average delay: {:.2} ms", summary.average_ms);
+                println!("  Median delay: {:.2} ms", summary.median_ms);
+                println!("  95th percentile: {:.2} ms", summary.p95_ms);
+                println!("  Min/Max delay: {:.2}/{:.2} ms", summary.min_ms, summary.max_ms);
+                if trim_percent > 0.0 {
+                    println!(
+                        "  Trimmed mean ({}% each tail): {:.2} ms",
+                        trim_percent, summary.trimmed_mean_ms
+                    );
+                }
+                println!("  Median absolute deviation: {:.2} ms", summary.mad_ms);
                 println!("  Valid transactions: {}", stats.total_valid_transactions);
                 if stats.old_transactions > 0 {
                     println!(
@@ -156,4 +249,2151 @@ pub fn analyze_delays(comparator: &Comparator, endpoint_names: Vec<String>) {
     } else {
         println!("Not enough data");
     }
-}
\ No newline at end of file
+
+    print_frame_timing(comparator, &endpoint_names);
+    print_startup(comparator, &endpoint_names);
+    print_block_sizes(comparator, &endpoint_names);
+    print_connections(comparator, &endpoint_names);
+    print_shards(comparator, &endpoint_names);
+    print_entry_decode(comparator, &endpoint_names);
+
+    let races = comparator.races(config.tie_policy);
+    let largest_margins: Vec<_> = races.iter().take(config.race_top_n).cloned().collect();
+    if !largest_margins.is_empty() {
+        print_race_table(&largest_margins, races.len());
+    }
+    if let Some(path) = &config.race_table_path {
+        if let Err(e) = write_race_table(path, &races) {
+            log::error!("Failed to write race table to {}: {}", path, e);
+        }
+    }
+
+    let endpoints: Vec<EndpointReport> = endpoint_names
+        .iter()
+        .map(|name| {
+            let stats = &endpoint_stats[name];
+            let mut endpoint = EndpointReport {
+                name: name.clone(),
+                first_detections: stats.first_detections,
+                total_valid_transactions: stats.total_valid_transactions,
+                old_transactions: stats.old_transactions,
+                win_rate: stats.win_rate(),
+                ties: stats.ties,
+                delays: summarize_delays(&stats.delays, trim_percent),
+                metrics: comparator.endpoint_metrics.get(name).cloned().unwrap_or_default(),
+                slo: Vec::new(),
+                with_meta: stats.with_meta,
+                missing_meta_rate: if stats.total_valid_transactions > 0 {
+                    (stats.total_valid_transactions - stats.with_meta) as f64
+                        / stats.total_valid_transactions as f64
+                        * 100.0
+                } else {
+                    0.0
+                },
+                downtime_secs: downtime_secs(comparator, name),
+            };
+            if let Some(slo_config) = &config.slo {
+                endpoint.slo = slo::evaluate(slo_config, &endpoint);
+            }
+            endpoint
+        })
+        .collect();
+    print_slo(&endpoints);
+    print_meta_completeness(&endpoints);
+    print_downtime(&endpoints);
+    print_late_arrivals(&endpoints);
+    print_token_refreshes(&endpoints);
+    print_duplicates(&endpoints, config.dedup_window_secs);
+
+    let gaps = summarize_gaps(comparator, &endpoint_names);
+    print_gaps(&gaps);
+
+    let scoreboard = summarize_scoreboard(comparator, &endpoint_stats, &endpoints, &gaps, &config.scoreboard);
+    print_scoreboard(&scoreboard, &config.scoreboard);
+
+    let provider_timing = summarize_provider_timing(comparator, &endpoints);
+    print_provider_timing(&provider_timing);
+
+    let rtt = summarize_rtt(comparator, &endpoint_names);
+    print_rtt(&rtt);
+
+    let connection_pools = summarize_connection_pools(comparator, &endpoint_names);
+    print_connection_pools(&connection_pools);
+
+    let event_order = events::summarize_event_order(comparator);
+    if let Some(event_order) = &event_order {
+        events::print_event_order(event_order);
+    }
+
+    let backpressure = if config.consumer_delay_us > 0 {
+        summarize_backpressure(comparator, &endpoints)
+    } else {
+        Vec::new()
+    };
+    print_backpressure(&backpressure, config.consumer_delay_us);
+
+    let status_checks = summarize_status_checks(comparator);
+    print_status_checks(&status_checks);
+
+    let slots = summarize_slots(comparator);
+    print_lagging_slots(&slots);
+
+    let slot_lag = summarize_slot_lag(comparator, &endpoint_names);
+    print_slot_lag(&slot_lag);
+
+    let pairs = summarize_pairs(comparator, &endpoint_names, config.heatmap_window_secs);
+    print_pairs(&pairs, config.heatmap_window_secs);
+    if let Some(path) = &config.heatmap_csv_path {
+        if let Err(e) = write_pair_windows(path, &pairs) {
+            log::error!("Failed to write heatmap windows to {}: {}", path, e);
+        }
+    }
+
+    print_account_sizes(comparator, &endpoint_names);
+    let mut account_diff = config.account_diff_path.as_ref().map(|_| Vec::new());
+    let account_integrity = summarize_account_integrity(comparator, account_diff.as_mut());
+    if let Some(integrity) = &account_integrity {
+        print_account_integrity(integrity);
+    }
+    let data_slices = summarize_data_slices(comparator, &endpoint_names, config);
+    print_data_slices(&data_slices);
+    if let (Some(path), Some(rows)) = (&config.account_diff_path, &mut account_diff) {
+        rows.sort_by(|a, b| (&a.pubkey, a.write_version, &a.endpoint).cmp(&(&b.pubkey, b.write_version, &b.endpoint)));
+        if let Err(e) = write_account_diff(path, rows) {
+            log::error!("Failed to write account diff to {}: {}", path, e);
+        }
+    }
+
+    let markets = summarize_markets(comparator, &endpoint_names);
+    print_markets(&markets);
+
+    let account_targets = summarize_account_targets(comparator, &endpoint_names);
+    print_account_targets(&account_targets);
+
+    let filter_groups = summarize_filter_groups(comparator, &endpoint_names, &config.filter_groups);
+    print_filter_groups(&filter_groups);
+
+    let replay = summarize_replay(comparator, &endpoint_names, config.from_slot);
+    if let Some(replay) = &replay {
+        print_replay(replay);
+    }
+
+    let anomalies = summarize_anomalies(comparator, &endpoint_names);
+    print_anomalies(comparator, &endpoint_names, &anomalies);
+
+    let sampling = comparator.sampler.as_ref().map(|sampler| SamplingReport {
+        rate: sampler.rate(),
+        kept: sampler.kept,
+        dropped: sampler.dropped,
+    });
+    if let Some(sampling) = &sampling {
+        println!(
+            "\nSampling: kept {} of {} distinct signatures (sample_rate {})",
+            sampling.kept,
+            sampling.kept + sampling.dropped,
+            sampling.rate
+        );
+    }
+
+    BenchmarkReport {
+        fastest_endpoint,
+        largest_margins,
+        tie_policy,
+        tied_signatures,
+        excluded_signatures,
+        before_overlap,
+        endpoints,
+        status_checks,
+        slots,
+        pairs,
+        account_integrity,
+        data_slices,
+        sampling,
+        anomalies,
+        markets,
+        account_targets,
+        filter_groups,
+        replay,
+        slot_lag,
+        connection_pools,
+        gaps,
+        scoreboard,
+        provider_timing,
+        rtt,
+        event_order,
+        backpressure,
+        ..Default::default()
+    }
+}
+
+/// Union of the endpoint's downtime intervals, so overlapping shard outages count once.
+fn downtime_secs(comparator: &Comparator, endpoint: &str) -> f64 {
+    let Some(metrics) = comparator.endpoint_metrics.get(endpoint) else {
+        return 0.0;
+    };
+    let mut intervals: Vec<(f64, f64)> = metrics
+        .events
+        .iter()
+        .filter(|event| event.kind == StreamEventKind::Downtime)
+        .map(|event| (event.at, event.at + event.duration_secs))
+        .collect();
+    intervals.sort_by(|a, b| a.0.partial_cmp(&b.0).unwrap());
+
+    let mut total = 0.0;
+    let mut covered_until = f64::NEG_INFINITY;
+    for (start, end) in intervals {
+        let start = start.max(covered_until);
+        if end > start {
+            total += end - start;
+            covered_until = end;
+        }
+    }
+    total
+}
+
+fn print_downtime(endpoints: &[EndpointReport]) {
+    if endpoints.iter().all(|endpoint| endpoint.downtime_secs == 0.0) {
+        return;
+    }
+
+    println!("\nStream downtime (watchdog)");
+    println!("--------------------------------------------");
+    for endpoint in endpoints.iter().filter(|endpoint| endpoint.downtime_secs > 0.0) {
+        println!("{}: {:.1}s without any update", endpoint.name, endpoint.downtime_secs);
+    }
+}
+
+// AIDEV-NOTE: Only signatures every connection delivered are compared, so a connection that
+// joined late or dropped out does not look slow; the gain is what the pool buys over picking one
+// connection at random.
+fn summarize_connection_pools(comparator: &Comparator, endpoint_names: &[String]) -> Vec<ConnectionPoolReport> {
+    let mut reports = Vec::new();
+    for name in endpoint_names {
+        let Some(sightings) = comparator.pool_sightings.get(name) else {
+            continue;
+        };
+        let connection_count = sightings
+            .values()
+            .flat_map(|seen| seen.iter().map(|(connection, _)| connection + 1))
+            .max()
+            .unwrap_or(0);
+        let mut wins = vec![0; connection_count];
+        let mut delays = vec![Vec::new(); connection_count];
+        let mut signatures = 0;
+        for (signature, seen) in sightings {
+            let live = comparator.data.get(signature).is_some_and(|sig_data| comparator.is_live(sig_data));
+            if !live || seen.len() < connection_count {
+                continue;
+            }
+            signatures += 1;
+            let first = seen.iter().map(|(_, timestamp)| *timestamp).fold(f64::INFINITY, f64::min);
+            for (connection, timestamp) in seen {
+                if *timestamp == first {
+                    wins[*connection] += 1;
+                }
+                delays[*connection].push((timestamp - first) * 1000.0);
+            }
+        }
+        if signatures == 0 {
+            continue;
+        }
+
+        let connections: Vec<PoolConnectionReport> = delays
+            .into_iter()
+            .enumerate()
+            .map(|(connection, mut delays)| {
+                delays.sort_by(|a, b| a.partial_cmp(b).unwrap());
+                PoolConnectionReport {
+                    connection,
+                    wins: wins[connection],
+                    median_delay_ms: percentile(&delays, 0.5),
+                }
+            })
+            .collect();
+        let median_gain_ms =
+            connections.iter().map(|c| c.median_delay_ms).sum::<f64>() / connections.len() as f64;
+        reports.push(ConnectionPoolReport {
+            name: name.clone(),
+            signatures,
+            connections,
+            median_gain_ms,
+        });
+    }
+    reports
+}
+
+fn print_connection_pools(pools: &[ConnectionPoolReport]) {
+    if pools.is_empty() {
+        return;
+    }
+
+    println!("\nParallel connections");
+    println!("--------------------------------------------");
+    for pool in pools {
+        println!(
+            "{}: {} signatures seen on all {} connections, pool gains {:.2} ms over a single connection (median)",
+            pool.name,
+            pool.signatures,
+            pool.connections.len(),
+            pool.median_gain_ms
+        );
+        for connection in &pool.connections {
+            println!(
+                "  #{}: first on {} ({:.2}%), median delay {:.2} ms",
+                connection.connection,
+                connection.wins,
+                connection.wins as f64 / pool.signatures as f64 * 100.0,
+                connection.median_delay_ms
+            );
+        }
+    }
+}
+
+const REPORTED_GAP_EXAMPLES: usize = 10;
+const PRINTED_GAP_EXAMPLES: usize = 3;
+
+// AIDEV-NOTE: Only live signatures count, so endpoints that joined late (start_delay_secs) are not
+// blamed for the time before they joined. A signature first seen just before the stop can still
+// be in flight elsewhere; the drain window (drain_ms) is what keeps those out of the gaps.
+fn summarize_gaps(comparator: &Comparator, endpoint_names: &[String]) -> Vec<GapReport> {
+    if endpoint_names.len() < 2 {
+        return Vec::new();
+    }
+    let live = comparator.data.values().filter(|sig_data| comparator.is_live(sig_data)).count();
+    endpoint_names
+        .iter()
+        .map(|name| {
+            let missed = comparator.missed_by(name);
+            GapReport {
+                name: name.clone(),
+                missed: missed.len(),
+                coverage: if live > 0 { (live - missed.len()) as f64 / live as f64 } else { 0.0 },
+                examples: missed
+                    .iter()
+                    .take(REPORTED_GAP_EXAMPLES)
+                    .map(|(signature, _)| signature.to_string())
+                    .collect(),
+            }
+        })
+        .collect()
+}
+
+fn print_gaps(gaps: &[GapReport]) {
+    if gaps.iter().all(|gap| gap.missed == 0) {
+        return;
+    }
+
+    println!("\nCoverage gaps");
+    println!("--------------------------------------------");
+    for gap in gaps {
+        if gap.missed == 0 {
+            println!("{}: complete", gap.name);
+            continue;
+        }
+        println!(
+            "{}: missed {} signatures others delivered ({:.2}% coverage), e.g. {}",
+            gap.name,
+            gap.missed,
+            gap.coverage * 100.0,
+            gap.examples.iter().take(PRINTED_GAP_EXAMPLES).cloned().collect::<Vec<_>>().join(", ")
+        );
+    }
+}
+
+// AIDEV-NOTE: Win rate, coverage and uptime are shares and score as they are. The two delay
+// components are scored min-max across endpoints, so the fastest gets 100 and the slowest 0
+// however small the spread; the raw deltas sit next to the score for judging whether the gap
+// matters. An endpoint that delivered nothing scores 0 on both.
+fn summarize_scoreboard(
+    comparator: &Comparator,
+    endpoint_stats: &HashMap<String, EndpointStats>,
+    endpoints: &[EndpointReport],
+    gaps: &[GapReport],
+    weights: &ScoreWeights,
+) -> Vec<ScoreboardEntry> {
+    if endpoints.len() < 2 {
+        return Vec::new();
+    }
+    let observed_secs = observed_secs(comparator);
+    // Median and p99 behind the first detection, with the endpoint's own first detections as zero
+    let deltas: Vec<Option<(f64, f64)>> = endpoints
+        .iter()
+        .map(|endpoint| {
+            let stats = &endpoint_stats[&endpoint.name];
+            let mut deltas = stats.delays.clone();
+            deltas.resize(deltas.len() + stats.first_detections, 0.0);
+            if deltas.is_empty() {
+                return None;
+            }
+            deltas.sort_by(|a, b| a.partial_cmp(b).unwrap());
+            Some((percentile(&deltas, 0.5), percentile(&deltas, 0.99)))
+        })
+        .collect();
+    let range = |pick: fn(&(f64, f64)) -> f64| {
+        deltas.iter().flatten().map(pick).fold((f64::INFINITY, f64::NEG_INFINITY), |(best, worst), value| {
+            (best.min(value), worst.max(value))
+        })
+    };
+    let (best_median, worst_median) = range(|delta| delta.0);
+    let (best_p99, worst_p99) = range(|delta| delta.1);
+    let latency_score = |value: Option<f64>, best: f64, worst: f64| match value {
+        Some(value) if worst > best => 100.0 * (worst - value) / (worst - best),
+        Some(_) => 100.0,
+        None => 0.0,
+    };
+
+    let mut entries: Vec<ScoreboardEntry> = endpoints
+        .iter()
+        .zip(deltas.iter().copied())
+        .map(|(endpoint, delta)| {
+            let coverage = gaps.iter().find(|gap| gap.name == endpoint.name).map_or(0.0, |gap| gap.coverage);
+            let uptime = if observed_secs > 0.0 {
+                (1.0 - endpoint.downtime_secs / observed_secs).clamp(0.0, 1.0)
+            } else {
+                1.0
+            };
+            let weighted = weights.win_rate * endpoint.win_rate.min(100.0)
+                + weights.median_delta * latency_score(delta.map(|delta| delta.0), best_median, worst_median)
+                + weights.p99_delta * latency_score(delta.map(|delta| delta.1), best_p99, worst_p99)
+                + weights.coverage * coverage * 100.0
+                + weights.uptime * uptime * 100.0;
+            ScoreboardEntry {
+                rank: 0,
+                name: endpoint.name.clone(),
+                score: weighted / weights.total(),
+                win_rate: endpoint.win_rate,
+                median_delta_ms: delta.map_or(0.0, |delta| delta.0),
+                p99_delta_ms: delta.map_or(0.0, |delta| delta.1),
+                coverage,
+                uptime,
+            }
+        })
+        .collect();
+    entries.sort_by(|a, b| b.score.partial_cmp(&a.score).unwrap().then_with(|| a.name.cmp(&b.name)));
+    for (index, entry) in entries.iter_mut().enumerate() {
+        entry.rank = index + 1;
+    }
+    entries
+}
+
+/// Seconds from the run's start to the last sighting, the span uptime is measured against.
+fn observed_secs(comparator: &Comparator) -> f64 {
+    let sightings = comparator.data.values().flat_map(|sig_data| sig_data.values());
+    let (start, end) = sightings.fold((f64::INFINITY, f64::NEG_INFINITY), |(start, end), tx| {
+        (start.min(tx.start_time), end.max(tx.timestamp))
+    });
+    if end > start {
+        end - start
+    } else {
+        0.0
+    }
+}
+
+fn print_scoreboard(entries: &[ScoreboardEntry], weights: &ScoreWeights) {
+    if entries.is_empty() {
+        return;
+    }
+
+    println!("\nScoreboard");
+    println!("--------------------------------------------");
+    println!(
+        "Weights: win rate {}, median delta {}, p99 delta {}, coverage {}, uptime {}",
+        weights.win_rate, weights.median_delta, weights.p99_delta, weights.coverage, weights.uptime
+    );
+    for entry in entries {
+        println!(
+            "{}. {}: {:.1} (win rate {:.2}%, median +{:.2} ms, p99 +{:.2} ms, coverage {:.2}%, uptime {:.2}%)",
+            entry.rank,
+            entry.name,
+            entry.score,
+            entry.win_rate,
+            entry.median_delta_ms,
+            entry.p99_delta_ms,
+            entry.coverage * 100.0,
+            entry.uptime * 100.0
+        );
+    }
+}
+
+// AIDEV-NOTE: An endpoint's delay behind the first detection is provider-side lag plus transit.
+// Skew measures transit against the provider's own stamp, so the part of an endpoint's delay its
+// extra transit does not explain (delay minus `transit_excess_ms`) was spent before it stamped the
+// update. Medians over different sets of updates, so the split is indicative only.
+fn summarize_provider_timing(comparator: &Comparator, endpoints: &[EndpointReport]) -> Vec<ProviderTimingReport> {
+    let mut reports: Vec<ProviderTimingReport> = endpoints
+        .iter()
+        .filter_map(|endpoint| {
+            let metrics = comparator.endpoint_metrics.get(&endpoint.name)?;
+            if metrics.provider_skews_ms.is_empty() && metrics.block_time_lags_ms.is_empty() {
+                return None;
+            }
+            let mut skews = metrics.provider_skews_ms.clone();
+            skews.sort_by(|a, b| a.total_cmp(b));
+            let mut block_lags = metrics.block_time_lags_ms.clone();
+            block_lags.sort_by(|a, b| a.total_cmp(b));
+            Some(ProviderTimingReport {
+                name: endpoint.name.clone(),
+                updates: skews.len(),
+                median_skew_ms: percentile(&skews, 0.5),
+                p95_skew_ms: percentile(&skews, 0.95),
+                min_skew_ms: skews.first().copied().unwrap_or(0.0),
+                transit_excess_ms: 0.0,
+                median_delay_ms: endpoint.delays.as_ref().map(|delays| delays.median_ms),
+                blocks: block_lags.len(),
+                median_block_time_lag_ms: (!block_lags.is_empty()).then(|| percentile(&block_lags, 0.5)),
+            })
+        })
+        .collect();
+    let lowest = reports
+        .iter()
+        .filter(|report| report.updates > 0)
+        .map(|report| report.median_skew_ms)
+        .fold(f64::INFINITY, f64::min);
+    for report in reports.iter_mut().filter(|report| report.updates > 0) {
+        report.transit_excess_ms = report.median_skew_ms - lowest;
+    }
+    reports
+}
+
+fn print_provider_timing(reports: &[ProviderTimingReport]) {
+    if reports.is_empty() {
+        return;
+    }
+
+    println!("\nProvider timing");
+    println!("--------------------------------------------");
+    for report in reports {
+        if report.updates > 0 {
+            println!(
+                "{}: received {:.2} ms after the provider stamp (median, p95 {:.2} ms, min {:.2} ms, {} updates)",
+                report.name, report.median_skew_ms, report.p95_skew_ms, report.min_skew_ms, report.updates
+            );
+            if let Some(delay) = report.median_delay_ms {
+                println!(
+                    "  median delay {:.2} ms: {:.2} ms more transit than the closest endpoint, ~{:.2} ms provider-side",
+                    delay,
+                    report.transit_excess_ms,
+                    (delay - report.transit_excess_ms).max(0.0)
+                );
+            }
+        }
+        if let Some(lag) = report.median_block_time_lag_ms {
+            println!(
+                "{}: blocks received {:.0} ms after their block time (median of {}, block time has 1s resolution)",
+                report.name, lag, report.blocks
+            );
+        }
+    }
+    if reports.iter().any(|report| report.min_skew_ms < 0.0) {
+        println!("Negative skews mean the provider's clock is ahead of this machine's; compare with clock_check");
+    }
+}
+
+fn summarize_rtt(comparator: &Comparator, endpoint_names: &[String]) -> Vec<RttReport> {
+    endpoint_names
+        .iter()
+        .filter_map(|name| {
+            let metrics = comparator.endpoint_metrics.get(name)?;
+            if metrics.ping_rtts_ms.is_empty() && metrics.pings_unanswered == 0 {
+                return None;
+            }
+            let mut rtts = metrics.ping_rtts_ms.clone();
+            rtts.sort_by(|a, b| a.total_cmp(b));
+            Some(RttReport {
+                name: name.clone(),
+                pings: rtts.len(),
+                unanswered: metrics.pings_unanswered,
+                min_ms: rtts.first().copied().unwrap_or(0.0),
+                median_ms: percentile(&rtts, 0.5),
+                p95_ms: percentile(&rtts, 0.95),
+                max_ms: rtts.last().copied().unwrap_or(0.0),
+            })
+        })
+        .collect()
+}
+
+fn print_rtt(reports: &[RttReport]) {
+    if reports.is_empty() {
+        return;
+    }
+
+    println!("\nPing round trips");
+    println!("--------------------------------------------");
+    for report in reports {
+        if report.pings == 0 {
+            println!("{}: no pong to {} pings", report.name, report.unanswered);
+            continue;
+        }
+        println!(
+            "{}: min {:.2} ms, median {:.2} ms, p95 {:.2} ms, max {:.2} ms over {} pings{}",
+            report.name,
+            report.min_ms,
+            report.median_ms,
+            report.p95_ms,
+            report.max_ms,
+            report.pings,
+            if report.unanswered > 0 { format!(" ({} unanswered)", report.unanswered) } else { String::new() }
+        );
+    }
+    println!("Half the minimum round trip approximates one-way network distance; the rest of a delay gap is stream processing");
+}
+
+// AIDEV-NOTE: A slow reader shows up as one of three things depending on the provider: lag that
+// grows over the run (the server buffers), disconnects or stalls (it drops the client), or
+// signatures the endpoint never delivers (it skips messages).
+fn summarize_backpressure(comparator: &Comparator, endpoints: &[EndpointReport]) -> Vec<BackpressureReport> {
+    let mut lags: HashMap<&str, Vec<(f64, f64)>> = HashMap::new();
+    let mut missed: HashMap<&str, usize> = HashMap::new();
+    for sig_data in comparator.data.values().filter(|sig_data| comparator.is_live(sig_data)) {
+        let first = sig_data
+            .values()
+            .map(|tx| tx.precise_timestamp())
+            .fold(f64::INFINITY, f64::min);
+        for endpoint in endpoints {
+            match sig_data.get(&endpoint.name) {
+                Some(tx) => lags
+                    .entry(endpoint.name.as_str())
+                    .or_default()
+                    .push((first, (tx.precise_timestamp() - first) * 1000.0)),
+                None => *missed.entry(endpoint.name.as_str()).or_default() += 1,
+            }
+        }
+    }
+
+    endpoints
+        .iter()
+        .map(|endpoint| {
+            let consumer = endpoint.metrics.consumer.clone().unwrap_or_default();
+            let count = |kind: StreamEventKind| {
+                endpoint.metrics.events.iter().filter(|event| event.kind == kind).count()
+            };
+            let lag_growth_ms = lags.get_mut(endpoint.name.as_str()).and_then(|lags| {
+                lags.sort_by(|a, b| a.0.partial_cmp(&b.0).unwrap());
+                let quarter = lags.len() / 4;
+                if quarter == 0 {
+                    return None;
+                }
+                let median = |slice: &[(f64, f64)]| {
+                    let mut delays: Vec<f64> = slice.iter().map(|(_, delay)| *delay).collect();
+                    delays.sort_by(|a, b| a.partial_cmp(b).unwrap());
+                    percentile(&delays, 0.5)
+                };
+                Some(median(&lags[lags.len() - quarter..]) - median(&lags[..quarter]))
+            });
+            BackpressureReport {
+                name: endpoint.name.clone(),
+                messages: consumer.messages,
+                delayed_secs: consumer.delayed_secs,
+                disconnects: count(StreamEventKind::Disconnect),
+                stalls: count(StreamEventKind::Stall),
+                downtime_secs: endpoint.downtime_secs,
+                missed: missed.get(endpoint.name.as_str()).copied().unwrap_or(0),
+                lag_growth_ms,
+            }
+        })
+        .collect()
+}
+
+fn print_backpressure(reports: &[BackpressureReport], consumer_delay_us: u64) {
+    if reports.is_empty() {
+        return;
+    }
+
+    println!("\nBackpressure ({} us per message)", consumer_delay_us);
+    println!("--------------------------------------------");
+    for report in reports {
+        let growth = report
+            .lag_growth_ms
+            .map_or("-".to_string(), |growth| format!("{:+.2} ms", growth));
+        println!(
+            "{}: {} messages, {:.1}s delayed, lag growth {}, {} missed, {} disconnects, {} stalls, {:.1}s down",
+            report.name,
+            report.messages,
+            report.delayed_secs,
+            growth,
+            report.missed,
+            report.disconnects,
+            report.stalls,
+            report.downtime_secs
+        );
+    }
+}
+
+fn print_late_arrivals(endpoints: &[EndpointReport]) {
+    if endpoints.iter().all(|endpoint| endpoint.metrics.late_arrivals == 0) {
+        return;
+    }
+
+    println!("\nLate arrivals (drain window)");
+    println!("--------------------------------------------");
+    for endpoint in endpoints {
+        println!("{}: {} sightings completed after the stop signal", endpoint.name, endpoint.metrics.late_arrivals);
+    }
+}
+
+fn print_token_refreshes(endpoints: &[EndpointReport]) {
+    if endpoints.iter().all(|endpoint| endpoint.metrics.token_refreshes == 0) {
+        return;
+    }
+
+    println!("\nToken refreshes");
+    println!("--------------------------------------------");
+    for endpoint in endpoints.iter().filter(|endpoint| endpoint.metrics.token_refreshes > 0) {
+        println!(
+            "{}: {} tokens fetched after the server rejected the previous one",
+            endpoint.name, endpoint.metrics.token_refreshes
+        );
+    }
+}
+
+fn print_duplicates(endpoints: &[EndpointReport], window_secs: Option<f64>) {
+    if endpoints
+        .iter()
+        .all(|endpoint| endpoint.metrics.duplicates == 0 && endpoint.metrics.redeliveries == 0)
+    {
+        return;
+    }
+
+    println!("\nRepeat deliveries (earliest sighting kept, repeats not counted as wins)");
+    println!("--------------------------------------------");
+    for endpoint in endpoints {
+        let metrics = &endpoint.metrics;
+        match window_secs {
+            Some(window) => println!(
+                "{}: {} duplicates within {}s, {} redeliveries later",
+                endpoint.name, metrics.duplicates, window, metrics.redeliveries
+            ),
+            None => println!("{}: {} duplicates", endpoint.name, metrics.duplicates),
+        }
+    }
+}
+
+fn print_meta_completeness(endpoints: &[EndpointReport]) {
+    if endpoints.iter().all(|endpoint| endpoint.with_meta == 0) {
+        return;
+    }
+
+    println!("\nTransaction meta completeness");
+    println!("--------------------------------------------");
+    for endpoint in endpoints {
+        println!(
+            "{}: {} of {} with meta ({:.2}% missing)",
+            endpoint.name, endpoint.with_meta, endpoint.total_valid_transactions, endpoint.missing_meta_rate
+        );
+    }
+}
+
+#[derive(Default)]
+struct RaceTally {
+    seen: usize,
+    win_credit: f64,
+    delays: Vec<f64>,
+}
+
+/// Win credit and delays per endpoint over the live signatures, with `offsets_ms` added to each
+/// endpoint's receive times.
+fn tally_races(comparator: &Comparator, offsets_ms: &HashMap<&str, f64>, tie_policy: TiePolicy) -> HashMap<String, RaceTally> {
+    let mut tallies: HashMap<String, RaceTally> = HashMap::new();
+    for sig_data in comparator.data.values().filter(|sig_data| comparator.is_live(sig_data)) {
+        let shifted: Vec<(&String, f64)> = sig_data
+            .iter()
+            .filter_map(|(name, tx)| {
+                let offset_ms = offsets_ms.get(name.as_str())?;
+                Some((name, tx.precise_timestamp() + offset_ms / 1000.0))
+            })
+            .collect();
+        let first = shifted.iter().map(|(_, timestamp)| *timestamp).fold(f64::INFINITY, f64::min);
+        let winners = shifted.iter().filter(|(_, timestamp)| *timestamp == first).count();
+        if winners > 1 && tie_policy == TiePolicy::Exclude {
+            continue;
+        }
+        let credit = match tie_policy {
+            TiePolicy::Half => 1.0 / winners as f64,
+            TiePolicy::Shared | TiePolicy::Exclude => 1.0,
+        };
+        for (name, timestamp) in shifted {
+            let tally = tallies.entry(name.clone()).or_default();
+            tally.seen += 1;
+            if timestamp == first {
+                tally.win_credit += credit;
+            } else {
+                tally.delays.push((timestamp - first) * 1000.0);
+            }
+        }
+    }
+    tallies
+}
+
+// AIDEV-NOTE: Handicaps shift receive times only in this what-if race, after the run; the
+// observations and every other statistic stay raw, so both columns come from the same deliveries.
+/// Win rate and median delay of every endpoint with and without the configured `handicap_ms`.
+pub fn summarize_handicap(comparator: &Comparator, endpoints: &[Endpoint], tie_policy: TiePolicy) -> Vec<HandicapReport> {
+    if endpoints.iter().all(|endpoint| endpoint.handicap_ms.is_none()) {
+        return Vec::new();
+    }
+    let raw_offsets: HashMap<&str, f64> = endpoints.iter().map(|endpoint| (endpoint.name.as_str(), 0.0)).collect();
+    let offsets: HashMap<&str, f64> = endpoints
+        .iter()
+        .map(|endpoint| (endpoint.name.as_str(), endpoint.handicap_ms.unwrap_or(0.0)))
+        .collect();
+    let mut raw = tally_races(comparator, &raw_offsets, tie_policy);
+    let mut handicapped = tally_races(comparator, &offsets, tie_policy);
+
+    let win_rate = |tally: &RaceTally| {
+        if tally.seen == 0 {
+            0.0
+        } else {
+            tally.win_credit / tally.seen as f64 * 100.0
+        }
+    };
+    let median_delay = |tally: &mut RaceTally| {
+        tally.delays.sort_by(|a, b| a.total_cmp(b));
+        percentile(&tally.delays, 0.5)
+    };
+    endpoints
+        .iter()
+        .filter_map(|endpoint| {
+            let mut raw = raw.remove(&endpoint.name)?;
+            let mut handicapped = handicapped.remove(&endpoint.name).unwrap_or_default();
+            Some(HandicapReport {
+                name: endpoint.name.clone(),
+                handicap_ms: endpoint.handicap_ms.unwrap_or(0.0),
+                signatures: raw.seen,
+                raw_win_rate: win_rate(&raw),
+                win_rate: win_rate(&handicapped),
+                raw_median_delay_ms: median_delay(&mut raw),
+                median_delay_ms: median_delay(&mut handicapped),
+            })
+        })
+        .collect()
+}
+
+pub fn print_handicap(reports: &[HandicapReport]) {
+    if reports.is_empty() {
+        return;
+    }
+    println!("\nHandicapped results");
+    println!("--------------------------------------------");
+    for report in reports {
+        println!(
+            "{} ({:+.2} ms): win rate {:.2}% -> {:.2}%, median delay {:.2} ms -> {:.2} ms over {} signatures",
+            report.name,
+            report.handicap_ms,
+            report.raw_win_rate,
+            report.win_rate,
+            report.raw_median_delay_ms,
+            report.median_delay_ms,
+            report.signatures
+        );
+    }
+    println!("Raw results are above; handicaps only shift receive times in this comparison");
+}
+
+// AIDEV-NOTE: Each tag value races as one endpoint that saw a signature at its fastest member's
+// time, and only against values in the same `within` partition, so "which provider wins per
+// region" never compares a provider in one region with another in a different one. Endpoints
+// missing the grouping's tags are left out of it.
+pub fn summarize_tag_groups(comparator: &Comparator, endpoints: &[Endpoint], groupings: &[TagGrouping]) -> Vec<TagGroupingReport> {
+    groupings
+        .iter()
+        .map(|grouping| {
+            let mut partitions: BTreeMap<Option<&str>, BTreeMap<&str, Vec<&str>>> = BTreeMap::new();
+            for endpoint in endpoints {
+                let Some(value) = endpoint.tag_value(&grouping.tag) else {
+                    continue;
+                };
+                let partition = match &grouping.within {
+                    Some(within) => match endpoint.tag_value(within) {
+                        Some(partition) => Some(partition),
+                        None => continue,
+                    },
+                    None => None,
+                };
+                partitions.entry(partition).or_default().entry(value).or_default().push(&endpoint.name);
+            }
+            TagGroupingReport {
+                tag: grouping.tag.clone(),
+                within: grouping.within.clone(),
+                partitions: partitions
+                    .into_iter()
+                    .map(|(partition, groups)| race_tag_groups(comparator, partition, &groups))
+                    .collect(),
+            }
+        })
+        .collect()
+}
+
+fn race_tag_groups(comparator: &Comparator, partition: Option<&str>, groups: &BTreeMap<&str, Vec<&str>>) -> TagPartitionReport {
+    let mut signatures = 0;
+    let mut delays: HashMap<&str, Vec<f64>> = HashMap::new();
+    for sig_data in comparator.data.values().filter(|sig_data| comparator.is_live(sig_data)) {
+        let fastest: Vec<(&str, f64)> = groups
+            .iter()
+            .filter_map(|(value, members)| {
+                let time = members
+                    .iter()
+                    .filter_map(|name| sig_data.get(*name))
+                    .map(|tx| tx.precise_timestamp())
+                    .reduce(f64::min)?;
+                Some((*value, time))
+            })
+            .collect();
+        let Some(first) = fastest.iter().map(|(_, time)| *time).reduce(f64::min) else {
+            continue;
+        };
+        signatures += 1;
+        for (value, time) in fastest {
+            delays.entry(value).or_default().push((time - first) * 1000.0);
+        }
+    }
+
+    TagPartitionReport {
+        value: partition.map(str::to_string),
+        signatures,
+        groups: groups
+            .iter()
+            .map(|(value, members)| {
+                let mut delays = delays.remove(value).unwrap_or_default();
+                delays.sort_by(|a, b| a.total_cmp(b));
+                let first_detections = delays.iter().filter(|delay| **delay == 0.0).count();
+                TagGroupReport {
+                    value: value.to_string(),
+                    endpoints: members.iter().map(|name| name.to_string()).collect(),
+                    seen: delays.len(),
+                    first_detections,
+                    win_rate: if signatures == 0 { 0.0 } else { first_detections as f64 / signatures as f64 * 100.0 },
+                    median_delay_ms: percentile(&delays, 0.5),
+                }
+            })
+            .collect(),
+    }
+}
+
+pub fn print_tag_groups(reports: &[TagGroupingReport]) {
+    if reports.is_empty() {
+        return;
+    }
+    println!("\nResults by tag");
+    println!("--------------------------------------------");
+    for report in reports {
+        for partition in &report.partitions {
+            match (&report.within, &partition.value) {
+                (Some(within), Some(value)) => {
+                    println!("{} within {}={} ({} signatures):", report.tag, within, value, partition.signatures)
+                }
+                _ => println!("{} ({} signatures):", report.tag, partition.signatures),
+            }
+            for group in &partition.groups {
+                println!(
+                    "  {}={} [{}]: win rate {:.2}%, median +{:.2} ms ({} seen)",
+                    report.tag,
+                    group.value,
+                    group.endpoints.join(", "),
+                    group.win_rate,
+                    group.median_delay_ms,
+                    group.seen
+                );
+            }
+        }
+    }
+}
+
+/// Gap between the earliest pre-execution and earliest post-execution sighting of each signature.
+pub fn summarize_execution_gap(comparator: &Comparator, endpoints: &[Endpoint]) -> Option<ExecutionGapReport> {
+    let names = |stage: ExecutionStage| -> Vec<String> {
+        endpoints
+            .iter()
+            .filter(|endpoint| endpoint.kind.execution_stage() == Some(stage))
+            .map(|endpoint| endpoint.name.clone())
+            .collect()
+    };
+    let pre_execution = names(ExecutionStage::PreExecution);
+    let post_execution = names(ExecutionStage::PostExecution);
+    if pre_execution.is_empty() || post_execution.is_empty() {
+        return None;
+    }
+
+    let earliest = |sig_data: &HashMap<String, TransactionData>, side: &[String]| {
+        side.iter()
+            .filter_map(|name| sig_data.get(name))
+            .map(|tx| tx.precise_timestamp())
+            .reduce(f64::min)
+    };
+    let mut gaps: Vec<f64> = comparator
+        .data
+        .values()
+        .filter(|sig_data| comparator.is_live(sig_data))
+        .filter_map(|sig_data| {
+            Some((earliest(sig_data, &post_execution)? - earliest(sig_data, &pre_execution)?) * 1000.0)
+        })
+        .collect();
+    if gaps.is_empty() {
+        return None;
+    }
+    gaps.sort_by(|a, b| a.partial_cmp(b).unwrap());
+
+    Some(ExecutionGapReport {
+        pre_execution,
+        post_execution,
+        signatures: gaps.len(),
+        pre_execution_first: gaps.iter().filter(|gap| **gap > 0.0).count(),
+        median_gap_ms: percentile(&gaps, 0.5),
+        p95_gap_ms: percentile(&gaps, 0.95),
+    })
+}
+
+pub fn print_execution_gap(report: &ExecutionGapReport) {
+    println!("\nPre-execution vs post-execution");
+    println!("--------------------------------------------");
+    println!(
+        "Shred-based ({}) vs executed ({}): {} signatures seen by both",
+        report.pre_execution.join(", "),
+        report.post_execution.join(", "),
+        report.signatures
+    );
+    println!(
+        "Shred-based first in {} ({:.2}%), median head start {:+.2} ms, p95 {:+.2} ms",
+        report.pre_execution_first,
+        report.pre_execution_first as f64 / report.signatures as f64 * 100.0,
+        report.median_gap_ms,
+        report.p95_gap_ms
+    );
+    println!("Shred-based streams deliver transactions before the validator executes them (no status, logs or");
+    println!("balance changes); Yellowstone at processed commitment delivers them after execution. This gap is");
+    println!("the cost of waiting for execution, not a like-for-like comparison between providers.");
+}
+
+const REPORTED_MARKETS: usize = 20;
+const PRINTED_MARKETS: usize = 10;
+
+// AIDEV-NOTE: Markets are ranked by live signature count; each endpoint's numbers cover only the
+// market's signatures it saw, so a provider that is slower on busy pools stands out against its
+// overall delay.
+fn summarize_markets(comparator: &Comparator, endpoint_names: &[String]) -> Vec<MarketReport> {
+    let mut by_market: HashMap<&MarketTag, Vec<&HashMap<String, TransactionData>>> = HashMap::new();
+    for (signature, tag) in &comparator.markets {
+        if let Some(sig_data) = comparator.data.get(signature).filter(|sig_data| comparator.is_live(sig_data)) {
+            by_market.entry(tag).or_default().push(sig_data);
+        }
+    }
+
+    let mut markets: Vec<MarketReport> = by_market
+        .into_iter()
+        .map(|(tag, signatures)| MarketReport {
+            program: tag.program,
+            market: tag.market.clone(),
+            signatures: signatures.len(),
+            endpoints: subset_endpoints(&signatures, endpoint_names),
+        })
+        .collect();
+    markets.sort_by(|a, b| b.signatures.cmp(&a.signatures).then_with(|| a.market.cmp(&b.market)));
+    markets.truncate(REPORTED_MARKETS);
+    markets
+}
+
+/// Each endpoint's first detections and median delay over the given signatures it saw.
+fn subset_endpoints(
+    signatures: &[&HashMap<String, TransactionData>],
+    endpoint_names: &[String],
+) -> Vec<MarketEndpointReport> {
+    endpoint_names
+        .iter()
+        .map(|name| {
+            let mut report = MarketEndpointReport {
+                name: name.clone(),
+                ..Default::default()
+            };
+            let mut delays = Vec::new();
+            for sig_data in signatures {
+                let Some(own) = sig_data.get(name) else {
+                    continue;
+                };
+                let first = sig_data
+                    .values()
+                    .map(|tx| tx.precise_timestamp())
+                    .fold(f64::INFINITY, f64::min);
+                let delay = (own.precise_timestamp() - first) * 1000.0;
+                report.seen += 1;
+                if delay == 0.0 {
+                    report.first_detections += 1;
+                }
+                delays.push(delay);
+            }
+            delays.sort_by(|a, b| a.partial_cmp(b).unwrap());
+            report.median_delay_ms = percentile(&delays, 0.5);
+            report
+        })
+        .collect()
+}
+
+fn summarize_account_targets(comparator: &Comparator, endpoint_names: &[String]) -> Vec<AccountTargetReport> {
+    let mut by_account: HashMap<&str, Vec<&HashMap<String, TransactionData>>> = HashMap::new();
+    for (signature, accounts) in &comparator.signature_accounts {
+        if let Some(sig_data) = comparator.data.get(signature).filter(|sig_data| comparator.is_live(sig_data)) {
+            for account in accounts {
+                by_account.entry(account.as_str()).or_default().push(sig_data);
+            }
+        }
+    }
+
+    comparator
+        .account_targets
+        .iter()
+        .map(|(account, target)| {
+            let signatures = by_account.remove(account.as_str()).unwrap_or_default();
+            AccountTargetReport {
+                account: account.clone(),
+                target: *target,
+                signatures: signatures.len(),
+                endpoints: subset_endpoints(&signatures, endpoint_names),
+            }
+        })
+        .collect()
+}
+
+fn print_account_targets(accounts: &[AccountTargetReport]) {
+    if accounts.is_empty() {
+        return;
+    }
+
+    println!("\nPer-account targets");
+    println!("--------------------------------------------");
+    for account in accounts {
+        let status = if account.signatures as u64 >= account.target { "met" } else { "not met" };
+        println!("{} ({} of {} signatures, {})", account.account, account.signatures, account.target, status);
+        for endpoint in account.endpoints.iter().filter(|endpoint| endpoint.seen > 0) {
+            println!(
+                "  {}: first in {} of {}, median delay {:.2} ms",
+                endpoint.name, endpoint.first_detections, endpoint.seen, endpoint.median_delay_ms
+            );
+        }
+    }
+}
+
+// AIDEV-NOTE: A signature belongs to every group its endpoints tagged it with, and each group's
+// first detection and delays are taken over its own signatures only, so traffic of one program
+// never decides wins in another's. Agent and log-replay sightings carry no groups.
+fn summarize_filter_groups(
+    comparator: &Comparator,
+    endpoint_names: &[String],
+    groups: &BTreeMap<String, Vec<String>>,
+) -> Vec<FilterGroupReport> {
+    let mut by_group: HashMap<&str, Vec<&HashMap<String, TransactionData>>> = HashMap::new();
+    for sig_data in comparator.data.values().filter(|sig_data| comparator.is_live(sig_data)) {
+        let mut tagged: Vec<&str> = sig_data.values().flat_map(|tx| tx.groups.iter().map(String::as_str)).collect();
+        tagged.sort_unstable();
+        tagged.dedup();
+        for group in tagged {
+            by_group.entry(group).or_default().push(sig_data);
+        }
+    }
+
+    groups
+        .iter()
+        .map(|(name, accounts)| {
+            let signatures = by_group.remove(name.as_str()).unwrap_or_default();
+            FilterGroupReport {
+                name: name.clone(),
+                accounts: accounts.clone(),
+                signatures: signatures.len(),
+                endpoints: subset_endpoints(&signatures, endpoint_names),
+            }
+        })
+        .collect()
+}
+
+fn print_filter_groups(groups: &[FilterGroupReport]) {
+    if groups.is_empty() {
+        return;
+    }
+
+    println!("\nFilter groups");
+    println!("--------------------------------------------");
+    for group in groups {
+        println!("{} ({} signatures, {} accounts)", group.name, group.signatures, group.accounts.len());
+        for endpoint in group.endpoints.iter().filter(|endpoint| endpoint.seen > 0) {
+            let win_rate = endpoint.first_detections as f64 / endpoint.seen as f64 * 100.0;
+            println!(
+                "  {}: first in {} of {} ({:.2}%), median delay {:.2} ms",
+                endpoint.name, endpoint.first_detections, endpoint.seen, win_rate, endpoint.median_delay_ms
+            );
+        }
+    }
+}
+
+// AIDEV-NOTE: The range ends at the lowest tip any endpoint reported, so every endpoint was asked
+// for all of it. Completeness is against the union of what the endpoints delivered in the range:
+// nothing tells how many matching signatures the range really holds.
+fn summarize_replay(comparator: &Comparator, endpoint_names: &[String], from_slot: Option<u64>) -> Option<ReplayReport> {
+    let from_slot = from_slot?;
+    let replays: Vec<(&String, &ReplayMetrics)> = endpoint_names
+        .iter()
+        .filter_map(|name| Some((name, comparator.endpoint_metrics.get(name)?.replay.as_ref()?)))
+        .collect();
+    let tip_slot = replays.iter().map(|(_, replay)| replay.tip_slot).min()?;
+    let in_range: Vec<&HashMap<String, TransactionData>> = comparator
+        .data
+        .values()
+        .filter(|sig_data| {
+            sig_data
+                .values()
+                .filter_map(|tx| tx.slot)
+                .min()
+                .is_some_and(|slot| (from_slot..=tip_slot).contains(&slot))
+        })
+        .collect();
+
+    let endpoints = replays
+        .iter()
+        .map(|(name, replay)| {
+            let receipts: Vec<(u64, f64)> = in_range
+                .iter()
+                .filter_map(|sig_data| {
+                    let tx = sig_data.get(*name)?;
+                    Some((tx.slot?, tx.timestamp))
+                })
+                .collect();
+            let signatures = receipts.len();
+            let duration_secs = receipts
+                .iter()
+                .map(|(_, timestamp)| timestamp - replay.subscribed_at)
+                .fold(0.0, f64::max);
+            ReplayEndpointReport {
+                name: (*name).clone(),
+                tip_slot: replay.tip_slot,
+                signatures,
+                completeness: if in_range.is_empty() { 0.0 } else { signatures as f64 / in_range.len() as f64 },
+                first_slot: receipts.iter().map(|(slot, _)| *slot).min(),
+                duration_secs,
+                signatures_per_sec: if duration_secs > 0.0 { signatures as f64 / duration_secs } else { 0.0 },
+            }
+        })
+        .collect();
+
+    Some(ReplayReport {
+        from_slot,
+        tip_slot,
+        signatures: in_range.len(),
+        endpoints,
+    })
+}
+
+fn print_replay(replay: &ReplayReport) {
+    println!("\nReplay");
+    println!("--------------------------------------------");
+    println!(
+        "Slots {} to {}: {} signatures seen by any endpoint",
+        replay.from_slot, replay.tip_slot, replay.signatures
+    );
+    // An endpoint starting later than the others likely did not honour from_slot
+    let earliest = replay.endpoints.iter().filter_map(|endpoint| endpoint.first_slot).min();
+    for endpoint in &replay.endpoints {
+        let late_start = endpoint
+            .first_slot
+            .filter(|slot| Some(*slot) > earliest)
+            .map_or(String::new(), |slot| format!(", first slot {}", slot));
+        println!(
+            "{}: {} signatures ({:.1}% complete) in {:.2}s, {:.1} signatures/s{}",
+            endpoint.name,
+            endpoint.signatures,
+            endpoint.completeness * 100.0,
+            endpoint.duration_secs,
+            endpoint.signatures_per_sec,
+            late_start
+        );
+    }
+}
+
+fn print_markets(markets: &[MarketReport]) {
+    if markets.is_empty() {
+        return;
+    }
+
+    println!("\nBusiest markets");
+    println!("--------------------------------------------");
+    for market in markets.iter().take(PRINTED_MARKETS) {
+        println!("{} {} ({} signatures)", market.program.name(), market.market, market.signatures);
+        for endpoint in market.endpoints.iter().filter(|endpoint| endpoint.seen > 0) {
+            println!(
+                "  {}: first in {} of {}, median delay {:.2} ms",
+                endpoint.name, endpoint.first_detections, endpoint.seen, endpoint.median_delay_ms
+            );
+        }
+    }
+}
+
+const SPIKE_WINDOW_SECS: f64 = 10.0;
+// Windows with fewer delays than this give no meaningful p99
+const SPIKE_MIN_SAMPLES: usize = 5;
+const SPIKE_FACTOR: f64 = 3.0;
+const SPIKE_MIN_EXCESS_MS: f64 = 5.0;
+const CORRELATION_SLACK_SECS: f64 = 5.0;
+
+// AIDEV-NOTE: Delays are bucketed by the first detection time of each signature, so a spike
+// window is when the endpoint fell behind, not when it eventually delivered. A window is a spike
+// when its p99 is SPIKE_FACTOR times the endpoint's median window p99 (and SPIKE_MIN_EXCESS_MS
+// above it, so near-zero baselines don't flag noise); it is annotated only when one of the same
+// endpoint's stream events falls within CORRELATION_SLACK_SECS of it.
+fn summarize_anomalies(comparator: &Comparator, endpoint_names: &[String]) -> Vec<AnomalyAnnotation> {
+    let mut annotations = Vec::new();
+    for endpoint in endpoint_names {
+        let Some(events) = comparator
+            .endpoint_metrics
+            .get(endpoint)
+            .map(|metrics| &metrics.events)
+            .filter(|events| !events.is_empty())
+        else {
+            continue;
+        };
+
+        let mut windows: BTreeMap<i64, Vec<f64>> = BTreeMap::new();
+        for sig_data in comparator.data.values().filter(|sig_data| comparator.is_live(sig_data)) {
+            let Some(own) = sig_data.get(endpoint) else {
+                continue;
+            };
+            let first = sig_data
+                .values()
+                .map(|tx| tx.precise_timestamp())
+                .fold(f64::INFINITY, f64::min);
+            windows
+                .entry((first / SPIKE_WINDOW_SECS).floor() as i64)
+                .or_default()
+                .push((own.precise_timestamp() - first) * 1000.0);
+        }
+
+        let window_p99: Vec<(f64, f64)> = windows
+            .into_iter()
+            .filter(|(_, delays)| delays.len() >= SPIKE_MIN_SAMPLES)
+            .map(|(window, mut delays)| {
+                delays.sort_by(|a, b| a.partial_cmp(b).unwrap());
+                (window as f64 * SPIKE_WINDOW_SECS, percentile(&delays, 0.99))
+            })
+            .collect();
+        if window_p99.len() < 2 {
+            continue;
+        }
+        let mut sorted_p99: Vec<f64> = window_p99.iter().map(|(_, p99)| *p99).collect();
+        sorted_p99.sort_by(|a, b| a.partial_cmp(b).unwrap());
+        let baseline = percentile(&sorted_p99, 0.5);
+
+        for (window_start, p99) in window_p99 {
+            if p99 < baseline * SPIKE_FACTOR || p99 < baseline + SPIKE_MIN_EXCESS_MS {
+                continue;
+            }
+            let window_end = window_start + SPIKE_WINDOW_SECS;
+            let Some(event) = events.iter().find(|event| {
+                event.at - CORRELATION_SLACK_SECS < window_end
+                    && event.at + event.duration_secs + CORRELATION_SLACK_SECS > window_start
+            }) else {
+                continue;
+            };
+            let cause = match event.kind {
+                StreamEventKind::Stall => format!("a {:.1}s stall starting", event.duration_secs),
+                StreamEventKind::Downtime => format!("{:.1}s of downtime starting", event.duration_secs),
+                StreamEventKind::Disconnect => format!(
+                    "a disconnect ({})",
+                    event.detail.as_deref().unwrap_or("no reason given")
+                ),
+            };
+            annotations.push(AnomalyAnnotation {
+                endpoint: endpoint.clone(),
+                at: window_start,
+                p99_ms: p99,
+                baseline_p99_ms: baseline,
+                event: event.kind,
+                event_at: event.at,
+                message: format!(
+                    "{}'s p99 spike to {:.2} ms at {} (baseline {:.2} ms) coincides with {} at {}",
+                    endpoint,
+                    p99,
+                    utc_time(window_start),
+                    baseline,
+                    cause,
+                    utc_time(event.at)
+                ),
+            });
+        }
+    }
+    annotations
+}
+
+fn print_anomalies(comparator: &Comparator, endpoint_names: &[String], anomalies: &[AnomalyAnnotation]) {
+    let events: usize = endpoint_names
+        .iter()
+        .filter_map(|name| comparator.endpoint_metrics.get(name))
+        .map(|metrics| metrics.events.len())
+        .sum();
+    if events == 0 {
+        return;
+    }
+
+    println!("\nAnomalies");
+    println!("--------------------------------------------");
+    for anomaly in anomalies {
+        println!("{}", anomaly.message);
+    }
+    if anomalies.is_empty() {
+        println!("{} stream stalls/disconnects, none coinciding with a latency spike", events);
+    }
+}
+
+const LAGGING_SLOTS: usize = 10;
+
+const ACCOUNT_MISMATCH_EXAMPLES: usize = 10;
+
+// AIDEV-NOTE: The largest payload is taken as complete, so any endpoint delivering fewer bytes for
+// the same write counts as short; equal sizes with different bytes are mismatches without a culprit
+// unless a strict majority of endpoints delivered the same content, which then outvotes the rest.
+// Data and meta (lamports, owner, executable, rent epoch) are hashed separately so a stale balance
+// shows up even when the data agrees.
+fn summarize_account_integrity(
+    comparator: &Comparator,
+    mut diff_rows: Option<&mut Vec<AccountDiffRow>>,
+) -> Option<AccountIntegrityReport> {
+    let mut report = AccountIntegrityReport::default();
+    for ((pubkey, write_version), writes) in &comparator.account_writes {
+        if writes.len() < 2 {
+            continue;
+        }
+        report.compared += 1;
+
+        let max_len = writes.values().map(|write| write.len).max().unwrap_or(0);
+        let same_size = writes.values().all(|write| write.len == max_len);
+        let Some(first) = writes.values().next().copied() else {
+            continue;
+        };
+        let data_agrees = same_size && writes.values().all(|write| write.hash == first.hash);
+        let meta_agrees = writes.values().all(|write| write.meta_hash == first.meta_hash);
+        if data_agrees && meta_agrees {
+            continue;
+        }
+
+        report.mismatches += 1;
+        if !meta_agrees {
+            report.meta_mismatches += 1;
+        }
+        for (endpoint, write) in writes {
+            if write.len < max_len {
+                *report.short_by_endpoint.entry(endpoint.clone()).or_default() += 1;
+            }
+        }
+
+        let mut votes: HashMap<(u64, u64), usize> = HashMap::new();
+        for write in writes.values() {
+            *votes.entry((write.hash, write.meta_hash)).or_default() += 1;
+        }
+        let majority = votes
+            .into_iter()
+            .max_by_key(|(_, count)| *count)
+            .filter(|(_, count)| count * 2 > writes.len())
+            .map(|(content, _)| content);
+        if let Some(majority) = majority {
+            for (endpoint, write) in writes {
+                if (write.hash, write.meta_hash) != majority {
+                    *report.outvoted_by_endpoint.entry(endpoint.clone()).or_default() += 1;
+                }
+            }
+        }
+        if let Some(rows) = diff_rows.as_deref_mut() {
+            rows.extend(writes.iter().map(|(endpoint, write)| AccountDiffRow {
+                pubkey: pubkey.clone(),
+                write_version: *write_version,
+                endpoint: endpoint.clone(),
+                len: write.len,
+                data_hash: write.hash,
+                meta_hash: write.meta_hash,
+                agrees_with_majority: majority.map(|majority| (write.hash, write.meta_hash) == majority),
+            }));
+        }
+
+        if report.examples.len() < ACCOUNT_MISMATCH_EXAMPLES {
+            report.examples.push(AccountMismatch {
+                pubkey: pubkey.clone(),
+                write_version: *write_version,
+                sizes: writes.iter().map(|(endpoint, write)| (endpoint.clone(), write.len)).collect(),
+                same_size: same_size && !data_agrees,
+                meta_differs: !meta_agrees,
+            });
+        }
+    }
+    (report.compared > 0).then_some(report)
+}
+
+// AIDEV-NOTE: Sliced streams never see an account's whole size, so savings are only estimated for
+// accounts whose size is known otherwise: every account matching a `datasize` filter has that size,
+// and an endpoint without slices reports the real one.
+fn summarize_data_slices(comparator: &Comparator, endpoint_names: &[String], config: &Config) -> Vec<DataSliceReport> {
+    let filter_size = config.accounts_filter.as_ref().and_then(|filter| {
+        filter.filters.iter().find_map(|rule| match rule {
+            AccountsFilterRule::Datasize { size } => Some(*size),
+            AccountsFilterRule::Memcmp { .. } => None,
+        })
+    });
+    endpoint_names
+        .iter()
+        .filter_map(|name| {
+            let accounts = comparator.sliced_account_updates.get(name)?;
+            let mut report = DataSliceReport {
+                name: name.clone(),
+                ..Default::default()
+            };
+            let mut sized_delivered = 0;
+            for (pubkey, (updates, delivered)) in accounts {
+                report.updates += updates;
+                report.delivered_bytes += delivered;
+                let full_size = comparator.account_full_sizes.get(pubkey).map(|size| *size as u64).or(filter_size);
+                if let Some(full_size) = full_size {
+                    report.sized_updates += updates;
+                    report.full_bytes += full_size * updates;
+                    sized_delivered += delivered;
+                }
+            }
+            report.saved_bytes = report.full_bytes.saturating_sub(sized_delivered);
+            Some(report)
+        })
+        .collect()
+}
+
+fn print_data_slices(reports: &[DataSliceReport]) {
+    if reports.is_empty() {
+        return;
+    }
+    println!("\nAccount data slices");
+    println!("--------------------------------------------");
+    for report in reports {
+        let delivered_kib = report.delivered_bytes as f64 / 1024.0;
+        if report.sized_updates == 0 {
+            println!(
+                "{}: {} updates, {:.1} KiB delivered; whole sizes unknown (add a datasize filter or an endpoint with accounts_data_slice = [])",
+                report.name, report.updates, delivered_kib
+            );
+            continue;
+        }
+        println!(
+            "{}: {} updates, {:.1} KiB delivered, {:.1} KiB saved ({:.1}% of {:.1} KiB) over {} updates of known size",
+            report.name,
+            report.updates,
+            delivered_kib,
+            report.saved_bytes as f64 / 1024.0,
+            report.saved_bytes as f64 / report.full_bytes.max(1) as f64 * 100.0,
+            report.full_bytes as f64 / 1024.0,
+            report.sized_updates
+        );
+    }
+}
+
+fn print_account_integrity(report: &AccountIntegrityReport) {
+    if report.mismatches == 0 {
+        println!(
+            "\nAccount data integrity: {} writes compared, all payloads identical",
+            report.compared
+        );
+        return;
+    }
+
+    println!("\nWARNING: account data integrity");
+    println!("--------------------------------------------");
+    println!(
+        "{} of {} writes seen by several endpoints had differing payloads",
+        report.mismatches, report.compared
+    );
+    if report.meta_mismatches > 0 {
+        println!("{} of them disagreed on lamports, owner, executable or rent epoch", report.meta_mismatches);
+    }
+    for (endpoint, short) in &report.short_by_endpoint {
+        println!("{}: {} writes shorter than another endpoint's", endpoint, short);
+    }
+    for (endpoint, outvoted) in &report.outvoted_by_endpoint {
+        println!("{}: {} writes differing from the majority of endpoints", endpoint, outvoted);
+    }
+    for example in &report.examples {
+        let sizes: Vec<String> = example
+            .sizes
+            .iter()
+            .map(|(endpoint, len)| format!("{} {} B", endpoint, len))
+            .collect();
+        println!(
+            "  {} v{}: {}{}{}",
+            example.pubkey,
+            example.write_version,
+            sizes.join(", "),
+            if example.same_size { " (same size, different bytes)" } else { "" },
+            if example.meta_differs { " (lamports, owner or rent epoch differ)" } else { "" }
+        );
+    }
+}
+
+fn print_block_sizes(comparator: &Comparator, endpoint_names: &[String]) {
+    let sized: Vec<_> = endpoint_names
+        .iter()
+        .filter_map(|name| Some((name, comparator.endpoint_metrics.get(name)?.block_sizes.as_ref()?)))
+        .collect();
+    if sized.is_empty() {
+        return;
+    }
+
+    println!("\nBlock sizes");
+    println!("--------------------------------------------");
+    for (name, sizes) in sized {
+        println!(
+            "{}: {} blocks, min {} B, avg {:.0} B, max {} B, avg {:.0} transactions (max {})",
+            name,
+            sizes.blocks,
+            sizes.min_bytes,
+            sizes.avg_bytes(),
+            sizes.max_bytes,
+            sizes.avg_transactions(),
+            sizes.max_transactions
+        );
+    }
+}
+
+fn print_account_sizes(comparator: &Comparator, endpoint_names: &[String]) {
+    let sized: Vec<_> = endpoint_names
+        .iter()
+        .filter_map(|name| Some((name, comparator.endpoint_metrics.get(name)?.account_sizes.as_ref()?)))
+        .collect();
+    if sized.is_empty() {
+        return;
+    }
+
+    println!("\nAccount update sizes");
+    println!("--------------------------------------------");
+    for (name, sizes) in sized {
+        println!(
+            "{}: {} updates, min {} B, avg {:.0} B, max {} B",
+            name,
+            sizes.updates,
+            sizes.min_bytes,
+            sizes.avg_bytes(),
+            sizes.max_bytes
+        );
+    }
+}
+
+// AIDEV-NOTE: Windows are aligned on the earliest live sighting of the run, so every pair's
+// heatmap row shares the same columns; a signature falls in the window of the pair's first sighting.
+fn summarize_pairs(comparator: &Comparator, endpoint_names: &[String], window_secs: u64) -> Vec<PairReport> {
+    let run_start = comparator
+        .data
+        .values()
+        .filter(|sig_data| comparator.is_live(sig_data))
+        .flat_map(|sig_data| sig_data.values().map(|tx| tx.precise_timestamp()))
+        .fold(f64::INFINITY, f64::min);
+
+    let mut pairs = Vec::new();
+    for (i, first) in endpoint_names.iter().enumerate() {
+        for second in &endpoint_names[i + 1..] {
+            let sightings: Vec<(f64, f64)> = comparator
+                .data
+                .values()
+                .filter(|sig_data| comparator.is_live(sig_data))
+                .filter_map(|sig_data| {
+                    let a = sig_data.get(first)?.precise_timestamp();
+                    let b = sig_data.get(second)?.precise_timestamp();
+                    Some((a.min(b), (b - a) * 1000.0))
+                })
+                .collect();
+            if sightings.is_empty() {
+                continue;
+            }
+            let mut deltas: Vec<f64> = sightings.iter().map(|(_, delta)| *delta).collect();
+            deltas.sort_by(|a, b| a.partial_cmp(b).unwrap());
+            let p_value = wilcoxon_signed_rank(&deltas);
+            pairs.push(PairReport {
+                first: first.clone(),
+                second: second.clone(),
+                signatures: deltas.len(),
+                first_wins: deltas.iter().filter(|delta| **delta > 0.0).count(),
+                median_delta_ms: percentile(&deltas, 0.5),
+                median_delta_ci_ms: median_confidence_interval(&deltas),
+                p_value,
+                significant: p_value.is_some_and(|p| p < SIGNIFICANCE_LEVEL),
+                windows: pair_windows(&sightings, run_start, window_secs),
+            });
+        }
+    }
+    pairs
+}
+
+fn pair_windows(sightings: &[(f64, f64)], run_start: f64, window_secs: u64) -> Vec<PairWindow> {
+    if window_secs == 0 {
+        return Vec::new();
+    }
+    let width = window_secs as f64;
+    let mut windows: BTreeMap<u64, Vec<f64>> = BTreeMap::new();
+    for (timestamp, delta) in sightings {
+        let index = ((timestamp - run_start) / width) as u64;
+        windows.entry(index).or_default().push(*delta);
+    }
+    windows
+        .into_iter()
+        .map(|(index, mut deltas)| {
+            deltas.sort_by(|a, b| a.partial_cmp(b).unwrap());
+            PairWindow {
+                start: run_start + index as f64 * width,
+                signatures: deltas.len(),
+                median_delta_ms: percentile(&deltas, 0.5),
+            }
+        })
+        .collect()
+}
+
+fn print_pairs(pairs: &[PairReport], window_secs: u64) {
+    // A single pair is printed too: its significance is not in the summary above
+    if pairs.is_empty() {
+        return;
+    }
+
+    println!("\nHead-to-head");
+    println!("--------------------------------------------");
+    for pair in pairs {
+        // Furthest from the run-wide median: the window a single number hides best
+        let outlier = pair.windows.iter().filter(|window| window.signatures > 1).max_by(|a, b| {
+            (a.median_delta_ms - pair.median_delta_ms)
+                .abs()
+                .partial_cmp(&(b.median_delta_ms - pair.median_delta_ms).abs())
+                .unwrap()
+        });
+        let outlier = outlier.map_or(String::new(), |window| {
+            format!(
+                ", most divergent {}s window {:+.2} ms at {}",
+                window_secs,
+                window.median_delta_ms,
+                utc_time(window.start)
+            )
+        });
+        let ci = pair
+            .median_delta_ci_ms
+            .map_or(String::new(), |(low, high)| format!(" (95% CI {:+.2} to {:+.2})", low, high));
+        let significance = match pair.p_value {
+            Some(p) if pair.significant => format!("significant, p = {:.3}", p),
+            Some(p) => format!("no significant difference, p = {:.3}", p),
+            None => "no difference".to_string(),
+        };
+        println!(
+            "{} vs {}: {} signatures, {} ahead in {}, median delta {:+.2} ms{} [{}]{}",
+            pair.first,
+            pair.second,
+            pair.signatures,
+            pair.first,
+            pair.first_wins,
+            pair.median_delta_ms,
+            ci,
+            significance,
+            outlier
+        );
+    }
+}
+
+// AIDEV-NOTE: Delays are averaged per endpoint over the signatures it saw in the slot, wins
+// counting as zero, so a slot where one endpoint fell behind stands out against its neighbours.
+const SLOT_LAG_WINDOW_SECS: f64 = 60.0;
+
+// AIDEV-NOTE: The tip is a proxy: the highest slot any endpoint had delivered by the time of each
+// sighting, replayed in receive order. Only transactions touching the watched accounts carry slots,
+// so on quiet accounts the tip moves in jumps and lags read low.
+fn summarize_slot_lag(comparator: &Comparator, endpoint_names: &[String]) -> Vec<SlotLagReport> {
+    let mut sightings: Vec<(f64, &str, u64)> = comparator
+        .data
+        .values()
+        .filter(|sig_data| comparator.is_live(sig_data))
+        .flat_map(|sig_data| {
+            sig_data
+                .iter()
+                .filter_map(|(endpoint, tx)| Some((tx.precise_timestamp(), endpoint.as_str(), tx.slot?)))
+        })
+        .collect();
+    if sightings.is_empty() {
+        return Vec::new();
+    }
+    sightings.sort_by(|a, b| a.0.partial_cmp(&b.0).unwrap());
+    let run_start = sightings[0].0;
+
+    let mut tip = 0;
+    let mut lags: HashMap<&str, Vec<(f64, u64)>> = HashMap::new();
+    for (timestamp, endpoint, slot) in sightings {
+        tip = tip.max(slot);
+        lags.entry(endpoint).or_default().push((timestamp, tip - slot));
+    }
+
+    endpoint_names
+        .iter()
+        .filter_map(|name| {
+            let samples = lags.get(name.as_str())?;
+            let mut sorted: Vec<f64> = samples.iter().map(|(_, lag)| *lag as f64).collect();
+            sorted.sort_by(|a, b| a.partial_cmp(b).unwrap());
+
+            let mut windows: BTreeMap<u64, (u64, usize, u64)> = BTreeMap::new();
+            for (timestamp, lag) in samples {
+                let index = ((timestamp - run_start) / SLOT_LAG_WINDOW_SECS) as u64;
+                let (sum, count, max) = windows.entry(index).or_default();
+                *sum += lag;
+                *count += 1;
+                *max = (*max).max(*lag);
+            }
+
+            Some(SlotLagReport {
+                name: name.clone(),
+                samples: samples.len(),
+                avg_slots: sorted.iter().sum::<f64>() / sorted.len() as f64,
+                p50_slots: percentile(&sorted, 0.5),
+                p95_slots: percentile(&sorted, 0.95),
+                max_slots: samples.iter().map(|(_, lag)| *lag).max().unwrap_or(0),
+                windows: windows
+                    .into_iter()
+                    .map(|(index, (sum, count, max))| SlotLagWindow {
+                        start: run_start + index as f64 * SLOT_LAG_WINDOW_SECS,
+                        avg_slots: sum as f64 / count as f64,
+                        max_slots: max,
+                    })
+                    .collect(),
+            })
+        })
+        .collect()
+}
+
+fn print_slot_lag(reports: &[SlotLagReport]) {
+    if reports.is_empty() {
+        return;
+    }
+
+    println!("\nSlot lag behind tip");
+    println!("--------------------------------------------");
+    for report in reports {
+        let worst_window = report
+            .windows
+            .iter()
+            .max_by(|a, b| a.avg_slots.partial_cmp(&b.avg_slots).unwrap());
+        let worst = worst_window.map_or(String::new(), |window| {
+            format!(", worst minute avg {:.2} at {}", window.avg_slots, utc_time(window.start))
+        });
+        println!(
+            "{}: avg {:.2} slots, p50 {:.0}, p95 {:.0}, max {} ({} sightings){}",
+            report.name,
+            report.avg_slots,
+            report.p50_slots,
+            report.p95_slots,
+            report.max_slots,
+            report.samples,
+            worst
+        );
+    }
+}
+
+// Per slot: the report being built plus each endpoint's (delay sum, count)
+type SlotAccumulator = (SlotReport, HashMap<String, (f64, usize)>);
+
+fn summarize_slots(comparator: &Comparator) -> Vec<SlotReport> {
+    let mut slots: BTreeMap<u64, SlotAccumulator> = BTreeMap::new();
+    for sig_data in comparator.data.values().filter(|sig_data| comparator.is_live(sig_data)) {
+        let Some(slot) = sig_data.values().filter_map(|tx| tx.slot).min() else {
+            continue;
+        };
+        let first_timestamp = sig_data
+            .values()
+            .map(|tx| tx.precise_timestamp())
+            .fold(f64::INFINITY, f64::min);
+
+        let (report, delays) = slots.entry(slot).or_insert_with(|| {
+            (
+                SlotReport {
+                    slot,
+                    ..Default::default()
+                },
+                HashMap::new(),
+            )
+        });
+        report.signatures += 1;
+        for (endpoint, tx) in sig_data {
+            let delay_ms = (tx.precise_timestamp() - first_timestamp) * 1000.0;
+            if delay_ms == 0.0 {
+                *report.first_detections.entry(endpoint.clone()).or_default() += 1;
+            }
+            let (sum, count) = delays.entry(endpoint.clone()).or_default();
+            *sum += delay_ms;
+            *count += 1;
+        }
+    }
+
+    slots
+        .into_values()
+        .map(|(mut report, delays)| {
+            for (endpoint, (sum, count)) in delays {
+                let avg = sum / count as f64;
+                if report.worst_endpoint.is_none() || avg > report.worst_avg_delay_ms {
+                    report.worst_endpoint = Some(endpoint.clone());
+                    report.worst_avg_delay_ms = avg;
+                }
+                report.avg_delay_ms.insert(endpoint, avg);
+            }
+            report
+        })
+        .collect()
+}
+
+fn print_lagging_slots(slots: &[SlotReport]) {
+    let mut lagging: Vec<&SlotReport> = slots.iter().filter(|slot| slot.worst_avg_delay_ms > 0.0).collect();
+    if lagging.is_empty() {
+        return;
+    }
+    lagging.sort_by(|a, b| b.worst_avg_delay_ms.partial_cmp(&a.worst_avg_delay_ms).unwrap());
+
+    println!(
+        "\nSlots with the largest lag ({} of {} slots)",
+        lagging.len().min(LAGGING_SLOTS),
+        slots.len()
+    );
+    println!("--------------------------------------------");
+    for slot in lagging.into_iter().take(LAGGING_SLOTS) {
+        let wins: Vec<String> = slot
+            .first_detections
+            .iter()
+            .map(|(endpoint, wins)| format!("{} {}", endpoint, wins))
+            .collect();
+        println!(
+            "Slot {}: {} signatures, {} behind by {:.2} ms avg (first: {})",
+            slot.slot,
+            slot.signatures,
+            slot.worst_endpoint.as_deref().unwrap_or("-"),
+            slot.worst_avg_delay_ms,
+            wins.join(", ")
+        );
+    }
+}
+
+const STATUS_EXAMPLES: usize = 10;
+
+fn summarize_status_checks(comparator: &Comparator) -> Vec<StatusPairReport> {
+    let mut pairs: BTreeMap<(String, String), StatusPairReport> = BTreeMap::new();
+    for check in comparator.status_checks() {
+        let pair = pairs
+            .entry((check.first.clone(), check.second.clone()))
+            .or_insert_with(|| StatusPairReport {
+                first: check.first.clone(),
+                second: check.second.clone(),
+                ..Default::default()
+            });
+        pair.compared += 1;
+        if !check.agrees {
+            pair.disagreements += 1;
+            if pair.examples.len() < STATUS_EXAMPLES {
+                pair.examples.push(StatusDisagreement {
+                    signature: check.signature,
+                    first_status: check.first_status,
+                    second_status: check.second_status,
+                });
+            }
+        }
+    }
+    pairs.into_values().collect()
+}
+
+fn print_status_checks(pairs: &[StatusPairReport]) {
+    if pairs.is_empty() {
+        return;
+    }
+
+    println!("\nTransaction status consistency");
+    println!("--------------------------------------------");
+    for pair in pairs {
+        println!(
+            "{} vs {}: {} disagreements out of {} compared",
+            pair.first, pair.second, pair.disagreements, pair.compared
+        );
+        for example in &pair.examples {
+            println!(
+                "  {}: {} failed={} fee={:?} cu={:?} / {} failed={} fee={:?} cu={:?}",
+                example.signature,
+                pair.first,
+                example.first_status.failed,
+                example.first_status.fee,
+                example.first_status.compute_units,
+                pair.second,
+                example.second_status.failed,
+                example.second_status.fee,
+                example.second_status.compute_units
+            );
+        }
+    }
+}
+
+fn print_race_table(races: &[RaceResult], total_races: usize) {
+    println!("\nLargest first-seen margins ({} of {} raced signatures)", races.len(), total_races);
+    println!("--------------------------------------------");
+    for race in races {
+        let winner = if race.tied_with.is_empty() {
+            race.winner.clone()
+        } else {
+            format!("{} (tied with {})", race.winner, race.tied_with.join(", "))
+        };
+        match &race.runner_up {
+            Some(runner_up) => println!(
+                "{}: {} beat {} by {:.2} ms ({} endpoints)",
+                race.signature, winner, runner_up, race.margin_ms, race.endpoints
+            ),
+            None => println!("{}: {}, all {} endpoints tied", race.signature, winner, race.endpoints),
+        }
+    }
+}
+
+fn print_frame_timing(comparator: &Comparator, endpoint_names: &[String]) {
+    let timed: Vec<_> = endpoint_names
+        .iter()
+        .filter_map(|name| {
+            let metrics = comparator.endpoint_metrics.get(name)?;
+            Some((name, metrics.frame_timing.as_ref()?))
+        })
+        .collect();
+    if timed.is_empty() {
+        return;
+    }
+
+    println!("\nMessage assembly (first frame to complete message)");
+    println!("--------------------------------------------");
+    for (name, timing) in timed {
+        println!(
+            "{}: {} messages, {:.2} MB, {} large messages",
+            name,
+            timing.messages,
+            timing.bytes as f64 / 1_000_000.0,
+            timing.large_messages
+        );
+        if timing.large_messages > 0 {
+            println!(
+                "  Assembly avg/p95/max: {:.2}/{:.2}/{:.2} ms",
+                timing.avg_assembly_ms, timing.p95_assembly_ms, timing.max_assembly_ms
+            );
+        }
+    }
+}
+
+fn print_startup(comparator: &Comparator, endpoint_names: &[String]) {
+    let timed: Vec<_> = endpoint_names
+        .iter()
+        .filter_map(|name| Some((name, comparator.endpoint_metrics.get(name)?.startup.as_ref()?)))
+        .collect();
+    if timed.is_empty() {
+        return;
+    }
+
+    let fmt = |ms: Option<f64>| ms.map_or("-".to_string(), |ms| format!("{:.2} ms", ms));
+    println!("\nStream startup");
+    println!("--------------------------------------------");
+    for (name, startup) in timed {
+        println!(
+            "{}: connect {:.2} ms, first update {}, first match {}",
+            name,
+            startup.connect_ms,
+            fmt(startup.first_update_ms),
+            fmt(startup.first_match_ms)
+        );
+    }
+}
+
+fn print_connections(comparator: &Comparator, endpoint_names: &[String]) {
+    let inspected: Vec<_> = endpoint_names
+        .iter()
+        .filter_map(|name| Some((name, comparator.endpoint_metrics.get(name)?.connection.as_ref()?)))
+        .collect();
+    if inspected.is_empty() {
+        return;
+    }
+
+    println!("\nConnections");
+    println!("--------------------------------------------");
+    for (name, connection) in inspected {
+        let peer = connection.peer_ip.as_deref().unwrap_or("-");
+        let protocol = match (&connection.tls_version, &connection.alpn) {
+            (Some(version), Some(alpn)) => format!("{} {}", version, alpn),
+            (Some(version), None) => format!("{} (no ALPN)", version),
+            (None, _) => "plaintext".to_string(),
+        };
+        let settings = connection
+            .http2_settings
+            .as_ref()
+            .map_or("-".to_string(), |settings| settings.describe());
+        println!("{}: {} ({}), {}, {}", name, connection.host, peer, protocol, settings);
+        if connection.resolved_ips.len() > 1 {
+            println!("  Resolved to {}", connection.resolved_ips.join(", "));
+        }
+        if connection.downgraded() {
+            println!("  Server did not negotiate h2");
+        }
+        if let Some(error) = &connection.error {
+            println!("  Inspection failed: {}", error);
+        }
+    }
+}
+
+fn print_shards(comparator: &Comparator, endpoint_names: &[String]) {
+    let sharded: Vec<_> = endpoint_names
+        .iter()
+        .filter_map(|name| {
+            let metrics = comparator.endpoint_metrics.get(name)?;
+            (!metrics.shards.is_empty()).then_some((name, &metrics.shards))
+        })
+        .collect();
+    if sharded.is_empty() {
+        return;
+    }
+
+    println!("\nShard throughput");
+    println!("--------------------------------------------");
+    for (name, shards) in sharded {
+        println!("{}:", name);
+        for shard in shards {
+            println!(
+                "  Shard {}: {} accounts, {} messages ({:.1}/s), {} matched",
+                shard.shard, shard.accounts, shard.messages, shard.messages_per_sec, shard.matched
+            );
+        }
+    }
+}
+
+fn print_slo(endpoints: &[EndpointReport]) {
+    if endpoints.iter().all(|endpoint| endpoint.slo.is_empty()) {
+        return;
+    }
+
+    println!("\nSLO targets");
+    println!("--------------------------------------------");
+    for endpoint in endpoints {
+        let failed = endpoint.slo.iter().filter(|result| !result.pass).count();
+        println!(
+            "{}: {}",
+            endpoint.name,
+            if failed == 0 { "PASS".to_string() } else { format!("FAIL ({} of {})", failed, endpoint.slo.len()) }
+        );
+        for result in endpoint.slo.iter().filter(|result| !result.pass) {
+            println!("  {} (got {:.2})", result.label(), result.value.unwrap_or_default());
+        }
+    }
+}
+
+fn print_entry_decode(comparator: &Comparator, endpoint_names: &[String]) {
+    let decoded: Vec<_> = endpoint_names
+        .iter()
+        .filter_map(|name| {
+            let metrics = comparator.endpoint_metrics.get(name)?;
+            let stats = metrics.entry_decode.as_ref()?;
+            (stats.failed_batches > 0).then_some((name, stats))
+        })
+        .collect();
+    if decoded.is_empty() {
+        return;
+    }
+
+    println!("\nEntry decoding failures");
+    println!("--------------------------------------------");
+    for (name, stats) in decoded {
+        println!(
+            "{}: {} of {} batches failed ({} truncated, {} unknown version, {} malformed)",
+            name, stats.failed_batches, stats.batches, stats.truncated, stats.unknown_versions, stats.malformed
+        );
+        println!(
+            "  Partially recovered: {} batches, {} entries recovered, {} entries lost",
+            stats.partial_batches, stats.recovered_entries, stats.lost_entries
+        );
+    }
+}
@@ -0,0 +1,317 @@
+//! The `geyserbench` command line: subcommands, repeated and continuous runs, and the report files
+//! written after them. The binary only sets up logging and the allocator before calling [`run_cli`].
+
+use std::{
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+    time::Duration,
+};
+
+use clap::Parser;
+use tokio::sync::{broadcast, watch};
+
+use crate::{
+    assertions,
+    cli::{Args, Command},
+    config, coordinator, diff, html_report, notifier, probe, reload, repeat, replay, report, retention, runner, signals,
+    signing,
+};
+
+/// Runs the `geyserbench` command line: parses the arguments and runs the subcommand or benchmark.
+pub async fn run_cli() -> Result<(), Box<dyn std::error::Error>> {
+    let mut args = Args::parse();
+    if let Some(command) = args.command.take() {
+        return match command {
+            Command::Verify { report, pubkey } => signing::verify_file(&report, pubkey.as_deref()),
+            Command::Diff { before, after } => diff::diff_files(&before, &after),
+            Command::Clean { keep_days, dir, dry_run } => {
+                let summary = retention::clean(std::path::Path::new(&dir), keep_days, dry_run)?;
+                retention::print_summary(&summary, keep_days, dry_run);
+                Ok(())
+            }
+            Command::Analyze { dir, since, until, report } => {
+                // Without a config file the statistics use the default trim and tie settings
+                let config = if std::path::Path::new(&args.config).exists() {
+                    config::ConfigToml::load(&args.config)?.config
+                } else {
+                    replay::default_config()
+                };
+                replay::analyze_logs(std::path::Path::new(&dir), &config, since, until, report.as_deref())?;
+                Ok(())
+            }
+            Command::Coordinate { listen, report } => {
+                // Endpoints in the config are ignored; the target, drain and analysis settings apply
+                let config = config::ConfigToml::load(&args.config)?;
+                coordinator::run_coordinator(&config, &listen, report.as_deref()).await?;
+                Ok(())
+            }
+            Command::Probe { url, token, account, duration_secs } => {
+                run_probe(&args.config, &url, &token, account, duration_secs).await
+            }
+        };
+    }
+
+    // Printed with Display rather than returned, so parser messages keep their line breaks and carets
+    let mut config = match config::ConfigToml::load_or_create(&args.config) {
+        Ok(config) => config,
+        Err(e) => {
+            eprintln!("Invalid configuration: {}", e);
+            std::process::exit(1);
+        }
+    };
+    config = apply_args(&args, config)?;
+    let assertions = assertions::parse_all(&args.assertions)?;
+    log::info!("Loaded configuration");
+    // AIDEV-NOTE: Printed after defaults are applied so the banner shows what actually runs
+    println!("Effective configuration ({}):", args.config);
+    println!("--------------------------------------------");
+    println!("{}", toml::to_string_pretty(&config.redacted())?);
+
+    // Only stop signals go through this channel; each run gets its own, fed from it, so stops a
+    // run raises itself (max_runtime_secs, the sender, the target) never reach the next run or pause
+    let (signal_tx, _) = broadcast::channel::<()>(1);
+    let interrupted = Arc::new(AtomicBool::new(false));
+    let handler_tx = signal_tx.clone();
+    let signal_flag = interrupted.clone();
+    signals::spawn_handler(move |_| {
+        signal_flag.store(true, Ordering::Relaxed);
+        let _ = handler_tx.send(());
+    });
+
+    // AIDEV-NOTE: Each repeated run gets its own numbered report and assertions; the last run's
+    // report carries the cross-run statistics and is the one written to report_path, rendered
+    // and sent. Ctrl+C ends the current run or pause and skips the rest. `--continuous` repeats
+    // until stopped: config file changes are applied to the running benchmark, a failed run is
+    // logged and the next one started, only the last CONTINUOUS_KEPT_RUNS reports are kept (the
+    // numbered files roll over), and every run's report is written and sent as it finishes.
+    let repeat = if args.continuous { usize::MAX } else { args.repeat as usize };
+    let mut updates = args.continuous.then(|| spawn_config_watcher(&args, config.clone()));
+    let mut reports = Vec::new();
+    let mut run = None;
+    for index in 0..repeat {
+        if index > 0 {
+            if interrupted.load(Ordering::Relaxed) {
+                break;
+            }
+            let mut pause_rx = signal_tx.subscribe();
+            if args.continuous {
+                println!("\nRun {} in {:.1}s", index + 1, args.pause.as_secs_f64());
+            } else {
+                println!("\nRun {} of {} in {:.1}s", index + 1, repeat, args.pause.as_secs_f64());
+            }
+            tokio::select! {
+                _ = tokio::time::sleep(args.pause) => {}
+                _ = pause_rx.recv() => break,
+            }
+        }
+        if let Some(updates) = &mut updates {
+            config = updates.borrow_and_update().config.clone();
+        }
+        let (shutdown_tx, _) = broadcast::channel::<()>(1);
+        let mut forward_rx = signal_tx.subscribe();
+        let forward_tx = shutdown_tx.clone();
+        let forward = tokio::spawn(async move {
+            if forward_rx.recv().await.is_ok() {
+                let _ = forward_tx.send(());
+            }
+        });
+        let result = match &updates {
+            Some(updates) => runner::run_benchmark_with_reload(&config, shutdown_tx, updates.clone()).await,
+            None => runner::run_benchmark_with_shutdown(&config, shutdown_tx).await,
+        };
+        forward.abort();
+        let mut current = match result {
+            Ok(current) => current,
+            Err(e) if args.continuous => {
+                log::error!("Run {} failed, continuing with the next: {}", index + 1, e);
+                continue;
+            }
+            Err(e) => return Err(e as Box<dyn std::error::Error>),
+        };
+        let mut report = std::mem::take(&mut current.report);
+        if report.stop_reason.is_none() && interrupted.load(Ordering::Relaxed) {
+            report.stop_reason = Some("interrupted".to_string());
+        }
+        report.config_generation = updates.as_ref().map(|updates| updates.borrow().number);
+        report.assertions = assertions::evaluate_all(&assertions, &report);
+        assertions::print_assertions(&report.assertions);
+        if let (Some(report_path), true) = (&config.config.report_path, repeat > 1) {
+            let slot = if args.continuous { index % repeat::CONTINUOUS_KEPT_RUNS } else { index };
+            let run_path = repeat::run_report_path(report_path, slot);
+            match report::write_report(&run_path, &report, config.config.signing_keypair.as_deref()) {
+                Err(e) if args.continuous => log::error!("Failed to write {}: {}", run_path, e),
+                result => result?,
+            }
+        }
+        if let (Some(previous), true) = (reports.last_mut(), args.continuous) {
+            *previous = repeat::summary_only(previous);
+        }
+        reports.push(report);
+        if args.continuous {
+            if reports.len() > repeat::CONTINUOUS_KEPT_RUNS {
+                reports.remove(0);
+            }
+            let mut latest = reports.last().cloned().unwrap_or_default();
+            if reports.len() > 1 {
+                latest.repetition = Some(repeat::aggregate(&reports, args.pause.as_secs_f64()));
+            }
+            if let Err(e) = publish(&config, &latest).await {
+                log::error!("Failed to write the report of run {}: {}", index + 1, e);
+            }
+        }
+        run = Some(current);
+    }
+    let run = run.ok_or("interrupted before the first run")?;
+    let mut report = reports.last().cloned().unwrap_or_default();
+    if reports.len() > 1 {
+        let repetition = repeat::aggregate(&reports, args.pause.as_secs_f64());
+        repeat::print_repetition(&repetition);
+        report.repetition = Some(repetition);
+    }
+
+    // Continuous runs have already published each report as it finished
+    if !args.continuous {
+        publish(&config, &report).await?;
+    }
+    if config.config.mode == config::BenchmarkMode::Throughput {
+        if args.report_html.is_some() || args.report_markdown.is_some() {
+            log::warn!("HTML and Markdown reports are not available in throughput mode");
+        }
+        exit_on_failed_assertions(&reports);
+        return Ok(());
+    }
+    let html_path = args.report_html.clone().or_else(|| preset_html_path(&config));
+    if let Some(html_path) = &html_path {
+        let charts = html_report::collect_chart_data(&run.comparator, &run.endpoint_names);
+        html_report::write_html_report(html_path, &report, &charts)?;
+    }
+    if let Some(markdown_path) = &args.report_markdown {
+        report::write_markdown_report(markdown_path, &report)?;
+    }
+    if config.config.alerts.as_ref().is_some_and(|alerts| alerts.fail_on_violation) && !report.alerts.is_empty() {
+        return Err(format!("latency budget violated {} times", report.alerts.len()).into());
+    }
+    exit_on_failed_assertions(&reports);
+
+    Ok(())
+}
+
+/// Writes the report to `report_path`, prunes old transaction logs and sends the notification.
+async fn publish(config: &config::ConfigToml, report: &report::BenchmarkReport) -> Result<(), Box<dyn std::error::Error>> {
+    if let Some(report_path) = &config.config.report_path {
+        report::write_report(report_path, report, config.config.signing_keypair.as_deref())?;
+    }
+    if let Some(keep_days) = config.config.retention_days {
+        match retention::clean(std::path::Path::new(&config.config.logs.dir), keep_days, false) {
+            Ok(summary) => retention::print_summary(&summary, keep_days, false),
+            Err(e) => log::error!("Failed to prune old transaction logs: {}", e),
+        }
+    }
+    // A failed notification must not cost the run its results, so it is only logged
+    if let Some(notify) = &config.config.notify {
+        if let Err(e) = notifier::send(notify, report).await {
+            log::error!("Failed to send webhook notification: {}", e);
+        }
+    }
+    Ok(())
+}
+
+// AIDEV-NOTE: A preset never picks a fixed file name of its own, which would silently replace a
+// report.html from another run in the working directory; the plots go next to the JSON report.
+/// HTML report path of a preset that writes plots: `report_path` with an `.html` extension.
+fn preset_html_path(config: &config::ConfigToml) -> Option<String> {
+    config.config.preset.filter(|preset| preset.writes_plots())?;
+    let html_path = config
+        .config
+        .report_path
+        .as_deref()
+        .map(|report_path| std::path::Path::new(report_path).with_extension("html"))
+        .filter(|html_path| config.config.report_path.as_deref() != html_path.to_str());
+    if html_path.is_none() {
+        log::warn!("Skipping the preset's HTML report: set a report_path not ending in .html, or pass --report-html");
+    }
+    html_path.map(|html_path| html_path.to_string_lossy().into_owned())
+}
+
+/// Polls the config file for the life of a `--continuous` process and publishes every applied
+/// generation, which the running benchmark picks up at once and the next run starts from.
+fn spawn_config_watcher(args: &Args, config: config::ConfigToml) -> watch::Receiver<reload::Generation> {
+    let (updates_tx, updates_rx) = watch::channel(reload::Generation { number: 1, config });
+    let args = args.clone();
+    tokio::spawn(async move {
+        let mut watcher = reload::ConfigWatcher::new(&args.config);
+        let mut interval = tokio::time::interval(Duration::from_secs(reload::POLL_INTERVAL_SECS));
+        loop {
+            interval.tick().await;
+            let current = updates_tx.borrow().config.clone();
+            if let Some(reloaded) = watcher.poll(&current, |reloaded| apply_args(&args, reloaded)) {
+                updates_tx.send_replace(reload::Generation {
+                    number: watcher.generation,
+                    config: reloaded,
+                });
+            }
+        }
+    });
+    updates_rx
+}
+
+/// Command line overrides on top of a loaded config file.
+fn apply_args(args: &Args, mut config: config::ConfigToml) -> Result<config::ConfigToml, Box<dyn std::error::Error>> {
+    if let Some(preset) = args.preset {
+        config.apply_preset(preset);
+        config.validate()?;
+    }
+    if let Some(scenario) = args.scenario {
+        config.apply_scenario(scenario);
+        config.validate()?;
+    }
+    if let Some(consumer_delay_us) = args.consumer_delay_us {
+        config.config.consumer_delay_us = consumer_delay_us;
+    }
+    if !args.tags.is_empty() {
+        config.select_by_tags(&args.tags)?;
+    }
+    Ok(config)
+}
+
+// AIDEV-NOTE: Runs only after every report is written, so CI keeps the artifacts of a failed gate.
+// A distinct status tells a failed assertion apart from a run that errored (1). With `--repeat`
+// every run is checked.
+fn exit_on_failed_assertions(reports: &[report::BenchmarkReport]) {
+    let results = reports.iter().flat_map(|report| &report.assertions);
+    let failed = results.clone().filter(|result| !result.pass).count();
+    if failed > 0 {
+        eprintln!("{} of {} assertions failed", failed, results.count());
+        std::process::exit(assertions::ASSERTION_FAILED_EXIT_CODE);
+    }
+}
+
+async fn run_probe(
+    config_path: &str,
+    url: &str,
+    token: &str,
+    account: Option<String>,
+    duration_secs: u64,
+) -> Result<(), Box<dyn std::error::Error>> {
+    // The config file is optional here; it only supplies the filter when `--account` is omitted
+    let (accounts, commitment) = match account {
+        Some(account) => (vec![account], config::ArgsCommitment::default()),
+        None if std::path::Path::new(config_path).exists() => {
+            let config = config::ConfigToml::load(config_path)?.config;
+            (config.watched_accounts(), config.commitment)
+        }
+        None => return Err(format!("Pass --account or create {} with the account to watch", config_path).into()),
+    };
+
+    let endpoint = probe::endpoint(url, token);
+    let result = probe::probe(&endpoint, accounts, commitment, Duration::from_secs(duration_secs))
+        .await
+        .map_err(|e| e as Box<dyn std::error::Error>)?;
+    probe::print_probe(url, &result);
+    if !result.healthy() {
+        return Err("Probe failed".into());
+    }
+    Ok(())
+}
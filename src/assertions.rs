@@ -0,0 +1,328 @@
+//! `--assert` expressions over the final report, for gating CI on provider regressions.
+//!
+//! An expression reads `<endpoint>.<metric> <op> <value>`, or
+//! `<endpoint>.p50_delta_vs <other> <op> <value>` for the median per-signature difference between
+//! two endpoints, e.g. `jito.p50_delta_vs helius < 10ms`.
+
+use std::{error::Error, str::FromStr};
+
+use serde::{Deserialize, Serialize};
+
+use crate::report::{BenchmarkReport, EndpointReport};
+
+/// Process exit status when the run finished but an assertion failed.
+pub const ASSERTION_FAILED_EXIT_CODE: i32 = 3;
+
+const DELTA_SUFFIX: &str = "_delta_vs";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Unit {
+    Millis,
+    Seconds,
+    Percent,
+    Count,
+}
+
+impl Unit {
+    fn name(self) -> &'static str {
+        match self {
+            Self::Millis => "ms",
+            Self::Seconds => "s",
+            Self::Percent => "%",
+            Self::Count => "counts",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Metric {
+    Average,
+    Median,
+    P95,
+    Min,
+    Max,
+    TrimmedMean,
+    Mad,
+    WinRate,
+    FirstDetections,
+    Transactions,
+    MissingMetaRate,
+    Downtime,
+}
+
+impl Metric {
+    fn parse(name: &str) -> Option<Self> {
+        Some(match name {
+            "avg" | "average" => Self::Average,
+            "p50" | "median" => Self::Median,
+            "p95" => Self::P95,
+            "min" => Self::Min,
+            "max" => Self::Max,
+            "trimmed_mean" => Self::TrimmedMean,
+            "mad" => Self::Mad,
+            "win_rate" => Self::WinRate,
+            "first_detections" => Self::FirstDetections,
+            "transactions" => Self::Transactions,
+            "missing_meta_rate" => Self::MissingMetaRate,
+            "downtime" => Self::Downtime,
+            _ => return None,
+        })
+    }
+
+    fn unit(self) -> Unit {
+        match self {
+            Self::WinRate | Self::MissingMetaRate => Unit::Percent,
+            Self::FirstDetections | Self::Transactions => Unit::Count,
+            Self::Downtime => Unit::Seconds,
+            _ => Unit::Millis,
+        }
+    }
+
+    fn is_delay(self) -> bool {
+        self.unit() == Unit::Millis
+    }
+
+    /// Whether the report's endpoint pairs carry a per-signature form of this metric.
+    fn is_paired(self) -> bool {
+        self == Self::Median
+    }
+
+    // AIDEV-NOTE: An endpoint that delivered something but has no delays was first every time, so
+    // its delay statistics are zero. One that delivered nothing has no delays to speak of: None,
+    // so the assertion fails instead of a dead endpoint passing every latency budget.
+    /// None for a delay metric of an endpoint without valid transactions.
+    fn value(self, endpoint: &EndpointReport) -> Option<f64> {
+        if self.is_delay() && endpoint.total_valid_transactions == 0 {
+            return None;
+        }
+        let delays = endpoint.delays.clone().unwrap_or_default();
+        Some(match self {
+            Self::Average => delays.average_ms,
+            Self::Median => delays.median_ms,
+            Self::P95 => delays.p95_ms,
+            Self::Min => delays.min_ms,
+            Self::Max => delays.max_ms,
+            Self::TrimmedMean => delays.trimmed_mean_ms,
+            Self::Mad => delays.mad_ms,
+            Self::WinRate => endpoint.win_rate,
+            Self::FirstDetections => endpoint.first_detections as f64,
+            Self::Transactions => endpoint.total_valid_transactions as f64,
+            Self::MissingMetaRate => endpoint.missing_meta_rate,
+            Self::Downtime => endpoint.downtime_secs,
+        })
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Op {
+    Lt,
+    Le,
+    Gt,
+    Ge,
+    Eq,
+    Ne,
+}
+
+impl Op {
+    // Two-character operators first, so `<=` is not read as `<`
+    const ALL: [(&'static str, Op); 6] = [
+        ("<=", Op::Le),
+        (">=", Op::Ge),
+        ("==", Op::Eq),
+        ("!=", Op::Ne),
+        ("<", Op::Lt),
+        (">", Op::Gt),
+    ];
+
+    fn holds(self, value: f64, target: f64) -> bool {
+        match self {
+            Self::Lt => value < target,
+            Self::Le => value <= target,
+            Self::Gt => value > target,
+            Self::Ge => value >= target,
+            Self::Eq => value == target,
+            Self::Ne => value != target,
+        }
+    }
+}
+
+/// A parsed `--assert` expression; the target is held in the metric's own unit.
+#[derive(Debug, Clone)]
+pub struct Assertion {
+    expression: String,
+    endpoint: String,
+    metric: Metric,
+    versus: Option<String>,
+    op: Op,
+    target: f64,
+}
+
+impl FromStr for Assertion {
+    type Err = Box<dyn Error>;
+
+    fn from_str(expression: &str) -> Result<Self, Self::Err> {
+        let invalid = |reason: String| -> Box<dyn Error> { format!("Invalid assertion '{}': {}", expression, reason).into() };
+        let (position, symbol, op) = Op::ALL
+            .iter()
+            .filter_map(|(symbol, op)| expression.find(symbol).map(|position| (position, *symbol, *op)))
+            .min_by_key(|(position, _, _)| *position)
+            .ok_or_else(|| invalid("expected one of <, <=, >, >=, ==, !=".to_string()))?;
+        let subject = expression[..position].trim();
+        let target = expression[position + symbol.len()..].trim();
+
+        let (subject, versus) = match subject.split_once(char::is_whitespace) {
+            Some((subject, versus)) => (subject, Some(versus.trim().to_string())),
+            None => (subject, None),
+        };
+        // Endpoint names may contain dots; the metric is whatever follows the last one
+        let (endpoint, metric_name) = subject
+            .rsplit_once('.')
+            .filter(|(endpoint, metric)| !endpoint.is_empty() && !metric.is_empty())
+            .ok_or_else(|| invalid("expected <endpoint>.<metric>".to_string()))?;
+        let metric_name = match (metric_name.strip_suffix(DELTA_SUFFIX), &versus) {
+            (Some(base), Some(_)) => base,
+            (None, None) => metric_name,
+            (Some(_), None) => return Err(invalid(format!("{} needs a second endpoint", DELTA_SUFFIX))),
+            (None, Some(_)) => return Err(invalid(format!("comparing endpoints needs a <metric>{} metric", DELTA_SUFFIX))),
+        };
+        let metric = Metric::parse(metric_name).ok_or_else(|| {
+            invalid(format!(
+                "unknown metric '{}' (avg, p50, p95, min, max, trimmed_mean, mad, win_rate, first_detections, transactions, missing_meta_rate, downtime)",
+                metric_name
+            ))
+        })?;
+        if versus.is_some() && !metric.is_paired() {
+            return Err(invalid(format!(
+                "'{}' has no per-signature form to compare; only p50{} is supported",
+                metric_name, DELTA_SUFFIX
+            )));
+        }
+
+        Ok(Self {
+            expression: expression.trim().to_string(),
+            endpoint: endpoint.to_string(),
+            metric,
+            versus,
+            op,
+            target: parse_target(target, metric.unit()).map_err(invalid)?,
+        })
+    }
+}
+
+/// The number after the operator, converted to `unit` from an optional `ms`, `s` or `%` suffix.
+fn parse_target(target: &str, unit: Unit) -> Result<f64, String> {
+    // `ms` is tried before `s`, which it also ends with
+    let suffixes: &[(&str, f64)] = match unit {
+        Unit::Millis => &[("ms", 1.0), ("s", 1000.0)],
+        Unit::Seconds => &[("ms", 0.001), ("s", 1.0)],
+        Unit::Percent => &[("%", 1.0)],
+        Unit::Count => &[],
+    };
+    let found = suffixes
+        .iter()
+        .find_map(|(suffix, scale)| target.strip_suffix(suffix).map(|number| (number, *scale)));
+    let (number, scale) = match found {
+        Some(found) => found,
+        None if target.ends_with(|c: char| c.is_ascii_alphabetic() || c == '%') => {
+            return Err(format!("'{}' does not fit a metric measured in {}", target, unit.name()))
+        }
+        None => (target, 1.0),
+    };
+    number
+        .trim()
+        .parse::<f64>()
+        .ok()
+        .filter(|value| value.is_finite())
+        .map(|value| value * scale)
+        .ok_or_else(|| format!("'{}' is not a number", target))
+}
+
+/// Outcome of one assertion, kept in the JSON report as the run's machine-readable verdict.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct AssertionResult {
+    pub expression: String,
+    /// None when an endpoint the expression names is not in the report, or has no delays for a
+    /// delay metric
+    pub value: Option<f64>,
+    pub target: f64,
+    pub pass: bool,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub reason: Option<String>,
+}
+
+impl Assertion {
+    pub fn evaluate(&self, report: &BenchmarkReport) -> AssertionResult {
+        let find = |name: &str| report.endpoints.iter().find(|endpoint| endpoint.name == name);
+        let names = std::iter::once(&self.endpoint).chain(&self.versus);
+        let names: Vec<&String> = names.collect();
+        let missing: Vec<&str> = names.iter().filter(|name| find(name.as_str()).is_none()).map(|name| name.as_str()).collect();
+        let silent: Vec<&str> = names
+            .iter()
+            .filter(|name| find(name.as_str()).is_some_and(|endpoint| self.metric.value(endpoint).is_none()))
+            .map(|name| name.as_str())
+            .collect();
+        let value = match &self.versus {
+            None => find(&self.endpoint).and_then(|endpoint| self.metric.value(endpoint)),
+            Some(versus) => paired_delta(report, &self.endpoint, versus),
+        };
+        let reason = if !missing.is_empty() {
+            Some(format!("no endpoint named {} in the report", missing.join(", ")))
+        } else if !silent.is_empty() {
+            Some(format!("{} delivered no transactions, so it has no delays", silent.join(", ")))
+        } else if value.is_none() {
+            Some(format!("{} and {} saw no signatures in common", self.endpoint, self.versus.as_deref().unwrap_or_default()))
+        } else {
+            None
+        };
+        AssertionResult {
+            expression: self.expression.clone(),
+            value,
+            target: self.target,
+            pass: value.is_some_and(|value| self.op.holds(value, self.target)),
+            reason,
+        }
+    }
+}
+
+// AIDEV-NOTE: Subtracting two endpoints' own medians compares delays measured over different
+// signature sets; the pair's median is taken over the signatures both delivered. Pair deltas are
+// `second - first` receive times, so the first endpoint's lag behind the second is the negation.
+/// Median of `endpoint`'s receive time minus `versus`'s over the signatures both delivered, in ms.
+fn paired_delta(report: &BenchmarkReport, endpoint: &str, versus: &str) -> Option<f64> {
+    report.pairs.iter().find_map(|pair| {
+        if pair.first == endpoint && pair.second == versus {
+            Some(-pair.median_delta_ms)
+        } else if pair.first == versus && pair.second == endpoint {
+            Some(pair.median_delta_ms)
+        } else {
+            None
+        }
+    })
+}
+
+/// Parses every expression up front, so a typo fails before the benchmark runs rather than after.
+pub fn parse_all(expressions: &[String]) -> Result<Vec<Assertion>, Box<dyn Error>> {
+    expressions.iter().map(|expression| expression.parse()).collect()
+}
+
+pub fn evaluate_all(assertions: &[Assertion], report: &BenchmarkReport) -> Vec<AssertionResult> {
+    assertions.iter().map(|assertion| assertion.evaluate(report)).collect()
+}
+
+pub fn print_assertions(results: &[AssertionResult]) {
+    if results.is_empty() {
+        return;
+    }
+    println!("\nAssertions");
+    println!("--------------------------------------------");
+    for result in results {
+        let verdict = if result.pass { "PASS" } else { "FAIL" };
+        match (&result.value, &result.reason) {
+            (_, Some(reason)) => println!("[{}] {} ({})", verdict, result.expression, reason),
+            (Some(value), None) => println!("[{}] {} (actual {:.2})", verdict, result.expression, value),
+            (None, None) => println!("[{}] {}", verdict, result.expression),
+        }
+    }
+    let failed = results.iter().filter(|result| !result.pass).count();
+    println!("{} of {} assertions passed", results.len() - failed, results.len());
+}
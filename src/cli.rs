@@ -0,0 +1,145 @@
+use std::time::Duration;
+
+use clap::{Parser, Subcommand};
+use crate::config::{Preset, Scenario};
+
+pub const CONFIG_PATH: &str = "config.toml";
+
+#[derive(Debug, Clone, Parser)]
+#[command(version, about = "Benchmark Solana Geyser streaming endpoints")]
+pub struct Args {
+    /// Path to the configuration file
+    #[arg(long, default_value = CONFIG_PATH)]
+    pub config: String,
+
+    /// Run preset (quick, standard, thorough); overrides the matching config settings
+    #[arg(long)]
+    pub preset: Option<Preset>,
+
+    /// Prebuilt comparison (hybrid: shred-based vs Yellowstone at processed commitment)
+    #[arg(long)]
+    pub scenario: Option<Scenario>,
+
+    /// Simulate a slow consumer by spending this long on each received message; overrides the config
+    #[arg(long)]
+    pub consumer_delay_us: Option<u64>,
+
+    /// Only run endpoints matching these tags, e.g. `--tags prod,!backup`
+    #[arg(long, value_delimiter = ',', allow_hyphen_values = true)]
+    pub tags: Vec<String>,
+
+    /// Also write a self-contained HTML report with latency charts to this path
+    #[arg(long)]
+    pub report_html: Option<String>,
+
+    /// Also write a Markdown summary (with SLO badges when configured) to this path
+    #[arg(long)]
+    pub report_markdown: Option<String>,
+
+    /// Check the final report, e.g. `--assert "a.p50_delta_vs b < 10ms"`; repeatable, exits 3 on failure
+    #[arg(long = "assert", value_name = "EXPRESSION")]
+    pub assertions: Vec<String>,
+
+    /// Run the benchmark this many times and print statistics across the runs
+    #[arg(long, default_value_t = 1, value_parser = clap::value_parser!(u32).range(1..))]
+    pub repeat: u32,
+
+    /// Wait between repeated runs, e.g. `30s`, `500ms` or `2m`
+    #[arg(long, default_value = "0s", value_parser = parse_pause)]
+    pub pause: Duration,
+
+    /// Keep running until stopped, reloading the config file between runs when it changes
+    #[arg(long, conflicts_with = "repeat")]
+    pub continuous: bool,
+
+    #[command(subcommand)]
+    pub command: Option<Command>,
+}
+
+#[derive(Debug, Clone, Subcommand)]
+pub enum Command {
+    /// Verify the detached signature of an exported report
+    Verify {
+        /// Path to the report JSON file (signature is read from `<report>.sig`)
+        report: String,
+        /// Expected signer public key (base58)
+        #[arg(long)]
+        pubkey: Option<String>,
+    },
+    /// Compare two JSON reports and print regressions and improvements
+    Diff {
+        /// Baseline report
+        before: String,
+        /// Report to compare against the baseline
+        after: String,
+    },
+    /// Prune transaction log entries older than the given age
+    Clean {
+        #[arg(long, default_value_t = 30)]
+        keep_days: u64,
+        /// Directory holding the transaction logs
+        #[arg(long, default_value = ".")]
+        dir: String,
+        /// Only list what would be removed
+        #[arg(long)]
+        dry_run: bool,
+    },
+    /// Rebuild results from transaction logs, e.g. after a run crashed before printing them
+    Analyze {
+        /// Directory holding the transaction logs
+        #[arg(default_value = ".")]
+        dir: String,
+        /// Ignore entries before this Unix timestamp
+        #[arg(long)]
+        since: Option<f64>,
+        /// Ignore entries after this Unix timestamp
+        #[arg(long)]
+        until: Option<f64>,
+        /// Also write a JSON report to this path
+        #[arg(long)]
+        report: Option<String>,
+    },
+    /// Merge the observations of agents in several regions into one comparison
+    Coordinate {
+        /// Address agents connect to
+        #[arg(long, default_value = "0.0.0.0:50070")]
+        listen: String,
+        /// Also write a JSON report to this path
+        #[arg(long)]
+        report: Option<String>,
+    },
+    /// Quick smoke test of a single Yellowstone endpoint: connect time, RTT and stream health
+    Probe {
+        /// Endpoint URL
+        url: String,
+        /// Value sent as `x-token`
+        #[arg(long, default_value = "")]
+        token: String,
+        /// Account to filter transactions by; defaults to the accounts in the config file
+        #[arg(long)]
+        account: Option<String>,
+        /// How long to stream after subscribing (capped at 45 seconds)
+        #[arg(long, default_value_t = 20)]
+        duration_secs: u64,
+    },
+}
+
+/// Seconds by default; `ms`, `s` and `m` suffixes are accepted.
+fn parse_pause(value: &str) -> Result<Duration, String> {
+    let (number, scale) = if let Some(number) = value.strip_suffix("ms") {
+        (number, 0.001)
+    } else if let Some(number) = value.strip_suffix('s') {
+        (number, 1.0)
+    } else if let Some(number) = value.strip_suffix('m') {
+        (number, 60.0)
+    } else {
+        (value, 1.0)
+    };
+    number
+        .trim()
+        .parse::<f64>()
+        .ok()
+        .filter(|secs| secs.is_finite() && *secs >= 0.0)
+        .map(|secs| Duration::from_secs_f64(secs * scale))
+        .ok_or_else(|| format!("'{}' is not a duration like 30s, 500ms or 2m", value))
+}
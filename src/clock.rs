@@ -0,0 +1,159 @@
+use std::{
+    fmt::Debug,
+    net::UdpSocket,
+    sync::{
+        Arc,
+        atomic::{AtomicU64, Ordering},
+    },
+    time::{Duration, Instant},
+};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{config::ClockCheckConfig, utils::get_current_timestamp};
+
+// Seconds between the NTP era (1900) and the Unix epoch
+const NTP_UNIX_OFFSET: f64 = 2_208_988_800.0;
+const NTP_PACKET_LEN: usize = 48;
+const SAMPLES: usize = 4;
+
+/// Source of receive timestamps, in seconds since the Unix epoch.
+pub trait Clock: Debug + Send + Sync {
+    fn now(&self) -> f64;
+}
+
+/// Wall clock; the default everywhere.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now(&self) -> f64 {
+        get_current_timestamp()
+    }
+}
+
+// AIDEV-NOTE: Anchored to the wall clock once, then advanced by Instant, so NTP slews or steps
+// during a run cannot reorder observations.
+#[derive(Debug, Clone, Copy)]
+pub struct MonotonicClock {
+    anchor: f64,
+    started: Instant,
+}
+
+impl MonotonicClock {
+    pub fn new() -> Self {
+        Self {
+            anchor: get_current_timestamp(),
+            started: Instant::now(),
+        }
+    }
+}
+
+impl Default for MonotonicClock {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Clock for MonotonicClock {
+    fn now(&self) -> f64 {
+        self.anchor + self.started.elapsed().as_secs_f64()
+    }
+}
+
+/// Manually driven clock for deterministic runs; clones share the same time.
+#[derive(Debug, Clone, Default)]
+pub struct MockClock(Arc<AtomicU64>);
+
+impl MockClock {
+    pub fn new(now: f64) -> Self {
+        Self(Arc::new(AtomicU64::new(now.to_bits())))
+    }
+
+    pub fn set(&self, now: f64) {
+        self.0.store(now.to_bits(), Ordering::SeqCst);
+    }
+
+    pub fn advance(&self, secs: f64) {
+        self.set(self.now() + secs);
+    }
+}
+
+impl Clock for MockClock {
+    fn now(&self) -> f64 {
+        f64::from_bits(self.0.load(Ordering::SeqCst))
+    }
+}
+
+pub fn system() -> Arc<dyn Clock> {
+    Arc::new(SystemClock)
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ClockOffset {
+    pub server: String,
+    /// Positive when the local clock is behind the server
+    pub offset_ms: f64,
+    pub round_trip_ms: f64,
+    pub measured_at: f64,
+}
+
+/// Measures the local clock offset against an SNTP server, keeping the lowest round trip sample.
+pub fn measure(config: &ClockCheckConfig) -> std::io::Result<ClockOffset> {
+    let socket = UdpSocket::bind("0.0.0.0:0")?;
+    socket.set_read_timeout(Some(Duration::from_millis(config.timeout_ms)))?;
+    socket.connect(&config.ntp_server)?;
+
+    let mut best: Option<ClockOffset> = None;
+    let mut last_error = None;
+    for _ in 0..SAMPLES {
+        match sample(&socket) {
+            Ok((offset, round_trip)) => {
+                if best.as_ref().is_none_or(|b| round_trip < b.round_trip_ms / 1000.0) {
+                    best = Some(ClockOffset {
+                        server: config.ntp_server.clone(),
+                        offset_ms: offset * 1000.0,
+                        round_trip_ms: round_trip * 1000.0,
+                        measured_at: get_current_timestamp(),
+                    });
+                }
+            }
+            Err(e) => last_error = Some(e),
+        }
+    }
+
+    best.ok_or_else(|| {
+        last_error.unwrap_or_else(|| std::io::Error::other("no NTP samples collected"))
+    })
+}
+
+fn sample(socket: &UdpSocket) -> std::io::Result<(f64, f64)> {
+    let mut request = [0u8; NTP_PACKET_LEN];
+    // LI = 0, version 4, mode 3 (client)
+    request[0] = 0b00_100_011;
+
+    let originate = get_current_timestamp();
+    socket.send(&request)?;
+    let mut response = [0u8; NTP_PACKET_LEN];
+    let len = socket.recv(&mut response)?;
+    let destination = get_current_timestamp();
+
+    if len < NTP_PACKET_LEN || response[0] & 0b111 != 4 {
+        return Err(std::io::Error::new(
+            std::io::ErrorKind::InvalidData,
+            "unexpected NTP response",
+        ));
+    }
+
+    let receive = read_timestamp(&response[32..40]);
+    let transmit = read_timestamp(&response[40..48]);
+    let offset = ((receive - originate) + (transmit - destination)) / 2.0;
+    let round_trip = (destination - originate) - (transmit - receive);
+    Ok((offset, round_trip))
+}
+
+fn read_timestamp(bytes: &[u8]) -> f64 {
+    let seconds = u32::from_be_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]) as f64;
+    let fraction = u32::from_be_bytes([bytes[4], bytes[5], bytes[6], bytes[7]]) as f64;
+    seconds - NTP_UNIX_OFFSET + fraction / 4_294_967_296.0
+}
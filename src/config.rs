@@ -1,10 +1,55 @@
 
 use serde::{Deserialize, Serialize};
-use std::{fs, path::Path};
-use yellowstone_grpc_proto::geyser::CommitmentLevel;
+use solana_sdk::pubkey::Pubkey;
+use std::{collections::{BTreeMap, BTreeSet}, env, fmt, fs, path::Path, str::FromStr};
+use yellowstone_grpc_proto::geyser::{
+    subscribe_request_filter_accounts_filter::Filter as AccountsFilterOneof,
+    subscribe_request_filter_accounts_filter_memcmp::Data as MemcmpData,
+    CommitmentLevel, SubscribeRequestAccountsDataSlice, SubscribeRequestFilterAccounts,
+    SubscribeRequestFilterAccountsFilter, SubscribeRequestFilterAccountsFilterMemcmp,
+};
 
+use crate::{
+    log_output,
+    proxy::{self, Proxy},
+};
 
-#[derive(Debug, Deserialize, Serialize)]
+
+/// File format of a config, picked from its extension.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ConfigFormat {
+    Toml,
+    Yaml,
+    Json,
+}
+
+impl ConfigFormat {
+    /// `.yaml`/`.yml` and `.json` files; anything else is read as TOML.
+    pub fn from_path(path: &str) -> Self {
+        match Path::new(path).extension().and_then(|ext| ext.to_str()).map(str::to_ascii_lowercase).as_deref() {
+            Some("yaml" | "yml") => ConfigFormat::Yaml,
+            Some("json") => ConfigFormat::Json,
+            _ => ConfigFormat::Toml,
+        }
+    }
+}
+
+// AIDEV-NOTE: Keys are looked up by their last path segment, so with a repeated name the first
+// occurrence wins; the line is a hint next to the full key path, not an exact location.
+/// 1-based line of the first `key = ...` (TOML), `key: ...` (YAML) or `"key": ...` (JSON).
+fn key_line(content: &str, key: &str) -> Option<usize> {
+    content
+        .lines()
+        .position(|line| {
+            let line = line.trim_start().trim_start_matches('-').trim_start().trim_start_matches(['"', '\'']);
+            line.strip_prefix(key).is_some_and(|rest| {
+                rest.trim_start_matches(['"', '\'']).trim_start().starts_with(['=', ':'])
+            })
+        })
+        .map(|index| index + 1)
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone)]
 pub struct ConfigToml {
     pub config: Config,
     pub endpoint: Vec<Endpoint>,
@@ -14,7 +59,871 @@ pub struct ConfigToml {
 pub struct Config {
     pub transactions: i32,
     pub account: String,
+    /// Additional accounts watched alongside `account`
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub accounts: Vec<String>,
+    /// Accounts with their own transaction target; the run stops once all are met instead of at `transactions`
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub account_targets: BTreeMap<String, u64>,
+    /// Named groups of accounts whose transactions are compared separately, e.g. one per program
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub filter_groups: BTreeMap<String, Vec<String>>,
     pub commitment: ArgsCommitment,
+    #[serde(default)]
+    pub tsc_timestamps: bool,
+    #[serde(default)]
+    pub transaction_filter: TransactionFilter,
+    /// Ask yellowstone endpoints to replay from this slot before streaming the live tail
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub from_slot: Option<u64>,
+    /// Also watch the token accounts of these wallets, looked up over RPC at startup
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub token_accounts: Option<TokenAccountsConfig>,
+    /// Submit memo transactions and time how fast each endpoint streams them back
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sender: Option<SenderConfig>,
+    /// Resolve address lookup tables so v0 transactions loading a watched account match
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub lookup_tables: Option<LookupTablesConfig>,
+    /// Break endpoint delays down by the leader of each transaction's slot
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub leaders: Option<LeadersConfig>,
+    /// Check each endpoint's coverage against the RPC's signatures for the watched accounts
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub ground_truth: Option<GroundTruthConfig>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub accounts_filter: Option<AccountsFilter>,
+    #[serde(default)]
+    pub accounts_strategy: AccountsStrategy,
+    /// Owner programs for the `owner` accounts strategy
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub accounts_owner: Vec<String>,
+    /// Seconds without account updates before falling back to a looser strategy; 0 disables
+    #[serde(default = "default_accounts_fallback_secs")]
+    pub accounts_fallback_secs: u64,
+    /// Byte ranges of account data to receive on account streams instead of whole payloads
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub accounts_data_slice: Vec<DataSlice>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub report_path: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub signing_keypair: Option<String>,
+    #[serde(default)]
+    pub trim_percent: f64,
+    #[serde(default)]
+    pub tie_policy: TiePolicy,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub influx: Option<InfluxConfig>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub history: Option<HistoryConfig>,
+    /// Every accepted sighting written to a Parquet file (needs the `parquet` feature)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub parquet: Option<ParquetConfig>,
+    #[serde(default)]
+    pub race_top_n: usize,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub race_table_path: Option<String>,
+    /// Width of the per-pair time windows behind the latency heatmap; 0 disables them
+    #[serde(default = "default_heatmap_window_secs")]
+    pub heatmap_window_secs: u64,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub heatmap_csv_path: Option<String>,
+    /// CSV of every endpoint's copy of each account write whose content differed between endpoints
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub account_diff_path: Option<String>,
+    #[serde(default)]
+    pub mode: BenchmarkMode,
+    #[serde(default)]
+    pub throughput: ThroughputConfig,
+    #[serde(default)]
+    pub blocks: BlocksConfig,
+    #[serde(default)]
+    pub fumarole: FumaroleConfig,
+    /// Weights of the final scoreboard's components
+    #[serde(default)]
+    pub scoreboard: ScoreWeights,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub slo: Option<SloConfig>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_runtime_secs: Option<u64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub clock_check: Option<ClockCheckConfig>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub preset: Option<Preset>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub notify: Option<NotifyConfig>,
+    /// Tag expression selecting the endpoints that take part, e.g. `["prod", "!backup"]`
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tags: Vec<String>,
+    /// Run each provider on its own OS thread and single-threaded runtime
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub dedicated_runtimes: Option<DedicatedRuntimesConfig>,
+    /// Endpoint tag keys to aggregate results by, e.g. providers compared within each region
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub group_by: Vec<TagGrouping>,
+    /// Fraction (0, 1] of distinct signatures compared; every endpoint's sighting of a kept one is recorded
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sample_rate: Option<f64>,
+    /// Repeats of a signature from the same endpoint within this many seconds are duplicates, later ones redeliveries
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub dedup_window_secs: Option<f64>,
+    /// Keep every delivery per signature in receive order for post-run queries (`Comparator::events`)
+    #[serde(default)]
+    pub event_log: bool,
+    /// Per-endpoint connect, subscribe, first match, reconnect and close times written as JSON
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub timeline_path: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub otel: Option<OtelConfig>,
+    /// Gap between updates recorded as a stream stall; 0 disables stall detection
+    #[serde(default = "default_stall_threshold_secs")]
+    pub stall_threshold_secs: f64,
+    /// Tag matched transactions with the Raydium/pump.fun market they swap on
+    #[serde(default)]
+    pub decode_markets: bool,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub watchdog: Option<WatchdogConfig>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub scenario: Option<Scenario>,
+    /// Milliseconds providers keep reading after the stop signal to complete in-flight races
+    #[serde(default = "default_drain_ms")]
+    pub drain_ms: u64,
+    /// Seconds after the start whose signatures are left out of the statistics, while connections settle
+    #[serde(default)]
+    pub warmup_secs: u64,
+    /// Prune transaction log entries older than this many days after each run
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub retention_days: Option<u64>,
+    /// Directory, per-run subdirectory and rotation of the per-endpoint transaction logs
+    #[serde(default)]
+    pub logs: LogsConfig,
+    /// Record each endpoint's resolved IP, TLS version, ALPN and HTTP/2 settings before the run
+    #[serde(default)]
+    pub inspect_connections: bool,
+    /// Ping yellowstone endpoints at this cadence to measure round trips; an endpoint's own `ping_interval_secs` wins
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub rtt_interval_secs: Option<u64>,
+    /// Time provider tasks' polls and report process CPU and memory use
+    #[serde(default)]
+    pub self_profile: bool,
+    /// Artificial processing time per received message, simulating a slow consumer
+    #[serde(default)]
+    pub consumer_delay_us: u64,
+    /// Stream every sighting to a `geyserbench coordinate` instance as well
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub agent: Option<AgentConfig>,
+    /// Latency budget checked on every window while the run goes on
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub alerts: Option<AlertConfig>,
+}
+
+/// Prebuilt comparisons that fix the settings the comparison depends on.
+#[derive(Debug, Clone, Copy, Deserialize, Serialize, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum Scenario {
+    /// Shred-based endpoints against Yellowstone at processed commitment on the same account
+    Hybrid,
+}
+
+impl std::str::FromStr for Scenario {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "hybrid" => Ok(Scenario::Hybrid),
+            _ => Err(format!("unknown scenario '{}', expected hybrid", s)),
+        }
+    }
+}
+
+/// Whether an endpoint kind delivers transactions before or after the validator executes them.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ExecutionStage {
+    /// Decoded from shreds: no execution result yet
+    PreExecution,
+    /// Streamed by the validator after execution, with transaction meta
+    PostExecution,
+}
+
+/// Stale-stream detection: no message of any kind (pings included) for `timeout_secs`.
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct WatchdogConfig {
+    #[serde(default = "default_watchdog_timeout_secs")]
+    pub timeout_secs: u64,
+    /// Reconnect and resubscribe when the watchdog fires (`yellowstone` endpoints only)
+    #[serde(default)]
+    pub reconnect: bool,
+}
+
+fn default_watchdog_timeout_secs() -> u64 {
+    30
+}
+
+fn default_drain_ms() -> u64 {
+    500
+}
+
+fn default_heatmap_window_secs() -> u64 {
+    10
+}
+
+// Providers that have not stopped this long after the signal are aborted by the runner
+const MAX_DRAIN_MS: u64 = 5_000;
+
+fn default_stall_threshold_secs() -> f64 {
+    5.0
+}
+
+/// Named run profiles so results from different people are produced with the same settings.
+#[derive(Debug, Clone, Copy, Deserialize, Serialize, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum Preset {
+    /// ~100 transactions, wall-clock timestamps, no warmup, no race table
+    Quick,
+    /// ~1,000 transactions with TSC timestamps, a short warmup and a short race table
+    Standard,
+    /// ~10,000 transactions, TSC timestamps, clock check, long warmup and race table, and HTML plots
+    Thorough,
+}
+
+impl std::str::FromStr for Preset {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "quick" => Ok(Preset::Quick),
+            "standard" => Ok(Preset::Standard),
+            "thorough" => Ok(Preset::Thorough),
+            _ => Err(format!("unknown preset '{}', expected quick, standard or thorough", s)),
+        }
+    }
+}
+
+impl Preset {
+    /// Whether runs with this preset write the HTML report next to `report_path` even without
+    /// `--report-html`.
+    pub fn writes_plots(&self) -> bool {
+        *self == Preset::Thorough
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, Deserialize, Serialize, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum BenchmarkMode {
+    /// First-seen latency race on transactions touching `account`
+    #[default]
+    Latency,
+    /// Maximum sustained delivery rate of an unfiltered transaction stream
+    Throughput,
+    /// First entry per slot: Yellowstone `entry` updates vs Shredstream proxy entries
+    Entries,
+    /// First `transactions_status` update per signature at the configured commitment
+    Status,
+    /// Complete `blocks` update per slot at the configured commitment
+    Blocks,
+    /// Account writes keyed on (pubkey, write_version), signature or not
+    Accounts,
+    /// Slot status updates keyed on (slot, status): processed, confirmed and finalized
+    Slots,
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct ThroughputConfig {
+    #[serde(default = "default_throughput_duration_secs")]
+    pub duration_secs: u64,
+    #[serde(default = "default_throughput_queue_capacity")]
+    pub queue_capacity: usize,
+    #[serde(default)]
+    pub overflow: OverflowPolicy,
+}
+
+/// What a full internal queue does with a new message.
+#[derive(Debug, Clone, Copy, Default, Deserialize, Serialize, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum OverflowPolicy {
+    /// Wait for room, pushing back on the stream reader
+    #[default]
+    Block,
+    /// Evict the oldest queued message and count it as dropped
+    DropOldest,
+    /// Discard the new message and count it as dropped
+    DropNewest,
+}
+
+impl Default for ThroughputConfig {
+    fn default() -> Self {
+        Self {
+            duration_secs: default_throughput_duration_secs(),
+            queue_capacity: default_throughput_queue_capacity(),
+            overflow: OverflowPolicy::default(),
+        }
+    }
+}
+
+/// Settings for `blocks` mode.
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct BlocksConfig {
+    /// Ask for the block's transactions, not just its header and counts
+    #[serde(default = "default_include_transactions")]
+    pub include_transactions: bool,
+}
+
+impl Default for BlocksConfig {
+    fn default() -> Self {
+        Self {
+            include_transactions: true,
+        }
+    }
+}
+
+fn default_include_transactions() -> bool {
+    true
+}
+
+/// How much each component counts towards an endpoint's scoreboard score; every component is
+/// scored 0-100 first, so the weights only need to be relative to each other.
+#[derive(Debug, Deserialize, Serialize, Clone)]
+#[serde(default)]
+pub struct ScoreWeights {
+    pub win_rate: f64,
+    pub median_delta: f64,
+    pub p99_delta: f64,
+    pub coverage: f64,
+    pub uptime: f64,
+}
+
+impl Default for ScoreWeights {
+    fn default() -> Self {
+        Self {
+            win_rate: 0.3,
+            median_delta: 0.25,
+            p99_delta: 0.15,
+            coverage: 0.2,
+            uptime: 0.1,
+        }
+    }
+}
+
+impl ScoreWeights {
+    pub fn total(&self) -> f64 {
+        self.win_rate + self.median_delta + self.p99_delta + self.coverage + self.uptime
+    }
+}
+
+#[derive(Debug, Default, Deserialize, Serialize, Clone)]
+pub struct DedicatedRuntimesConfig {
+    /// Cores to pin endpoints to, in endpoint order; endpoints past the end run unpinned unless
+    /// they set `core`
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub cores: Vec<usize>,
+}
+
+/// Endpoints aggregated by the value of their `key=value` tag `tag`, optionally raced only against
+/// endpoints sharing the same value of tag `within`.
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct TagGrouping {
+    pub tag: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub within: Option<String>,
+}
+
+/// Consumer group handling for `fumarole` endpoints.
+#[derive(Debug, Default, Deserialize, Serialize, Clone)]
+pub struct FumaroleConfig {
+    /// Existing consumer group to join, resuming from its committed position; None creates a
+    /// fresh group per endpoint for the run and deletes it afterwards
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub consumer_group: Option<String>,
+    /// Where a freshly created group starts reading
+    #[serde(default)]
+    pub initial_offset: FumaroleOffset,
+}
+
+/// Where an agent reports its observations in a multi-region run.
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct AgentConfig {
+    /// gRPC address of the coordinator, e.g. `http://coordinator:50070`
+    pub coordinator_url: String,
+    /// Prefix of this agent's endpoint names on the coordinator, e.g. `fra`
+    pub region: String,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum FumaroleOffset {
+    /// Live edge, comparable with plain subscriptions
+    #[default]
+    Latest,
+    /// Oldest retained slot; replays history before catching up
+    Earliest,
+}
+
+fn default_throughput_duration_secs() -> u64 {
+    60
+}
+
+fn default_throughput_queue_capacity() -> usize {
+    10_000
+}
+
+#[derive(Debug, Clone, Copy, Deserialize, Serialize, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum WebhookKind {
+    Discord,
+    Slack,
+    /// `webhook_url` is the bot's `sendMessage` URL
+    Telegram,
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct NotifyConfig {
+    pub webhook_url: String,
+    pub kind: WebhookKind,
+    /// Telegram only
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub chat_id: Option<String>,
+}
+
+/// Per-endpoint targets; delay targets are upper bounds on the delay behind the first detection.
+#[derive(Debug, Deserialize, Serialize, Clone, Default)]
+pub struct SloConfig {
+    pub average_ms: Option<f64>,
+    pub median_ms: Option<f64>,
+    pub p95_ms: Option<f64>,
+    pub max_ms: Option<f64>,
+    /// Lower bound, in percent
+    pub min_win_rate: Option<f64>,
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct ClockCheckConfig {
+    #[serde(default = "default_ntp_server")]
+    pub ntp_server: String,
+    /// Offsets beyond this are warned about
+    #[serde(default = "default_clock_max_offset_ms")]
+    pub max_offset_ms: f64,
+    #[serde(default = "default_clock_timeout_ms")]
+    pub timeout_ms: u64,
+}
+
+fn default_ntp_server() -> String {
+    "pool.ntp.org:123".to_string()
+}
+
+fn default_clock_max_offset_ms() -> f64 {
+    50.0
+}
+
+fn default_clock_timeout_ms() -> u64 {
+    2000
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct InfluxConfig {
+    /// `udp://host:port` or a file path to append line protocol to
+    pub target: String,
+    #[serde(default = "default_influx_window_secs")]
+    pub window_secs: u64,
+}
+
+fn default_influx_window_secs() -> u64 {
+    10
+}
+
+/// OTLP export of per-message pipeline spans and timings; needs the `otel` build feature.
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct OtelConfig {
+    /// OTLP gRPC collector endpoint
+    #[serde(default = "default_otel_endpoint")]
+    pub endpoint: String,
+    #[serde(default = "default_otel_service_name")]
+    pub service_name: String,
+}
+
+fn default_otel_endpoint() -> String {
+    "http://localhost:4317".to_string()
+}
+
+fn default_otel_service_name() -> String {
+    "geyserbench".to_string()
+}
+
+/// Thresholds checked per alert window; each optional.
+#[derive(Debug, Deserialize, Serialize, Clone, Default)]
+pub struct AlertThresholds {
+    /// Upper bound on the window's p95 delay behind the first detection
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub p95_ms: Option<f64>,
+    /// Longest allowed stretch without a matched transaction from the endpoint
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_gap_secs: Option<f64>,
+}
+
+impl AlertThresholds {
+    /// These thresholds, falling back to `defaults` for the ones left unset.
+    pub fn or(&self, defaults: &AlertThresholds) -> AlertThresholds {
+        AlertThresholds {
+            p95_ms: self.p95_ms.or(defaults.p95_ms),
+            max_gap_secs: self.max_gap_secs.or(defaults.max_gap_secs),
+        }
+    }
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct AlertConfig {
+    #[serde(default = "default_alert_window_secs")]
+    pub window_secs: u64,
+    /// Defaults for every endpoint; `[endpoint.alerts]` overrides them per endpoint
+    #[serde(default)]
+    pub thresholds: AlertThresholds,
+    /// Windows with fewer sightings from an endpoint skip its p95 check
+    #[serde(default = "default_alert_min_samples")]
+    pub min_samples: usize,
+    /// Exit non-zero when any window violated a threshold
+    #[serde(default = "default_alert_fail_on_violation")]
+    pub fail_on_violation: bool,
+}
+
+fn default_alert_window_secs() -> u64 {
+    60
+}
+
+fn default_alert_min_samples() -> usize {
+    20
+}
+
+fn default_alert_fail_on_violation() -> bool {
+    true
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct ParquetConfig {
+    /// Output file, replaced if it exists
+    pub path: String,
+    /// Rows buffered before a row group is written
+    #[serde(default = "default_parquet_row_group_size")]
+    pub row_group_size: usize,
+}
+
+fn default_parquet_row_group_size() -> usize {
+    65_536
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct HistoryConfig {
+    /// SQLite database file, created if missing
+    pub path: String,
+    #[serde(default = "default_history_window_secs")]
+    pub window_secs: u64,
+    /// Days of raw window rows kept before only the daily roll-up remains
+    #[serde(default = "default_history_window_retention_days")]
+    pub window_retention_days: u64,
+    #[serde(default = "default_history_daily_retention_days")]
+    pub daily_retention_days: u64,
+}
+
+fn default_history_window_secs() -> u64 {
+    60
+}
+
+fn default_history_window_retention_days() -> u64 {
+    7
+}
+
+fn default_history_daily_retention_days() -> u64 {
+    365
+}
+
+/// How a signature first seen by several endpoints at the same timestamp is credited.
+#[derive(Debug, Clone, Copy, Default, Deserialize, Serialize, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum TiePolicy {
+    /// Every tied endpoint gets a full win
+    #[default]
+    Shared,
+    /// The win is split evenly between tied endpoints
+    Half,
+    /// Tied signatures are left out of the statistics
+    Exclude,
+}
+
+const VOTE_PROGRAM_ID: &str = "Vote111111111111111111111111111111111111111";
+
+/// Yellowstone's `vote` and `failed` transaction filter flags: unset includes both kinds, `true`
+/// keeps only them, `false` drops them.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Deserialize, Serialize)]
+pub struct TransactionFilter {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub vote: Option<bool>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub failed: Option<bool>,
+}
+
+impl TransactionFilter {
+    // AIDEV-NOTE: Client-side twin of the server-side `vote` flag for streams without one. A
+    // transaction invoking the vote program counts as a vote; Yellowstone only flags simple votes,
+    // but vote transactions doing anything else are vanishingly rare. `failed` has no twin:
+    // shred-based streams carry no execution result.
+    /// Whether a transaction with these account keys passes the `vote` flag.
+    pub fn admits(&self, account_keys: &[String]) -> bool {
+        match self.vote {
+            Some(vote) => vote == account_keys.iter().any(|key| key == VOTE_PROGRAM_ID),
+            None => true,
+        }
+    }
+}
+
+/// A filter map of the Yellowstone subscribe request.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SubscribeFilter {
+    Slots,
+    Transactions,
+    TransactionsStatus,
+    Accounts,
+    Entry,
+    Blocks,
+}
+
+/// Per-endpoint changes to the Yellowstone subscribe request, for plans that reject parts of the
+/// default one. Unset fields keep what the mode and the top-level config send.
+#[derive(Debug, Clone, Default, PartialEq, Deserialize, Serialize)]
+pub struct SubscribeOverrides {
+    /// Filter maps to send; the mode's other maps are left out
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub include: Option<Vec<SubscribeFilter>>,
+    /// Name every filter is sent under, instead of `account`, `entries` or `blocks`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub filter_name: Option<String>,
+    /// Overrides `transaction_filter` for this endpoint
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub transaction_filter: Option<TransactionFilter>,
+}
+
+/// How `yellowstone_accounts` endpoints filter their account subscription.
+#[derive(Debug, Clone, Copy, Default, Deserialize, Serialize, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum AccountsStrategy {
+    /// The watched accounts themselves
+    #[default]
+    Account,
+    /// Accounts owned by `accounts_owner`
+    Owner,
+    /// Every account update on the node
+    Unfiltered,
+}
+
+fn default_accounts_fallback_secs() -> u64 {
+    30
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct TokenAccountsConfig {
+    /// Solana JSON-RPC endpoint answering `getTokenAccountsByOwner`
+    pub rpc_url: String,
+    /// Wallets whose token accounts are added; `account` and `accounts` when empty
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub wallets: Vec<String>,
+    /// Include accounts of the Token-2022 program as well as SPL Token
+    #[serde(default = "default_token_2022")]
+    pub token_2022: bool,
+    /// Cap on the token accounts added across all wallets
+    #[serde(default = "default_token_accounts_max")]
+    pub max_accounts: usize,
+}
+
+fn default_token_2022() -> bool {
+    true
+}
+
+fn default_token_accounts_max() -> usize {
+    256
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct TokenRefresh {
+    /// Shell command printing the token on stdout
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub command: Option<String>,
+    /// URL answering with the token, as plain text or JSON with a `token`, `access_token` or `id_token` field
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub url: Option<String>,
+    /// Header to put the token in instead of `x_token`, e.g. `authorization`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub header: Option<String>,
+    /// Prepended to the token, e.g. `Bearer `
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub prefix: String,
+    /// Fetch a token before the first connection too, for configs without a static one
+    #[serde(default)]
+    pub on_start: bool,
+    /// Refreshes in a row that may still be rejected before the endpoint gives up
+    #[serde(default = "default_token_refresh_attempts")]
+    pub max_attempts: u32,
+}
+
+fn default_token_refresh_attempts() -> u32 {
+    3
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct SenderConfig {
+    /// Solana JSON-RPC endpoint for blockhashes and, without `jito_url`, `sendTransaction`
+    pub rpc_url: String,
+    /// Solana CLI keypair file paying the fees; its address is added to the watched accounts
+    pub keypair: String,
+    #[serde(default = "default_sender_count")]
+    pub count: u32,
+    #[serde(default = "default_sender_interval_ms")]
+    pub interval_ms: u64,
+    /// Wait after the last endpoint started before the first send, for subscriptions to settle
+    #[serde(default = "default_sender_warmup_secs")]
+    pub warmup_secs: u64,
+    /// Keep streaming this long after the last send, then stop the run
+    #[serde(default = "default_sender_settle_secs")]
+    pub settle_secs: u64,
+    /// Jito block engine bundle URL; when set each transaction goes out as a one-transaction bundle
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub jito_url: Option<String>,
+    #[serde(default = "default_jito_tip_lamports")]
+    pub jito_tip_lamports: u64,
+}
+
+fn default_sender_count() -> u32 {
+    20
+}
+
+fn default_sender_interval_ms() -> u64 {
+    2000
+}
+
+fn default_sender_warmup_secs() -> u64 {
+    5
+}
+
+fn default_sender_settle_secs() -> u64 {
+    30
+}
+
+fn default_jito_tip_lamports() -> u64 {
+    1000
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct LogsConfig {
+    #[serde(default = "default_logs_dir")]
+    pub dir: String,
+    /// Write each run's logs to a `run-<UTC start time>` subdirectory of `dir`
+    #[serde(default)]
+    pub run_subdir: bool,
+    /// Start a new file once the current one reaches this size
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_size_mb: Option<u64>,
+    /// Start a new file once the current one is this old
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_age_hours: Option<u64>,
+}
+
+impl Default for LogsConfig {
+    fn default() -> Self {
+        Self {
+            dir: default_logs_dir(),
+            run_subdir: false,
+            max_size_mb: None,
+            max_age_hours: None,
+        }
+    }
+}
+
+fn default_logs_dir() -> String {
+    ".".to_string()
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct LookupTablesConfig {
+    /// Solana JSON-RPC endpoint the tables are fetched from, for streams without loaded addresses
+    pub rpc_url: String,
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct LeadersConfig {
+    /// Solana JSON-RPC endpoint answering `getSlotLeaders` for the observed slots
+    pub rpc_url: String,
+    /// Leaders with fewer live signatures than this are left out
+    #[serde(default = "default_leaders_min_signatures")]
+    pub min_signatures: usize,
+    /// Leaders printed, most signatures first; the JSON report keeps every one
+    #[serde(default = "default_leaders_top_n")]
+    pub top_n: usize,
+}
+
+fn default_leaders_min_signatures() -> usize {
+    10
+}
+
+fn default_leaders_top_n() -> usize {
+    10
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct GroundTruthConfig {
+    /// Solana JSON-RPC endpoint answering `getSignaturesForAddress` for the watched accounts
+    pub rpc_url: String,
+    /// Signatures fetched per account before the check gives up
+    #[serde(default = "default_ground_truth_max_signatures")]
+    pub max_signatures: usize,
+}
+
+fn default_ground_truth_max_signatures() -> usize {
+    100_000
+}
+
+// AIDEV-NOTE: Mirrors SubscribeRequestFilterAccounts; used by yellowstone_accounts only
+#[derive(Debug, Deserialize, Serialize, Clone, Default)]
+pub struct AccountsFilter {
+    #[serde(default)]
+    pub account: Vec<String>,
+    #[serde(default)]
+    pub owner: Vec<String>,
+    #[serde(default)]
+    pub filters: Vec<AccountsFilterRule>,
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone)]
+#[serde(tag = "type", rename_all = "lowercase")]
+pub enum AccountsFilterRule {
+    Datasize { size: u64 },
+    Memcmp { offset: u64, base58: String },
+}
+
+/// Byte range of account data to receive, as in Yellowstone's `accounts_data_slice`.
+#[derive(Debug, Deserialize, Serialize, Clone, Copy, PartialEq, Eq)]
+pub struct DataSlice {
+    pub offset: u64,
+    pub length: u64,
+}
+
+impl From<&DataSlice> for SubscribeRequestAccountsDataSlice {
+    fn from(slice: &DataSlice) -> Self {
+        SubscribeRequestAccountsDataSlice {
+            offset: slice.offset,
+            length: slice.length,
+        }
+    }
+}
+
+/// Slices must be sorted by offset and must not overlap, as Yellowstone servers require. An empty
+/// slice is allowed and receives no data at all.
+fn validate_data_slices(slices: &[DataSlice]) -> Result<(), String> {
+    for pair in slices.windows(2) {
+        if pair[0].offset.saturating_add(pair[0].length) > pair[1].offset {
+            return Err(format!(
+                "slices must be sorted by offset and must not overlap ({}+{} and {}+{})",
+                pair[0].offset, pair[0].length, pair[1].offset, pair[1].length
+            ));
+        }
+    }
+    Ok(())
 }
 
 #[derive(Debug, Deserialize, Serialize, Clone)]
@@ -23,21 +932,288 @@ pub struct Endpoint {
     pub url: String,
     pub x_token: String,
     pub kind: EndpointKind,
+    #[serde(default)]
+    pub frame_timing: bool,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub keepalive_interval_secs: Option<u64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub keepalive_timeout_secs: Option<u64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub ping_interval_secs: Option<u64>,
+    /// Largest gRPC message decoded, in bytes; `DEFAULT_MAX_DECODING_MESSAGE_SIZE` when unset
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_decoding_message_size: Option<usize>,
+    /// HTTP/2 flow control windows in bytes; the transport's defaults when unset
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub initial_connection_window_size: Option<u32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub initial_stream_window_size: Option<u32>,
+    /// Disable Nagle's algorithm on the connection; on unless set to false
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tcp_nodelay: Option<bool>,
+    /// Core this endpoint's dedicated runtime is pinned to, overriding `dedicated_runtimes.cores`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub core: Option<usize>,
+    #[serde(default)]
+    pub start_delay_secs: u64,
+    #[serde(default = "default_shards")]
+    pub shards: usize,
+    /// Parallel subscriptions with the same filter; each signature keeps its earliest sighting
+    #[serde(default = "default_connections")]
+    pub connections: usize,
+    /// Labels for `tags` selection; `key=value` labels such as `region=fra` also feed `group_by`
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tags: Vec<String>,
+    /// Extra gRPC metadata sent with every request, e.g. `authorization` or `x-api-key`
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub headers: BTreeMap<String, String>,
+    /// Overrides `alerts.thresholds` for this endpoint
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub alerts: Option<AlertThresholds>,
+    /// What-if offset in ms added to this endpoint's receive times in the "Handicapped results" race
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub handicap_ms: Option<f64>,
+    /// Fetches a fresh token and resubscribes when the server answers UNAUTHENTICATED
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub token_refresh: Option<TokenRefresh>,
+    /// `http://`, `socks5://` or `socks5h://` proxy to connect through, or `none` to ignore the proxy variables
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub proxy: Option<String>,
+    /// Overrides `accounts_data_slice` for this endpoint; `[]` receives whole payloads
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub accounts_data_slice: Option<Vec<DataSlice>>,
+    /// Overrides parts of the subscribe request for this endpoint
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub subscribe: Option<SubscribeOverrides>,
+    /// `url` as written, when it referenced environment variables; logged and reported instead
+    #[serde(skip)]
+    pub raw_url: Option<String>,
+}
+
+impl EndpointKind {
+    /// Registry key of a kind as written in a config: lowercase, with `-` read as `_`.
+    pub fn normalize(kind: &str) -> String {
+        kind.trim().to_lowercase().replace('-', "_")
+    }
+
+    pub fn as_str(&self) -> &str {
+        match self {
+            EndpointKind::Yellowstone => "yellowstone",
+            EndpointKind::YellowstoneAccounts => "yellowstone_accounts",
+            EndpointKind::Arpc => "arpc",
+            EndpointKind::Thor => "thor",
+            EndpointKind::Shreder => "shreder",
+            EndpointKind::Jetstream => "jetstream",
+            EndpointKind::ShredstreamProxy => "shredstream_proxy",
+            EndpointKind::UnixSocket => "unix_socket",
+            EndpointKind::Fumarole => "fumarole",
+            EndpointKind::Custom(kind) => kind,
+        }
+    }
+
+    /// None for kinds whose source is not known to be either.
+    pub fn execution_stage(&self) -> Option<ExecutionStage> {
+        match self {
+            EndpointKind::Shreder | EndpointKind::ShredstreamProxy | EndpointKind::Jetstream => {
+                Some(ExecutionStage::PreExecution)
+            }
+            EndpointKind::Yellowstone
+            | EndpointKind::YellowstoneAccounts
+            | EndpointKind::Thor
+            | EndpointKind::UnixSocket
+            | EndpointKind::Fumarole => Some(ExecutionStage::PostExecution),
+            EndpointKind::Arpc | EndpointKind::Custom(_) => None,
+        }
+    }
+}
+
+impl From<String> for EndpointKind {
+    fn from(kind: String) -> Self {
+        match EndpointKind::normalize(&kind).as_str() {
+            "yellowstone" => EndpointKind::Yellowstone,
+            "yellowstone_accounts" => EndpointKind::YellowstoneAccounts,
+            "arpc" => EndpointKind::Arpc,
+            "thor" => EndpointKind::Thor,
+            "shreder" => EndpointKind::Shreder,
+            "jetstream" => EndpointKind::Jetstream,
+            "shredstream_proxy" => EndpointKind::ShredstreamProxy,
+            "unix_socket" => EndpointKind::UnixSocket,
+            "fumarole" => EndpointKind::Fumarole,
+            other => EndpointKind::Custom(other.to_string()),
+        }
+    }
+}
+
+impl From<EndpointKind> for String {
+    fn from(kind: EndpointKind) -> Self {
+        kind.as_str().to_string()
+    }
+}
+
+impl fmt::Display for EndpointKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl Endpoint {
+    /// `max_decoding_message_size`, or the default when unset.
+    pub fn decoding_limit(&self) -> usize {
+        self.max_decoding_message_size.unwrap_or(DEFAULT_MAX_DECODING_MESSAGE_SIZE)
+    }
+
+    /// URL safe to log: the template when it referenced environment variables, else the URL with
+    /// its credentials masked.
+    pub fn display_url(&self) -> String {
+        match &self.raw_url {
+            Some(raw_url) => raw_url.clone(),
+            None => redact_url(&self.url),
+        }
+    }
+
+    /// Account data slices this endpoint subscribes with: its own, else the config-wide ones.
+    pub fn data_slices<'a>(&'a self, config: &'a Config) -> &'a [DataSlice] {
+        self.accounts_data_slice.as_deref().unwrap_or(&config.accounts_data_slice)
+    }
+
+    /// `vote` and `failed` flags this endpoint subscribes with: its own, else the config-wide ones.
+    pub fn transaction_filter(&self, config: &Config) -> TransactionFilter {
+        self.subscribe
+            .as_ref()
+            .and_then(|subscribe| subscribe.transaction_filter)
+            .unwrap_or(config.transaction_filter)
+    }
+
+    /// Value of this endpoint's `key=value` tag, e.g. `fra` for `region` with the tag `region=fra`.
+    pub fn tag_value(&self, key: &str) -> Option<&str> {
+        self.tags
+            .iter()
+            .find_map(|tag| tag.split_once('=').filter(|(own, _)| own.trim() == key).map(|(_, value)| value.trim()))
+    }
+
+    // AIDEV-NOTE: Plain tags are alternatives (any one selects), `!tag` excludes; with only
+    // exclusions every endpoint not carrying them is selected.
+    pub fn matches_tags(&self, expression: &[String]) -> bool {
+        let (excluded, included): (Vec<&str>, Vec<&str>) = expression
+            .iter()
+            .map(|tag| tag.trim())
+            .filter(|tag| !tag.is_empty())
+            .partition(|tag| tag.starts_with('!'));
+        let has = |tag: &str| self.tags.iter().any(|own| own == tag);
+        (included.is_empty() || included.iter().any(|tag| has(tag)))
+            && !excluded.iter().any(|tag| has(&tag[1..]))
+    }
+}
+
+/// Expands `${NAME}` references; unset names are collected and left in place.
+fn interpolate(value: &str, missing: &mut BTreeSet<String>) -> String {
+    let mut result = String::with_capacity(value.len());
+    let mut rest = value;
+    while let Some(start) = rest.find("${") {
+        let Some(len) = rest[start + 2..].find('}') else {
+            break;
+        };
+        let name = &rest[start + 2..start + 2 + len];
+        result.push_str(&rest[..start]);
+        match env::var(name) {
+            Ok(resolved) => result.push_str(&resolved),
+            Err(_) => {
+                missing.insert(name.to_string());
+                result.push_str(&rest[start..start + 3 + len]);
+            }
+        }
+        rest = &rest[start + 3 + len..];
+    }
+    result.push_str(rest);
+    result
+}
+
+// AIDEV-NOTE: Providers like Helius and Triton put the API key in the query or as a path
+// segment, so those are masked along with any userinfo. A URL needing no masking is returned
+// unchanged rather than re-serialized, which would append a trailing slash.
+/// `url` with its username and password dropped, query values and token-like path segments
+/// replaced by `redacted`. Values that are not URLs (socket and file paths) pass through unless
+/// they look like they carry credentials.
+pub fn redact_url(url: &str) -> String {
+    let looks_secret = url.contains('@') || url.contains('?');
+    let Ok(mut parsed) = reqwest::Url::parse(url) else {
+        return if looks_secret { "<redacted>".to_string() } else { url.to_string() };
+    };
+    if parsed.cannot_be_a_base() {
+        return if looks_secret { "<redacted>".to_string() } else { url.to_string() };
+    }
+    let mut changed = false;
+    if !parsed.username().is_empty() || parsed.password().is_some() {
+        let _ = parsed.set_username("");
+        let _ = parsed.set_password(None);
+        changed = true;
+    }
+    if parsed.query().is_some() {
+        let keys: Vec<String> = parsed.query_pairs().map(|(key, _)| key.into_owned()).collect();
+        parsed.set_query(None);
+        if !keys.is_empty() {
+            let mut pairs = parsed.query_pairs_mut();
+            for key in &keys {
+                pairs.append_pair(key, "redacted");
+            }
+        }
+        changed = true;
+    }
+    let is_token = |segment: &str| {
+        segment.len() >= 16
+            && segment.chars().all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+            && segment.chars().any(|c| c.is_ascii_digit())
+    };
+    let segments: Vec<String> = parsed.path_segments().into_iter().flatten().map(str::to_string).collect();
+    if segments.iter().any(|segment| is_token(segment)) {
+        let path: Vec<&str> = segments
+            .iter()
+            .map(|segment| if is_token(segment) { "redacted" } else { segment.as_str() })
+            .collect();
+        parsed.set_path(&format!("/{}", path.join("/")));
+        changed = true;
+    }
+    if changed {
+        parsed.to_string()
+    } else {
+        url.to_string()
+    }
+}
+
+/// Decoding limit for gRPC messages when an endpoint sets none. tonic's own 4 MiB rejects full
+/// blocks of busy slots.
+pub const DEFAULT_MAX_DECODING_MESSAGE_SIZE: usize = 64 * 1024 * 1024;
+/// Largest HTTP/2 flow control window (2^31 - 1)
+const MAX_WINDOW_SIZE: u32 = i32::MAX as u32;
+
+fn default_connections() -> usize {
+    1
+}
+
+fn default_shards() -> usize {
+    1
 }
 
 
+// AIDEV-NOTE: Kinds are plain strings in the config, looked up in `providers::registry`; the
+// built-in ones get variants so the runner can match on their capabilities, anything else is
+// `Custom` and only resolves once an embedder registers a factory under that name.
 #[derive(Debug, Deserialize, Serialize, Clone, PartialEq)]
-#[serde(rename_all = "lowercase")]
+#[serde(from = "String", into = "String")]
 pub enum EndpointKind {
     Yellowstone,
-    #[serde(rename = "yellowstone_accounts")]
     YellowstoneAccounts,
     Arpc,
     Thor,
     Shreder,
     Jetstream,
-    #[serde(rename = "shredstream_proxy")]
     ShredstreamProxy,
+    /// Local geyser plugin streaming length-delimited `SubscribeUpdate` frames over a Unix socket or FIFO
+    UnixSocket,
+    /// Yellowstone Fumarole persisted stream read through a consumer group
+    Fumarole,
+    /// Provider registered at runtime with `providers::register_provider`
+    Custom(String),
 }
 
 #[derive(Debug, Clone, Copy, Default, Deserialize, Serialize)]
@@ -49,6 +1225,40 @@ pub enum ArgsCommitment {
     Finalized,
 }
 
+impl Config {
+    /// `account` followed by any extra `accounts`, `account_targets` and `filter_groups` accounts,
+    /// without duplicates.
+    pub fn watched_accounts(&self) -> Vec<String> {
+        let mut watched = vec![self.account.clone()];
+        let grouped = self.filter_groups.values().flatten();
+        for account in self.accounts.iter().chain(self.account_targets.keys()).chain(grouped) {
+            if !watched.contains(account) {
+                watched.push(account.clone());
+            }
+        }
+        watched
+    }
+
+    pub fn watches_any(&self, account_keys: &[String]) -> bool {
+        account_keys.iter().any(|key| {
+            key == &self.account
+                || self.accounts.contains(key)
+                || self.account_targets.contains_key(key)
+                || self.filter_groups.values().any(|accounts| accounts.contains(key))
+        })
+    }
+
+    /// Names of the `filter_groups` holding any of a transaction's matched accounts; empty without
+    /// groups, so ungrouped runs allocate nothing.
+    pub fn filter_groups_for(&self, matched: &[String]) -> Vec<String> {
+        self.filter_groups
+            .iter()
+            .filter(|(_, accounts)| accounts.iter().any(|account| matched.contains(account)))
+            .map(|(name, _)| name.clone())
+            .collect()
+    }
+}
+
 impl From<ArgsCommitment> for CommitmentLevel {
     fn from(commitment: ArgsCommitment) -> Self {
         match commitment {
@@ -59,20 +1269,481 @@ impl From<ArgsCommitment> for CommitmentLevel {
     }
 }
 
+impl From<&AccountsFilterRule> for SubscribeRequestFilterAccountsFilter {
+    fn from(rule: &AccountsFilterRule) -> Self {
+        let filter = match rule {
+            AccountsFilterRule::Datasize { size } => AccountsFilterOneof::Datasize(*size),
+            AccountsFilterRule::Memcmp { offset, base58 } => {
+                AccountsFilterOneof::Memcmp(SubscribeRequestFilterAccountsFilterMemcmp {
+                    offset: *offset,
+                    data: Some(MemcmpData::Base58(base58.clone())),
+                })
+            }
+        };
+        SubscribeRequestFilterAccountsFilter {
+            filter: Some(filter),
+        }
+    }
+}
+
+impl From<&AccountsFilter> for SubscribeRequestFilterAccounts {
+    fn from(filter: &AccountsFilter) -> Self {
+        SubscribeRequestFilterAccounts {
+            account: filter.account.clone(),
+            owner: filter.owner.clone(),
+            filters: filter.filters.iter().map(Into::into).collect(),
+            nonempty_txn_signature: None,
+        }
+    }
+}
 
 impl ConfigToml {
     pub fn load(path: &str) -> Result<Self, Box<dyn std::error::Error>> {
-        let content = fs::read_to_string(path)?;
-        let config = toml::from_str(&content)?;
+        let content = fs::read_to_string(path).map_err(|e| format!("{}: {}", path, e))?;
+        let mut config = Self::parse(&content, ConfigFormat::from_path(path)).map_err(|e| format!("{}: {}", path, e))?;
+        config.interpolate_env()?;
+        if let Some(preset) = config.config.preset {
+            config.apply_preset(preset);
+        }
+        if let Some(scenario) = config.config.scenario {
+            config.apply_scenario(scenario);
+        }
+        if !config.config.tags.is_empty() {
+            let tags = config.config.tags.clone();
+            config.select_by_tags(&tags)?;
+        }
+        config.validate()?;
+        Ok(config)
+    }
+
+    // AIDEV-NOTE: serde would skip unknown keys silently, so a misspelt option quietly fell back to
+    // its default; they are collected while deserializing and rejected instead. Syntax and type
+    // errors keep the line and column reported by the format's own parser.
+    /// Parses a config without validating it, rejecting keys no option knows.
+    pub fn parse(content: &str, format: ConfigFormat) -> Result<Self, Box<dyn std::error::Error>> {
+        let mut unknown = Vec::new();
+        // Option and newtype layers show up as `?` segments; they mean nothing to the reader
+        let mut collect = |path: serde_ignored::Path<'_>| unknown.push(path.to_string().replace(".?", "").replace("?.", ""));
+        let config: Self = match format {
+            ConfigFormat::Toml => serde_ignored::deserialize(toml::Deserializer::new(content), &mut collect)?,
+            ConfigFormat::Yaml => serde_ignored::deserialize(serde_yaml::Deserializer::from_str(content), &mut collect)?,
+            ConfigFormat::Json => {
+                let mut deserializer = serde_json::Deserializer::from_str(content);
+                let config = serde_ignored::deserialize(&mut deserializer, &mut collect)?;
+                deserializer.end()?;
+                config
+            }
+        };
+        if !unknown.is_empty() {
+            let keys: Vec<String> = unknown
+                .iter()
+                .map(|path| {
+                    let key = path.rsplit('.').next().unwrap_or(path);
+                    match key_line(content, key) {
+                        Some(line) => format!("{} (line {})", path, line),
+                        None => path.clone(),
+                    }
+                })
+                .collect();
+            return Err(format!("unknown config keys, check their spelling and section: {}", keys.join(", ")).into());
+        }
         Ok(config)
     }
 
+    // AIDEV-NOTE: Only endpoint URLs, tokens and headers are interpolated, as those are where API
+    // keys live. An interpolated URL keeps its template in `raw_url` so the key stays out of logs and
+    // the reported configuration.
+    /// Replaces `${NAME}` in endpoint URLs, tokens and headers with environment variables.
+    pub fn interpolate_env(&mut self) -> Result<(), Box<dyn std::error::Error>> {
+        let mut missing = BTreeSet::new();
+        for endpoint in &mut self.endpoint {
+            let url = interpolate(&endpoint.url, &mut missing);
+            if url != endpoint.url {
+                endpoint.raw_url = Some(std::mem::replace(&mut endpoint.url, url));
+            }
+            endpoint.x_token = interpolate(&endpoint.x_token, &mut missing);
+            for value in endpoint.headers.values_mut() {
+                *value = interpolate(value, &mut missing);
+            }
+        }
+        if !missing.is_empty() {
+            let names: Vec<_> = missing.into_iter().collect();
+            return Err(format!("config references unset environment variables: {}", names.join(", ")).into());
+        }
+        Ok(())
+    }
+
+    /// Keeps only the endpoints matching the tag expression and records it in the config.
+    pub fn select_by_tags(&mut self, expression: &[String]) -> Result<(), Box<dyn std::error::Error>> {
+        self.endpoint.retain(|endpoint| endpoint.matches_tags(expression));
+        if self.endpoint.is_empty() {
+            return Err(format!("no endpoint matches tags {:?}", expression).into());
+        }
+        self.config.tags = expression.to_vec();
+        Ok(())
+    }
+
+    // AIDEV-NOTE: A preset overrides its settings even when the file sets them, so two runs with
+    // the same preset are comparable regardless of who wrote the config.
+    pub fn apply_preset(&mut self, preset: Preset) {
+        let config = &mut self.config;
+        config.preset = Some(preset);
+        match preset {
+            Preset::Quick => {
+                config.transactions = 100;
+                config.tsc_timestamps = false;
+                config.race_top_n = 0;
+                config.warmup_secs = 0;
+                config.clock_check = None;
+            }
+            Preset::Standard => {
+                config.transactions = 1_000;
+                config.tsc_timestamps = true;
+                config.race_top_n = 10;
+                config.warmup_secs = 5;
+            }
+            Preset::Thorough => {
+                config.transactions = 10_000;
+                config.tsc_timestamps = true;
+                config.race_top_n = 50;
+                config.warmup_secs = 15;
+                config.clock_check.get_or_insert_with(|| ClockCheckConfig {
+                    ntp_server: default_ntp_server(),
+                    max_offset_ms: default_clock_max_offset_ms(),
+                    timeout_ms: default_clock_timeout_ms(),
+                });
+            }
+        }
+    }
+
+    // AIDEV-NOTE: The hybrid gap is only meaningful at processed commitment in latency mode, so the
+    // scenario forces both; endpoints themselves come from the file and are checked in validate.
+    pub fn apply_scenario(&mut self, scenario: Scenario) {
+        let config = &mut self.config;
+        config.scenario = Some(scenario);
+        match scenario {
+            Scenario::Hybrid => {
+                config.mode = BenchmarkMode::Latency;
+                config.commitment = ArgsCommitment::Processed;
+            }
+        }
+    }
+
+    pub fn validate(&self) -> Result<(), Box<dyn std::error::Error>> {
+        if !(0.0..50.0).contains(&self.config.trim_percent) {
+            return Err(format!(
+                "trim_percent must be in [0, 50), got {}",
+                self.config.trim_percent
+            )
+            .into());
+        }
+        if let Some(window) = self.config.dedup_window_secs {
+            if window.is_nan() || window <= 0.0 {
+                return Err(format!("dedup_window_secs must be positive, got {}", window).into());
+            }
+        }
+        if let Some(rate) = self.config.sample_rate {
+            if !(rate > 0.0 && rate <= 1.0) {
+                return Err(format!("sample_rate must be in (0, 1], got {}", rate).into());
+            }
+        }
+        if self.config.scenario == Some(Scenario::Hybrid) {
+            let has_stage = |stage: ExecutionStage| self.endpoint.iter().any(|e| e.kind.execution_stage() == Some(stage));
+            if !has_stage(ExecutionStage::PreExecution) || !has_stage(ExecutionStage::PostExecution) {
+                return Err("the hybrid scenario needs a shred-based endpoint (shreder, shredstream_proxy, jetstream) and a yellowstone, thor, unix_socket or fumarole endpoint".into());
+            }
+        }
+        if self.config.drain_ms > MAX_DRAIN_MS {
+            return Err(format!("drain_ms must be at most {}, got {}", MAX_DRAIN_MS, self.config.drain_ms).into());
+        }
+        if self.config.watchdog.as_ref().is_some_and(|watchdog| watchdog.timeout_secs == 0) {
+            return Err("watchdog.timeout_secs must be at least 1".into());
+        }
+        if let Some(history) = &self.config.history {
+            if history.window_retention_days == 0 || history.daily_retention_days == 0 {
+                return Err("history retention must be at least one day".into());
+            }
+        }
+        if self.config.accounts_strategy == AccountsStrategy::Owner
+            && self.config.accounts_filter.is_none()
+            && self.config.accounts_owner.is_empty()
+        {
+            return Err("accounts_strategy = \"owner\" needs accounts_owner".into());
+        }
+        if let Some(notify) = &self.config.notify {
+            if notify.kind == WebhookKind::Telegram && notify.chat_id.is_none() {
+                return Err("notify.chat_id is required for telegram webhooks".into());
+            }
+        }
+        if !self.config.account_targets.is_empty() {
+            if self.config.mode != BenchmarkMode::Latency {
+                return Err("account_targets needs latency mode, the only one that matches transactions by account".into());
+            }
+            if let Some((account, _)) = self.config.account_targets.iter().find(|(_, target)| **target == 0) {
+                return Err(format!("account_targets: target for {} must be at least 1", account).into());
+            }
+        }
+        if !self.config.filter_groups.is_empty() {
+            if self.config.mode != BenchmarkMode::Latency {
+                return Err("filter_groups needs latency mode, the only one that matches transactions by account".into());
+            }
+            if let Some((name, _)) = self.config.filter_groups.iter().find(|(_, accounts)| accounts.is_empty()) {
+                return Err(format!("filter_groups: group {} has no accounts", name).into());
+            }
+        }
+        if self.config.token_accounts.as_ref().is_some_and(|token_accounts| token_accounts.rpc_url.is_empty()) {
+            return Err("token_accounts.rpc_url must be set".into());
+        }
+        if self.config.logs.dir.is_empty() {
+            return Err("logs.dir must not be empty".into());
+        }
+        if self.config.logs.max_size_mb == Some(0) || self.config.logs.max_age_hours == Some(0) {
+            return Err("logs.max_size_mb and logs.max_age_hours must be at least 1".into());
+        }
+        if self.config.lookup_tables.as_ref().is_some_and(|lookup_tables| lookup_tables.rpc_url.is_empty()) {
+            return Err("lookup_tables.rpc_url must be set".into());
+        }
+        if self.config.leaders.as_ref().is_some_and(|leaders| leaders.rpc_url.is_empty()) {
+            return Err("leaders.rpc_url must be set".into());
+        }
+        if let Some(ground_truth) = &self.config.ground_truth {
+            if self.config.mode != BenchmarkMode::Latency {
+                return Err("ground_truth is only supported in latency mode".into());
+            }
+            if ground_truth.rpc_url.is_empty() {
+                return Err("ground_truth.rpc_url must be set".into());
+            }
+        }
+        if let Some(sender) = &self.config.sender {
+            if sender.rpc_url.is_empty() || sender.keypair.is_empty() {
+                return Err("sender.rpc_url and sender.keypair must be set".into());
+            }
+            if sender.count == 0 {
+                return Err("sender.count must be at least 1".into());
+            }
+            if self.config.mode != BenchmarkMode::Latency {
+                return Err("sender needs latency mode, the only one that matches transactions by account".into());
+            }
+        }
+        if let Some(agent) = &self.config.agent {
+            if agent.region.is_empty() || agent.region.contains('/') {
+                return Err(format!("agent.region must be non-empty without '/', got {:?}", agent.region).into());
+            }
+        }
+        if let Some(alerts) = &self.config.alerts {
+            if alerts.window_secs == 0 {
+                return Err("alerts.window_secs must be at least 1".into());
+            }
+            if self.config.mode != BenchmarkMode::Latency {
+                return Err("alerts need latency mode".into());
+            }
+        }
+        if let Some(endpoint) = self.endpoint.iter().find(|e| e.shards == 0) {
+            return Err(format!("[{}] shards must be at least 1", endpoint.name).into());
+        }
+        if let Some(endpoint) = self.endpoint.iter().find(|e| e.connections == 0) {
+            return Err(format!("[{}] connections must be at least 1", endpoint.name).into());
+        }
+        for endpoint in &self.endpoint {
+            if let Some(refresh) = &endpoint.token_refresh {
+                if refresh.command.is_some() == refresh.url.is_some() {
+                    return Err(format!("[{}] token_refresh needs exactly one of command and url", endpoint.name).into());
+                }
+                if refresh.max_attempts == 0 {
+                    return Err(format!("[{}] token_refresh.max_attempts must be at least 1", endpoint.name).into());
+                }
+            }
+        }
+        if let Some(endpoint) = self.endpoint.iter().find(|e| e.handicap_ms.is_some_and(|handicap| !handicap.is_finite())) {
+            return Err(format!("[{}] handicap_ms must be a finite number", endpoint.name).into());
+        }
+        if let Some(endpoint) = self.endpoint.iter().find(|e| e.connections > 1 && e.shards > 1) {
+            return Err(format!("[{}] connections and shards cannot be combined", endpoint.name).into());
+        }
+        validate_data_slices(&self.config.accounts_data_slice).map_err(|e| format!("accounts_data_slice: {}", e))?;
+        let weights = &self.config.scoreboard;
+        let components = [weights.win_rate, weights.median_delta, weights.p99_delta, weights.coverage, weights.uptime];
+        if components.iter().any(|weight| !weight.is_finite() || *weight < 0.0) || weights.total() <= 0.0 {
+            return Err("scoreboard weights must be non-negative and at least one must be positive".into());
+        }
+        if self.config.dedicated_runtimes.is_none() {
+            if let Some(endpoint) = self.endpoint.iter().find(|endpoint| endpoint.core.is_some()) {
+                return Err(format!("[{}] core needs dedicated_runtimes", endpoint.name).into());
+            }
+        }
+        for grouping in &self.config.group_by {
+            if grouping.tag.trim().is_empty() || grouping.within.as_deref().is_some_and(|within| within.trim().is_empty()) {
+                return Err("group_by: tag and within must name a tag key".into());
+            }
+            if grouping.within.as_deref() == Some(grouping.tag.as_str()) {
+                return Err(format!("group_by: {} cannot be grouped within itself", grouping.tag).into());
+            }
+        }
+        self.validate_endpoints()?;
+        if self.config.mode != BenchmarkMode::Throughput {
+            for account in self.config.watched_accounts() {
+                if Pubkey::from_str(&account).is_err() {
+                    return Err(format!("{:?} is not a base58 account address (set in account, accounts, account_targets or filter_groups)", account).into());
+                }
+            }
+        }
+        Ok(())
+    }
+
+    // AIDEV-NOTE: Catches what providers would otherwise only fail on once connecting, deep in
+    // their setup: malformed URLs, tokens pasted with a trailing newline and invalid gRPC header names.
+    fn validate_endpoints(&self) -> Result<(), Box<dyn std::error::Error>> {
+        let mut names = BTreeSet::new();
+        // Log file names, compared case-insensitively as Windows and macOS do
+        let mut file_names: BTreeMap<String, &str> = BTreeMap::new();
+        for endpoint in &self.endpoint {
+            if endpoint.name.is_empty() {
+                return Err(format!("endpoint with url {} has no name", endpoint.display_url()).into());
+            }
+            if !names.insert(endpoint.name.as_str()) {
+                return Err(format!("[{}] endpoint names must be unique", endpoint.name).into());
+            }
+            let file_name = log_output::sanitize_name(&endpoint.name).to_lowercase();
+            if let Some(other) = file_names.insert(file_name, &endpoint.name) {
+                return Err(format!(
+                    "[{}] endpoint name would share a transaction log file with [{}]; names must differ in more than case or characters that are not allowed in file names",
+                    endpoint.name, other
+                )
+                .into());
+            }
+            let checks_url = !matches!(endpoint.kind, EndpointKind::UnixSocket | EndpointKind::Custom(_));
+            if checks_url {
+                let scheme = reqwest::Url::parse(&endpoint.url).map(|url| url.scheme().to_string());
+                if !matches!(scheme.as_deref(), Ok("http" | "https")) {
+                    return Err(format!(
+                        "[{}] url must be an http:// or https:// URL including the port, got {}",
+                        endpoint.name,
+                        endpoint.display_url()
+                    )
+                    .into());
+                }
+            }
+            if endpoint.max_decoding_message_size == Some(0) {
+                return Err(format!("[{}] max_decoding_message_size must be at least 1 byte", endpoint.name).into());
+            }
+            let windows = [
+                ("initial_connection_window_size", endpoint.initial_connection_window_size),
+                ("initial_stream_window_size", endpoint.initial_stream_window_size),
+            ];
+            for (option, size) in windows {
+                if size.is_some_and(|size| size == 0 || size > MAX_WINDOW_SIZE) {
+                    return Err(format!("[{}] {} must be between 1 and {} bytes", endpoint.name, option, MAX_WINDOW_SIZE).into());
+                }
+            }
+            if let Some(slices) = &endpoint.accounts_data_slice {
+                validate_data_slices(slices).map_err(|e| format!("[{}] accounts_data_slice: {}", endpoint.name, e))?;
+            }
+            if let Some(subscribe) = &endpoint.subscribe {
+                if subscribe.include.as_ref().is_some_and(|include| include.is_empty()) {
+                    return Err(format!("[{}] subscribe.include must name at least one filter", endpoint.name).into());
+                }
+                if subscribe.filter_name.as_ref().is_some_and(|name| name.trim().is_empty()) {
+                    return Err(format!("[{}] subscribe.filter_name must not be empty", endpoint.name).into());
+                }
+            }
+            if let Some(url) = endpoint.proxy.as_deref().filter(|url| *url != proxy::NO_PROXY) {
+                Proxy::parse(url).map_err(|e| format!("[{}] proxy: {}", endpoint.name, e))?;
+            }
+            if endpoint.x_token.chars().any(|c| c.is_whitespace() || c.is_control()) {
+                return Err(format!(
+                    "[{}] x_token contains whitespace or control characters; check for a stray space or newline",
+                    endpoint.name
+                )
+                .into());
+            }
+            for (name, value) in &endpoint.headers {
+                let valid_name = !name.is_empty()
+                    && name.chars().all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.'));
+                if !valid_name {
+                    return Err(format!(
+                        "[{}] header name {:?} must be ASCII letters, digits, '-', '_' or '.'",
+                        endpoint.name, name
+                    )
+                    .into());
+                }
+                if !value.chars().all(|c| c.is_ascii_graphic() || c == ' ') {
+                    return Err(format!(
+                        "[{}] header {} has a value with non-ASCII or control characters",
+                        endpoint.name, name
+                    )
+                    .into());
+                }
+            }
+        }
+        Ok(())
+    }
+
     pub fn create_default(path: &str) -> Result<Self, Box<dyn std::error::Error>> {
         let default_config = ConfigToml {
             config: Config {
                 transactions: 100,
                 account: "pubkey".to_string(),
+                accounts: Vec::new(),
+                account_targets: BTreeMap::new(),
+                filter_groups: BTreeMap::new(),
                 commitment: ArgsCommitment::Processed,
+                tsc_timestamps: false,
+                transaction_filter: TransactionFilter::default(),
+                from_slot: None,
+                token_accounts: None,
+                sender: None,
+                lookup_tables: None,
+                leaders: None,
+                ground_truth: None,
+                accounts_filter: None,
+                accounts_strategy: AccountsStrategy::Account,
+                accounts_owner: Vec::new(),
+                accounts_fallback_secs: default_accounts_fallback_secs(),
+                accounts_data_slice: Vec::new(),
+                report_path: None,
+                signing_keypair: None,
+                trim_percent: 0.0,
+                tie_policy: TiePolicy::Shared,
+                influx: None,
+                history: None,
+                parquet: None,
+                race_top_n: 0,
+                race_table_path: None,
+                heatmap_window_secs: default_heatmap_window_secs(),
+                heatmap_csv_path: None,
+                account_diff_path: None,
+                mode: BenchmarkMode::Latency,
+                throughput: ThroughputConfig::default(),
+                blocks: BlocksConfig::default(),
+                fumarole: FumaroleConfig::default(),
+                scoreboard: ScoreWeights::default(),
+                slo: None,
+                max_runtime_secs: None,
+                clock_check: None,
+                preset: None,
+                notify: None,
+                tags: Vec::new(),
+                dedicated_runtimes: None,
+                group_by: Vec::new(),
+                sample_rate: None,
+                dedup_window_secs: None,
+                event_log: false,
+                timeline_path: None,
+                otel: None,
+                stall_threshold_secs: default_stall_threshold_secs(),
+                decode_markets: false,
+                watchdog: None,
+                scenario: None,
+                drain_ms: default_drain_ms(),
+                warmup_secs: 0,
+                retention_days: None,
+                logs: LogsConfig::default(),
+                inspect_connections: false,
+                rtt_interval_secs: None,
+                self_profile: false,
+                consumer_delay_us: 0,
+                agent: None,
+                alerts: None,
             },
             endpoint: vec![
                 Endpoint {
@@ -80,22 +1751,120 @@ impl ConfigToml {
                     url: "http://0.0.0.0:10101".to_string(),
                     x_token: "".to_string(),
                     kind: EndpointKind::Yellowstone,
+                    frame_timing: false,
+                    keepalive_interval_secs: None,
+                    keepalive_timeout_secs: None,
+                    ping_interval_secs: None,
+                    max_decoding_message_size: None,
+                    initial_connection_window_size: None,
+                    initial_stream_window_size: None,
+                    tcp_nodelay: None,
+                    core: None,
+                    start_delay_secs: 0,
+                    shards: 1,
+                    connections: 1,
+                    tags: Vec::new(),
+                    headers: BTreeMap::new(),
+                    alerts: None,
+                    handicap_ms: None,
+                    token_refresh: None,
+                    proxy: None,
+                    accounts_data_slice: None,
+                    subscribe: None,
+                    raw_url: None,
                 },
                 Endpoint {
                     name: "arpc".to_string(),
                     url: "http://0.0.0.0:20202".to_string(),
                     x_token: "".to_string(),
                     kind: EndpointKind::Arpc,
+                    frame_timing: false,
+                    keepalive_interval_secs: None,
+                    keepalive_timeout_secs: None,
+                    ping_interval_secs: None,
+                    max_decoding_message_size: None,
+                    initial_connection_window_size: None,
+                    initial_stream_window_size: None,
+                    tcp_nodelay: None,
+                    core: None,
+                    start_delay_secs: 0,
+                    shards: 1,
+                    connections: 1,
+                    tags: Vec::new(),
+                    headers: BTreeMap::new(),
+                    alerts: None,
+                    handicap_ms: None,
+                    token_refresh: None,
+                    proxy: None,
+                    accounts_data_slice: None,
+                    subscribe: None,
+                    raw_url: None,
                 },
             ],
         };
 
-        let toml_string = toml::to_string_pretty(&default_config)?;
-        fs::write(path, toml_string)?;
+        let content = match ConfigFormat::from_path(path) {
+            ConfigFormat::Toml => toml::to_string_pretty(&default_config)?,
+            ConfigFormat::Yaml => serde_yaml::to_string(&default_config)?,
+            ConfigFormat::Json => serde_json::to_string_pretty(&default_config)?,
+        };
+        fs::write(path, content)?;
 
         Ok(default_config)
     }
 
+    /// Copy safe to print or share: endpoint tokens, header values and webhook URLs are masked, and
+    /// every other URL loses its credentials.
+    pub fn redacted(&self) -> Self {
+        let mut config = self.clone();
+        if let Some(notify) = &mut config.config.notify {
+            notify.webhook_url = "<redacted>".to_string();
+        }
+        if let Some(token_accounts) = &mut config.config.token_accounts {
+            token_accounts.rpc_url = "<redacted>".to_string();
+        }
+        if let Some(lookup_tables) = &mut config.config.lookup_tables {
+            lookup_tables.rpc_url = "<redacted>".to_string();
+        }
+        if let Some(leaders) = &mut config.config.leaders {
+            leaders.rpc_url = "<redacted>".to_string();
+        }
+        if let Some(ground_truth) = &mut config.config.ground_truth {
+            ground_truth.rpc_url = "<redacted>".to_string();
+        }
+        if let Some(sender) = &mut config.config.sender {
+            sender.rpc_url = "<redacted>".to_string();
+            if sender.jito_url.is_some() {
+                sender.jito_url = Some("<redacted>".to_string());
+            }
+        }
+        if let Some(otel) = &mut config.config.otel {
+            otel.endpoint = redact_url(&otel.endpoint);
+        }
+        if let Some(agent) = &mut config.config.agent {
+            agent.coordinator_url = redact_url(&agent.coordinator_url);
+        }
+        if let Some(influx) = &mut config.config.influx {
+            influx.target = redact_url(&influx.target);
+        }
+        for endpoint in &mut config.endpoint {
+            endpoint.url = endpoint.display_url();
+            endpoint.raw_url = None;
+            if !endpoint.x_token.is_empty() {
+                endpoint.x_token = "<redacted>".to_string();
+            }
+            if let Some(refresh) = &mut endpoint.token_refresh {
+                refresh.command = refresh.command.as_ref().map(|_| "<redacted>".to_string());
+                refresh.url = refresh.url.as_ref().map(|_| "<redacted>".to_string());
+            }
+            for value in endpoint.headers.values_mut() {
+                *value = "<redacted>".to_string();
+            }
+            endpoint.proxy = endpoint.proxy.as_deref().map(redact_url);
+        }
+        config
+    }
+
     pub fn load_or_create(path: &str) -> Result<Self, Box<dyn std::error::Error>> {
         if Path::new(path).exists() {
             Self::load(path)
@@ -0,0 +1,183 @@
+use std::{error::Error, net::SocketAddr, sync::Arc, time::Duration};
+
+use serde::{Deserialize, Serialize};
+use tokio::{
+    io::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt},
+    net::{lookup_host, TcpStream},
+};
+use tokio_rustls::{
+    rustls::{crypto::ring, pki_types::ServerName, ClientConfig, RootCertStore},
+    TlsConnector,
+};
+use tonic::transport::Uri;
+
+use crate::proxy::Proxy;
+
+const INSPECT_TIMEOUT: Duration = Duration::from_secs(5);
+const HTTP2_PREFACE: &[u8] = b"PRI * HTTP/2.0\r\n\r\nSM\r\n\r\n";
+const FRAME_HEADER_LEN: usize = 9;
+const FRAME_SETTINGS: u8 = 0x4;
+const FLAG_ACK: u8 = 0x1;
+// Largest frame a peer may send before we advertise a bigger SETTINGS_MAX_FRAME_SIZE
+const DEFAULT_MAX_FRAME_SIZE: usize = 16_384;
+
+/// Where an endpoint's URL led and what its server negotiated.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ConnectionInfo {
+    pub host: String,
+    /// Every address the host resolved to
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub resolved_ips: Vec<String>,
+    /// Address the inspection connection landed on
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub peer_ip: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tls_version: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub alpn: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub http2_settings: Option<Http2Settings>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+/// SETTINGS the server sent on connect; unset entries use the HTTP/2 defaults.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Http2Settings {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub header_table_size: Option<u32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_concurrent_streams: Option<u32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub initial_window_size: Option<u32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_frame_size: Option<u32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_header_list_size: Option<u32>,
+}
+
+impl Http2Settings {
+    fn parse(payload: &[u8]) -> Self {
+        let mut settings = Self::default();
+        for entry in payload.chunks_exact(6) {
+            let value = u32::from_be_bytes([entry[2], entry[3], entry[4], entry[5]]);
+            match u16::from_be_bytes([entry[0], entry[1]]) {
+                0x1 => settings.header_table_size = Some(value),
+                0x3 => settings.max_concurrent_streams = Some(value),
+                0x4 => settings.initial_window_size = Some(value),
+                0x5 => settings.max_frame_size = Some(value),
+                0x6 => settings.max_header_list_size = Some(value),
+                _ => {}
+            }
+        }
+        settings
+    }
+
+    pub fn describe(&self) -> String {
+        let fmt = |value: Option<u32>| value.map_or("default".to_string(), |v| v.to_string());
+        format!(
+            "max streams {}, window {}, max frame {}",
+            fmt(self.max_concurrent_streams),
+            fmt(self.initial_window_size),
+            fmt(self.max_frame_size)
+        )
+    }
+}
+
+impl ConnectionInfo {
+    /// True when TLS was negotiated to something other than HTTP/2, which gRPC cannot run over.
+    pub fn downgraded(&self) -> bool {
+        self.tls_version.is_some() && self.alpn.as_deref() != Some("h2")
+    }
+}
+
+// AIDEV-NOTE: tonic does not expose the peer address or the TLS session of its channels, so this
+// opens a separate short-lived connection (DNS, TCP, TLS offering h2 and http/1.1, HTTP/2 preface)
+// to the same URL. With DNS round-robin the benchmark's own connection may land elsewhere; all
+// resolved addresses are kept for that reason.
+/// Inspects the connection behind `url`; failures are recorded in `error` rather than returned.
+pub async fn inspect(url: &str, proxy: Option<&Proxy>) -> ConnectionInfo {
+    let mut info = ConnectionInfo::default();
+    let result = tokio::time::timeout(INSPECT_TIMEOUT, handshake(url, proxy, &mut info))
+        .await
+        .unwrap_or_else(|_| Err(format!("timed out after {}s", INSPECT_TIMEOUT.as_secs()).into()));
+    if let Err(e) = result {
+        info.error = Some(e.to_string());
+    }
+    info
+}
+
+async fn handshake(url: &str, proxy: Option<&Proxy>, info: &mut ConnectionInfo) -> Result<(), Box<dyn Error + Send + Sync>> {
+    let uri: Uri = url.parse()?;
+    let host = uri.host().ok_or("URL has no host")?.trim_matches(['[', ']']).to_string();
+    let tls = uri.scheme_str() == Some("https");
+    let port = uri.port_u16().unwrap_or(if tls { 443 } else { 80 });
+    info.host = host.clone();
+
+    // Behind a proxy the host is resolved and dialled by the proxy, so neither is known here
+    let mut tcp = match proxy {
+        Some(proxy) => proxy.connect(&host, port).await?,
+        None => {
+            let addrs: Vec<SocketAddr> = lookup_host((host.as_str(), port)).await?.collect();
+            info.resolved_ips = addrs.iter().map(|addr| addr.ip().to_string()).collect();
+            let tcp = TcpStream::connect(addrs.as_slice()).await?;
+            info.peer_ip = Some(tcp.peer_addr()?.ip().to_string());
+            tcp
+        }
+    };
+
+    if !tls {
+        // Plaintext gRPC is HTTP/2 with prior knowledge
+        info.http2_settings = Some(read_http2_settings(&mut tcp).await?);
+        return Ok(());
+    }
+
+    let connector = TlsConnector::from(tls_config()?);
+    let mut stream = connector.connect(ServerName::try_from(host)?, tcp).await?;
+    let (_, session) = stream.get_ref();
+    info.tls_version = session.protocol_version().map(|version| format!("{:?}", version));
+    info.alpn = session
+        .alpn_protocol()
+        .map(|protocol| String::from_utf8_lossy(protocol).into_owned());
+    if info.alpn.as_deref() == Some("h2") {
+        info.http2_settings = Some(read_http2_settings(&mut stream).await?);
+    }
+    Ok(())
+}
+
+fn tls_config() -> Result<Arc<ClientConfig>, Box<dyn Error + Send + Sync>> {
+    let roots = RootCertStore {
+        roots: webpki_roots::TLS_SERVER_ROOTS.to_vec(),
+    };
+    let mut config = ClientConfig::builder_with_provider(Arc::new(ring::default_provider()))
+        .with_safe_default_protocol_versions()?
+        .with_root_certificates(roots)
+        .with_no_client_auth();
+    // http/1.1 is offered too so a server that will not speak HTTP/2 shows up as a downgrade
+    config.alpn_protocols = vec![b"h2".to_vec(), b"http/1.1".to_vec()];
+    Ok(Arc::new(config))
+}
+
+async fn read_http2_settings<S>(stream: &mut S) -> Result<Http2Settings, Box<dyn Error + Send + Sync>>
+where
+    S: AsyncRead + AsyncWrite + Unpin,
+{
+    stream.write_all(HTTP2_PREFACE).await?;
+    // Empty SETTINGS frame: keep every client default
+    stream.write_all(&[0, 0, 0, FRAME_SETTINGS, 0, 0, 0, 0, 0]).await?;
+    stream.flush().await?;
+
+    loop {
+        let mut header = [0u8; FRAME_HEADER_LEN];
+        stream.read_exact(&mut header).await?;
+        let len = u32::from_be_bytes([0, header[0], header[1], header[2]]) as usize;
+        if len > DEFAULT_MAX_FRAME_SIZE {
+            return Err(format!("server sent a {} byte frame, not HTTP/2", len).into());
+        }
+        let mut payload = vec![0u8; len];
+        stream.read_exact(&mut payload).await?;
+        if header[3] == FRAME_SETTINGS && header[4] & FLAG_ACK == 0 {
+            return Ok(Http2Settings::parse(&payload));
+        }
+    }
+}
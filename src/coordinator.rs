@@ -0,0 +1,280 @@
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    error::Error,
+    net::SocketAddr,
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+
+use futures::channel::mpsc::{unbounded, UnboundedSender};
+use tokio::{sync::broadcast, task::JoinHandle};
+use tonic::{transport::Server, Request, Response, Status, Streaming};
+
+use crate::{
+    analysis,
+    clock::{self, ClockOffset},
+    config::{self, AgentConfig, ConfigToml},
+    metadata::RunMetadata,
+    report::{self, BenchmarkReport},
+    signals,
+    utils::{Comparator, TransactionData},
+};
+
+pub mod proto {
+    #![allow(clippy::clone_on_ref_ptr)]
+    #![allow(clippy::missing_const_for_fn)]
+
+    include!(concat!(env!("OUT_DIR"), "/coordinator.rs"));
+}
+
+use proto::{
+    agent_message::Message,
+    coordinator_client::CoordinatorClient,
+    coordinator_server::{Coordinator, CoordinatorServer},
+    AgentMessage, Hello, Observation, ReportAck,
+};
+
+// Observations still queued when the run ends get this long to reach the coordinator
+const FLUSH_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Agent side: queues the comparator's accepted sightings on a stream to the coordinator.
+#[derive(Debug)]
+pub struct AgentForwarder {
+    tx: UnboundedSender<AgentMessage>,
+    task: Mutex<Option<JoinHandle<()>>>,
+}
+
+impl AgentForwarder {
+    pub fn forward(&self, endpoint: &str, data: &TransactionData) {
+        // A failed stream is logged once by the stream task; the local run carries on regardless
+        let _ = self.tx.unbounded_send(AgentMessage {
+            message: Some(Message::Observation(Observation {
+                endpoint: endpoint.to_string(),
+                signature: data.signature.clone(),
+                timestamp: data.timestamp,
+                start_time: data.start_time,
+                slot: data.slot,
+            })),
+        });
+    }
+
+    /// Ends the stream once the queued observations are sent.
+    pub async fn finish(&self) {
+        self.tx.close_channel();
+        let task = self.task.lock().unwrap().take();
+        if let Some(task) = task {
+            if tokio::time::timeout(FLUSH_TIMEOUT, task).await.is_err() {
+                log::warn!("Coordinator did not take the remaining observations within {:?}", FLUSH_TIMEOUT);
+            }
+        }
+    }
+}
+
+/// Connects to the coordinator and opens the observation stream, introduced by this agent's
+/// region and clock offset.
+pub async fn start_agent(
+    config: &AgentConfig,
+    clock_offset: Option<&ClockOffset>,
+) -> Result<Arc<AgentForwarder>, Box<dyn Error + Send + Sync>> {
+    if clock_offset.is_none() {
+        log::warn!("Running as an agent without a clock check; the coordinator cannot correct this machine's clock");
+    }
+    let mut client = CoordinatorClient::connect(config.coordinator_url.clone()).await?;
+    log::info!(
+        "Connected to coordinator {} as region {}",
+        config::redact_url(&config.coordinator_url),
+        config.region
+    );
+
+    let (tx, rx) = unbounded();
+    tx.unbounded_send(AgentMessage {
+        message: Some(Message::Hello(Hello {
+            region: config.region.clone(),
+            clock_offset_ms: clock_offset.map(|offset| offset.offset_ms),
+        })),
+    })?;
+    let task = tokio::spawn(async move {
+        match client.report(rx).await {
+            Ok(ack) => log::info!("Coordinator accepted {} observations", ack.into_inner().accepted),
+            Err(e) => log::error!("Coordinator stream failed: {}", e),
+        }
+    });
+    Ok(Arc::new(AgentForwarder {
+        tx,
+        task: Mutex::new(Some(task)),
+    }))
+}
+
+#[derive(Debug, Clone, Default)]
+struct AgentSummary {
+    clock_offset_ms: Option<f64>,
+    observations: u64,
+    connections: u64,
+}
+
+struct CoordinatorService {
+    comparator: Arc<Mutex<Comparator>>,
+    endpoint_names: Arc<Mutex<BTreeSet<String>>>,
+    agents: Arc<Mutex<BTreeMap<String, AgentSummary>>>,
+    shutdown_tx: broadcast::Sender<String>,
+    target: usize,
+}
+
+impl CoordinatorService {
+    // AIDEV-NOTE: Agent timestamps are moved onto the reference clock with the agent's own SNTP
+    // offset (positive when its clock is behind), so regions compare as if read from one clock.
+    // TSC ticks and status are not forwarded: ticks only mean something on the machine that read them.
+    fn accept(&self, region: &str, offset_secs: f64, observation: Observation) {
+        let name = format!("{}/{}", region, observation.endpoint);
+        if self.endpoint_names.lock().unwrap().insert(name.clone()) {
+            log::info!("New endpoint {}", name);
+        }
+        if let Some(agent) = self.agents.lock().unwrap().get_mut(region) {
+            agent.observations += 1;
+        }
+
+        let mut comparator = self.comparator.lock().unwrap();
+        comparator.add(
+            name,
+            TransactionData {
+                timestamp: observation.timestamp + offset_secs,
+                signature: observation.signature,
+                start_time: observation.start_time + offset_secs,
+                tsc: None,
+                status: None,
+                slot: observation.slot,
+                entry: None,
+                groups: Vec::new(),
+            },
+        );
+        if !comparator.draining && comparator.get_valid_count() == self.target {
+            log::info!("Coordinator shutting down after {} transactions seen by all agents", self.target);
+            comparator.begin_drain();
+            let _ = self.shutdown_tx.send(format!("transaction target of {} reached", self.target));
+        }
+    }
+}
+
+#[tonic::async_trait]
+impl Coordinator for CoordinatorService {
+    async fn report(&self, request: Request<Streaming<AgentMessage>>) -> Result<Response<ReportAck>, Status> {
+        let mut stream = request.into_inner();
+        let hello = match stream.message().await? {
+            Some(AgentMessage {
+                message: Some(Message::Hello(hello)),
+            }) => hello,
+            _ => return Err(Status::invalid_argument("the first message must be a hello")),
+        };
+        if hello.region.is_empty() || hello.region.contains('/') {
+            return Err(Status::invalid_argument("region must be non-empty without '/'"));
+        }
+        match hello.clock_offset_ms {
+            Some(offset) => log::info!("Agent {} connected, clock offset {:+.2} ms", hello.region, offset),
+            None => log::warn!("Agent {} connected without a clock offset; its timestamps are used as is", hello.region),
+        }
+        {
+            let mut agents = self.agents.lock().unwrap();
+            let agent = agents.entry(hello.region.clone()).or_default();
+            agent.clock_offset_ms = hello.clock_offset_ms;
+            agent.connections += 1;
+        }
+
+        let offset_secs = hello.clock_offset_ms.unwrap_or(0.0) / 1000.0;
+        let mut accepted = 0;
+        while let Some(message) = stream.message().await? {
+            if let Some(Message::Observation(observation)) = message.message {
+                self.accept(&hello.region, offset_secs, observation);
+                accepted += 1;
+            }
+        }
+        log::info!("Agent {} disconnected after {} observations", hello.region, accepted);
+        Ok(Response::new(ReportAck { accepted }))
+    }
+}
+
+fn print_agents(agents: &BTreeMap<String, AgentSummary>) {
+    println!("\nAgents");
+    println!("--------------------------------------------");
+    for (region, agent) in agents {
+        println!(
+            "{}: {} observations over {} connections, clock offset {}",
+            region,
+            agent.observations,
+            agent.connections,
+            agent
+                .clock_offset_ms
+                .map_or("unknown".to_string(), |offset| format!("{:+.2} ms", offset))
+        );
+    }
+}
+
+/// Serves agents on `listen` until the transaction target, `max_runtime_secs` or Ctrl+C, then
+/// prints the merged statistics and optionally writes a report.
+pub async fn run_coordinator(
+    config: &ConfigToml,
+    listen: &str,
+    report_path: Option<&str>,
+) -> Result<BenchmarkReport, Box<dyn Error>> {
+    let addr: SocketAddr = listen.parse()?;
+    let clock = clock::system();
+    let start_time = clock.now();
+    let (shutdown_tx, mut shutdown_rx) = broadcast::channel::<String>(1);
+    let comparator = Arc::new(Mutex::new(
+        Comparator::new(config.config.transactions as usize).with_dedup_window(config.config.dedup_window_secs),
+    ));
+    let endpoint_names = Arc::new(Mutex::new(BTreeSet::new()));
+    let agents = Arc::new(Mutex::new(BTreeMap::new()));
+    let service = CoordinatorService {
+        comparator: comparator.clone(),
+        endpoint_names: endpoint_names.clone(),
+        agents: agents.clone(),
+        shutdown_tx: shutdown_tx.clone(),
+        target: config.config.transactions as usize,
+    };
+
+    let signal_tx = shutdown_tx.clone();
+    signals::spawn_handler(move |_| {
+        let _ = signal_tx.send("interrupted".to_string());
+    });
+    if let Some(max_runtime_secs) = config.config.max_runtime_secs {
+        let runtime_tx = shutdown_tx.clone();
+        tokio::spawn(async move {
+            tokio::time::sleep(Duration::from_secs(max_runtime_secs)).await;
+            let _ = runtime_tx.send(format!("max runtime of {}s reached", max_runtime_secs));
+        });
+    }
+
+    println!("Coordinator listening on {}", addr);
+    let mut server = tokio::spawn(Server::builder().add_service(CoordinatorServer::new(service)).serve(addr));
+    let stop_reason = tokio::select! {
+        result = &mut server => {
+            result??;
+            return Err("coordinator server stopped unexpectedly".into());
+        }
+        reason = shutdown_rx.recv() => reason.ok(),
+    };
+    // AIDEV-NOTE: Agents stream until their own runs end, so the server is not shut down gracefully;
+    // the drain window lets in-flight races finish before the connections are cut.
+    comparator.lock().unwrap().begin_drain();
+    tokio::time::sleep(Duration::from_millis(config.config.drain_ms)).await;
+    server.abort();
+
+    let agents = agents.lock().unwrap().clone();
+    print_agents(&agents);
+    let comparator = std::mem::take(&mut *comparator.lock().unwrap());
+    let endpoint_names: Vec<String> = endpoint_names.lock().unwrap().iter().cloned().collect();
+    if endpoint_names.is_empty() {
+        return Err("no agent reported any observation".into());
+    }
+    let mut report = analysis::analyze_delays(&comparator, endpoint_names, &config.config);
+    report.mode = config.config.mode;
+    report.start_time = start_time;
+    report.end_time = clock.now();
+    report.effective_config = Some(config.redacted());
+    report.stop_reason = stop_reason;
+    report.metadata = Some(RunMetadata::collect(config));
+    if let Some(path) = report_path {
+        report::write_report(path, &report, config.config.signing_keypair.as_deref())?;
+    }
+    Ok(report)
+}
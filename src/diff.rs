@@ -0,0 +1,207 @@
+use std::error::Error;
+
+use crate::report::{self, BenchmarkReport, EndpointReport, PairReport, PairWindow};
+
+// Two-sided 5% level
+const Z_CRITICAL: f64 = 1.96;
+// Standard error of a median from the MAD, assuming roughly normal delays:
+// sigma ~ 1.4826 * MAD and se(median) ~ 1.2533 * sigma / sqrt(n)
+const MEDIAN_SE_FACTOR: f64 = 1.4826 * 1.2533;
+
+/// Prints per-endpoint, per-pair and throughput changes from `before_path` to `after_path`.
+pub fn diff_files(before_path: &str, after_path: &str) -> Result<(), Box<dyn Error>> {
+    let before = report::load_report(before_path)?;
+    let after = report::load_report(after_path)?;
+    if before.mode != after.mode {
+        log::warn!("Comparing a {:?} run with a {:?} run", before.mode, after.mode);
+    }
+    if let (Some(old), Some(new)) = (&before.metadata, &after.metadata) {
+        if old.config_hash != new.config_hash {
+            log::warn!("The runs used different configs ({} vs {})", old.config_hash, new.config_hash);
+        }
+        if old.hostname != new.hostname {
+            log::warn!(
+                "The runs came from different hosts ({} vs {})",
+                old.hostname.as_deref().unwrap_or("unknown"),
+                new.hostname.as_deref().unwrap_or("unknown")
+            );
+        }
+    }
+
+    println!("Comparing {} -> {}", before_path, after_path);
+    print_endpoints(&before, &after);
+    print_pairs(&before, &after);
+    print_throughput(&before, &after);
+    Ok(())
+}
+
+fn print_endpoints(before: &BenchmarkReport, after: &BenchmarkReport) {
+    if before.endpoints.is_empty() && after.endpoints.is_empty() {
+        return;
+    }
+
+    println!("\nEndpoints");
+    println!("--------------------------------------------");
+    for old in &before.endpoints {
+        let Some(new) = after.endpoints.iter().find(|e| e.name == old.name) else {
+            println!("{}: only in the first report", old.name);
+            continue;
+        };
+
+        let win_z = proportion_z(
+            old.first_detections,
+            old.total_valid_transactions,
+            new.first_detections,
+            new.total_valid_transactions,
+        );
+        println!(
+            "{}: win rate {:.2}% -> {:.2}% ({:+.2} pp) {}",
+            old.name,
+            old.win_rate,
+            new.win_rate,
+            new.win_rate - old.win_rate,
+            annotate(win_z, true)
+        );
+
+        if let (Some(old_delays), Some(new_delays)) = (&old.delays, &new.delays) {
+            let z = median_z(old, new);
+            println!(
+                "{}: median delay {:.2} ms -> {:.2} ms ({:+.2} ms) {}",
+                old.name,
+                old_delays.median_ms,
+                new_delays.median_ms,
+                new_delays.median_ms - old_delays.median_ms,
+                annotate(z, false)
+            );
+        }
+    }
+    for new in &after.endpoints {
+        if !before.endpoints.iter().any(|e| e.name == new.name) {
+            println!("{}: only in the second report", new.name);
+        }
+    }
+}
+
+fn print_pairs(before: &BenchmarkReport, after: &BenchmarkReport) {
+    if before.pairs.is_empty() || after.pairs.is_empty() {
+        return;
+    }
+
+    println!("\nEndpoint pairs (positive delta: first endpoint ahead)");
+    println!("--------------------------------------------");
+    for old in &before.pairs {
+        let Some(new) = find_pair(&after.pairs, old) else {
+            continue;
+        };
+        let z = proportion_z(old.first_wins, old.signatures, new.first_wins, new.signatures);
+        println!(
+            "{} vs {}: median delta {:+.2} ms -> {:+.2} ms ({:+.2} ms), {} ahead {:.1}% -> {:.1}% {}",
+            old.first,
+            old.second,
+            old.median_delta_ms,
+            new.median_delta_ms,
+            new.median_delta_ms - old.median_delta_ms,
+            old.first,
+            share(old.first_wins, old.signatures),
+            share(new.first_wins, new.signatures),
+            annotate(z, true)
+        );
+    }
+}
+
+fn print_throughput(before: &BenchmarkReport, after: &BenchmarkReport) {
+    if before.throughput.is_empty() || after.throughput.is_empty() {
+        return;
+    }
+
+    println!("\nThroughput (no per-second samples in reports, so no significance test)");
+    println!("--------------------------------------------");
+    for old in &before.throughput {
+        let Some(new) = after.throughput.iter().find(|t| t.name == old.name) else {
+            continue;
+        };
+        let change = if old.sustained_rate > 0.0 {
+            format!("{:+.1}%", (new.sustained_rate - old.sustained_rate) / old.sustained_rate * 100.0)
+        } else {
+            "n/a".to_string()
+        };
+        println!(
+            "{}: sustained {:.0} -> {:.0} msg/s ({}), max {:.0} -> {:.0} msg/s",
+            old.name, old.sustained_rate, new.sustained_rate, change, old.max_rate, new.max_rate
+        );
+    }
+}
+
+// Pairs may be listed in the opposite order when endpoint order changed between runs
+fn find_pair(pairs: &[PairReport], pair: &PairReport) -> Option<PairReport> {
+    pairs.iter().find_map(|candidate| {
+        if candidate.first == pair.first && candidate.second == pair.second {
+            Some(candidate.clone())
+        } else if candidate.first == pair.second && candidate.second == pair.first {
+            Some(PairReport {
+                first: candidate.second.clone(),
+                second: candidate.first.clone(),
+                signatures: candidate.signatures,
+                first_wins: candidate.signatures - candidate.first_wins,
+                median_delta_ms: -candidate.median_delta_ms,
+                median_delta_ci_ms: candidate.median_delta_ci_ms.map(|(low, high)| (-high, -low)),
+                p_value: candidate.p_value,
+                significant: candidate.significant,
+                windows: candidate
+                    .windows
+                    .iter()
+                    .map(|window| PairWindow {
+                        median_delta_ms: -window.median_delta_ms,
+                        ..window.clone()
+                    })
+                    .collect(),
+            })
+        } else {
+            None
+        }
+    })
+}
+
+fn share(count: usize, total: usize) -> f64 {
+    if total == 0 {
+        return 0.0;
+    }
+    count as f64 / total as f64 * 100.0
+}
+
+/// Two-proportion z statistic for the change from `a1/n1` to `a2/n2`.
+fn proportion_z(a1: usize, n1: usize, a2: usize, n2: usize) -> Option<f64> {
+    if n1 == 0 || n2 == 0 {
+        return None;
+    }
+    let (p1, p2) = (a1 as f64 / n1 as f64, a2 as f64 / n2 as f64);
+    let pooled = (a1 + a2) as f64 / (n1 + n2) as f64;
+    let se = (pooled * (1.0 - pooled) * (1.0 / n1 as f64 + 1.0 / n2 as f64)).sqrt();
+    (se > 0.0).then(|| (p2 - p1) / se)
+}
+
+fn median_z(old: &EndpointReport, new: &EndpointReport) -> Option<f64> {
+    let se = |endpoint: &EndpointReport| {
+        let delays = endpoint.delays.as_ref()?;
+        let n = endpoint.total_valid_transactions;
+        (n > 0).then(|| MEDIAN_SE_FACTOR * delays.mad_ms / (n as f64).sqrt())
+    };
+    let (old_se, new_se) = (se(old)?, se(new)?);
+    let se = (old_se.powi(2) + new_se.powi(2)).sqrt();
+    let (old_median, new_median) = (old.delays.as_ref()?.median_ms, new.delays.as_ref()?.median_ms);
+    (se > 0.0).then(|| (new_median - old_median) / se)
+}
+
+fn annotate(z: Option<f64>, higher_is_better: bool) -> &'static str {
+    match z {
+        Some(z) if z.abs() >= Z_CRITICAL => {
+            if (z > 0.0) == higher_is_better {
+                "[improvement, p<0.05]"
+            } else {
+                "[regression, p<0.05]"
+            }
+        }
+        Some(_) => "[not significant]",
+        None => "[not enough data]",
+    }
+}
@@ -0,0 +1,190 @@
+use std::collections::{BTreeMap, HashMap};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    config::BenchmarkMode,
+    utils::{percentile, Comparator},
+};
+
+/// One delivery of a signature (or slot key) by one endpoint, repeats included.
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+pub struct SightingEvent {
+    pub endpoint: String,
+    /// `transaction`, `status`, `entry`, `block` or `account`, after the run's mode
+    pub stream: String,
+    pub timestamp: f64,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub slot: Option<u64>,
+    /// A later delivery by an endpoint that had already delivered the signature
+    pub repeat: bool,
+}
+
+/// Summary of the ordered event log, with `event_log`.
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+pub struct EventOrderReport {
+    pub signatures: usize,
+    pub events: usize,
+    /// Live signatures delivered by at least two endpoints
+    pub contested: usize,
+    pub runner_up_gap_median_ms: f64,
+    pub runner_up_gap_p95_ms: f64,
+    pub runner_up_gap_max_ms: f64,
+    /// Slots with at least two live signatures
+    pub slots: usize,
+    pub mean_winner_changes_per_slot: f64,
+    /// Share of those slots in which the first-seeing endpoint changed at least once
+    pub slots_with_winner_change: f64,
+}
+
+pub fn stream_label(mode: BenchmarkMode) -> &'static str {
+    match mode {
+        BenchmarkMode::Status => "status",
+        BenchmarkMode::Entries => "entry",
+        BenchmarkMode::Blocks => "block",
+        BenchmarkMode::Accounts => "account",
+        BenchmarkMode::Slots => "slot",
+        BenchmarkMode::Latency | BenchmarkMode::Throughput => "transaction",
+    }
+}
+
+// AIDEV-NOTE: The Comparator keeps only each endpoint's earliest sighting; this keeps every accepted
+// delivery in arrival order per signature, so orderings beyond the winner can be queried after the
+// run. It grows with every delivery, repeats included, which is why it is opt-in.
+/// Every delivery per signature, ordered by receive time.
+#[derive(Debug, Clone, Default)]
+pub struct EventLog {
+    stream: String,
+    events: HashMap<String, Vec<SightingEvent>>,
+}
+
+impl EventLog {
+    pub fn new(mode: BenchmarkMode) -> Self {
+        Self {
+            stream: stream_label(mode).to_string(),
+            events: HashMap::new(),
+        }
+    }
+
+    pub fn record(&mut self, signature: &str, endpoint: &str, timestamp: f64, slot: Option<u64>) {
+        let events = self.events.entry(signature.to_string()).or_default();
+        let repeat = events.iter().any(|event| event.endpoint == endpoint);
+        // Deliveries mostly arrive in order; shards and TSC readings can land a little out of it
+        let position = events.partition_point(|event| event.timestamp <= timestamp);
+        events.insert(
+            position,
+            SightingEvent {
+                endpoint: endpoint.to_string(),
+                stream: self.stream.clone(),
+                timestamp,
+                slot,
+                repeat,
+            },
+        );
+    }
+
+    /// Deliveries of `signature` in receive order; empty when it was never seen.
+    pub fn events(&self, signature: &str) -> &[SightingEvent] {
+        self.events.get(signature).map(Vec::as_slice).unwrap_or_default()
+    }
+
+    pub fn signatures(&self) -> impl Iterator<Item = &str> {
+        self.events.keys().map(String::as_str)
+    }
+
+    pub fn len(&self) -> usize {
+        self.events.values().map(Vec::len).sum()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.events.is_empty()
+    }
+
+    /// First delivery per endpoint, in receive order.
+    pub fn placings(&self, signature: &str) -> Vec<&SightingEvent> {
+        self.events(signature).iter().filter(|event| !event.repeat).collect()
+    }
+
+    /// Gap between the first and second endpoint, in ms, for each signature `include` accepts
+    /// that at least two endpoints delivered.
+    pub fn runner_up_gaps_ms(&self, include: impl Fn(&str) -> bool) -> Vec<f64> {
+        self.signatures()
+            .filter(|signature| include(signature))
+            .filter_map(|signature| {
+                let placings = self.placings(signature);
+                (placings.len() >= 2).then(|| (placings[1].timestamp - placings[0].timestamp) * 1000.0)
+            })
+            .collect()
+    }
+
+    /// Per slot, how often the first-seeing endpoint changes from one signature to the next, with
+    /// signatures in the order they were first seen. Slots with fewer than two signatures are left out.
+    pub fn winner_changes_per_slot(&self, include: impl Fn(&str) -> bool) -> BTreeMap<u64, usize> {
+        let mut by_slot: BTreeMap<u64, Vec<&SightingEvent>> = BTreeMap::new();
+        for signature in self.signatures().filter(|signature| include(signature)) {
+            let Some(first) = self.events(signature).first() else {
+                continue;
+            };
+            if let Some(slot) = self.events(signature).iter().find_map(|event| event.slot) {
+                by_slot.entry(slot).or_default().push(first);
+            }
+        }
+        by_slot
+            .into_iter()
+            .filter(|(_, winners)| winners.len() >= 2)
+            .map(|(slot, mut winners)| {
+                winners.sort_by(|a, b| a.timestamp.total_cmp(&b.timestamp));
+                let changes = winners.windows(2).filter(|pair| pair[0].endpoint != pair[1].endpoint).count();
+                (slot, changes)
+            })
+            .collect()
+    }
+}
+
+pub fn summarize_event_order(comparator: &Comparator) -> Option<EventOrderReport> {
+    let event_log = comparator.events.as_ref()?;
+    let live = |signature: &str| comparator.data.get(signature).is_some_and(|sig_data| comparator.is_live(sig_data));
+    let mut gaps = event_log.runner_up_gaps_ms(live);
+    gaps.sort_by(|a, b| a.total_cmp(b));
+    let changes = event_log.winner_changes_per_slot(live);
+    let slots = changes.len();
+    Some(EventOrderReport {
+        signatures: event_log.signatures().count(),
+        events: event_log.len(),
+        contested: gaps.len(),
+        runner_up_gap_median_ms: percentile(&gaps, 0.5),
+        runner_up_gap_p95_ms: percentile(&gaps, 0.95),
+        runner_up_gap_max_ms: gaps.last().copied().unwrap_or(0.0),
+        slots,
+        mean_winner_changes_per_slot: if slots > 0 {
+            changes.values().sum::<usize>() as f64 / slots as f64
+        } else {
+            0.0
+        },
+        slots_with_winner_change: if slots > 0 {
+            changes.values().filter(|count| **count > 0).count() as f64 / slots as f64
+        } else {
+            0.0
+        },
+    })
+}
+
+pub fn print_event_order(report: &EventOrderReport) {
+    println!("\nEvent ordering");
+    println!("--------------------------------------------");
+    println!("{} deliveries of {} signatures recorded", report.events, report.signatures);
+    if report.contested > 0 {
+        println!(
+            "Second place behind first: median {:.2} ms, p95 {:.2} ms, max {:.2} ms over {} contested signatures",
+            report.runner_up_gap_median_ms, report.runner_up_gap_p95_ms, report.runner_up_gap_max_ms, report.contested
+        );
+    }
+    if report.slots > 0 {
+        println!(
+            "Winner changes per slot: {:.2} on average, at least one in {:.1}% of {} slots",
+            report.mean_winner_changes_per_slot,
+            report.slots_with_winner_change * 100.0,
+            report.slots
+        );
+    }
+}
@@ -0,0 +1,192 @@
+use std::{
+    pin::Pin,
+    sync::{Arc, Mutex},
+    task::{Context, Poll},
+};
+
+use http_body::{Body, Frame, SizeHint};
+use serde::{Deserialize, Serialize};
+use tonic::codegen::{http, Bytes, Service};
+
+use crate::utils::{get_current_timestamp, percentile};
+
+/// Messages at least this large get their assembly time reported.
+pub const LARGE_MESSAGE_BYTES: usize = 64 * 1024;
+const GRPC_HEADER_LEN: usize = 5;
+
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+pub struct FrameTimingSummary {
+    pub messages: u64,
+    pub bytes: u64,
+    pub large_messages: u64,
+    pub avg_assembly_ms: f64,
+    pub p95_assembly_ms: f64,
+    pub max_assembly_ms: f64,
+}
+
+#[derive(Debug, Default)]
+struct FrameState {
+    header: Vec<u8>,
+    remaining: usize,
+    size: usize,
+    first_byte_at: f64,
+    messages: u64,
+    bytes: u64,
+    assembly_ms: Vec<f64>,
+}
+
+impl FrameState {
+    fn finish_message(&mut self, now: f64) {
+        self.messages += 1;
+        self.bytes += self.size as u64;
+        if self.size >= LARGE_MESSAGE_BYTES {
+            self.assembly_ms.push((now - self.first_byte_at) * 1000.0);
+        }
+    }
+}
+
+// AIDEV-NOTE: Follows gRPC length-prefixed framing across HTTP/2 DATA frames to find where each
+// message starts and ends; first-byte to last-byte time is the assembly time.
+#[derive(Debug, Clone, Default)]
+pub struct FrameTracker(Arc<Mutex<FrameState>>);
+
+impl FrameTracker {
+    fn on_data(&self, data: &[u8]) {
+        let now = get_current_timestamp();
+        let mut state = self.0.lock().unwrap();
+        let mut offset = 0;
+
+        while offset < data.len() {
+            if state.remaining == 0 {
+                if state.header.is_empty() {
+                    state.first_byte_at = now;
+                }
+                let take = (GRPC_HEADER_LEN - state.header.len()).min(data.len() - offset);
+                state.header.extend_from_slice(&data[offset..offset + take]);
+                offset += take;
+
+                if state.header.len() == GRPC_HEADER_LEN {
+                    let header = &state.header;
+                    let len = u32::from_be_bytes([header[1], header[2], header[3], header[4]]) as usize;
+                    state.header.clear();
+                    state.size = len;
+                    if len == 0 {
+                        state.finish_message(now);
+                    } else {
+                        state.remaining = len;
+                    }
+                }
+            } else {
+                let take = state.remaining.min(data.len() - offset);
+                state.remaining -= take;
+                offset += take;
+                if state.remaining == 0 {
+                    state.finish_message(now);
+                }
+            }
+        }
+    }
+
+    pub fn summary(&self) -> FrameTimingSummary {
+        let state = self.0.lock().unwrap();
+        let mut sorted = state.assembly_ms.clone();
+        sorted.sort_by(|a, b| a.partial_cmp(b).unwrap());
+
+        FrameTimingSummary {
+            messages: state.messages,
+            bytes: state.bytes,
+            large_messages: sorted.len() as u64,
+            avg_assembly_ms: if sorted.is_empty() {
+                0.0
+            } else {
+                sorted.iter().sum::<f64>() / sorted.len() as f64
+            },
+            p95_assembly_ms: percentile(&sorted, 0.95),
+            max_assembly_ms: sorted.last().copied().unwrap_or(0.0),
+        }
+    }
+}
+
+pub struct TimedBody<B> {
+    inner: Pin<Box<B>>,
+    tracker: FrameTracker,
+}
+
+// Generated clients require a default response body; it reports to a tracker nobody reads
+impl<B: Default> Default for TimedBody<B> {
+    fn default() -> Self {
+        Self {
+            inner: Box::pin(B::default()),
+            tracker: FrameTracker::default(),
+        }
+    }
+}
+
+impl<B> Body for TimedBody<B>
+where
+    B: Body<Data = Bytes>,
+{
+    type Data = Bytes;
+    type Error = B::Error;
+
+    fn poll_frame(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+    ) -> Poll<Option<Result<Frame<Self::Data>, Self::Error>>> {
+        let poll = self.inner.as_mut().poll_frame(cx);
+        if let Poll::Ready(Some(Ok(frame))) = &poll {
+            if let Some(data) = frame.data_ref() {
+                self.tracker.on_data(data);
+            }
+        }
+        poll
+    }
+
+    fn is_end_stream(&self) -> bool {
+        self.inner.is_end_stream()
+    }
+
+    fn size_hint(&self) -> SizeHint {
+        self.inner.size_hint()
+    }
+}
+
+/// Wraps a gRPC transport so every response body reports its frames to a `FrameTracker`.
+#[derive(Clone)]
+pub struct FrameTimingService<S> {
+    inner: S,
+    tracker: FrameTracker,
+}
+
+impl<S> FrameTimingService<S> {
+    pub fn new(inner: S, tracker: FrameTracker) -> Self {
+        Self { inner, tracker }
+    }
+}
+
+impl<S, ReqBody, ResBody> Service<http::Request<ReqBody>> for FrameTimingService<S>
+where
+    S: Service<http::Request<ReqBody>, Response = http::Response<ResBody>>,
+    S::Future: Send + 'static,
+{
+    type Response = http::Response<TimedBody<ResBody>>;
+    type Error = S::Error;
+    type Future =
+        Pin<Box<dyn std::future::Future<Output = Result<Self::Response, Self::Error>> + Send>>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, request: http::Request<ReqBody>) -> Self::Future {
+        let tracker = self.tracker.clone();
+        let future = self.inner.call(request);
+        Box::pin(async move {
+            let response = future.await?;
+            Ok(response.map(|body| TimedBody {
+                inner: Box::pin(body),
+                tracker,
+            }))
+        })
+    }
+}
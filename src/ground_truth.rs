@@ -0,0 +1,207 @@
+//! Endpoint coverage against the RPC's record of the watched accounts' transactions.
+//!
+//! Endpoints are otherwise only compared with each other, so a transaction every endpoint dropped
+//! goes unnoticed; `getSignaturesForAddress` over the observed slot range gives the full count.
+
+use std::{
+    collections::{HashMap, HashSet},
+    error::Error,
+    time::Duration,
+};
+
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+
+use crate::{
+    config::{Config, GroundTruthConfig},
+    utils::{Comparator, TransactionData},
+};
+
+const REQUEST_TIMEOUT_SECS: u64 = 10;
+/// Most signatures `getSignaturesForAddress` answers in one call
+const SIGNATURES_PER_REQUEST: usize = 1000;
+
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+pub struct GroundTruthEndpointReport {
+    pub name: String,
+    /// RPC signatures in the range this endpoint delivered
+    pub seen: usize,
+    /// `seen` as a percentage of the RPC's signatures
+    pub coverage: f64,
+}
+
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+pub struct GroundTruthReport {
+    /// Slot range compared, inclusive
+    pub first_slot: u64,
+    pub last_slot: u64,
+    /// Signatures the RPC lists for the watched accounts in the range
+    pub signatures: usize,
+    /// RPC signatures no endpoint delivered
+    pub missed_by_all: usize,
+    /// Signatures endpoints delivered in the range that the RPC does not list
+    pub unknown_to_rpc: usize,
+    pub endpoints: Vec<GroundTruthEndpointReport>,
+}
+
+async fn rpc_call(client: &reqwest::Client, rpc_url: &str, method: &str, params: Value) -> Result<Value, Box<dyn Error + Send + Sync>> {
+    let request = json!({ "jsonrpc": "2.0", "id": 1, "method": method, "params": params });
+    let response: Value = client
+        .post(rpc_url)
+        .timeout(Duration::from_secs(REQUEST_TIMEOUT_SECS))
+        .json(&request)
+        .send()
+        .await?
+        .error_for_status()?
+        .json()
+        .await?;
+    if let Some(error) = response.get("error") {
+        return Err(format!("{} failed: {}", method, error).into());
+    }
+    Ok(response["result"].clone())
+}
+
+// AIDEV-NOTE: Pages backwards from the newest signature until the page reaches below
+// `first_slot`; the run has only just ended, so few pages fall after the range.
+/// Signatures the RPC lists for `account` in `first_slot..=last_slot`, failed ones only when
+/// `failed` allows them.
+async fn account_signatures(
+    client: &reqwest::Client,
+    config: &GroundTruthConfig,
+    account: &str,
+    first_slot: u64,
+    last_slot: u64,
+    failed: Option<bool>,
+) -> Result<HashSet<String>, Box<dyn Error + Send + Sync>> {
+    let mut signatures = HashSet::new();
+    let mut before: Option<String> = None;
+    let mut fetched = 0;
+    loop {
+        let mut options = json!({ "limit": SIGNATURES_PER_REQUEST, "commitment": "confirmed" });
+        if let Some(before) = &before {
+            options["before"] = json!(before);
+        }
+        let result = rpc_call(client, &config.rpc_url, "getSignaturesForAddress", json!([account, options])).await?;
+        let page = result
+            .as_array()
+            .ok_or_else(|| format!("getSignaturesForAddress for {} returned no signature list", account))?;
+        fetched += page.len();
+        let mut reached_start = page.len() < SIGNATURES_PER_REQUEST;
+        for entry in page {
+            let (Some(signature), Some(slot)) = (entry["signature"].as_str(), entry["slot"].as_u64()) else {
+                continue;
+            };
+            before = Some(signature.to_string());
+            if slot < first_slot {
+                reached_start = true;
+                continue;
+            }
+            let is_failed = !entry["err"].is_null();
+            if slot <= last_slot && failed.is_none_or(|failed| failed == is_failed) {
+                signatures.insert(signature.to_string());
+            }
+        }
+        if reached_start {
+            return Ok(signatures);
+        }
+        if fetched >= config.max_signatures {
+            return Err(format!(
+                "{} has more than {} signatures since slot {}; raise ground_truth.max_signatures",
+                account, config.max_signatures, first_slot
+            )
+            .into());
+        }
+    }
+}
+
+// AIDEV-NOTE: The first and last observed slots are left out, since the run started and stopped
+// partway through them, and the range is capped at the RPC's confirmed tip so transactions the
+// RPC has not confirmed yet are not counted as missed.
+/// Fetches the RPC's signatures over the observed slot range and counts each endpoint's share.
+/// RPC failures are logged and leave the check out rather than failing the run.
+pub async fn analyze(comparator: &Comparator, endpoint_names: &[String], config: &Config) -> Option<GroundTruthReport> {
+    let ground_truth = config.ground_truth.as_ref()?;
+    let observed: HashMap<&str, (u64, &HashMap<String, TransactionData>)> = comparator
+        .data
+        .iter()
+        .filter(|(_, sig_data)| comparator.is_live(sig_data))
+        .filter_map(|(signature, sig_data)| Some((signature.as_str(), (sig_data.values().find_map(|tx| tx.slot)?, sig_data))))
+        .collect();
+    let (Some(first), Some(last)) = (
+        observed.values().map(|(slot, _)| *slot).min(),
+        observed.values().map(|(slot, _)| *slot).max(),
+    ) else {
+        log::warn!("Skipping the ground truth check: no delivered transaction carried a slot");
+        return None;
+    };
+
+    let client = reqwest::Client::new();
+    let tip = match rpc_call(&client, &ground_truth.rpc_url, "getSlot", json!([{ "commitment": "confirmed" }])).await {
+        Ok(tip) => tip.as_u64().unwrap_or(u64::MAX),
+        Err(e) => {
+            log::warn!("Failed to fetch the RPC's confirmed slot for the ground truth check: {}", e);
+            return None;
+        }
+    };
+    let first_slot = first + 1;
+    let last_slot = last.saturating_sub(1).min(tip);
+    if first_slot > last_slot {
+        log::warn!("Skipping the ground truth check: the run covered no complete confirmed slot");
+        return None;
+    }
+
+    let mut truth = HashSet::new();
+    for account in config.watched_accounts() {
+        match account_signatures(&client, ground_truth, &account, first_slot, last_slot, config.transaction_filter.failed).await {
+            Ok(signatures) => truth.extend(signatures),
+            Err(e) => {
+                log::warn!("Failed to fetch signatures for the ground truth check: {}", e);
+                return None;
+            }
+        }
+    }
+
+    let in_range: Vec<(&str, &HashMap<String, TransactionData>)> = observed
+        .iter()
+        .filter(|(_, (slot, _))| (first_slot..=last_slot).contains(slot))
+        .map(|(signature, (_, sig_data))| (*signature, *sig_data))
+        .collect();
+    let delivered: HashSet<&str> = in_range.iter().map(|(signature, _)| *signature).collect();
+    let endpoints = endpoint_names
+        .iter()
+        .map(|name| {
+            let seen = in_range
+                .iter()
+                .filter(|(signature, sig_data)| sig_data.contains_key(name) && truth.contains(*signature))
+                .count();
+            GroundTruthEndpointReport {
+                name: name.clone(),
+                seen,
+                coverage: if truth.is_empty() { 0.0 } else { seen as f64 / truth.len() as f64 * 100.0 },
+            }
+        })
+        .collect();
+    Some(GroundTruthReport {
+        first_slot,
+        last_slot,
+        signatures: truth.len(),
+        missed_by_all: truth.iter().filter(|signature| !delivered.contains(signature.as_str())).count(),
+        unknown_to_rpc: delivered.iter().filter(|signature| !truth.contains(**signature)).count(),
+        endpoints,
+    })
+}
+
+pub fn print_ground_truth(report: &GroundTruthReport) {
+    println!("\nCoverage against RPC");
+    println!("--------------------------------------------");
+    println!(
+        "Slots {} to {}: {} signatures listed by the RPC, {} delivered by no endpoint",
+        report.first_slot, report.last_slot, report.signatures, report.missed_by_all
+    );
+    for endpoint in &report.endpoints {
+        println!("{}: {}/{} ({:.2}%)", endpoint.name, endpoint.seen, report.signatures, endpoint.coverage);
+    }
+    if report.unknown_to_rpc > 0 {
+        println!("{} delivered signatures are not listed by the RPC (dropped forks or filtered out)", report.unknown_to_rpc);
+    }
+}
@@ -0,0 +1,190 @@
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+
+use rusqlite::{params, Connection};
+
+use crate::{clock::Clock, config::HistoryConfig};
+
+const SECONDS_PER_DAY: f64 = 86_400.0;
+
+const SCHEMA: &str = "
+CREATE TABLE IF NOT EXISTS windows (
+    window_start REAL NOT NULL,
+    window_end REAL NOT NULL,
+    endpoint TEXT NOT NULL,
+    observations INTEGER NOT NULL,
+    first_detections INTEGER NOT NULL,
+    delayed INTEGER NOT NULL,
+    delay_sum_ms REAL NOT NULL,
+    max_delay_ms REAL NOT NULL
+);
+CREATE INDEX IF NOT EXISTS windows_end ON windows (window_end);
+CREATE TABLE IF NOT EXISTS daily (
+    day TEXT NOT NULL,
+    endpoint TEXT NOT NULL,
+    windows INTEGER NOT NULL,
+    observations INTEGER NOT NULL,
+    first_detections INTEGER NOT NULL,
+    delayed INTEGER NOT NULL,
+    delay_sum_ms REAL NOT NULL,
+    avg_delay_ms REAL NOT NULL,
+    max_delay_ms REAL NOT NULL,
+    PRIMARY KEY (day, endpoint)
+);
+";
+
+// AIDEV-NOTE: Windows are pruned a whole UTC day at a time, so every day still present in
+// `windows` is complete and re-rolling it into `daily` is idempotent. A flush only adds a window to
+// the day it ends in, so only that day (?1) is rolled up again rather than the whole table.
+const ROLL_UP: &str = "
+INSERT OR REPLACE INTO daily
+SELECT
+    date(window_end, 'unixepoch'),
+    endpoint,
+    COUNT(*),
+    SUM(observations),
+    SUM(first_detections),
+    SUM(delayed),
+    SUM(delay_sum_ms),
+    CASE WHEN SUM(delayed) > 0 THEN SUM(delay_sum_ms) / SUM(delayed) ELSE 0 END,
+    MAX(max_delay_ms)
+FROM windows
+WHERE date(window_end, 'unixepoch') = date(?1, 'unixepoch')
+GROUP BY 1, 2
+";
+
+#[derive(Debug, Default)]
+struct WindowStats {
+    observations: u64,
+    first_detections: u64,
+    delayed: u64,
+    delay_sum_ms: f64,
+    max_delay_ms: f64,
+}
+
+#[derive(Debug)]
+struct PendingWindow {
+    start: f64,
+    stats: HashMap<String, WindowStats>,
+}
+
+/// SQLite store of rolling-window summaries, rolled up into per-day aggregates.
+#[derive(Debug)]
+pub struct HistoryStore {
+    connection: Mutex<Connection>,
+    pending: Mutex<PendingWindow>,
+    window_retention_days: u64,
+    daily_retention_days: u64,
+    clock: Arc<dyn Clock>,
+}
+
+impl HistoryStore {
+    pub fn open(config: &HistoryConfig, clock: Arc<dyn Clock>) -> rusqlite::Result<Self> {
+        let connection = Connection::open(&config.path)?;
+        connection.execute_batch(SCHEMA)?;
+
+        Ok(Self {
+            connection: Mutex::new(connection),
+            pending: Mutex::new(PendingWindow {
+                start: clock.now(),
+                stats: HashMap::new(),
+            }),
+            window_retention_days: config.window_retention_days,
+            daily_retention_days: config.daily_retention_days,
+            clock,
+        })
+    }
+
+    pub fn record_observation(&self, endpoint: &str, delay_ms: f64) {
+        let mut pending = self.pending.lock().unwrap();
+        let stats = pending.stats.entry(endpoint.to_string()).or_default();
+        stats.observations += 1;
+        if delay_ms <= 0.0 {
+            stats.first_detections += 1;
+        } else {
+            stats.delayed += 1;
+            stats.delay_sum_ms += delay_ms;
+            stats.max_delay_ms = stats.max_delay_ms.max(delay_ms);
+        }
+    }
+
+    /// Persists the current window, refreshes its day's roll-up and applies retention. Blocks on
+    /// SQLite, so async callers run it with `spawn_blocking`.
+    pub fn flush_window(&self) {
+        let window_end = self.clock.now();
+        let (window_start, stats) = {
+            let mut pending = self.pending.lock().unwrap();
+            let start = std::mem::replace(&mut pending.start, window_end);
+            (start, std::mem::take(&mut pending.stats))
+        };
+
+        if let Err(e) = self.persist(window_start, window_end, stats) {
+            log::error!("Failed to persist history window: {}", e);
+        }
+    }
+
+    fn persist(
+        &self,
+        window_start: f64,
+        window_end: f64,
+        stats: HashMap<String, WindowStats>,
+    ) -> rusqlite::Result<()> {
+        let mut connection = self.connection.lock().unwrap();
+        let transaction = connection.transaction()?;
+
+        for (endpoint, stats) in &stats {
+            transaction.execute(
+                "INSERT INTO windows VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)",
+                params![
+                    window_start,
+                    window_end,
+                    endpoint,
+                    stats.observations as i64,
+                    stats.first_detections as i64,
+                    stats.delayed as i64,
+                    stats.delay_sum_ms,
+                    stats.max_delay_ms,
+                ],
+            )?;
+        }
+
+        transaction.execute(ROLL_UP, params![window_end])?;
+
+        let window_cutoff = window_end - self.window_retention_days as f64 * SECONDS_PER_DAY;
+        let pruned_windows = transaction.execute(
+            "DELETE FROM windows WHERE date(window_end, 'unixepoch') < date(?1, 'unixepoch')",
+            params![window_cutoff],
+        )?;
+        let daily_cutoff = window_end - self.daily_retention_days as f64 * SECONDS_PER_DAY;
+        let pruned_days = transaction.execute(
+            "DELETE FROM daily WHERE day < date(?1, 'unixepoch')",
+            params![daily_cutoff],
+        )?;
+
+        transaction.commit()?;
+
+        if pruned_windows > 0 || pruned_days > 0 {
+            log::info!(
+                "History retention pruned {} windows and {} daily rows",
+                pruned_windows,
+                pruned_days
+            );
+        }
+        Ok(())
+    }
+}
+
+pub fn spawn_window_flusher(store: Arc<HistoryStore>, window: Duration) -> tokio::task::JoinHandle<()> {
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(window);
+        interval.tick().await;
+        loop {
+            interval.tick().await;
+            let store = store.clone();
+            let _ = tokio::task::spawn_blocking(move || store.flush_window()).await;
+        }
+    })
+}
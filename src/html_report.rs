@@ -0,0 +1,91 @@
+use std::fs;
+
+use serde::Serialize;
+
+use crate::{report::BenchmarkReport, utils::Comparator};
+
+const TEMPLATE: &str = include_str!("report.html");
+
+#[derive(Debug, Default, Serialize)]
+pub struct ChartData {
+    pub endpoints: Vec<EndpointDelays>,
+    pub pairs: Vec<PairDelays>,
+}
+
+/// Delays behind the first detection, one sample per signature the endpoint did not win.
+#[derive(Debug, Serialize)]
+pub struct EndpointDelays {
+    pub name: String,
+    pub delays_ms: Vec<f64>,
+}
+
+/// `second - first` receive time for every signature both endpoints saw.
+#[derive(Debug, Serialize)]
+pub struct PairDelays {
+    pub first: String,
+    pub second: String,
+    pub diffs_ms: Vec<f64>,
+}
+
+pub fn collect_chart_data(comparator: &Comparator, endpoint_names: &[String]) -> ChartData {
+    let mut endpoints: Vec<EndpointDelays> = endpoint_names
+        .iter()
+        .map(|name| EndpointDelays {
+            name: name.clone(),
+            delays_ms: Vec::new(),
+        })
+        .collect();
+    let mut pairs = Vec::new();
+    for (i, first) in endpoint_names.iter().enumerate() {
+        for second in &endpoint_names[i + 1..] {
+            pairs.push(PairDelays {
+                first: first.clone(),
+                second: second.clone(),
+                diffs_ms: Vec::new(),
+            });
+        }
+    }
+
+    for sig_data in comparator.data.values().filter(|sig_data| comparator.is_live(sig_data)) {
+        let first_timestamp = sig_data
+            .values()
+            .map(|tx| tx.precise_timestamp())
+            .fold(f64::INFINITY, f64::min);
+
+        for endpoint in &mut endpoints {
+            if let Some(tx) = sig_data.get(&endpoint.name) {
+                let delay_ms = (tx.precise_timestamp() - first_timestamp) * 1000.0;
+                if delay_ms > 0.0 {
+                    endpoint.delays_ms.push(delay_ms);
+                }
+            }
+        }
+
+        for pair in &mut pairs {
+            if let (Some(first), Some(second)) = (sig_data.get(&pair.first), sig_data.get(&pair.second)) {
+                pair.diffs_ms
+                    .push((second.precise_timestamp() - first.precise_timestamp()) * 1000.0);
+            }
+        }
+    }
+
+    ChartData { endpoints, pairs }
+}
+
+pub fn write_html_report(
+    path: &str,
+    report: &BenchmarkReport,
+    charts: &ChartData,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let html = TEMPLATE
+        .replace("__REPORT__", &script_json(report)?)
+        .replace("__CHARTS__", &script_json(charts)?);
+    fs::write(path, html)?;
+    log::info!("Wrote HTML report to {}", path);
+    Ok(())
+}
+
+// Endpoint names end up inside a <script> block, so `</script>` must not survive serialization
+fn script_json<T: Serialize>(value: &T) -> serde_json::Result<String> {
+    Ok(serde_json::to_string(value)?.replace("</", "<\\/"))
+}
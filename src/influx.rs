@@ -0,0 +1,219 @@
+use std::{
+    collections::HashMap,
+    fs::{File, OpenOptions},
+    io::{BufWriter, Write},
+    net::UdpSocket,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        mpsc::{self, Receiver, SyncSender},
+        Arc, Mutex,
+    },
+    thread,
+    time::Duration,
+};
+
+use crate::{clock::Clock, config::InfluxConfig, utils::TransactionData};
+
+const MEASUREMENT_OBSERVATION: &str = "geyserbench_observation";
+const MEASUREMENT_WINDOW: &str = "geyserbench_window";
+/// Lines queued for the writer thread before new ones are dropped
+const QUEUE_CAPACITY: usize = 65_536;
+/// Lines written between flushes when the queue never runs empty
+const MAX_BATCH: usize = 4096;
+const FINISH_TIMEOUT: Duration = Duration::from_secs(10);
+
+#[derive(Debug)]
+enum InfluxWriter {
+    Udp(UdpSocket),
+    File(BufWriter<File>),
+}
+
+impl InfluxWriter {
+    fn write_line(&mut self, line: &str) -> std::io::Result<()> {
+        match self {
+            InfluxWriter::Udp(socket) => socket.send(line.as_bytes()).map(|_| ()),
+            InfluxWriter::File(file) => {
+                file.write_all(line.as_bytes())?;
+                file.write_all(b"\n")
+            }
+        }
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        match self {
+            InfluxWriter::Udp(_) => Ok(()),
+            InfluxWriter::File(file) => file.flush(),
+        }
+    }
+}
+
+enum InfluxMessage {
+    Line(String),
+    Flush(mpsc::Sender<()>),
+}
+
+#[derive(Debug, Default)]
+struct WindowStats {
+    observations: u64,
+    first_detections: u64,
+    delay_sum_ms: f64,
+    delayed: u64,
+}
+
+// AIDEV-NOTE: Line protocol sink; `udp://host:port` targets an Influx/Telegraf UDP listener, anything else is a file path.
+// Observations are recorded under the Comparator lock, so lines are only `try_send` to a writer
+// thread: a full queue drops the line and counts it. The thread writes files through a buffer
+// flushed once per batch, the way the transaction logs are written.
+#[derive(Debug)]
+pub struct InfluxSink {
+    tx: SyncSender<InfluxMessage>,
+    dropped: AtomicU64,
+    windows: Mutex<HashMap<(String, &'static str), WindowStats>>,
+    clock: Arc<dyn Clock>,
+}
+
+impl InfluxSink {
+    pub fn open(config: &InfluxConfig, clock: Arc<dyn Clock>) -> std::io::Result<Self> {
+        let writer = match config.target.strip_prefix("udp://") {
+            Some(addr) => {
+                let socket = UdpSocket::bind("0.0.0.0:0")?;
+                socket.connect(addr)?;
+                InfluxWriter::Udp(socket)
+            }
+            None => InfluxWriter::File(BufWriter::new(
+                OpenOptions::new()
+                    .create(true)
+                    .append(true)
+                    .open(&config.target)?,
+            )),
+        };
+        let (tx, rx) = mpsc::sync_channel(QUEUE_CAPACITY);
+        thread::Builder::new()
+            .name("influx-writer".to_string())
+            .spawn(move || write_lines(rx, writer))?;
+
+        Ok(Self {
+            tx,
+            dropped: AtomicU64::new(0),
+            windows: Mutex::new(HashMap::new()),
+            clock,
+        })
+    }
+
+    fn write_line(&self, line: String) {
+        if self.tx.try_send(InfluxMessage::Line(line)).is_err() {
+            self.dropped.fetch_add(1, Ordering::Relaxed);
+        }
+    }
+
+    pub fn record_observation(
+        &self,
+        endpoint: &str,
+        stream: &'static str,
+        data: &TransactionData,
+        delay_ms: f64,
+    ) {
+        let first = delay_ms <= 0.0;
+        self.write_line(format!(
+            "{},endpoint={},stream={} signature=\"{}\",first={},delay_ms={} {}",
+            MEASUREMENT_OBSERVATION,
+            escape_tag(endpoint),
+            stream,
+            escape_field(&data.signature),
+            first,
+            delay_ms,
+            to_nanos(data.timestamp)
+        ));
+
+        let mut windows = self.windows.lock().unwrap();
+        let window = windows.entry((endpoint.to_string(), stream)).or_default();
+        window.observations += 1;
+        if first {
+            window.first_detections += 1;
+        } else {
+            window.delay_sum_ms += delay_ms;
+            window.delayed += 1;
+        }
+    }
+
+    /// Emits one aggregate line per (endpoint, stream) seen since the previous flush.
+    pub fn flush_window(&self) {
+        let windows = std::mem::take(&mut *self.windows.lock().unwrap());
+        let timestamp = to_nanos(self.clock.now());
+        for ((endpoint, stream), stats) in windows {
+            let avg_delay_ms = if stats.delayed > 0 {
+                stats.delay_sum_ms / stats.delayed as f64
+            } else {
+                0.0
+            };
+            self.write_line(format!(
+                "{},endpoint={},stream={} observations={}i,first_detections={}i,avg_delay_ms={} {}",
+                MEASUREMENT_WINDOW,
+                escape_tag(&endpoint),
+                stream,
+                stats.observations,
+                stats.first_detections,
+                avg_delay_ms,
+                timestamp
+            ));
+        }
+    }
+
+    /// Waits until every queued line is written and returns the lines dropped because the queue was full.
+    pub fn finish(&self) -> u64 {
+        let (ack_tx, ack_rx) = mpsc::channel();
+        if self.tx.send(InfluxMessage::Flush(ack_tx)).is_ok() && ack_rx.recv_timeout(FINISH_TIMEOUT).is_err() {
+            log::warn!("Influx lines were still being written {}s after the run", FINISH_TIMEOUT.as_secs());
+        }
+        self.dropped.load(Ordering::Relaxed)
+    }
+}
+
+fn write_lines(rx: Receiver<InfluxMessage>, mut writer: InfluxWriter) {
+    while let Ok(first) = rx.recv() {
+        let mut acks = Vec::new();
+        for message in std::iter::once(first).chain(rx.try_iter().take(MAX_BATCH - 1)) {
+            match message {
+                InfluxMessage::Line(line) => {
+                    if let Err(e) = writer.write_line(&line) {
+                        log::debug!("Failed to write influx line: {}", e);
+                    }
+                }
+                InfluxMessage::Flush(ack) => acks.push(ack),
+            }
+        }
+        if let Err(e) = writer.flush() {
+            log::debug!("Failed to flush influx lines: {}", e);
+        }
+        for ack in acks {
+            let _ = ack.send(());
+        }
+    }
+}
+
+pub fn spawn_window_flusher(sink: Arc<InfluxSink>, window: Duration) -> tokio::task::JoinHandle<()> {
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(window);
+        interval.tick().await;
+        loop {
+            interval.tick().await;
+            sink.flush_window();
+        }
+    })
+}
+
+fn to_nanos(timestamp: f64) -> i64 {
+    (timestamp * 1_000_000_000.0) as i64
+}
+
+fn escape_tag(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace(',', "\\,")
+        .replace('=', "\\=")
+        .replace(' ', "\\ ")
+}
+
+fn escape_field(value: &str) -> String {
+    value.replace('\\', "\\\\").replace('"', "\\\"")
+}
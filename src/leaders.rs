@@ -0,0 +1,200 @@
+//! Endpoint delays broken down by the leader that produced each transaction's slot.
+//!
+//! Providers peer with some validators more closely than others, so one that wins overall can
+//! still trail on particular leaders' slots.
+
+use std::{
+    collections::{HashMap, HashSet},
+    error::Error,
+    time::Duration,
+};
+
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+
+use crate::{
+    config::LeadersConfig,
+    utils::{percentile, Comparator},
+};
+
+const REQUEST_TIMEOUT_SECS: u64 = 10;
+/// Most slots `getSlotLeaders` answers in one call
+const MAX_SLOTS_PER_REQUEST: u64 = 5000;
+/// Wider slot ranges (long replays, stray historical slots) are not fetched
+const MAX_SLOT_RANGE: u64 = 500_000;
+
+/// One endpoint's results on the slots of one leader.
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+pub struct LeaderEndpointReport {
+    pub name: String,
+    pub signatures: usize,
+    pub wins: usize,
+    pub win_rate: f64,
+    /// Delay behind the first detection, first detections counted as zero
+    pub median_delta_ms: f64,
+    pub p95_delta_ms: f64,
+}
+
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+pub struct LeaderReport {
+    pub leader: String,
+    pub slots: usize,
+    /// Live signatures in the leader's slots
+    pub signatures: usize,
+    pub endpoints: Vec<LeaderEndpointReport>,
+}
+
+async fn slot_leaders(
+    client: &reqwest::Client,
+    rpc_url: &str,
+    start_slot: u64,
+    limit: u64,
+) -> Result<Vec<String>, Box<dyn Error + Send + Sync>> {
+    let request = json!({ "jsonrpc": "2.0", "id": 1, "method": "getSlotLeaders", "params": [start_slot, limit] });
+    let response: Value = client
+        .post(rpc_url)
+        .timeout(Duration::from_secs(REQUEST_TIMEOUT_SECS))
+        .json(&request)
+        .send()
+        .await?
+        .error_for_status()?
+        .json()
+        .await?;
+    if let Some(error) = response.get("error") {
+        return Err(format!("getSlotLeaders from slot {} failed: {}", start_slot, error).into());
+    }
+    let leaders = response["result"]
+        .as_array()
+        .ok_or_else(|| format!("getSlotLeaders from slot {} returned no leader list", start_slot))?;
+    Ok(leaders.iter().filter_map(|leader| leader.as_str().map(str::to_string)).collect())
+}
+
+// AIDEV-NOTE: The whole range between the lowest and highest observed slot is fetched in
+// 5000-slot pages rather than slot by slot; a minute of traffic spans about 150 slots, so a normal
+// run needs one request.
+/// Leader of every slot between the lowest and highest in `slots`.
+pub async fn fetch_leaders(
+    rpc_url: &str,
+    slots: &HashSet<u64>,
+) -> Result<HashMap<u64, String>, Box<dyn Error + Send + Sync>> {
+    let (Some(first), Some(last)) = (slots.iter().min().copied(), slots.iter().max().copied()) else {
+        return Ok(HashMap::new());
+    };
+    if last - first > MAX_SLOT_RANGE {
+        return Err(format!("observed slots span {} slots, more than the {} fetched", last - first, MAX_SLOT_RANGE).into());
+    }
+    let client = reqwest::Client::new();
+    let mut leaders = HashMap::new();
+    let mut start = first;
+    while start <= last {
+        let limit = (last - start + 1).min(MAX_SLOTS_PER_REQUEST);
+        let page = slot_leaders(&client, rpc_url, start, limit).await?;
+        leaders.extend((start..).zip(page));
+        start += limit;
+    }
+    Ok(leaders)
+}
+
+#[derive(Default)]
+struct LeaderStats {
+    slots: HashSet<u64>,
+    signatures: usize,
+    deltas: HashMap<String, Vec<f64>>,
+}
+
+/// Groups live signatures by their slot's leader. Signatures without a slot, or whose slot has no
+/// known leader, are left out.
+pub fn summarize_leaders(
+    comparator: &Comparator,
+    endpoint_names: &[String],
+    leaders: &HashMap<u64, String>,
+    min_signatures: usize,
+) -> Vec<LeaderReport> {
+    let mut by_leader: HashMap<&str, LeaderStats> = HashMap::new();
+    for sig_data in comparator.data.values().filter(|sig_data| comparator.is_live(sig_data)) {
+        let Some(slot) = sig_data.values().find_map(|tx| tx.slot) else {
+            continue;
+        };
+        let Some(leader) = leaders.get(&slot) else {
+            continue;
+        };
+        let first = sig_data.values().map(|tx| tx.precise_timestamp()).fold(f64::INFINITY, f64::min);
+        let stats = by_leader.entry(leader.as_str()).or_default();
+        stats.slots.insert(slot);
+        stats.signatures += 1;
+        for (endpoint, tx) in sig_data {
+            stats
+                .deltas
+                .entry(endpoint.clone())
+                .or_default()
+                .push((tx.precise_timestamp() - first) * 1000.0);
+        }
+    }
+
+    let mut reports: Vec<LeaderReport> = by_leader
+        .into_iter()
+        .filter(|(_, stats)| stats.signatures >= min_signatures)
+        .map(|(leader, mut stats)| LeaderReport {
+            leader: leader.to_string(),
+            slots: stats.slots.len(),
+            signatures: stats.signatures,
+            endpoints: endpoint_names
+                .iter()
+                .filter_map(|name| {
+                    let mut deltas = stats.deltas.remove(name)?;
+                    deltas.sort_by(|a, b| a.partial_cmp(b).unwrap());
+                    let wins = deltas.iter().filter(|delta| **delta == 0.0).count();
+                    Some(LeaderEndpointReport {
+                        name: name.clone(),
+                        signatures: deltas.len(),
+                        wins,
+                        win_rate: wins as f64 / deltas.len() as f64 * 100.0,
+                        median_delta_ms: percentile(&deltas, 0.5),
+                        p95_delta_ms: percentile(&deltas, 0.95),
+                    })
+                })
+                .collect(),
+        })
+        .collect();
+    reports.sort_by(|a, b| b.signatures.cmp(&a.signatures).then_with(|| a.leader.cmp(&b.leader)));
+    reports
+}
+
+/// Fetches the leaders of the observed slots and summarizes; RPC failures are logged and leave
+/// the breakdown empty rather than failing the run.
+pub async fn analyze(comparator: &Comparator, endpoint_names: &[String], config: &LeadersConfig) -> Vec<LeaderReport> {
+    let slots: HashSet<u64> = comparator
+        .data
+        .values()
+        .filter(|sig_data| comparator.is_live(sig_data))
+        .filter_map(|sig_data| sig_data.values().find_map(|tx| tx.slot))
+        .collect();
+    match fetch_leaders(&config.rpc_url, &slots).await {
+        Ok(leaders) => summarize_leaders(comparator, endpoint_names, &leaders, config.min_signatures),
+        Err(e) => {
+            log::warn!("Failed to fetch slot leaders: {}", e);
+            Vec::new()
+        }
+    }
+}
+
+pub fn print_leaders(reports: &[LeaderReport], top_n: usize) {
+    if reports.is_empty() {
+        return;
+    }
+
+    println!("\nDelays by slot leader");
+    println!("--------------------------------------------");
+    for report in reports.iter().take(top_n) {
+        println!("{} ({} signatures over {} slots):", report.leader, report.signatures, report.slots);
+        for endpoint in &report.endpoints {
+            println!(
+                "  {}: win rate {:.2}%, median +{:.2} ms, p95 +{:.2} ms ({} signatures)",
+                endpoint.name, endpoint.win_rate, endpoint.median_delta_ms, endpoint.p95_delta_ms, endpoint.signatures
+            );
+        }
+    }
+    if reports.len() > top_n {
+        println!("... {} more leaders in the JSON report", reports.len() - top_n);
+    }
+}
@@ -0,0 +1,85 @@
+//! Benchmark Solana Geyser streaming endpoints.
+//!
+//! The `geyserbench` binary is a thin CLI over [`run_benchmark`]; embedders can call it (or
+//! [`run_benchmark_with_shutdown`] to stop a run on their own signal) with a [`ConfigToml`]
+//! built in code or loaded with [`ConfigToml::load`].
+//!
+//! Only the embedding API below is public; the modules behind it are internal.
+
+pub(crate) mod app;
+pub(crate) mod cli;
+pub(crate) mod config;
+pub(crate) mod utils;
+pub(crate) mod analysis;
+pub(crate) mod providers;
+pub(crate) mod tsc;
+pub(crate) mod report;
+pub(crate) mod signing;
+pub(crate) mod influx;
+pub(crate) mod history;
+pub(crate) mod frame_timing;
+pub(crate) mod throughput;
+pub(crate) mod html_report;
+pub(crate) mod slo;
+pub(crate) mod clock;
+pub(crate) mod runner;
+pub(crate) mod notifier;
+pub(crate) mod queue;
+pub(crate) mod diff;
+pub(crate) mod probe;
+pub(crate) mod sampler;
+pub(crate) mod telemetry;
+pub(crate) mod markets;
+pub(crate) mod retention;
+pub(crate) mod connection;
+pub(crate) mod replay;
+pub(crate) mod metadata;
+pub(crate) mod profile;
+pub(crate) mod coordinator;
+pub(crate) mod alerts;
+pub(crate) mod token_accounts;
+pub(crate) mod parquet_sink;
+pub(crate) mod sender;
+pub(crate) mod events;
+pub(crate) mod token_refresh;
+pub(crate) mod timefmt;
+pub(crate) mod timeline;
+pub(crate) mod lookup_tables;
+pub(crate) mod log_output;
+pub(crate) mod assertions;
+pub(crate) mod proxy;
+pub(crate) mod leaders;
+pub(crate) mod repeat;
+pub(crate) mod signals;
+pub(crate) mod reload;
+pub(crate) mod runtimes;
+pub(crate) mod slot_position;
+pub(crate) mod slot_status;
+pub(crate) mod ground_truth;
+
+pub use clock::{Clock, MockClock, MonotonicClock, SystemClock};
+pub use config::ConfigToml;
+pub use providers::{
+    register_provider, start_when_ready, Capabilities, GeyserProvider, ProviderError, ProviderFactory, ProviderRun, ProviderStart,
+    Ready,
+};
+pub use report::BenchmarkReport;
+pub use profile::CountingAllocator;
+pub use reload::Generation;
+pub use runner::{
+    BenchmarkRun, run_benchmark, run_benchmark_with_clock, run_benchmark_with_reload, run_benchmark_with_shutdown,
+};
+pub use utils::{Comparator, TransactionData};
+
+/// The `geyserbench` binary's entry point; not part of the embedding API.
+#[doc(hidden)]
+pub use app::run_cli;
+
+/// Internals measured by `benches/`; not part of the embedding API.
+#[doc(hidden)]
+pub mod bench_support {
+    pub use crate::{
+        profile::{allocation_count, count_allocations},
+        providers::WatchedKeys,
+    };
+}
@@ -0,0 +1,432 @@
+//! Where the per-endpoint transaction logs go: the configured directory, an optional run
+//! subdirectory, file names safe on every platform, and size/age rotation. Lines are written by a
+//! dedicated thread so file IO never runs on a stream task.
+
+use std::{
+    collections::{BTreeMap, HashMap},
+    fs::{self, File, OpenOptions},
+    io::{self, BufWriter, Write},
+    path::{Path, PathBuf},
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        mpsc::{self, Receiver, SyncSender},
+        Arc, Mutex, OnceLock,
+    },
+    thread,
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+use crate::{
+    config::LogsConfig,
+    timefmt,
+    utils::{LOG_FILE_PREFIX, LOG_FILE_SUFFIX},
+};
+
+const RUN_DIR_PREFIX: &str = "run-";
+const BYTES_PER_MB: u64 = 1024 * 1024;
+const SECONDS_PER_HOUR: u64 = 3600;
+/// Lines queued across every endpoint's log before new ones are dropped
+const QUEUE_CAPACITY: usize = 65_536;
+/// Messages written between flushes when the queue never runs empty
+const MAX_BATCH: usize = 4096;
+const WRITE_BUFFER_BYTES: usize = 64 * 1024;
+const FINISH_TIMEOUT: Duration = Duration::from_secs(10);
+
+// Device names Windows reserves in every directory, with or without an extension
+const RESERVED_NAMES: [&str; 22] = [
+    "CON", "PRN", "AUX", "NUL", "COM1", "COM2", "COM3", "COM4", "COM5", "COM6", "COM7", "COM8", "COM9", "LPT1",
+    "LPT2", "LPT3", "LPT4", "LPT5", "LPT6", "LPT7", "LPT8", "LPT9",
+];
+
+/// Endpoint name made safe as part of a file name on Windows, macOS and Linux.
+pub fn sanitize_name(name: &str) -> String {
+    let mut sanitized: String = name
+        .chars()
+        .map(|c| match c {
+            '<' | '>' | ':' | '"' | '/' | '\\' | '|' | '?' | '*' => '_',
+            c if c.is_control() => '_',
+            c => c,
+        })
+        .collect();
+    // Windows drops trailing dots and spaces, which would merge distinct names
+    let kept = sanitized.trim_end_matches(['.', ' ']).len();
+    let dropped = sanitized.len() - kept;
+    sanitized.replace_range(kept.., &"_".repeat(dropped));
+    let stem = sanitized.split('.').next().unwrap_or_default();
+    if sanitized.is_empty() || RESERVED_NAMES.iter().any(|reserved| stem.eq_ignore_ascii_case(reserved)) {
+        sanitized.insert(0, '_');
+    }
+    sanitized
+}
+
+/// Directory and rotation settings shared by every endpoint's log in a run.
+#[derive(Debug, Clone)]
+pub struct LogOutput {
+    dir: PathBuf,
+    max_bytes: Option<u64>,
+    max_age: Option<Duration>,
+    /// Writer thread, started by the first `open`
+    sink: Arc<OnceLock<LogSink>>,
+}
+
+impl Default for LogOutput {
+    fn default() -> Self {
+        Self {
+            dir: PathBuf::from("."),
+            max_bytes: None,
+            max_age: None,
+            sink: Arc::default(),
+        }
+    }
+}
+
+impl LogOutput {
+    /// Creates the log directory, and with `run_subdir` a `run-<UTC time>` directory inside it.
+    pub fn create(config: &LogsConfig, start_time: f64) -> io::Result<Self> {
+        let mut dir = PathBuf::from(&config.dir);
+        if config.run_subdir {
+            dir.push(format!("{}{}", RUN_DIR_PREFIX, timefmt::utc_compact(start_time)));
+        }
+        fs::create_dir_all(&dir)?;
+        Ok(Self {
+            dir,
+            max_bytes: config.max_size_mb.map(|mb| mb.saturating_mul(BYTES_PER_MB)),
+            max_age: config.max_age_hours.map(|hours| Duration::from_secs(hours.saturating_mul(SECONDS_PER_HOUR))),
+            sink: Arc::default(),
+        })
+    }
+
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    // AIDEV-NOTE: Shards and pooled connections of one endpoint all open its log. They share one
+    // writer on the writer thread, so their lines never interleave mid-buffer and a rotation
+    // renames the file only once, with no second writer left appending to the renamed file.
+    /// Opens the log for `name` for appending, rotating it first if it is already over a limit.
+    /// The file is opened here, so errors still reach the caller; writes go to the writer thread.
+    /// A log that is already open is shared rather than opened again.
+    pub fn open(&self, name: &str) -> io::Result<LogHandle> {
+        let path = self
+            .dir
+            .join(format!("{}{}{}", LOG_FILE_PREFIX, sanitize_name(name), LOG_FILE_SUFFIX));
+        let sink = self.sink.get_or_init(LogSink::spawn);
+        let mut open_paths = sink.open_paths.lock().unwrap();
+        let writer = if open_paths.contains_key(&path) {
+            None
+        } else {
+            let mut writer = LogWriter {
+                path: path.clone(),
+                file: None,
+                written: 0,
+                opened_at: SystemTime::now(),
+                max_bytes: self.max_bytes,
+                max_age: self.max_age,
+            };
+            writer.open()?;
+            if writer.over_limit() {
+                writer.rotate()?;
+            }
+            Some(Box::new(writer))
+        };
+        let handle = sink.register(name, path.clone(), writer)?;
+        *open_paths.entry(path).or_default() += 1;
+        Ok(handle)
+    }
+
+    /// Waits until every queued line is on disk and returns the lines dropped per log because the
+    /// queue was full.
+    pub fn finish(&self) -> BTreeMap<String, u64> {
+        let Some(sink) = self.sink.get() else {
+            return BTreeMap::new();
+        };
+        let (ack_tx, ack_rx) = mpsc::channel();
+        if sink.tx.send(LogMessage::Flush(ack_tx)).is_ok() && ack_rx.recv_timeout(FINISH_TIMEOUT).is_err() {
+            log::warn!("Transaction logs were still being written {}s after the run", FINISH_TIMEOUT.as_secs());
+        }
+        let mut dropped = BTreeMap::new();
+        for (name, count) in sink.dropped.lock().unwrap().iter() {
+            let count = count.load(Ordering::Relaxed);
+            if count > 0 {
+                *dropped.entry(name.clone()).or_default() += count;
+            }
+        }
+        dropped
+    }
+}
+
+enum LogMessage {
+    /// A new handle on a path; the writer is None when the path's writer is already open
+    Open(u64, PathBuf, Option<Box<LogWriter>>),
+    Line(u64, Vec<u8>),
+    Close(u64),
+    Flush(mpsc::Sender<()>),
+}
+
+// AIDEV-NOTE: Stream tasks only format the line and `try_send` it: a full queue drops the line and
+// counts it rather than blocking the task that is timestamping messages. The writer thread drains
+// everything queued (up to MAX_BATCH) into buffered files and flushes once per batch, so a burst
+// costs one write per file instead of one per line.
+#[derive(Debug)]
+struct LogSink {
+    tx: SyncSender<LogMessage>,
+    next_id: AtomicU64,
+    dropped: Mutex<Vec<(String, Arc<AtomicU64>)>>,
+    /// Live handles per log path
+    open_paths: Arc<Mutex<HashMap<PathBuf, usize>>>,
+}
+
+impl LogSink {
+    fn spawn() -> Self {
+        let (tx, rx) = mpsc::sync_channel(QUEUE_CAPACITY);
+        thread::Builder::new()
+            .name("transaction-log".to_string())
+            .spawn(move || write_logs(rx))
+            .expect("failed to start the transaction log writer");
+        Self {
+            tx,
+            next_id: AtomicU64::new(0),
+            dropped: Mutex::new(Vec::new()),
+            open_paths: Arc::default(),
+        }
+    }
+
+    fn register(&self, name: &str, path: PathBuf, writer: Option<Box<LogWriter>>) -> io::Result<LogHandle> {
+        let id = self.next_id.fetch_add(1, Ordering::Relaxed);
+        self.tx
+            .send(LogMessage::Open(id, path.clone(), writer))
+            .map_err(|_| io::Error::new(io::ErrorKind::BrokenPipe, "transaction log writer stopped"))?;
+        let dropped = Arc::new(AtomicU64::new(0));
+        self.dropped.lock().unwrap().push((name.to_string(), dropped.clone()));
+        Ok(LogHandle {
+            id,
+            path,
+            tx: self.tx.clone(),
+            dropped,
+            open_paths: self.open_paths.clone(),
+        })
+    }
+}
+
+fn write_logs(rx: Receiver<LogMessage>) {
+    let mut writers: HashMap<PathBuf, BufWriter<LogWriter>> = HashMap::new();
+    let mut handles: HashMap<u64, PathBuf> = HashMap::new();
+    while let Ok(first) = rx.recv() {
+        let mut acks = Vec::new();
+        for message in std::iter::once(first).chain(rx.try_iter().take(MAX_BATCH - 1)) {
+            match message {
+                LogMessage::Open(id, path, writer) => {
+                    // A fresh writer replaces one whose close was lost to a full queue
+                    if let Some(writer) = writer {
+                        if let Some(mut stale) = writers.insert(path.clone(), BufWriter::with_capacity(WRITE_BUFFER_BYTES, *writer)) {
+                            let _ = stale.flush();
+                        }
+                    }
+                    handles.insert(id, path);
+                }
+                LogMessage::Line(id, line) => {
+                    let Some(path) = handles.get(&id) else {
+                        continue;
+                    };
+                    let Some(writer) = writers.get_mut(path) else {
+                        continue;
+                    };
+                    if let Err(e) = writer.write_all(&line) {
+                        log::error!("Stopped writing {}: {}", path.display(), e);
+                        writers.remove(path);
+                    }
+                }
+                LogMessage::Close(id) => {
+                    let Some(path) = handles.remove(&id) else {
+                        continue;
+                    };
+                    if handles.values().any(|other| *other == path) {
+                        continue;
+                    }
+                    if let Some(mut writer) = writers.remove(&path) {
+                        if let Err(e) = writer.flush() {
+                            log::error!("Failed to flush {}: {}", path.display(), e);
+                        }
+                    }
+                }
+                LogMessage::Flush(ack) => acks.push(ack),
+            }
+        }
+        writers.retain(|_, writer| match writer.flush() {
+            Ok(()) => true,
+            Err(e) => {
+                log::error!("Stopped writing {}: {}", writer.get_ref().path.display(), e);
+                false
+            }
+        });
+        for ack in acks {
+            let _ = ack.send(());
+        }
+    }
+}
+
+/// One endpoint's transaction log as seen by its stream task: writes are queued for the writer
+/// thread and never block or fail. Dropping the last handle on a log closes the file.
+#[derive(Debug)]
+pub struct LogHandle {
+    id: u64,
+    path: PathBuf,
+    tx: SyncSender<LogMessage>,
+    dropped: Arc<AtomicU64>,
+    open_paths: Arc<Mutex<HashMap<PathBuf, usize>>>,
+}
+
+impl Write for LogHandle {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        if self.tx.try_send(LogMessage::Line(self.id, buf.to_vec())).is_err() {
+            self.dropped.fetch_add(1, Ordering::Relaxed);
+        }
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+impl Drop for LogHandle {
+    fn drop(&mut self) {
+        // Closed under the lock, so a later `open` of the same path is queued after the close
+        let mut open_paths = self.open_paths.lock().unwrap();
+        if let Some(count) = open_paths.get_mut(&self.path) {
+            *count -= 1;
+            if *count == 0 {
+                open_paths.remove(&self.path);
+            }
+        }
+        // A full queue keeps the file open until `finish` flushes it
+        let _ = self.tx.try_send(LogMessage::Close(self.id));
+    }
+}
+
+// AIDEV-NOTE: A rotated log is renamed to `transaction_log_<name>.<UTC time>.txt`, which still
+// matches the prefix and suffix `analyze` and `clean` look for. The file is closed before the
+// rename, as Windows refuses to rename open files.
+/// Append-only transaction log that starts a new file once the current one is too big or too old.
+#[derive(Debug)]
+pub struct LogWriter {
+    path: PathBuf,
+    file: Option<File>,
+    written: u64,
+    opened_at: SystemTime,
+    max_bytes: Option<u64>,
+    max_age: Option<Duration>,
+}
+
+impl LogWriter {
+    fn open(&mut self) -> io::Result<()> {
+        let file = OpenOptions::new().create(true).append(true).open(&self.path)?;
+        let metadata = file.metadata()?;
+        self.written = metadata.len();
+        // Creation time is not tracked everywhere; a file without one ages from now
+        self.opened_at = metadata.created().unwrap_or_else(|_| SystemTime::now());
+        self.file = Some(file);
+        Ok(())
+    }
+
+    fn over_limit(&self) -> bool {
+        self.max_bytes.is_some_and(|max_bytes| self.written >= max_bytes)
+            || self
+                .max_age
+                .is_some_and(|max_age| self.opened_at.elapsed().is_ok_and(|age| age >= max_age))
+    }
+
+    fn rotate(&mut self) -> io::Result<()> {
+        if self.written == 0 {
+            return Ok(());
+        }
+        if let Some(mut file) = self.file.take() {
+            file.flush()?;
+        }
+        let now = SystemTime::now().duration_since(UNIX_EPOCH).map_or(0.0, |d| d.as_secs_f64());
+        let stem = self
+            .path
+            .file_name()
+            .and_then(|name| name.to_str())
+            .and_then(|name| name.strip_suffix(LOG_FILE_SUFFIX))
+            .unwrap_or_default()
+            .to_string();
+        let mut archived = self
+            .path
+            .with_file_name(format!("{}.{}{}", stem, timefmt::utc_compact(now), LOG_FILE_SUFFIX));
+        let mut attempt = 1;
+        while archived.exists() {
+            archived = self
+                .path
+                .with_file_name(format!("{}.{}-{}{}", stem, timefmt::utc_compact(now), attempt, LOG_FILE_SUFFIX));
+            attempt += 1;
+        }
+        fs::rename(&self.path, &archived)?;
+        log::info!("Rotated {} to {}", self.path.display(), archived.display());
+        self.open()?;
+        self.opened_at = SystemTime::now();
+        Ok(())
+    }
+}
+
+impl Write for LogWriter {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        if self.over_limit() {
+            self.rotate()?;
+        }
+        // A failed rotation leaves the log closed; it is reopened on the next write
+        if self.file.is_none() {
+            self.open()?;
+        }
+        let file = self.file.as_mut().expect("opened above");
+        let written = file.write(buf)?;
+        self.written += written as u64;
+        Ok(written)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        match &mut self.file {
+            Some(file) => file.flush(),
+            None => Ok(()),
+        }
+    }
+}
+
+/// Transaction logs in `dir` and in its `run-*` subdirectories.
+pub fn log_files(dir: &Path) -> io::Result<Vec<PathBuf>> {
+    let mut files = Vec::new();
+    collect_log_files(dir, true, &mut files)?;
+    files.sort();
+    Ok(files)
+}
+
+/// `run-*` subdirectories of `dir`, as created with `logs.run_subdir`.
+pub fn run_dirs(dir: &Path) -> io::Result<Vec<PathBuf>> {
+    let mut dirs = Vec::new();
+    for entry in fs::read_dir(dir)? {
+        let entry = entry?;
+        if entry.file_type()?.is_dir() && entry.file_name().to_str().is_some_and(|name| name.starts_with(RUN_DIR_PREFIX)) {
+            dirs.push(entry.path());
+        }
+    }
+    Ok(dirs)
+}
+
+fn collect_log_files(dir: &Path, descend: bool, files: &mut Vec<PathBuf>) -> io::Result<()> {
+    for entry in fs::read_dir(dir)? {
+        let entry = entry?;
+        let name = entry.file_name();
+        let Some(name) = name.to_str() else {
+            continue;
+        };
+        if name.starts_with(LOG_FILE_PREFIX) && name.ends_with(LOG_FILE_SUFFIX) && entry.file_type()?.is_file() {
+            files.push(entry.path());
+        }
+    }
+    if descend {
+        for run_dir in run_dirs(dir)? {
+            collect_log_files(&run_dir, false, files)?;
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,203 @@
+//! Address lookup table resolution for v0 transactions on streams that carry only the static
+//! account keys (shred-based endpoints, `arpc`). Yellowstone-based streams carry the loaded
+//! addresses in the transaction meta and need no RPC.
+
+use std::{
+    collections::{HashMap, HashSet},
+    error::Error,
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
+};
+
+use base64::{engine::general_purpose::STANDARD, Engine};
+use serde_json::{json, Value};
+use tokio::sync::Semaphore;
+
+use crate::{config::LookupTablesConfig, providers::WatchedKeys};
+
+// Account data before the first address: the state discriminant and the table's metadata
+const LOOKUP_TABLE_META_SIZE: usize = 56;
+// Tables hold at most 256 addresses, as lookups index them with a u8
+const MAX_TABLE_ADDRESSES: usize = 256;
+const REQUEST_TIMEOUT_SECS: u64 = 10;
+const MAX_CONCURRENT_FETCHES: usize = 4;
+// A cached table is fetched again when a lookup indexes past its end, at most this often
+const REFETCH_INTERVAL: Duration = Duration::from_secs(1);
+// A table whose fetch failed is retried after this long
+const RETRY_INTERVAL: Duration = Duration::from_secs(30);
+
+/// One address table lookup of a v0 message, borrowed from whichever protobuf carried it.
+pub struct TableLookup<'a> {
+    pub table: &'a [u8],
+    pub writable: &'a [u8],
+    pub readonly: &'a [u8],
+}
+
+impl TableLookup<'_> {
+    fn indexes(&self) -> impl Iterator<Item = usize> + '_ {
+        self.writable.iter().chain(self.readonly).map(|index| usize::from(*index))
+    }
+}
+
+/// What a table holds, reduced to the positions of watched accounts.
+#[derive(Debug)]
+struct CachedTable {
+    len: usize,
+    watched: Vec<(usize, String)>,
+    fetched_at: Instant,
+}
+
+#[derive(Debug, Clone, Copy, Default)]
+pub struct LookupTableStats {
+    pub fetched: u64,
+    pub failed: u64,
+    /// Lookups into a table that was not cached yet when its transaction arrived
+    pub misses: u64,
+}
+
+#[derive(Debug, Default)]
+struct Cache {
+    tables: HashMap<[u8; 32], CachedTable>,
+    in_flight: HashSet<[u8; 32]>,
+    failed: HashMap<[u8; 32], Instant>,
+    stats: LookupTableStats,
+}
+
+// AIDEV-NOTE: Resolution never waits on RPC: a table not cached yet is fetched in the background and
+// the transaction that asked is matched on its static keys only. Waiting would hold up the stream
+// and skew the endpoint's receive times behind it. Tables are shared by every provider, so the
+// first sighting fetches for all, and only a table's first transactions can be missed.
+/// Shared cache of address lookup tables, fetched over RPC on first use.
+#[derive(Debug)]
+pub struct LookupTables {
+    client: reqwest::Client,
+    rpc_url: String,
+    watched: WatchedKeys,
+    fetches: Semaphore,
+    cache: Mutex<Cache>,
+}
+
+impl LookupTables {
+    pub fn new(config: &LookupTablesConfig, watched_accounts: &[String]) -> Self {
+        Self {
+            client: reqwest::Client::new(),
+            rpc_url: config.rpc_url.clone(),
+            watched: WatchedKeys::from_base58(watched_accounts),
+            fetches: Semaphore::new(MAX_CONCURRENT_FETCHES),
+            cache: Mutex::new(Cache::default()),
+        }
+    }
+
+    /// Base58 form of the watched accounts the lookups load. Tables that are not cached yet, or
+    /// too short for an index, are fetched in the background for later transactions.
+    pub fn resolve<'a>(self: &Arc<Self>, lookups: impl IntoIterator<Item = TableLookup<'a>>) -> Vec<String> {
+        let mut lookups = lookups.into_iter().peekable();
+        let mut matched = Vec::new();
+        // Legacy messages have no lookups and never need the lock
+        if lookups.peek().is_none() {
+            return matched;
+        }
+        let mut guard = self.cache.lock().unwrap();
+        let cache = &mut *guard;
+        for lookup in lookups {
+            let Ok(key) = <[u8; 32]>::try_from(lookup.table) else {
+                continue;
+            };
+            let fetch = match cache.tables.get(&key) {
+                Some(table) => {
+                    matched.extend(
+                        table
+                            .watched
+                            .iter()
+                            .filter(|(position, _)| lookup.indexes().any(|index| index == *position))
+                            .map(|(_, account)| account.clone()),
+                    );
+                    lookup.indexes().any(|index| index >= table.len) && table.fetched_at.elapsed() >= REFETCH_INTERVAL
+                }
+                None => {
+                    cache.stats.misses += 1;
+                    cache.failed.get(&key).is_none_or(|at| at.elapsed() >= RETRY_INTERVAL)
+                }
+            };
+            if fetch && cache.in_flight.insert(key) {
+                let tables = Arc::clone(self);
+                tokio::spawn(async move { tables.fetch(key).await });
+            }
+        }
+        matched
+    }
+
+    pub fn stats(&self) -> LookupTableStats {
+        self.cache.lock().unwrap().stats
+    }
+
+    async fn fetch(&self, key: [u8; 32]) {
+        let result = match self.fetches.acquire().await {
+            Ok(_permit) => self.fetch_addresses(&key).await,
+            Err(e) => Err(e.into()),
+        };
+        let mut guard = self.cache.lock().unwrap();
+        let cache = &mut *guard;
+        cache.in_flight.remove(&key);
+        match result {
+            Ok(addresses) => {
+                let watched = addresses
+                    .iter()
+                    .enumerate()
+                    .filter(|(_, address)| self.watched.contains(*address))
+                    .map(|(position, address)| (position, bs58::encode(address).into_string()))
+                    .collect();
+                cache.failed.remove(&key);
+                cache.tables.insert(
+                    key,
+                    CachedTable {
+                        len: addresses.len(),
+                        watched,
+                        fetched_at: Instant::now(),
+                    },
+                );
+                cache.stats.fetched += 1;
+            }
+            Err(e) => {
+                log::debug!("Failed to fetch lookup table {}: {}", bs58::encode(key).into_string(), e);
+                cache.failed.insert(key, Instant::now());
+                cache.stats.failed += 1;
+            }
+        }
+    }
+
+    async fn fetch_addresses(&self, key: &[u8; 32]) -> Result<Vec<[u8; 32]>, Box<dyn Error + Send + Sync>> {
+        let table = bs58::encode(key).into_string();
+        let request = json!({
+            "jsonrpc": "2.0",
+            "id": 1,
+            "method": "getAccountInfo",
+            "params": [table, { "encoding": "base64", "commitment": "processed" }]
+        });
+        let response: Value = self
+            .client
+            .post(&self.rpc_url)
+            .timeout(Duration::from_secs(REQUEST_TIMEOUT_SECS))
+            .json(&request)
+            .send()
+            .await?
+            .error_for_status()?
+            .json()
+            .await?;
+        if let Some(error) = response.get("error") {
+            return Err(format!("getAccountInfo for {} failed: {}", table, error).into());
+        }
+        let data = response["result"]["value"]["data"][0]
+            .as_str()
+            .ok_or_else(|| format!("lookup table {} does not exist", table))?;
+        let data = STANDARD.decode(data)?;
+        if data.len() < LOOKUP_TABLE_META_SIZE {
+            return Err(format!("{} is not a lookup table ({} bytes)", table, data.len()).into());
+        }
+        Ok(data[LOOKUP_TABLE_META_SIZE..]
+            .chunks_exact(32)
+            .take(MAX_TABLE_ADDRESSES)
+            .filter_map(|address| <[u8; 32]>::try_from(address).ok())
+            .collect())
+    }
+}
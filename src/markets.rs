@@ -0,0 +1,101 @@
+use serde::{Deserialize, Serialize};
+use yellowstone_grpc_proto::prelude::{Message, TransactionStatusMeta};
+
+/// AMM programs whose swaps are tagged with the market they trade on.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AmmProgram {
+    RaydiumAmm,
+    RaydiumCpmm,
+    RaydiumClmm,
+    PumpFun,
+    PumpAmm,
+}
+
+impl AmmProgram {
+    pub fn name(self) -> &'static str {
+        match self {
+            AmmProgram::RaydiumAmm => "Raydium AMM",
+            AmmProgram::RaydiumCpmm => "Raydium CPMM",
+            AmmProgram::RaydiumClmm => "Raydium CLMM",
+            AmmProgram::PumpFun => "pump.fun",
+            AmmProgram::PumpAmm => "PumpSwap",
+        }
+    }
+}
+
+/// Market a transaction traded on: the pool, or the mint for pump.fun bonding curves.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct MarketTag {
+    pub program: AmmProgram,
+    pub market: String,
+}
+
+// Anchor discriminators (first 8 bytes of sha256("global:<instruction>"))
+const ANCHOR_BUY: [u8; 8] = [102, 6, 61, 18, 1, 218, 235, 234];
+const ANCHOR_SELL: [u8; 8] = [51, 230, 133, 164, 1, 127, 131, 173];
+const CPMM_SWAP_BASE_INPUT: [u8; 8] = [143, 190, 90, 218, 196, 30, 51, 222];
+const CPMM_SWAP_BASE_OUTPUT: [u8; 8] = [55, 217, 98, 86, 163, 74, 180, 173];
+const CLMM_SWAP: [u8; 8] = [248, 198, 158, 145, 225, 117, 135, 200];
+const CLMM_SWAP_V2: [u8; 8] = [43, 4, 237, 11, 26, 201, 30, 98];
+// Raydium AMM v4 uses a one-byte tag: 9 = SwapBaseIn, 11 = SwapBaseOut
+const RAYDIUM_AMM_SWAPS: [u8; 2] = [9, 11];
+
+/// Program id, then which instruction account holds the market for its swap instructions.
+struct KnownProgram {
+    program: AmmProgram,
+    id: Vec<u8>,
+    market_account: usize,
+}
+
+lazy_static::lazy_static! {
+    static ref PROGRAMS: Vec<KnownProgram> = [
+        (AmmProgram::RaydiumAmm, "675kPX9MHTjS2zt1qfr1NYHuzeLXfQM9H24wFSUt1Mp8", 1),
+        (AmmProgram::RaydiumCpmm, "CPMMoo8L3F4NbTegBCKVNunggL7H1ZpdTHKxQB5qKP1C", 3),
+        (AmmProgram::RaydiumClmm, "CAMMCzo5YL8w4VFF8KVHrK22GGUsp5VTaW7grrKgrWqK", 2),
+        (AmmProgram::PumpFun, "6EF8rrecthR5Dkzon8Nwu78hRvfCKubJ14M5uBEwF6P", 2),
+        (AmmProgram::PumpAmm, "pAMMBay6oceH9fJKBRHGP5D4bD4sWpmSwMn52FMfXEA", 0),
+    ]
+    .into_iter()
+    .map(|(program, id, market_account)| KnownProgram {
+        program,
+        id: bs58::decode(id).into_vec().expect("valid program id"),
+        market_account,
+    })
+    .collect();
+}
+
+fn is_swap(program: AmmProgram, data: &[u8]) -> bool {
+    match program {
+        AmmProgram::RaydiumAmm => data.first().is_some_and(|tag| RAYDIUM_AMM_SWAPS.contains(tag)),
+        AmmProgram::RaydiumCpmm => data.starts_with(&CPMM_SWAP_BASE_INPUT) || data.starts_with(&CPMM_SWAP_BASE_OUTPUT),
+        AmmProgram::RaydiumClmm => data.starts_with(&CLMM_SWAP) || data.starts_with(&CLMM_SWAP_V2),
+        AmmProgram::PumpFun | AmmProgram::PumpAmm => data.starts_with(&ANCHOR_BUY) || data.starts_with(&ANCHOR_SELL),
+    }
+}
+
+/// First known swap among the top-level instructions of a Yellowstone transaction.
+// AIDEV-NOTE: Only top-level instructions are decoded, so swaps routed through an aggregator
+// (a CPI from Jupiter etc.) stay untagged. Account indexes past the static keys point into the
+// address lookup tables, which the meta resolves as loaded writable then readonly addresses.
+pub fn decode_yellowstone(message: &Message, meta: Option<&TransactionStatusMeta>) -> Option<MarketTag> {
+    let mut keys: Vec<&[u8]> = message.account_keys.iter().map(Vec::as_slice).collect();
+    if let Some(meta) = meta {
+        keys.extend(meta.loaded_writable_addresses.iter().map(Vec::as_slice));
+        keys.extend(meta.loaded_readonly_addresses.iter().map(Vec::as_slice));
+    }
+
+    message.instructions.iter().find_map(|instruction| {
+        let program_id = keys.get(instruction.program_id_index as usize)?;
+        let known = PROGRAMS.iter().find(|known| known.id == *program_id)?;
+        if !is_swap(known.program, &instruction.data) {
+            return None;
+        }
+        let market_index = *instruction.accounts.get(known.market_account)?;
+        let market = keys.get(market_index as usize)?;
+        Some(MarketTag {
+            program: known.program,
+            market: bs58::encode(market).into_string(),
+        })
+    })
+}
@@ -0,0 +1,101 @@
+use std::fs;
+
+use serde::{Deserialize, Serialize};
+
+use crate::config::ConfigToml;
+
+const FNV_OFFSET_BASIS: u64 = 0xcbf2_9ce4_8422_2325;
+const FNV_PRIME: u64 = 0x0000_0100_0000_01b3;
+
+/// What produced a report: build, machine and configuration.
+#[derive(Debug, Clone, Default, PartialEq, Deserialize, Serialize)]
+pub struct RunMetadata {
+    pub geyserbench_version: String,
+    /// Short commit hash of the build; None outside a git checkout
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub git_rev: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub yellowstone_grpc_proto_version: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub hostname: Option<String>,
+    /// `std::env::consts::OS`, with the distribution name when known
+    pub os: String,
+    pub arch: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cpu_model: Option<String>,
+    pub cpu_count: usize,
+    /// Hash of the redacted config, so runs with identical settings share it
+    pub config_hash: String,
+}
+
+impl RunMetadata {
+    pub fn collect(config: &ConfigToml) -> Self {
+        Self {
+            geyserbench_version: env!("CARGO_PKG_VERSION").to_string(),
+            git_rev: option_env!("GEYSERBENCH_GIT_REV").map(str::to_string),
+            yellowstone_grpc_proto_version: option_env!("GEYSERBENCH_YELLOWSTONE_PROTO_VERSION")
+                .map(str::to_string),
+            hostname: hostname(),
+            os: os_name(),
+            arch: std::env::consts::ARCH.to_string(),
+            cpu_model: cpu_model(),
+            cpu_count: std::thread::available_parallelism().map_or(1, |count| count.get()),
+            config_hash: config_hash(config),
+        }
+    }
+
+    pub fn describe(&self) -> String {
+        format!(
+            "geyserbench {}{} (yellowstone-grpc-proto {}) on {} ({}, {}, {} x {}), config {}",
+            self.geyserbench_version,
+            self.git_rev.as_ref().map_or(String::new(), |rev| format!("@{}", rev)),
+            self.yellowstone_grpc_proto_version.as_deref().unwrap_or("unknown"),
+            self.hostname.as_deref().unwrap_or("unknown host"),
+            self.os,
+            self.arch,
+            self.cpu_count,
+            self.cpu_model.as_deref().unwrap_or("unknown CPU"),
+            self.config_hash
+        )
+    }
+}
+
+// AIDEV-NOTE: FNV-1a over the redacted TOML: stable across Rust releases (unlike DefaultHasher),
+// and tokens, headers and env-interpolated URLs do not change it
+fn config_hash(config: &ConfigToml) -> String {
+    let serialized = toml::to_string(&config.redacted()).unwrap_or_default();
+    let hash = serialized.bytes().fold(FNV_OFFSET_BASIS, |hash, byte| {
+        (hash ^ u64::from(byte)).wrapping_mul(FNV_PRIME)
+    });
+    format!("{:016x}", hash)
+}
+
+fn hostname() -> Option<String> {
+    std::env::var("HOSTNAME")
+        .ok()
+        .or_else(|| fs::read_to_string("/proc/sys/kernel/hostname").ok())
+        .or_else(|| std::env::var("COMPUTERNAME").ok())
+        .map(|name| name.trim().to_string())
+        .filter(|name| !name.is_empty())
+}
+
+fn os_name() -> String {
+    let os = std::env::consts::OS;
+    let pretty_name = fs::read_to_string("/etc/os-release").ok().and_then(|release| {
+        release.lines().find_map(|line| {
+            line.strip_prefix("PRETTY_NAME=").map(|name| name.trim_matches('"').to_string())
+        })
+    });
+    match pretty_name {
+        Some(name) => format!("{} ({})", os, name),
+        None => os.to_string(),
+    }
+}
+
+fn cpu_model() -> Option<String> {
+    let cpuinfo = fs::read_to_string("/proc/cpuinfo").ok()?;
+    cpuinfo.lines().find_map(|line| {
+        let (key, value) = line.split_once(':')?;
+        (key.trim() == "model name").then(|| value.trim().to_string())
+    })
+}
@@ -0,0 +1,92 @@
+use std::{error::Error, time::Duration};
+
+use serde_json::json;
+
+use crate::{
+    config::{BenchmarkMode, NotifyConfig, WebhookKind},
+    report::BenchmarkReport,
+};
+
+const REQUEST_TIMEOUT_SECS: u64 = 10;
+
+/// Posts a compact run summary to the configured chat webhook.
+pub async fn send(config: &NotifyConfig, report: &BenchmarkReport) -> Result<(), Box<dyn Error + Send + Sync>> {
+    send_text(config, &summary(report)).await
+}
+
+/// Posts `text` as is to the configured chat webhook.
+pub async fn send_text(config: &NotifyConfig, text: &str) -> Result<(), Box<dyn Error + Send + Sync>> {
+    let body = match config.kind {
+        WebhookKind::Discord => json!({ "content": text }),
+        WebhookKind::Slack => json!({ "text": text }),
+        WebhookKind::Telegram => json!({ "chat_id": config.chat_id, "text": text }),
+    };
+
+    reqwest::Client::new()
+        .post(&config.webhook_url)
+        .timeout(Duration::from_secs(REQUEST_TIMEOUT_SECS))
+        .json(&body)
+        .send()
+        .await?
+        .error_for_status()?;
+    Ok(())
+}
+
+// AIDEV-NOTE: Plain text only; the three chat formats disagree on markdown flavours
+pub fn summary(report: &BenchmarkReport) -> String {
+    let mut lines = vec![format!(
+        "geyserbench {:?} run finished after {:.0}s",
+        report.mode,
+        report.end_time - report.start_time
+    )];
+    if let Some(reason) = &report.stop_reason {
+        lines.push(format!("Stopped early: {}", reason));
+    }
+
+    if report.mode == BenchmarkMode::Throughput {
+        for endpoint in &report.throughput {
+            lines.push(format!(
+                "{}: {:.0} msg/s sustained, {:.0} max ({})",
+                endpoint.name, endpoint.sustained_rate, endpoint.max_rate, endpoint.bottleneck
+            ));
+        }
+        return lines.join("\n");
+    }
+
+    match &report.fastest_endpoint {
+        Some(fastest) => {
+            let win_rate = report
+                .endpoints
+                .iter()
+                .find(|endpoint| &endpoint.name == fastest)
+                .map_or(0.0, |endpoint| endpoint.win_rate);
+            lines.push(format!("Winner: {} ({:.2}% first)", fastest, win_rate));
+        }
+        None => lines.push("Winner: none".to_string()),
+    }
+    for pair in &report.pairs {
+        lines.push(format!(
+            "{} vs {}: median {:+.2} ms ({} ahead in {}/{})",
+            pair.first, pair.second, pair.median_delta_ms, pair.first, pair.first_wins, pair.signatures
+        ));
+    }
+
+    let breaches: Vec<String> = report
+        .endpoints
+        .iter()
+        .flat_map(|endpoint| {
+            endpoint
+                .slo
+                .iter()
+                .filter(|result| !result.pass)
+                .map(move |result| format!("{} {}", endpoint.name, result.label()))
+        })
+        .collect();
+    if !breaches.is_empty() {
+        lines.push(format!("SLO breached: {}", breaches.join(", ")));
+    }
+    if !report.alerts.is_empty() {
+        lines.push(format!("Latency budget violated in {} endpoint windows", report.alerts.len()));
+    }
+    lines.join("\n")
+}
@@ -0,0 +1,252 @@
+//! Raw observation export to Parquet, one row per accepted sighting, for querying large runs with
+//! DuckDB, Polars or pandas.
+//!
+//! Only compiled in with the `parquet` feature; without it [`open`] warns and returns None.
+//!
+//! Schema:
+//! - `endpoint` (utf8)
+//! - `signature` (utf8): transaction signature, or the slot key in entries and blocks modes
+//! - `received_at` (timestamp, microseconds, UTC)
+//! - `delay_ms` (float64): behind the earliest sighting of the signature at insert time; 0 when first
+//! - `first` (bool)
+//! - `slot` (uint64, nullable)
+//! - `tsc` (uint64, nullable): raw tick counter with `tsc_timestamps`
+//! - `failed` (bool, nullable): execution result, for streams carrying transaction meta
+//! - `entry_index`, `entry_transaction_index` (uint32, nullable): entry within its batch and
+//!   transaction within the entry, for shredstream endpoints
+//! - `num_hashes` (uint64, nullable): PoH hashes before the transaction's entry
+//! - `ticks_before` (uint32, nullable): tick entries the endpoint delivered for the slot before it
+
+use std::error::Error;
+
+use crate::config::ParquetConfig;
+
+#[cfg(feature = "parquet")]
+mod writer {
+    use std::{
+        error::Error,
+        fs::File,
+        sync::{
+            atomic::{AtomicU64, Ordering},
+            mpsc::{self, Receiver, SyncSender},
+            Arc, Mutex,
+        },
+        thread::{self, JoinHandle},
+    };
+
+    use arrow_array::{
+        ArrayRef, BooleanArray, Float64Array, RecordBatch, StringArray, TimestampMicrosecondArray, UInt32Array, UInt64Array,
+    };
+    use arrow_schema::{DataType, Field, Schema, SchemaRef, TimeUnit};
+    use parquet::{arrow::ArrowWriter, basic::Compression, file::properties::WriterProperties};
+
+    use crate::{config::ParquetConfig, utils::TransactionData};
+
+    /// Full row groups queued for the writer thread before new ones are dropped
+    const QUEUE_ROW_GROUPS: usize = 8;
+
+    #[derive(Debug, Default)]
+    struct Rows {
+        endpoint: Vec<String>,
+        signature: Vec<String>,
+        received_at: Vec<i64>,
+        delay_ms: Vec<f64>,
+        first: Vec<bool>,
+        slot: Vec<Option<u64>>,
+        tsc: Vec<Option<u64>>,
+        failed: Vec<Option<bool>>,
+        entry_index: Vec<Option<u32>>,
+        entry_transaction_index: Vec<Option<u32>>,
+        num_hashes: Vec<Option<u64>>,
+        ticks_before: Vec<Option<u32>>,
+    }
+
+    impl Rows {
+        fn len(&self) -> usize {
+            self.endpoint.len()
+        }
+    }
+
+    // AIDEV-NOTE: Rows are buffered up to `row_group_size` and handed to a dedicated writer thread,
+    // which encodes and writes each row group, so the Comparator lock is only held for the push.
+    // The queue holds QUEUE_ROW_GROUPS groups; a full queue drops the group and counts its rows
+    // rather than blocking the stream tasks. The footer is only written by `finish`, and a file from
+    // a killed run is unreadable.
+    /// Parquet writer fed by the Comparator with every accepted sighting.
+    pub struct ParquetSink {
+        rows: Mutex<Rows>,
+        row_group_size: usize,
+        path: String,
+        tx: Mutex<Option<SyncSender<Rows>>>,
+        thread: Mutex<Option<JoinHandle<()>>>,
+        dropped: AtomicU64,
+    }
+
+    impl std::fmt::Debug for ParquetSink {
+        fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+            f.debug_struct("ParquetSink").field("path", &self.path).finish()
+        }
+    }
+
+    impl ParquetSink {
+        pub fn open(config: &ParquetConfig) -> Result<Self, Box<dyn Error + Send + Sync>> {
+            let schema: SchemaRef = Arc::new(Schema::new(vec![
+                Field::new("endpoint", DataType::Utf8, false),
+                Field::new("signature", DataType::Utf8, false),
+                Field::new("received_at", DataType::Timestamp(TimeUnit::Microsecond, Some("UTC".into())), false),
+                Field::new("delay_ms", DataType::Float64, false),
+                Field::new("first", DataType::Boolean, false),
+                Field::new("slot", DataType::UInt64, true),
+                Field::new("tsc", DataType::UInt64, true),
+                Field::new("failed", DataType::Boolean, true),
+                Field::new("entry_index", DataType::UInt32, true),
+                Field::new("entry_transaction_index", DataType::UInt32, true),
+                Field::new("num_hashes", DataType::UInt64, true),
+                Field::new("ticks_before", DataType::UInt32, true),
+            ]));
+            let properties = WriterProperties::builder()
+                .set_compression(Compression::SNAPPY)
+                .set_max_row_group_size(config.row_group_size.max(1))
+                .build();
+            let writer = ArrowWriter::try_new(File::create(&config.path)?, schema.clone(), Some(properties))?;
+            let (tx, rx) = mpsc::sync_channel(QUEUE_ROW_GROUPS);
+            let path = config.path.clone();
+            let thread = thread::Builder::new()
+                .name("parquet-writer".to_string())
+                .spawn(move || write_row_groups(rx, writer, schema, path))?;
+            Ok(Self {
+                rows: Mutex::new(Rows::default()),
+                row_group_size: config.row_group_size.max(1),
+                path: config.path.clone(),
+                tx: Mutex::new(Some(tx)),
+                thread: Mutex::new(Some(thread)),
+                dropped: AtomicU64::new(0),
+            })
+        }
+
+        pub fn record_observation(&self, endpoint: &str, data: &TransactionData, delay_ms: f64) {
+            let full = {
+                let mut rows = self.rows.lock().unwrap();
+                rows.endpoint.push(endpoint.to_string());
+                rows.signature.push(data.signature.clone());
+                rows.received_at.push((data.timestamp * 1_000_000.0) as i64);
+                rows.delay_ms.push(delay_ms.max(0.0));
+                rows.first.push(delay_ms <= 0.0);
+                rows.slot.push(data.slot);
+                rows.tsc.push(data.tsc);
+                rows.failed.push(data.status.as_ref().map(|status| status.failed));
+                rows.entry_index.push(data.entry.map(|entry| entry.index));
+                rows.entry_transaction_index.push(data.entry.map(|entry| entry.transaction_index));
+                rows.num_hashes.push(data.entry.map(|entry| entry.num_hashes));
+                rows.ticks_before.push(data.entry.map(|entry| entry.ticks_before));
+                (rows.len() >= self.row_group_size).then(|| std::mem::take(&mut *rows))
+            };
+            if let Some(rows) = full {
+                self.queue(rows);
+            }
+        }
+
+        fn queue(&self, rows: Rows) {
+            if rows.endpoint.is_empty() {
+                return;
+            }
+            let len = rows.len() as u64;
+            let sent = match &*self.tx.lock().unwrap() {
+                Some(tx) => tx.try_send(rows).is_ok(),
+                None => false,
+            };
+            if !sent {
+                self.dropped.fetch_add(len, Ordering::Relaxed);
+            }
+        }
+
+        /// Writes the buffered rows and the file footer, waiting for the writer thread; later
+        /// observations are dropped.
+        pub fn finish(&self) {
+            let rows = std::mem::take(&mut *self.rows.lock().unwrap());
+            let Some(tx) = self.tx.lock().unwrap().take() else {
+                return;
+            };
+            // The last group waits for room rather than being dropped
+            if !rows.endpoint.is_empty() {
+                let len = rows.len() as u64;
+                if tx.send(rows).is_err() {
+                    self.dropped.fetch_add(len, Ordering::Relaxed);
+                }
+            }
+            drop(tx);
+            if let Some(thread) = self.thread.lock().unwrap().take() {
+                if thread.join().is_err() {
+                    log::error!("Parquet writer for {} panicked", self.path);
+                }
+            }
+            let dropped = self.dropped.load(Ordering::Relaxed);
+            if dropped > 0 {
+                log::warn!("Dropped {} observations from {}: the writer fell behind", dropped, self.path);
+            }
+        }
+    }
+
+    fn write_row_groups(rx: Receiver<Rows>, mut writer: ArrowWriter<File>, schema: SchemaRef, path: String) {
+        for rows in rx {
+            let columns: Vec<ArrayRef> = vec![
+                Arc::new(StringArray::from(rows.endpoint)),
+                Arc::new(StringArray::from(rows.signature)),
+                Arc::new(TimestampMicrosecondArray::from(rows.received_at).with_timezone("UTC")),
+                Arc::new(Float64Array::from(rows.delay_ms)),
+                Arc::new(BooleanArray::from(rows.first)),
+                Arc::new(UInt64Array::from(rows.slot)),
+                Arc::new(UInt64Array::from(rows.tsc)),
+                Arc::new(BooleanArray::from(rows.failed)),
+                Arc::new(UInt32Array::from(rows.entry_index)),
+                Arc::new(UInt32Array::from(rows.entry_transaction_index)),
+                Arc::new(UInt64Array::from(rows.num_hashes)),
+                Arc::new(UInt32Array::from(rows.ticks_before)),
+            ];
+            let result = RecordBatch::try_new(schema.clone(), columns)
+                .map_err(|e| e.to_string())
+                .and_then(|batch| writer.write(&batch).map_err(|e| e.to_string()));
+            if let Err(e) = result {
+                log::error!("Failed to write observations to {}: {}", path, e);
+            }
+        }
+        match writer.close() {
+            Ok(metadata) => log::info!("Wrote {} observations to {}", metadata.num_rows, path),
+            Err(e) => log::error!("Failed to finish {}: {}", path, e),
+        }
+    }
+}
+
+#[cfg(feature = "parquet")]
+pub use writer::ParquetSink;
+
+/// Opens the export file when `parquet` is configured; warns and returns None when built without it.
+pub fn open(config: Option<&ParquetConfig>) -> Result<Option<ParquetSink>, Box<dyn Error + Send + Sync>> {
+    let Some(config) = config else {
+        return Ok(None);
+    };
+    #[cfg(feature = "parquet")]
+    {
+        log::info!("Writing observations to {}", config.path);
+        Ok(Some(ParquetSink::open(config)?))
+    }
+    #[cfg(not(feature = "parquet"))]
+    {
+        log::warn!(
+            "parquet is configured for {} but geyserbench was built without the `parquet` feature",
+            config.path
+        );
+        Ok(None)
+    }
+}
+
+#[cfg(not(feature = "parquet"))]
+#[derive(Debug)]
+pub struct ParquetSink;
+
+#[cfg(not(feature = "parquet"))]
+impl ParquetSink {
+    pub fn record_observation(&self, _endpoint: &str, _data: &crate::utils::TransactionData, _delay_ms: f64) {}
+
+    pub fn finish(&self) {}
+}
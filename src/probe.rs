@@ -0,0 +1,225 @@
+use std::{
+    collections::{BTreeMap, HashMap, HashSet},
+    error::Error,
+    time::{Duration, Instant},
+};
+
+use futures::{channel::mpsc, SinkExt};
+use futures_util::StreamExt;
+use yellowstone_grpc_proto::{
+    geyser::{
+        subscribe_update::UpdateOneof, CommitmentLevel, PingRequest, SubscribeRequest,
+        SubscribeRequestFilterSlots, SubscribeRequestPing,
+    },
+    prelude::SubscribeRequestFilterTransactions,
+};
+
+use crate::{
+    config::{ArgsCommitment, Endpoint, EndpointKind},
+    providers::connect_yellowstone,
+};
+
+/// Upper bound on the streaming phase so a probe always finishes in under a minute.
+pub const MAX_PROBE_SECS: u64 = 45;
+const RTT_SAMPLES: i32 = 5;
+
+#[derive(Debug, Default)]
+pub struct ProbeResult {
+    pub connect_ms: f64,
+    pub rtt_ms: Vec<f64>,
+    pub first_message_ms: Option<f64>,
+    pub first_transaction_ms: Option<f64>,
+    pub messages: u64,
+    pub transactions: u64,
+    pub slot_updates: u64,
+    pub highest_slot: Option<u64>,
+    pub max_gap_ms: f64,
+    pub duration_secs: f64,
+    /// Why the stream stopped before the probe duration, if it did
+    pub stream_error: Option<String>,
+}
+
+impl ProbeResult {
+    pub fn healthy(&self) -> bool {
+        self.stream_error.is_none() && self.messages > 0
+    }
+}
+
+/// Yellowstone endpoint for a one-off probe outside of a config file.
+pub fn endpoint(url: &str, token: &str) -> Endpoint {
+    Endpoint {
+        name: "probe".to_string(),
+        url: url.to_string(),
+        x_token: token.to_string(),
+        kind: EndpointKind::Yellowstone,
+        frame_timing: false,
+        keepalive_interval_secs: None,
+        keepalive_timeout_secs: None,
+        ping_interval_secs: None,
+        max_decoding_message_size: None,
+        initial_connection_window_size: None,
+        initial_stream_window_size: None,
+        tcp_nodelay: None,
+        core: None,
+        start_delay_secs: 0,
+        shards: 1,
+        connections: 1,
+        tags: Vec::new(),
+        headers: BTreeMap::new(),
+        alerts: None,
+        handicap_ms: None,
+        token_refresh: None,
+        proxy: None,
+        accounts_data_slice: None,
+        subscribe: None,
+        raw_url: None,
+    }
+}
+
+// AIDEV-NOTE: RTT comes from the unary Ping RPC, so it measures the request path without any
+// stream buffering; slot updates are subscribed alongside the account filter so a quiet account
+// still shows whether the stream itself is healthy.
+pub async fn probe(
+    endpoint: &Endpoint,
+    accounts: Vec<String>,
+    commitment: ArgsCommitment,
+    duration: Duration,
+) -> Result<ProbeResult, Box<dyn Error + Send + Sync>> {
+    let duration = duration.min(Duration::from_secs(MAX_PROBE_SECS));
+    let mut result = ProbeResult::default();
+
+    let connect_started = Instant::now();
+    let mut client = connect_yellowstone(endpoint).await?;
+    result.connect_ms = connect_started.elapsed().as_secs_f64() * 1000.0;
+
+    for count in 0..RTT_SAMPLES {
+        let sent = Instant::now();
+        client.ping(PingRequest { count }).await?;
+        result.rtt_ms.push(sent.elapsed().as_secs_f64() * 1000.0);
+    }
+
+    let (mut subscribe_tx, subscribe_rx) = mpsc::unbounded();
+    let mut stream = client.subscribe(subscribe_rx).await?.into_inner();
+    let commitment: CommitmentLevel = commitment.into();
+    let watched: HashSet<String> = accounts.iter().cloned().collect();
+    let subscribed_at = Instant::now();
+    subscribe_tx
+        .send(SubscribeRequest {
+            slots: HashMap::from([("slots".to_string(), SubscribeRequestFilterSlots::default())]),
+            transactions: HashMap::from([(
+                "account".to_string(),
+                SubscribeRequestFilterTransactions {
+                    account_include: accounts,
+                    ..Default::default()
+                },
+            )]),
+            commitment: Some(commitment as i32),
+            ..Default::default()
+        })
+        .await?;
+
+    let deadline = tokio::time::sleep(duration);
+    tokio::pin!(deadline);
+    let mut last_message = subscribed_at;
+    loop {
+        tokio::select! {
+            _ = &mut deadline => break,
+            message = stream.next() => {
+                let msg = match message {
+                    Some(Ok(msg)) => msg,
+                    Some(Err(e)) => {
+                        result.stream_error = Some(e.to_string());
+                        break;
+                    }
+                    None => {
+                        result.stream_error = Some("stream closed by server".to_string());
+                        break;
+                    }
+                };
+                let now = Instant::now();
+                match msg.update_oneof {
+                    Some(UpdateOneof::Ping(_)) => {
+                        subscribe_tx
+                            .send(SubscribeRequest {
+                                ping: Some(SubscribeRequestPing { id: 1 }),
+                                ..Default::default()
+                            })
+                            .await?;
+                        continue;
+                    }
+                    Some(UpdateOneof::Pong(_)) => continue,
+                    Some(UpdateOneof::Slot(slot)) => {
+                        result.slot_updates += 1;
+                        result.highest_slot = result.highest_slot.max(Some(slot.slot));
+                    }
+                    Some(UpdateOneof::Transaction(tx_msg)) => {
+                        let matched = tx_msg
+                            .transaction
+                            .as_ref()
+                            .and_then(|tx| tx.transaction.as_ref())
+                            .and_then(|tx| tx.message.as_ref())
+                            .is_some_and(|message| {
+                                message
+                                    .account_keys
+                                    .iter()
+                                    .any(|key| watched.contains(&bs58::encode(key).into_string()))
+                            });
+                        if matched {
+                            result.transactions += 1;
+                            result.first_transaction_ms.get_or_insert(
+                                (now - subscribed_at).as_secs_f64() * 1000.0,
+                            );
+                        }
+                    }
+                    _ => {}
+                }
+                result.messages += 1;
+                result
+                    .first_message_ms
+                    .get_or_insert((now - subscribed_at).as_secs_f64() * 1000.0);
+                result.max_gap_ms = result.max_gap_ms.max((now - last_message).as_secs_f64() * 1000.0);
+                last_message = now;
+            }
+        }
+    }
+    result.duration_secs = subscribed_at.elapsed().as_secs_f64();
+    Ok(result)
+}
+
+pub fn print_probe(url: &str, result: &ProbeResult) {
+    let fmt = |ms: Option<f64>| ms.map_or("-".to_string(), |ms| format!("{:.2} ms", ms));
+    let mut rtt = result.rtt_ms.clone();
+    rtt.sort_by(|a, b| a.partial_cmp(b).unwrap());
+
+    println!("\nProbe: {}", url);
+    println!("--------------------------------------------");
+    println!("Connect: {:.2} ms", result.connect_ms);
+    if !rtt.is_empty() {
+        println!(
+            "RTT (ping, {} samples): min {:.2} ms, median {:.2} ms, max {:.2} ms",
+            rtt.len(),
+            rtt[0],
+            rtt[rtt.len() / 2],
+            rtt[rtt.len() - 1]
+        );
+    }
+    println!("Time to first message: {}", fmt(result.first_message_ms));
+    println!("Time to first matching transaction: {}", fmt(result.first_transaction_ms));
+    println!(
+        "Stream: {} messages in {:.1}s ({:.1} msg/s), {} matching transactions, {} slot updates",
+        result.messages,
+        result.duration_secs,
+        result.messages as f64 / result.duration_secs.max(f64::EPSILON),
+        result.transactions,
+        result.slot_updates
+    );
+    if let Some(slot) = result.highest_slot {
+        println!("Highest slot: {}", slot);
+    }
+    println!("Longest gap between messages: {:.2} ms", result.max_gap_ms);
+    match &result.stream_error {
+        Some(error) => println!("Health: FAILED ({})", error),
+        None if !result.healthy() => println!("Health: FAILED (no messages received)"),
+        None => println!("Health: OK"),
+    }
+}
@@ -0,0 +1,329 @@
+use std::{
+    alloc::{GlobalAlloc, Layout, System},
+    fs,
+    future::Future,
+    pin::Pin,
+    sync::{
+        atomic::{AtomicBool, AtomicU64, Ordering},
+        Arc, Mutex,
+    },
+    task::{Context, Poll},
+    time::{Duration, Instant},
+};
+
+use serde::{Deserialize, Serialize};
+
+use crate::utils::Comparator;
+
+// Linux reports /proc CPU times in USER_HZ ticks, fixed at 100 for userspace on every mainstream arch
+const USER_HZ: f64 = 100.0;
+// Above this share of the wall clock a task (or the process, per core) is likely the bottleneck
+const SATURATION_SHARE: f64 = 0.8;
+
+static COUNTING: AtomicBool = AtomicBool::new(false);
+static HEAP_ALLOCATIONS: AtomicU64 = AtomicU64::new(0);
+static HEAP_CURRENT: AtomicU64 = AtomicU64::new(0);
+static HEAP_PEAK: AtomicU64 = AtomicU64::new(0);
+
+// AIDEV-NOTE: Installed as the global allocator by the binary only; embedders keep their own,
+// in which case the counters stay at zero and the report omits `heap`. Counting is switched on
+// only for the length of a profiled run: outside it every call costs one relaxed load, so runs
+// without `self_profile` do not pay contended atomics on the providers' decode path.
+/// System allocator that counts allocations and live heap bytes while a profiled run is going.
+pub struct CountingAllocator;
+
+unsafe impl GlobalAlloc for CountingAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        let ptr = System.alloc(layout);
+        if !ptr.is_null() {
+            record_alloc(layout.size());
+        }
+        ptr
+    }
+
+    unsafe fn alloc_zeroed(&self, layout: Layout) -> *mut u8 {
+        let ptr = System.alloc_zeroed(layout);
+        if !ptr.is_null() {
+            record_alloc(layout.size());
+        }
+        ptr
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout);
+        record_free(layout.size());
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        let new_ptr = System.realloc(ptr, layout, new_size);
+        if !new_ptr.is_null() {
+            record_free(layout.size());
+            record_alloc(new_size);
+        }
+        new_ptr
+    }
+}
+
+/// Switches counting in [`CountingAllocator`] on or off outside a profiled run, e.g. around a
+/// benchmark loop.
+pub fn count_allocations(enabled: bool) {
+    COUNTING.store(enabled, Ordering::Relaxed);
+}
+
+/// Allocations counted by [`CountingAllocator`] since the process started.
+pub fn allocation_count() -> u64 {
+    HEAP_ALLOCATIONS.load(Ordering::Relaxed)
+}
+
+fn record_alloc(size: usize) {
+    if !COUNTING.load(Ordering::Relaxed) {
+        return;
+    }
+    HEAP_ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+    let current = HEAP_CURRENT.fetch_add(size as u64, Ordering::Relaxed) + size as u64;
+    HEAP_PEAK.fetch_max(current, Ordering::Relaxed);
+}
+
+// Blocks allocated before counting started are freed while it runs, so live bytes saturate at zero
+fn record_free(size: usize) {
+    if !COUNTING.load(Ordering::Relaxed) {
+        return;
+    }
+    let _ = HEAP_CURRENT.fetch_update(Ordering::Relaxed, Ordering::Relaxed, |current| {
+        Some(current.saturating_sub(size as u64))
+    });
+}
+
+/// Heap usage seen by [`CountingAllocator`].
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct HeapStats {
+    /// Allocations during the run
+    pub allocations: u64,
+    /// Live heap allocated since counting started
+    pub current_bytes: u64,
+    /// Highest live heap since counting started
+    pub peak_bytes: u64,
+}
+
+/// Time one provider task spent running on the runtime, and what it handled.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct TaskProfile {
+    /// Wall time inside `poll`; close to the CPU time of a task that never blocks a thread
+    pub busy_secs: f64,
+    pub polls: u64,
+    /// Stream updates read, keep-alives excluded
+    pub messages: u64,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct TaskProfileReport {
+    pub name: String,
+    pub busy_secs: f64,
+    /// `busy_secs` over the run's wall time; near 1 the task cannot keep up
+    pub busy_share: f64,
+    pub polls: u64,
+    pub messages: u64,
+    pub messages_per_sec: f64,
+}
+
+/// Resource use of geyserbench itself over the run, with `self_profile`.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct SelfProfileReport {
+    pub wall_secs: f64,
+    /// User plus system CPU time of the whole process; None off Linux
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub process_cpu_secs: Option<f64>,
+    pub cores: usize,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub peak_rss_bytes: Option<u64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub heap: Option<HeapStats>,
+    pub tasks: Vec<TaskProfileReport>,
+}
+
+impl SelfProfileReport {
+    /// Process CPU time as a share of all cores over the run.
+    pub fn cpu_share(&self) -> Option<f64> {
+        let cpu = self.process_cpu_secs?;
+        (self.wall_secs > 0.0).then(|| cpu / (self.wall_secs * self.cores.max(1) as f64))
+    }
+
+    pub fn saturated(&self) -> bool {
+        self.cpu_share().is_some_and(|share| share >= SATURATION_SHARE)
+            || self.tasks.iter().any(|task| task.busy_share >= SATURATION_SHARE)
+    }
+}
+
+/// Process counters at the start of a profiled run.
+#[derive(Debug, Clone, Copy)]
+pub struct ProfileStart {
+    at: Instant,
+    cpu_secs: Option<f64>,
+    allocations: u64,
+}
+
+impl ProfileStart {
+    /// Also switches on allocation counting until [`ProfileStart::finish`], with the live and peak
+    /// heap measured afresh for this run.
+    pub fn take() -> Self {
+        HEAP_CURRENT.store(0, Ordering::Relaxed);
+        HEAP_PEAK.store(0, Ordering::Relaxed);
+        COUNTING.store(true, Ordering::Relaxed);
+        Self {
+            at: Instant::now(),
+            cpu_secs: process_cpu_secs(),
+            allocations: HEAP_ALLOCATIONS.load(Ordering::Relaxed),
+        }
+    }
+
+    pub fn finish(self, comparator: &Comparator, endpoint_names: &[String]) -> SelfProfileReport {
+        let wall_secs = self.at.elapsed().as_secs_f64();
+        COUNTING.store(false, Ordering::Relaxed);
+        let allocations = HEAP_ALLOCATIONS.load(Ordering::Relaxed);
+        let heap = (allocations > 0).then(|| HeapStats {
+            allocations: allocations - self.allocations,
+            current_bytes: HEAP_CURRENT.load(Ordering::Relaxed),
+            peak_bytes: HEAP_PEAK.load(Ordering::Relaxed),
+        });
+        let tasks = endpoint_names
+            .iter()
+            .filter_map(|name| {
+                let profile = comparator.endpoint_metrics.get(name)?.profile.as_ref()?;
+                Some(TaskProfileReport {
+                    name: name.clone(),
+                    busy_secs: profile.busy_secs,
+                    busy_share: if wall_secs > 0.0 { profile.busy_secs / wall_secs } else { 0.0 },
+                    polls: profile.polls,
+                    messages: profile.messages,
+                    messages_per_sec: if wall_secs > 0.0 { profile.messages as f64 / wall_secs } else { 0.0 },
+                })
+            })
+            .collect();
+        SelfProfileReport {
+            wall_secs,
+            process_cpu_secs: process_cpu_secs()
+                .zip(self.cpu_secs)
+                .map(|(end, start)| end - start),
+            cores: std::thread::available_parallelism().map_or(1, |cores| cores.get()),
+            peak_rss_bytes: peak_rss_bytes(),
+            heap,
+            tasks,
+        }
+    }
+}
+
+pub fn print_self_profile(report: &SelfProfileReport) {
+    println!("\nSelf profile");
+    println!("--------------------------------------------");
+    match (report.process_cpu_secs, report.cpu_share()) {
+        (Some(cpu), Some(share)) => println!(
+            "Process: {:.2}s CPU over {:.2}s ({:.1}% of {} cores)",
+            cpu,
+            report.wall_secs,
+            share * 100.0,
+            report.cores
+        ),
+        _ => println!("Process: {:.2}s wall, CPU time unavailable on this platform", report.wall_secs),
+    }
+    if let Some(rss) = report.peak_rss_bytes {
+        println!("Peak resident memory: {:.1} MiB", rss as f64 / (1024.0 * 1024.0));
+    }
+    if let Some(heap) = &report.heap {
+        println!(
+            "Heap: {} allocations, {:.1} MiB live at the end, {:.1} MiB peak",
+            heap.allocations,
+            heap.current_bytes as f64 / (1024.0 * 1024.0),
+            heap.peak_bytes as f64 / (1024.0 * 1024.0)
+        );
+    }
+    for task in &report.tasks {
+        println!(
+            "{}: busy {:.2}s ({:.1}% of the run), {} polls, {} messages ({:.1}/s)",
+            task.name,
+            task.busy_secs,
+            task.busy_share * 100.0,
+            task.polls,
+            task.messages,
+            task.messages_per_sec
+        );
+    }
+    if report.saturated() {
+        println!("geyserbench itself is close to saturation; latencies may be client-bound");
+    }
+}
+
+/// Adds a stream's message count to the endpoint's task profile.
+pub fn record_messages(comparator: &Mutex<Comparator>, endpoint_name: &str, messages: u64) {
+    let mut comparator = comparator.lock().unwrap();
+    let metrics = comparator.endpoint_metrics.entry(endpoint_name.to_string()).or_default();
+    metrics.profile.get_or_insert_with(TaskProfile::default).messages += messages;
+}
+
+// AIDEV-NOTE: Busy time is measured around each poll of the provider's future, so it covers the
+// task's own work (decoding, filtering, comparator inserts) and none of its waiting. Shard and
+// connection subtasks are wrapped too and merge into the endpoint's profile.
+/// Future wrapper adding its poll time to the endpoint's [`TaskProfile`] when it completes or is dropped.
+pub struct Profiled<T> {
+    inner: Pin<Box<dyn Future<Output = T> + Send>>,
+    name: String,
+    comparator: Arc<Mutex<Comparator>>,
+    busy: Duration,
+    polls: u64,
+}
+
+impl<T> Profiled<T> {
+    pub fn new(
+        name: String,
+        comparator: Arc<Mutex<Comparator>>,
+        future: impl Future<Output = T> + Send + 'static,
+    ) -> Self {
+        Self {
+            inner: Box::pin(future),
+            name,
+            comparator,
+            busy: Duration::ZERO,
+            polls: 0,
+        }
+    }
+}
+
+impl<T> Future for Profiled<T> {
+    type Output = T;
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<T> {
+        let started = Instant::now();
+        let result = self.inner.as_mut().poll(cx);
+        self.busy += started.elapsed();
+        self.polls += 1;
+        result
+    }
+}
+
+impl<T> Drop for Profiled<T> {
+    fn drop(&mut self) {
+        // A poisoned lock means a provider panicked; its profile is not worth a second panic
+        if let Ok(mut comparator) = self.comparator.lock() {
+            let metrics = comparator.endpoint_metrics.entry(self.name.clone()).or_default();
+            let profile = metrics.profile.get_or_insert_with(TaskProfile::default);
+            profile.busy_secs += self.busy.as_secs_f64();
+            profile.polls += self.polls;
+        }
+    }
+}
+
+fn process_cpu_secs() -> Option<f64> {
+    let stat = fs::read_to_string("/proc/self/stat").ok()?;
+    // The command name may contain spaces; fields resume after its closing parenthesis
+    let fields: Vec<&str> = stat.rsplit_once(')')?.1.split_whitespace().collect();
+    // utime and stime are fields 14 and 15 of the full line, 12 and 13 after the name
+    let utime: f64 = fields.get(11)?.parse().ok()?;
+    let stime: f64 = fields.get(12)?.parse().ok()?;
+    Some((utime + stime) / USER_HZ)
+}
+
+fn peak_rss_bytes() -> Option<u64> {
+    let status = fs::read_to_string("/proc/self/status").ok()?;
+    let line = status.lines().find(|line| line.starts_with("VmHWM:"))?;
+    let kib: u64 = line.split_whitespace().nth(1)?.parse().ok()?;
+    Some(kib * 1024)
+}
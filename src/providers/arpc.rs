@@ -4,18 +4,26 @@ use std::{
     sync::{Arc, Mutex},
 };
 
-use futures_util::{stream::StreamExt, sink::SinkExt};
-use tokio::{sync::broadcast, task};
+use futures_util::stream::StreamExt;
+use tokio::sync::broadcast;
 use tokio_stream::Stream;
 
 use crate::{
     config::{Config, Endpoint},
-    utils::{Comparator, TransactionData, get_current_timestamp, open_log_file, write_log_entry},
+    lookup_tables::TableLookup,
+    utils::{Comparator, TransactionData, write_log_entry},
+    telemetry::PipelineTrace,
+    tsc,
 };
 
-use super::GeyserProvider;
+use super::{
+    connect_channel, start_when_ready, GeyserProvider, ConsumerDelay, ProviderError, ProviderStart, Drain, Ready, StartupTimer,
+    StreamMonitor,
+};
 
 pub mod arpc {
+    #![allow(dead_code)]
+    #![allow(clippy::module_inception)]
     #![allow(clippy::clone_on_ref_ptr)]
     #![allow(clippy::missing_const_for_fn)]
 
@@ -33,7 +41,7 @@ use arpc::{
 pub struct ArpcProvider;
 
 impl GeyserProvider for ArpcProvider {
-    fn process(
+    fn start(
         &self,
         endpoint: Endpoint,
         config: Config,
@@ -41,8 +49,8 @@ impl GeyserProvider for ArpcProvider {
         shutdown_rx: broadcast::Receiver<()>,
         start_time: f64,
         comparator: Arc<Mutex<Comparator>>,
-    ) -> task::JoinHandle<Result<(), Box<dyn Error + Send + Sync>>> {
-        task::spawn(async move {
+    ) -> ProviderStart {
+        start_when_ready(|ready| {
             process_arpc_endpoint(
                 endpoint,
                 config,
@@ -50,8 +58,8 @@ impl GeyserProvider for ArpcProvider {
                 shutdown_rx,
                 start_time,
                 comparator,
+                ready,
             )
-                .await
         })
     }
 }
@@ -63,26 +71,36 @@ async fn process_arpc_endpoint(
     mut shutdown_rx: broadcast::Receiver<()>,
     start_time: f64,
     comparator: Arc<Mutex<Comparator>>,
+    mut ready: Ready,
 ) -> Result<(), Box<dyn Error + Send + Sync>> {
     let mut transaction_count = 0;
+    let clock = comparator.lock().unwrap().clock.clone();
+    let lookup_tables = comparator.lock().unwrap().lookup_tables.clone();
 
-    let mut log_file = open_log_file(&endpoint.name)?;
+    let mut log_file = comparator.lock().unwrap().log_output.open(&endpoint.name)?;
 
     log::info!(
         "[{}] Connecting to endpoint: {}",
         endpoint.name,
-        endpoint.url
+        endpoint.display_url()
     );
 
-    let mut client = ArpcServiceClient::connect(endpoint.url).await?;
+    let mut startup = StartupTimer::start(clock.clone());
+    let mut monitor = StreamMonitor::new(clock.clone(), &config);
+    let mut watchdog_timer = monitor.watchdog_timer();
+    let mut drain = Drain::new(&config);
+    let mut consumer = ConsumerDelay::new(&config);
+    let mut client = ArpcServiceClient::new(connect_channel(&endpoint).await?)
+        .max_decoding_message_size(endpoint.decoding_limit());
+    startup.connected();
     log::info!("[{}] Connected successfully", endpoint.name);
 
-    fn reqstream(account: String) -> impl Stream<Item = ArpcSubscribeRequest> {
+    fn reqstream(accounts: Vec<String>) -> impl Stream<Item = ArpcSubscribeRequest> {
         let mut transactions = HashMap::new();
         transactions.insert(
             String::from("account"),
             SubscribeRequestFilterTransactions {
-                account_include: vec![account],
+                account_include: accounts,
                 account_exclude: vec![],
                 account_required: vec![],
             },
@@ -93,32 +111,60 @@ async fn process_arpc_endpoint(
         }])
     }
 
-    let in_stream = reqstream(config.account.clone());
+    let in_stream = reqstream(config.watched_accounts());
 
-    let mut stream = client.subscribe(in_stream).await?.into_inner();
+    startup.subscribed();
+    let mut stream = client.subscribe(in_stream).await.map_err(ProviderError::from_status)?.into_inner();
+    ready.signal();
 
     'ploop: loop {
         tokio::select! {
-            _ = shutdown_rx.recv() => {
+            _ = shutdown_rx.recv(), if !drain.is_draining() => {
                 log::info!("[{}] Received stop signal...", endpoint.name);
+                if !drain.begin(&endpoint.name) {
+                    break;
+                }
+            }
+
+            _ = drain.finished(), if drain.is_draining() => {
+                log::info!("[{}] Drain window over", endpoint.name);
                 break;
             }
 
+            _ = watchdog_timer.tick(), if monitor.watchdog_enabled() => {
+                monitor.check_watchdog(&endpoint.name);
+            }
+
             message = stream.next() => {
                 if let Some(Ok(msg)) = message {
+                    let mut trace = PipelineTrace::start(&endpoint.name);
+                    startup.on_update();
+                    monitor.on_update();
+                    consumer.consume().await;
                     if let Some(tx) = msg.transaction {
-                        let accounts = tx.account_keys
+                        let mut accounts = tx.account_keys
                             .iter()
                             .map(|key| bs58::encode(key).into_string())
                             .collect::<Vec<String>>();
+                        if let Some(lookup_tables) = &lookup_tables {
+                            accounts.extend(lookup_tables.resolve(tx.address_table_lookups.iter().map(|lookup| TableLookup {
+                                table: &lookup.account_key,
+                                writable: &lookup.writable_indexes,
+                                readonly: &lookup.readonly_indexes,
+                            })));
+                        }
 
-                        if accounts.contains(&config.account) {
-                            let timestamp = get_current_timestamp();
+                        if config.watches_any(&accounts) && config.transaction_filter.admits(&accounts) {
+                            let timestamp = clock.now();
+                            startup.on_match(timestamp);
+                            trace.filtered();
+                            let tsc = tsc::read();
                             let signature = bs58::encode(&tx.signatures[0]).into_string();
 
-                            write_log_entry(&mut log_file, timestamp, &endpoint.name, &signature)?;
+                            write_log_entry(&mut log_file, timestamp, &endpoint.name, &signature, tsc)?;
 
                             let mut comp = comparator.lock().unwrap();
+                            trace.locked();
 
                             comp.add(
                                 endpoint.name.clone(),
@@ -126,14 +172,24 @@ async fn process_arpc_endpoint(
                                     timestamp,
                                     signature: signature.clone(),
                                     start_time,
+                                    tsc,
+                                    status: None,
+                                    slot: Some(tx.slot),
+                                    entry: None,
+                                    groups: config.filter_groups_for(&accounts),
                                 },
                             );
+                            trace.inserted(&signature);
+                            comp.tag_accounts(&signature, &accounts);
 
-                            if comp.get_valid_count() == config.transactions as usize {
+                            if !drain.is_draining() && comp.target_reached() {
                                 log::info!("Endpoint {} shutting down after {} transactions seen and {} by all workers",
                                     endpoint.name, transaction_count, config.transactions);
+                                comp.begin_drain();
                                 shutdown_tx.send(()).unwrap();
-                                break 'ploop;
+                                if !drain.begin(&endpoint.name) {
+                                    break 'ploop;
+                                }
                             }
 
                             log::info!("[{:.3}] [{}] {}", timestamp, endpoint.name, signature);
@@ -145,6 +201,9 @@ async fn process_arpc_endpoint(
         }
     }
 
+    monitor.finish(&endpoint.name, &comparator);
+    consumer.finish(&endpoint.name, &comparator);
+    startup.finish(&endpoint.name, &comparator);
     log::info!("[{}] Stream closed", endpoint.name);
     Ok(())
 }
\ No newline at end of file
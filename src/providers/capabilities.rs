@@ -0,0 +1,66 @@
+use serde::Serialize;
+
+use crate::config::BenchmarkMode;
+
+/// What a provider can stream, checked against the configured mode before any task is spawned.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize)]
+pub struct Capabilities {
+    /// Transactions touching the watched accounts (`latency` mode)
+    pub transactions: bool,
+    /// Account writes (`accounts` mode)
+    pub accounts: bool,
+    /// Slot status updates (`slots` mode)
+    pub slots: bool,
+    /// Ledger entries (`entries` mode)
+    pub entries: bool,
+    /// Complete blocks (`blocks` mode)
+    pub blocks: bool,
+    /// `transactions_status` updates (`status` mode)
+    pub transaction_status: bool,
+    /// An unfiltered transaction stream (`throughput` mode)
+    pub throughput: bool,
+}
+
+impl Capabilities {
+    /// Transactions only; the default for providers that declare nothing else.
+    pub const TRANSACTIONS: Self = Self {
+        transactions: true,
+        accounts: false,
+        slots: false,
+        entries: false,
+        blocks: false,
+        transaction_status: false,
+        throughput: false,
+    };
+
+    pub fn supports(&self, mode: BenchmarkMode) -> bool {
+        match mode {
+            BenchmarkMode::Latency => self.transactions,
+            BenchmarkMode::Throughput => self.throughput,
+            BenchmarkMode::Entries => self.entries,
+            BenchmarkMode::Status => self.transaction_status,
+            BenchmarkMode::Blocks => self.blocks,
+            BenchmarkMode::Accounts => self.accounts,
+            BenchmarkMode::Slots => self.slots,
+        }
+    }
+
+    /// Comma-separated names of what is supported, for error messages.
+    pub fn describe(&self) -> String {
+        let names = [
+            (self.transactions, "transactions"),
+            (self.accounts, "accounts"),
+            (self.slots, "slots"),
+            (self.entries, "entries"),
+            (self.blocks, "blocks"),
+            (self.transaction_status, "transaction status"),
+            (self.throughput, "throughput"),
+        ];
+        let supported: Vec<&str> = names.iter().filter(|(supported, _)| *supported).map(|(_, name)| *name).collect();
+        if supported.is_empty() {
+            "nothing".to_string()
+        } else {
+            supported.join(", ")
+        }
+    }
+}
@@ -0,0 +1,79 @@
+use std::{error::Error, fmt};
+
+use tonic::{Code, Status};
+
+// AIDEV-NOTE: `GeyserProvider::start` resolves to a ProviderError, so connect and auth failures are
+// typed before the runner spawns the stream. Provider bodies still return `Box<dyn Error + Send +
+// Sync>`; once streaming, a ProviderError travels boxed and the runner downcasts it to label the
+// failure, while errors that were never classified (channel sends, log writes) stay plain.
+/// Why a provider failed: it could not prepare its request, never connected, the endpoint refused
+/// its credentials, or the subscription broke.
+#[derive(Debug)]
+pub enum ProviderError {
+    /// The log file, request metadata or token could not be prepared before connecting
+    Setup(Box<dyn Error + Send + Sync>),
+    /// DNS, TCP, proxy, TLS or HTTP/2 setup failed
+    Connect(Box<dyn Error + Send + Sync>),
+    /// The endpoint answered `UNAUTHENTICATED` or `PERMISSION_DENIED`
+    Auth(Status),
+    /// Subscribing failed or the stream ended with an error
+    Stream(Box<dyn Error + Send + Sync>),
+}
+
+impl ProviderError {
+    /// Classifies a gRPC status from subscribing or reading the stream.
+    pub fn from_status(status: Status) -> Self {
+        match status.code() {
+            Code::Unauthenticated | Code::PermissionDenied => Self::Auth(status),
+            _ => Self::Stream(Box::new(status)),
+        }
+    }
+
+    /// Classifies an error a provider body returned before subscribing: a ProviderError as it is,
+    /// a gRPC status by its code and anything else as `Setup`.
+    pub fn from_boxed(error: Box<dyn Error + Send + Sync>) -> Self {
+        let error = match error.downcast::<ProviderError>() {
+            Ok(error) => return *error,
+            Err(error) => error,
+        };
+        match error.downcast::<Status>() {
+            Ok(status) => Self::from_status(*status),
+            Err(error) => Self::Setup(error),
+        }
+    }
+
+    pub fn kind(&self) -> &'static str {
+        match self {
+            Self::Setup(_) => "setup",
+            Self::Connect(_) => "connect",
+            Self::Auth(_) => "auth",
+            Self::Stream(_) => "stream",
+        }
+    }
+}
+
+impl fmt::Display for ProviderError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Setup(e) => write!(f, "setup failed: {}", e),
+            Self::Connect(e) => write!(f, "connect failed: {}", e),
+            Self::Auth(status) => write!(f, "authentication rejected: {}", status.message()),
+            Self::Stream(e) => write!(f, "stream failed: {}", e),
+        }
+    }
+}
+
+impl Error for ProviderError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            Self::Setup(e) | Self::Connect(e) | Self::Stream(e) => Some(e.as_ref()),
+            Self::Auth(status) => Some(status),
+        }
+    }
+}
+
+impl From<Status> for ProviderError {
+    fn from(status: Status) -> Self {
+        Self::from_status(status)
+    }
+}
@@ -0,0 +1,356 @@
+use std::{
+    collections::HashMap,
+    error::Error,
+    sync::{Arc, Mutex},
+};
+
+use futures::channel::mpsc::unbounded;
+use futures_util::stream::StreamExt;
+use tokio::sync::broadcast;
+use tonic::transport::Channel;
+use tonic_prost::ProstCodec;
+use yellowstone_grpc_proto::{
+    geyser::{subscribe_update::UpdateOneof, CommitmentLevel, SubscribeUpdate},
+    tonic::{
+        client::Grpc,
+        codegen::http::uri::PathAndQuery,
+        service::interceptor::InterceptedService,
+        transport::ClientTlsConfig,
+        Request,
+    },
+};
+
+use crate::{
+    config::{Config, Endpoint, FumaroleOffset},
+    utils::{Comparator, TransactionData, TransactionStatus, write_log_entry},
+    telemetry::PipelineTrace,
+    tsc,
+};
+
+use super::{
+    channel_builder,
+    open_channel,
+    yellowstone::{created_at_secs, decode_transaction_error},
+    start_when_ready, GeyserProvider, ConsumerDelay, Drain, MetadataInterceptor, ProviderError, ProviderStart, ProviderTiming,
+    Ready, StartupTimer, StreamMonitor, WatchedKeys,
+};
+
+pub mod fumarole {
+    #![allow(clippy::module_inception)]
+    #![allow(clippy::clone_on_ref_ptr)]
+    #![allow(clippy::missing_const_for_fn)]
+
+    include!(concat!(env!("OUT_DIR"), "/fumarole.rs"));
+}
+
+use fumarole::{
+    CreateStaticConsumerGroupRequest, CreateStaticConsumerGroupResponse, DeleteConsumerGroupRequest,
+    DeleteConsumerGroupResponse, EventSubscriptionPolicy, InitialOffsetPolicy, SubscribeRequest,
+    SubscribeRequestFilterTransactions,
+};
+
+const CREATE_CONSUMER_GROUP_PATH: &str = "/fumarole.Fumarole/CreateStaticConsumerGroup";
+const DELETE_CONSUMER_GROUP_PATH: &str = "/fumarole.Fumarole/DeleteConsumerGroup";
+const SUBSCRIBE_PATH: &str = "/fumarole.Fumarole/Subscribe";
+
+type FumaroleClient = Grpc<InterceptedService<Channel, MetadataInterceptor>>;
+
+pub struct FumaroleProvider;
+
+impl GeyserProvider for FumaroleProvider {
+    fn start(
+        &self,
+        endpoint: Endpoint,
+        config: Config,
+        shutdown_tx: broadcast::Sender<()>,
+        shutdown_rx: broadcast::Receiver<()>,
+        start_time: f64,
+        comparator: Arc<Mutex<Comparator>>,
+    ) -> ProviderStart {
+        start_when_ready(|ready| {
+            process_fumarole_endpoint(
+                endpoint,
+                config,
+                shutdown_tx,
+                shutdown_rx,
+                start_time,
+                comparator,
+                ready,
+            )
+        })
+    }
+}
+
+async fn connect_fumarole(endpoint: &Endpoint) -> Result<FumaroleClient, Box<dyn Error + Send + Sync>> {
+    let interceptor = MetadataInterceptor::new(endpoint)?;
+    let builder = channel_builder(endpoint)?.tls_config(ClientTlsConfig::new().with_native_roots())?;
+    let channel = open_channel(builder, endpoint).await?;
+    Ok(Grpc::new(InterceptedService::new(channel, interceptor)).max_decoding_message_size(endpoint.decoding_limit()))
+}
+
+async fn unary<Req, Resp>(
+    client: &mut FumaroleClient,
+    path: &'static str,
+    request: Req,
+) -> Result<Resp, Box<dyn Error + Send + Sync>>
+where
+    Req: prost::Message + Send + Sync + 'static,
+    Resp: prost::Message + Default + Send + Sync + 'static,
+{
+    client
+        .ready()
+        .await
+        .map_err(|e| ProviderError::Connect(format!("service was not ready: {}", e).into()))?;
+    let response = client
+        .unary(Request::new(request), PathAndQuery::from_static(path), ProstCodec::default())
+        .await
+        .map_err(ProviderError::from_status)?;
+    Ok(response.into_inner())
+}
+
+// AIDEV-NOTE: A consumer group stores its read position server-side. Without `fumarole.consumer_group`
+// each endpoint gets a throwaway group starting at `initial_offset`, so every run begins at the
+// live edge by default and leaves nothing behind; a named group resumes where it last stopped.
+/// Label of the group to read from, and whether this run created it (and so must delete it).
+async fn consumer_group(
+    client: &mut FumaroleClient,
+    endpoint: &Endpoint,
+    config: &Config,
+    start_time: f64,
+) -> Result<(String, bool), Box<dyn Error + Send + Sync>> {
+    if let Some(label) = &config.fumarole.consumer_group {
+        log::info!("[{}] Joining consumer group {}", endpoint.name, label);
+        return Ok((label.clone(), false));
+    }
+
+    let label = format!(
+        "geyserbench-{}-{}",
+        endpoint.name.replace(|c: char| !c.is_ascii_alphanumeric(), "-").to_lowercase(),
+        start_time as u64
+    );
+    let initial_offset_policy = match config.fumarole.initial_offset {
+        FumaroleOffset::Latest => InitialOffsetPolicy::Latest,
+        FumaroleOffset::Earliest => InitialOffsetPolicy::Earliest,
+    };
+    let request = CreateStaticConsumerGroupRequest {
+        consumer_group_label: label.clone(),
+        member_count: Some(1),
+        initial_offset_policy: initial_offset_policy as i32,
+        commitment_level: CommitmentLevel::from(config.commitment) as i32,
+        event_subscription_policy: EventSubscriptionPolicy::TransactionOnly as i32,
+        at_slot: None,
+    };
+    let response: CreateStaticConsumerGroupResponse =
+        unary(client, CREATE_CONSUMER_GROUP_PATH, request).await?;
+    log::info!(
+        "[{}] Created consumer group {} (id {}) at {:?}",
+        endpoint.name,
+        label,
+        response.group_id,
+        config.fumarole.initial_offset
+    );
+    Ok((label, true))
+}
+
+async fn delete_consumer_group(client: &mut FumaroleClient, endpoint: &Endpoint, label: &str) {
+    let request = DeleteConsumerGroupRequest {
+        consumer_group_label: label.to_string(),
+    };
+    match unary::<_, DeleteConsumerGroupResponse>(client, DELETE_CONSUMER_GROUP_PATH, request).await {
+        Ok(response) if response.success => log::info!("[{}] Deleted consumer group {}", endpoint.name, label),
+        Ok(_) => log::warn!("[{}] Server refused to delete consumer group {}", endpoint.name, label),
+        Err(e) => log::warn!("[{}] Failed to delete consumer group {}: {}", endpoint.name, label, e),
+    }
+}
+
+async fn process_fumarole_endpoint(
+    endpoint: Endpoint,
+    config: Config,
+    shutdown_tx: broadcast::Sender<()>,
+    mut shutdown_rx: broadcast::Receiver<()>,
+    start_time: f64,
+    comparator: Arc<Mutex<Comparator>>,
+    mut ready: Ready,
+) -> Result<(), Box<dyn Error + Send + Sync>> {
+    let mut transaction_count = 0;
+    let clock = comparator.lock().unwrap().clock.clone();
+
+    let mut log_file = comparator.lock().unwrap().log_output.open(&endpoint.name)?;
+
+    log::info!("[{}] Connecting to endpoint: {}", endpoint.name, endpoint.display_url());
+
+    let mut startup = StartupTimer::start(clock.clone());
+    let mut timing = ProviderTiming::new(clock.clone());
+    let mut monitor = StreamMonitor::new(clock.clone(), &config);
+    let mut watchdog_timer = monitor.watchdog_timer();
+    let mut drain = Drain::new(&config);
+    let mut consumer = ConsumerDelay::new(&config);
+    let mut client = connect_fumarole(&endpoint).await?;
+    startup.connected();
+    log::info!("[{}] Connected successfully", endpoint.name);
+
+    let (group_label, owns_group) = consumer_group(&mut client, &endpoint, &config, start_time).await?;
+    let watched = WatchedKeys::from_base58(&config.watched_accounts());
+
+    let mut transactions = HashMap::new();
+    transactions.insert(
+        "account".to_string(),
+        SubscribeRequestFilterTransactions {
+            account_include: config.watched_accounts(),
+            vote: config.transaction_filter.vote,
+            failed: config.transaction_filter.failed,
+            ..Default::default()
+        },
+    );
+    let request = SubscribeRequest {
+        consumer_group_label: group_label.clone(),
+        consumer_id: Some(0),
+        transactions,
+    };
+
+    // The sender stays alive for the whole stream; dropping it would end the subscription
+    let (subscribe_tx, subscribe_rx) = unbounded::<SubscribeRequest>();
+    subscribe_tx.unbounded_send(request)?;
+    startup.subscribed();
+    client
+        .ready()
+        .await
+        .map_err(|e| ProviderError::Connect(format!("service was not ready: {}", e).into()))?;
+    let subscribe = client
+        .streaming::<_, SubscribeRequest, SubscribeUpdate, _>(
+            Request::new(subscribe_rx),
+            PathAndQuery::from_static(SUBSCRIBE_PATH),
+            ProstCodec::default(),
+        )
+        .await;
+    let mut stream = match subscribe {
+        Ok(response) => response.into_inner(),
+        Err(e) => {
+            if owns_group {
+                delete_consumer_group(&mut client, &endpoint, &group_label).await;
+            }
+            return Err(ProviderError::from_status(e).into());
+        }
+    };
+    ready.signal();
+
+    'ploop: loop {
+        tokio::select! {
+            _ = shutdown_rx.recv(), if !drain.is_draining() => {
+                log::info!("[{}] Received stop signal...", endpoint.name);
+                if !drain.begin(&endpoint.name) {
+                    break;
+                }
+            }
+
+            _ = drain.finished(), if drain.is_draining() => {
+                log::info!("[{}] Drain window over", endpoint.name);
+                break;
+            }
+
+            _ = watchdog_timer.tick(), if monitor.watchdog_enabled() => {
+                monitor.check_watchdog(&endpoint.name);
+            }
+
+            message = stream.next() => {
+                let msg = match message {
+                    Some(Ok(msg)) => msg,
+                    Some(Err(e)) => {
+                        log::error!("[{}] Error receiving message: {:?}", endpoint.name, e);
+                        monitor.disconnected(e.to_string());
+                        break;
+                    }
+                    None => {
+                        log::info!("[{}] Stream closed", endpoint.name);
+                        monitor.disconnected("stream closed by server".to_string());
+                        break;
+                    }
+                };
+                monitor.on_message();
+                consumer.consume().await;
+                if matches!(msg.update_oneof, Some(UpdateOneof::Ping(_)) | Some(UpdateOneof::Pong(_))) {
+                    continue;
+                }
+                let mut trace = PipelineTrace::start(&endpoint.name);
+                startup.on_update();
+                monitor.on_update();
+                timing.on_update(created_at_secs(&msg));
+                let Some(UpdateOneof::Transaction(tx_msg)) = msg.update_oneof else {
+                    continue;
+                };
+                let Some(tx) = tx_msg.transaction else {
+                    continue;
+                };
+                let Some(message) = tx.transaction.as_ref().and_then(|transaction| transaction.message.as_ref()) else {
+                    continue;
+                };
+                let mut accounts = watched.matching(&message.account_keys);
+                if let Some(meta) = tx.meta.as_ref().filter(|_| config.lookup_tables.is_some()) {
+                    accounts.extend(watched.matching(&meta.loaded_writable_addresses));
+                    accounts.extend(watched.matching(&meta.loaded_readonly_addresses));
+                }
+                if accounts.is_empty() {
+                    continue;
+                }
+
+                let timestamp = clock.now();
+                startup.on_match(timestamp);
+                trace.filtered();
+                let tsc = tsc::read();
+                let status = tx.meta.as_ref().map(|meta| TransactionStatus {
+                    failed: meta.err.is_some(),
+                    fee: Some(meta.fee),
+                    compute_units: meta.compute_units_consumed,
+                    error: meta.err.as_ref().map(|err| decode_transaction_error(&err.err)),
+                });
+                let signature = bs58::encode(&tx.signature).into_string();
+
+                write_log_entry(&mut log_file, timestamp, &endpoint.name, &signature, tsc)?;
+
+                let mut comp = comparator.lock().unwrap();
+                trace.locked();
+
+                comp.add(
+                    endpoint.name.clone(),
+                    TransactionData {
+                        timestamp,
+                        signature: signature.clone(),
+                        start_time,
+                        tsc,
+                        status,
+                        slot: Some(tx_msg.slot),
+                        entry: None,
+                        groups: config.filter_groups_for(&accounts),
+                    },
+                );
+                trace.inserted(&signature);
+                comp.tag_accounts(&signature, &accounts);
+
+                if !drain.is_draining() && comp.target_reached() {
+                    log::info!("Endpoint {} shutting down after {} transactions seen and {} by all workers",
+                        endpoint.name, transaction_count, config.transactions);
+                    comp.begin_drain();
+                    shutdown_tx.send(()).unwrap();
+                    if !drain.begin(&endpoint.name) {
+                        break 'ploop;
+                    }
+                }
+
+                log::info!("[{:.3}] [{}] {}", timestamp, endpoint.name, signature);
+                transaction_count += 1;
+            }
+        }
+    }
+
+    drop(stream);
+    if owns_group {
+        delete_consumer_group(&mut client, &endpoint, &group_label).await;
+    }
+
+    monitor.finish(&endpoint.name, &comparator);
+    consumer.finish(&endpoint.name, &comparator);
+    timing.finish(&endpoint.name, &comparator);
+    startup.finish(&endpoint.name, &comparator);
+    log::info!("[{}] Stream closed", endpoint.name);
+    Ok(())
+}
@@ -1,17 +1,21 @@
 use std::{ collections::HashMap, error::Error, sync::{ Arc, Mutex } };
 use futures::channel::mpsc::unbounded;
 use futures_util::{ stream::StreamExt, sink::SinkExt };
-use tokio::{ sync::broadcast, task };
-use tokio_stream::Stream;
+use tokio::sync::broadcast;
 
 use crate::{
     config::{ Config, Endpoint },
-    utils::{ Comparator, TransactionData, get_current_timestamp, open_log_file, write_log_entry },
+    lookup_tables::TableLookup,
+    utils::{ Comparator, TransactionData, write_log_entry },
+    telemetry::PipelineTrace,
+    tsc,
 };
 
-use super::GeyserProvider;
+use super::{ connect_channel, start_when_ready, GeyserProvider, ConsumerDelay, Drain, ProviderError, ProviderStart, Ready, StartupTimer, StreamMonitor };
 
 pub mod jetstream {
+    #![allow(dead_code)]
+    #![allow(clippy::module_inception)]
     #![allow(clippy::clone_on_ref_ptr)]
     #![allow(clippy::missing_const_for_fn)]
 
@@ -20,16 +24,12 @@ pub mod jetstream {
     pub const FILE_DESCRIPTOR_SET: &[u8] = tonic::include_file_descriptor_set!("proto_descriptors");
 }
 
-use jetstream::{
-    jetstream_client::JetstreamClient,
-    SubscribeRequest, SubscribeUpdate,
-    SubscribeRequestFilterTransactions,
-};
+use jetstream::jetstream_client::JetstreamClient;
 
 pub struct JetstreamProvider;
 
 impl GeyserProvider for JetstreamProvider {
-    fn process(
+    fn start(
         &self,
         endpoint: Endpoint,
         config: Config,
@@ -37,16 +37,17 @@ impl GeyserProvider for JetstreamProvider {
         shutdown_rx: broadcast::Receiver<()>,
         start_time: f64,
         comparator: Arc<Mutex<Comparator>>
-    ) -> task::JoinHandle<Result<(), Box<dyn Error + Send + Sync>>> {
-        task::spawn(async move {
+    ) -> ProviderStart {
+        start_when_ready(|ready| {
             process_jetstream_endpoint(
                 endpoint,
                 config,
                 shutdown_tx,
                 shutdown_rx,
                 start_time,
-                comparator
-            ).await
+                comparator,
+                ready
+            )
         })
     }
 }
@@ -57,15 +58,25 @@ async fn process_jetstream_endpoint(
     shutdown_tx: broadcast::Sender<()>,
     mut shutdown_rx: broadcast::Receiver<()>,
     start_time: f64,
-    comparator: Arc<Mutex<Comparator>>
+    comparator: Arc<Mutex<Comparator>>,
+    mut ready: Ready
 ) -> Result<(), Box<dyn Error + Send + Sync>> {
     let mut transaction_count = 0;
+    let clock = comparator.lock().unwrap().clock.clone();
+    let lookup_tables = comparator.lock().unwrap().lookup_tables.clone();
 
-    let mut log_file = open_log_file(&endpoint.name)?;
+    let mut log_file = comparator.lock().unwrap().log_output.open(&endpoint.name)?;
 
-    log::info!("[{}] Connecting to endpoint: {}", endpoint.name, endpoint.url);
+    log::info!("[{}] Connecting to endpoint: {}", endpoint.name, endpoint.display_url());
 
-    let mut client = JetstreamClient::connect(endpoint.url).await?;
+    let mut startup = StartupTimer::start(clock.clone());
+    let mut monitor = StreamMonitor::new(clock.clone(), &config);
+    let mut watchdog_timer = monitor.watchdog_timer();
+    let mut drain = Drain::new(&config);
+    let mut consumer = ConsumerDelay::new(&config);
+    let mut client = JetstreamClient::new(connect_channel(&endpoint).await?)
+        .max_decoding_message_size(endpoint.decoding_limit());
+    startup.connected();
     log::info!("[{}] Connected successfully", endpoint.name);
 
     let mut transactions: HashMap<
@@ -74,8 +85,8 @@ async fn process_jetstream_endpoint(
     > = HashMap::new();
     transactions.insert(String::from("account"), jetstream::SubscribeRequestFilterTransactions {
         account_exclude: vec![],
-        account_include: vec![],
-        account_required: vec![config.account.clone()],
+        account_include: config.watched_accounts(),
+        account_required: vec![],
     });
 
     let request = jetstream::SubscribeRequest { 
@@ -85,32 +96,60 @@ async fn process_jetstream_endpoint(
     };
 
     let (mut subscribe_tx, subscribe_rx) = unbounded::<jetstream::SubscribeRequest>();
+    startup.subscribed();
     subscribe_tx.send(request).await?;
-    let mut stream = client.subscribe(subscribe_rx).await?.into_inner();
+    let mut stream = client.subscribe(subscribe_rx).await.map_err(ProviderError::from_status)?.into_inner();
+    ready.signal();
 
     'ploop: loop {
         tokio::select! {
-            _ = shutdown_rx.recv() => {
+            _ = shutdown_rx.recv(), if !drain.is_draining() => {
                 log::info!("[{}] Received stop signal...", endpoint.name);
+                if !drain.begin(&endpoint.name) {
+                    break;
+                }
+            }
+
+            _ = drain.finished(), if drain.is_draining() => {
+                log::info!("[{}] Drain window over", endpoint.name);
                 break;
             }
 
+            _ = watchdog_timer.tick(), if monitor.watchdog_enabled() => {
+                monitor.check_watchdog(&endpoint.name);
+            }
+
             message = stream.next() => {
                 if let Some(Ok(msg)) = message {
+                    let mut trace = PipelineTrace::start(&endpoint.name);
+                    startup.on_update();
+                    monitor.on_update();
+                    consumer.consume().await;
                     if let Some(jetstream::subscribe_update::UpdateOneof::Transaction(tx)) = msg.update_oneof {
                         if let Some(tx_info) = &tx.transaction {
-                            let account_keys = tx_info.account_keys
+                            let mut account_keys = tx_info.account_keys
                                 .iter()
                                 .map(|key| bs58::encode(key).into_string())
                                 .collect::<Vec<String>>();
+                            if let Some(lookup_tables) = &lookup_tables {
+                                account_keys.extend(lookup_tables.resolve(tx_info.address_table_lookups.iter().map(|lookup| TableLookup {
+                                    table: &lookup.account_key,
+                                    writable: &lookup.writable_indexes,
+                                    readonly: &lookup.readonly_indexes,
+                                })));
+                            }
 
-                            if account_keys.contains(&config.account) {
-                                let timestamp = get_current_timestamp();
+                            if config.watches_any(&account_keys) && config.transaction_filter.admits(&account_keys) {
+                                let timestamp = clock.now();
+                                startup.on_match(timestamp);
+                                trace.filtered();
+                                let tsc = tsc::read();
                                 let signature = bs58::encode(&tx_info.signature).into_string();
 
-                                write_log_entry(&mut log_file, timestamp, &endpoint.name, &signature)?;
+                                write_log_entry(&mut log_file, timestamp, &endpoint.name, &signature, tsc)?;
 
                                 let mut comp = comparator.lock().unwrap();
+                                trace.locked();
 
                                 comp.add(
                                     endpoint.name.clone(),
@@ -118,14 +157,24 @@ async fn process_jetstream_endpoint(
                                         timestamp,
                                         signature: signature.clone(),
                                         start_time,
+                                        tsc,
+                                        status: None,
+                                        slot: Some(tx.slot),
+                                        entry: None,
+                                        groups: config.filter_groups_for(&account_keys),
                                     },
                                 );
+                                trace.inserted(&signature);
+                                comp.tag_accounts(&signature, &account_keys);
 
-                                if comp.get_valid_count() == config.transactions as usize {
+                                if !drain.is_draining() && comp.target_reached() {
                                     log::info!("Endpoint {} shutting down after {} transactions seen and {} by all workers",
                                         endpoint.name, transaction_count, config.transactions);
+                                    comp.begin_drain();
                                     shutdown_tx.send(()).unwrap();
-                                    break 'ploop;
+                                    if !drain.begin(&endpoint.name) {
+                                        break 'ploop;
+                                    }
                                 }
 
                                 log::info!("[{:.3}] [{}] {}", timestamp, endpoint.name, signature);
@@ -138,6 +187,9 @@ async fn process_jetstream_endpoint(
         }
     }
 
+    monitor.finish(&endpoint.name, &comparator);
+    consumer.finish(&endpoint.name, &comparator);
+    startup.finish(&endpoint.name, &comparator);
     log::info!("[{}] Stream closed", endpoint.name);
     Ok(())
 }
\ No newline at end of file
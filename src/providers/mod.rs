@@ -1,9 +1,23 @@
-use std::{sync::Arc, error::Error};
-use tokio::sync::broadcast;
+use std::{collections::{HashMap, HashSet}, sync::{Arc, Mutex}, error::Error, future::Future, pin::Pin, time::Duration};
+use tokio::{sync::{broadcast, oneshot}, task};
+use tonic::transport::Channel;
+use yellowstone_grpc_proto::{
+    geyser::{geyser_client::GeyserClient, SubscribeRequest},
+    tonic::{
+        metadata::{AsciiMetadataKey, AsciiMetadataValue},
+        service::{interceptor::InterceptedService, Interceptor},
+        transport::ClientTlsConfig,
+        Request, Status,
+    },
+};
 
 use crate::{
-    utils::{Comparator, TransactionData},
-    config::{Config, Endpoint, EndpointKind},
+    clock::Clock,
+    profile::{self, Profiled},
+    timeline::{TimelineEvent, TimelineEventKind},
+    utils::{Comparator, ConsumerStats, StartupMetrics, StreamEvent, StreamEventKind},
+    config::{BenchmarkMode, Config, Endpoint, EndpointKind, SubscribeFilter},
+    proxy::{Proxy, ProxyConnector},
 };
 
 pub mod arpc;
@@ -13,9 +27,29 @@ pub mod thor;
 pub mod shreder;
 pub mod jetstream;
 pub mod shredstream_proxy;
+pub mod unix_socket;
+pub mod fumarole;
+pub mod registry;
+pub mod capabilities;
+pub mod error;
+
+pub use capabilities::Capabilities;
+pub use error::ProviderError;
+pub use registry::{create_provider, register_provider, registered_kinds, ProviderFactory};
+
+/// Only used to build the timer; pings are sent when `ping_interval_secs` or `rtt_interval_secs` is configured.
+pub const DEFAULT_PING_INTERVAL_SECS: u64 = 30;
+
+/// A provider's stream, read until shutdown; returned by [`GeyserProvider::start`] once subscribed.
+pub type ProviderRun = Pin<Box<dyn Future<Output = Result<(), Box<dyn Error + Send + Sync>>> + Send>>;
+/// Connects and subscribes one endpoint, resolving to its [`ProviderRun`] or to why it could not.
+pub type ProviderStart = Pin<Box<dyn Future<Output = Result<ProviderRun, ProviderError>> + Send>>;
 
 pub trait GeyserProvider: Send + Sync {
-    fn process(
+    /// Connects to the endpoint and subscribes. Connect, auth and setup failures resolve to a
+    /// [`ProviderError`] before the runner spawns anything; [`start_when_ready`] builds this from a
+    /// single provider body.
+    fn start(
         &self,
         endpoint: Endpoint,
         config: Config,
@@ -23,17 +57,822 @@ pub trait GeyserProvider: Send + Sync {
         shutdown_rx: broadcast::Receiver<()>,
         start_time: f64,
         comparator: Arc<std::sync::Mutex<Comparator>>,
-    ) -> tokio::task::JoinHandle<Result<(), Box<dyn Error + Send + Sync>>>;
+    ) -> ProviderStart;
+
+    /// What this provider can stream; transactions only unless overridden.
+    fn capabilities(&self) -> Capabilities {
+        Capabilities::TRANSACTIONS
+    }
+
+    /// Whether this provider implements the given benchmark mode, from its [`Capabilities`].
+    fn supports_mode(&self, mode: BenchmarkMode) -> bool {
+        self.capabilities().supports(mode)
+    }
+}
+
+/// Handed to a provider body by [`start_when_ready`] and signalled once the endpoint accepted the
+/// subscription. Dropping it unsignalled while the body keeps running hands over as well.
+pub struct Ready(Option<oneshot::Sender<()>>);
+
+impl Ready {
+    /// A handle and the receiver its signal resolves, for subscriptions a provider spawns itself.
+    pub fn pair() -> (Self, oneshot::Receiver<()>) {
+        let (ready_tx, ready_rx) = oneshot::channel();
+        (Self(Some(ready_tx)), ready_rx)
+    }
+
+    /// Marks the provider connected and subscribed; later calls do nothing.
+    pub fn signal(&mut self) {
+        if let Some(ready_tx) = self.0.take() {
+            let _ = ready_tx.send(());
+        }
+    }
+}
+
+// AIDEV-NOTE: The body is one future from connect to stream end: the runner polls it through
+// connecting and subscribing, then spawns that same future, so nothing reconnects at the handover
+// and updates only wait in the stream's buffer until the spawned task polls it.
+/// [`GeyserProvider::start`] future for a provider body that calls [`Ready::signal`] once
+/// subscribed. A body failing before that resolves to its error, see [`ProviderError::from_boxed`].
+pub fn start_when_ready<F, Fut>(body: F) -> ProviderStart
+where
+    F: FnOnce(Ready) -> Fut,
+    Fut: Future<Output = Result<(), Box<dyn Error + Send + Sync>>> + Send + 'static,
+{
+    let (ready, ready_rx) = Ready::pair();
+    let mut run: ProviderRun = Box::pin(body(ready));
+    Box::pin(async move {
+        tokio::select! {
+            biased;
+            result = &mut run => match result {
+                // Stopped before it subscribed
+                Ok(()) => Ok(Box::pin(async { Ok(()) }) as ProviderRun),
+                Err(e) => Err(ProviderError::from_boxed(e)),
+            },
+            _ = ready_rx => Ok(run),
+        }
+    })
+}
+
+/// Spawns a provider task, timing its polls under `name` when `self_profile` is set.
+pub fn spawn_provider<F, T>(
+    name: String,
+    self_profile: bool,
+    comparator: Arc<Mutex<Comparator>>,
+    future: F,
+) -> task::JoinHandle<T>
+where
+    F: Future<Output = T> + Send + 'static,
+    T: Send + 'static,
+{
+    if self_profile {
+        task::spawn(Profiled::new(name, comparator, future))
+    } else {
+        task::spawn(future)
+    }
+}
+
+/// Aborts a spawned task when dropped, so the subscriptions a provider fans out to end with it
+/// when the runner aborts the provider (a reload stopping its endpoint mid-run).
+pub struct AbortOnDrop(pub task::AbortHandle);
+
+impl Drop for AbortOnDrop {
+    fn drop(&mut self) {
+        self.0.abort();
+    }
+}
+
+/// Whether the provider registered for `kind` implements the given benchmark mode; false for
+/// unregistered kinds.
+pub fn supports_mode(kind: &EndpointKind, mode: BenchmarkMode) -> bool {
+    create_provider(kind).is_some_and(|provider| provider.supports_mode(mode))
+}
+
+/// Capabilities of the provider registered for `kind`; None for unregistered kinds.
+pub fn capabilities(kind: &EndpointKind) -> Option<Capabilities> {
+    create_provider(kind).map(|provider| provider.capabilities())
+}
+
+// AIDEV-NOTE: Hot path of the yellowstone-shaped providers: raw 32-byte keys are compared against
+// the decoded watch list, so a message that matches nothing is checked without allocating and
+// only matched keys (and the signature) are base58-encoded.
+/// Watched accounts decoded to raw pubkeys for matching against wire-format account keys.
+#[derive(Debug, Clone, Default)]
+pub struct WatchedKeys {
+    keys: HashSet<[u8; 32]>,
+}
+
+impl WatchedKeys {
+    /// Decodes base58 accounts, skipping (with a warning) any that are not 32-byte pubkeys.
+    pub fn from_base58(accounts: &[String]) -> Self {
+        let keys = accounts
+            .iter()
+            .filter_map(|account| {
+                let key = bs58::decode(account).into_vec().ok().and_then(|bytes| <[u8; 32]>::try_from(bytes).ok());
+                if key.is_none() {
+                    log::warn!("Watched account {} is not a valid pubkey and can never match", account);
+                }
+                key
+            })
+            .collect();
+        Self { keys }
+    }
+
+    pub fn len(&self) -> usize {
+        self.keys.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.keys.is_empty()
+    }
+
+    pub fn contains(&self, key: &[u8]) -> bool {
+        <&[u8; 32]>::try_from(key).is_ok_and(|key| self.keys.contains(key))
+    }
+
+    /// Base58 form of the watched keys among `account_keys`; empty when the transaction does not match.
+    pub fn matching(&self, account_keys: &[Vec<u8>]) -> Vec<String> {
+        account_keys
+            .iter()
+            .filter(|key| self.contains(key))
+            .map(|key| bs58::encode(key).into_string())
+            .collect()
+    }
+}
+
+/// Comparator key for the first entry of a slot in entries mode.
+pub fn entry_slot_key(slot: u64) -> String {
+    format!("slot:{}", slot)
+}
+
+/// Comparator key for one account write in accounts mode.
+pub fn account_write_key(pubkey: &str, write_version: u64) -> String {
+    format!("acct:{}:{}", pubkey, write_version)
+}
+
+/// Comparator key for a slot's complete block in blocks mode.
+pub fn block_slot_key(slot: u64) -> String {
+    format!("block:{}", slot)
+}
+
+// AIDEV-NOTE: All plain tonic providers connect through here so per-endpoint transport settings apply
+pub async fn connect_channel(endpoint: &Endpoint) -> Result<Channel, Box<dyn Error + Send + Sync>> {
+    open_channel(channel_builder(endpoint)?, endpoint).await
+}
+
+/// Connects a built channel, through the endpoint's proxy when one applies (see [`Proxy::for_endpoint`]).
+pub async fn open_channel(
+    builder: tonic::transport::Endpoint,
+    endpoint: &Endpoint,
+) -> Result<Channel, Box<dyn Error + Send + Sync>> {
+    let channel = match Proxy::for_endpoint(endpoint)? {
+        Some(proxy) => {
+            log::info!("[{}] Connecting through proxy {}", endpoint.name, proxy);
+            builder.connect_with_connector(ProxyConnector::new(proxy)).await
+        }
+        None => builder.connect().await,
+    };
+    channel.map_err(|e| ProviderError::Connect(Box::new(e)).into())
+}
+
+pub fn channel_builder(
+    endpoint: &Endpoint,
+) -> Result<tonic::transport::Endpoint, Box<dyn Error + Send + Sync>> {
+    let mut builder = Channel::from_shared(endpoint.url.clone())?;
+    if let Some(interval) = endpoint.keepalive_interval_secs {
+        builder = builder
+            .http2_keep_alive_interval(Duration::from_secs(interval))
+            .keep_alive_while_idle(true);
+    }
+    if let Some(timeout) = endpoint.keepalive_timeout_secs {
+        builder = builder.keep_alive_timeout(Duration::from_secs(timeout));
+    }
+    if let Some(size) = endpoint.initial_connection_window_size {
+        builder = builder.initial_connection_window_size(size);
+    }
+    if let Some(size) = endpoint.initial_stream_window_size {
+        builder = builder.initial_stream_window_size(size);
+    }
+    if let Some(nodelay) = endpoint.tcp_nodelay {
+        builder = builder.tcp_nodelay(nodelay);
+    }
+    Ok(builder)
+}
+
+pub type YellowstoneClient = GeyserClient<InterceptedService<Channel, MetadataInterceptor>>;
+
+/// Yellowstone client with the endpoint's metadata, TLS roots and transport settings applied.
+pub async fn connect_yellowstone(endpoint: &Endpoint) -> Result<YellowstoneClient, Box<dyn Error + Send + Sync>> {
+    let interceptor = MetadataInterceptor::new(endpoint)?;
+    let builder = channel_builder(endpoint)?.tls_config(ClientTlsConfig::new().with_native_roots())?;
+    let channel = open_channel(builder, endpoint).await?;
+    Ok(GeyserClient::with_interceptor(channel, interceptor).max_decoding_message_size(endpoint.decoding_limit()))
+}
+
+// AIDEV-NOTE: Applied to the finished request so the mode's own construction stays the single
+// source of the default filters; `include` can only drop maps, never invent filters for them.
+/// Applies the endpoint's `subscribe` overrides to a Yellowstone subscribe request.
+pub fn apply_subscribe_overrides(endpoint: &Endpoint, request: &mut SubscribeRequest) {
+    let Some(overrides) = &endpoint.subscribe else {
+        return;
+    };
+    if let Some(include) = &overrides.include {
+        let keep = |filter: SubscribeFilter| include.contains(&filter);
+        if !keep(SubscribeFilter::Slots) {
+            request.slots.clear();
+        }
+        if !keep(SubscribeFilter::Transactions) {
+            request.transactions.clear();
+        }
+        if !keep(SubscribeFilter::TransactionsStatus) {
+            request.transactions_status.clear();
+        }
+        if !keep(SubscribeFilter::Accounts) {
+            request.accounts.clear();
+            request.accounts_data_slice.clear();
+        }
+        if !keep(SubscribeFilter::Entry) {
+            request.entry.clear();
+        }
+        if !keep(SubscribeFilter::Blocks) {
+            request.blocks.clear();
+        }
+        let empty = request.slots.is_empty()
+            && request.transactions.is_empty()
+            && request.transactions_status.is_empty()
+            && request.accounts.is_empty()
+            && request.entry.is_empty()
+            && request.blocks.is_empty();
+        if empty {
+            log::warn!("[{}] subscribe.include leaves no filter this mode sends; nothing will stream", endpoint.name);
+        }
+    }
+    if let Some(name) = &overrides.filter_name {
+        fn rename<T>(filters: &mut HashMap<String, T>, name: &str) {
+            let first = filters.drain().next().map(|(_, filter)| filter);
+            if let Some(filter) = first {
+                filters.insert(name.to_string(), filter);
+            }
+        }
+        rename(&mut request.slots, name);
+        rename(&mut request.transactions, name);
+        rename(&mut request.transactions_status, name);
+        rename(&mut request.accounts, name);
+        rename(&mut request.entry, name);
+        rename(&mut request.blocks, name);
+    }
+}
+
+// AIDEV-NOTE: Attaches `x-token` (when set) and every configured `headers` entry to each request,
+// so providers authenticating with other headers need no code of their own.
+#[derive(Debug, Clone)]
+pub struct MetadataInterceptor {
+    metadata: Vec<(AsciiMetadataKey, AsciiMetadataValue)>,
+}
+
+impl MetadataInterceptor {
+    pub fn new(endpoint: &Endpoint) -> Result<Self, Box<dyn Error + Send + Sync>> {
+        let mut metadata = Vec::new();
+        if !endpoint.x_token.is_empty() {
+            metadata.push((AsciiMetadataKey::from_static("x-token"), endpoint.x_token.parse()?));
+        }
+        for (name, value) in &endpoint.headers {
+            let key = AsciiMetadataKey::from_bytes(name.to_ascii_lowercase().as_bytes())
+                .map_err(|e| format!("[{}] invalid header name '{}': {}", endpoint.name, name, e))?;
+            let value = value
+                .parse()
+                .map_err(|e| format!("[{}] invalid value for header '{}': {}", endpoint.name, name, e))?;
+            metadata.push((key, value));
+        }
+        Ok(Self { metadata })
+    }
+}
+
+impl Interceptor for MetadataInterceptor {
+    fn call(&mut self, mut request: Request<()>) -> Result<Request<()>, Status> {
+        for (key, value) in &self.metadata {
+            request.metadata_mut().insert(key.clone(), value.clone());
+        }
+        Ok(request)
+    }
+}
+
+// AIDEV-NOTE: Connect time is measured around the eager connect call; update times start when the
+// subscribe request is sent, so slow server-side spin-up shows separately from the handshake.
+pub struct StartupTimer {
+    clock: Arc<dyn Clock>,
+    connect_started: f64,
+    subscribed_at: f64,
+    metrics: StartupMetrics,
+    timeline: Vec<TimelineEvent>,
+}
+
+impl StartupTimer {
+    pub fn start(clock: Arc<dyn Clock>) -> Self {
+        let now = clock.now();
+        Self {
+            clock,
+            connect_started: now,
+            subscribed_at: now,
+            metrics: StartupMetrics::default(),
+            timeline: vec![TimelineEvent {
+                kind: TimelineEventKind::Connect,
+                at: now,
+            }],
+        }
+    }
+
+    fn mark(&mut self, kind: TimelineEventKind, at: f64) {
+        self.timeline.push(TimelineEvent { kind, at });
+    }
+
+    pub fn connected(&mut self) {
+        let now = self.clock.now();
+        self.metrics.connect_ms = (now - self.connect_started) * 1000.0;
+        self.mark(TimelineEventKind::Connected, now);
+    }
+
+    pub fn subscribed(&mut self) {
+        self.subscribed_at = self.clock.now();
+        self.mark(TimelineEventKind::Subscribed, self.subscribed_at);
+    }
+
+    pub fn on_update(&mut self) {
+        if self.metrics.first_update_ms.is_none() {
+            self.metrics.first_update_ms = Some((self.clock.now() - self.subscribed_at) * 1000.0);
+        }
+    }
+
+    pub fn on_match(&mut self, timestamp: f64) {
+        if self.metrics.first_match_ms.is_none() {
+            self.metrics.first_match_ms = Some((timestamp - self.subscribed_at) * 1000.0);
+            self.mark(TimelineEventKind::FirstMatch, timestamp);
+        }
+    }
+
+    /// Logs the timings and stores them in the endpoint's metrics, unless an earlier connection
+    /// (before a watchdog reconnect) already did. The connection's timeline is appended either way.
+    pub fn finish(mut self, endpoint_name: &str, comparator: &Mutex<Comparator>) {
+        let fmt = |ms: Option<f64>| ms.map_or("-".to_string(), |ms| format!("{:.2} ms", ms));
+        log::info!(
+            "[{}] Startup: connect {:.2} ms, first update {}, first match {}",
+            endpoint_name,
+            self.metrics.connect_ms,
+            fmt(self.metrics.first_update_ms),
+            fmt(self.metrics.first_match_ms)
+        );
+        let now = self.clock.now();
+        self.mark(TimelineEventKind::Closed, now);
+        let mut comparator = comparator.lock().unwrap();
+        let metrics = comparator.endpoint_metrics.entry(endpoint_name.to_string()).or_default();
+        // Every connection after the first is a reconnect, and only that one can show a first match
+        if !metrics.timeline.is_empty() {
+            for event in &mut self.timeline {
+                if event.kind == TimelineEventKind::Connect {
+                    event.kind = TimelineEventKind::Reconnect;
+                }
+            }
+            let had_match = metrics.timeline.iter().any(|event| event.kind == TimelineEventKind::FirstMatch);
+            self.timeline.retain(|event| !had_match || event.kind != TimelineEventKind::FirstMatch);
+        }
+        metrics.timeline.append(&mut self.timeline);
+        metrics.startup.get_or_insert(self.metrics);
+    }
+}
+
+// AIDEV-NOTE: Yellowstone stamps every update with `created_at` when the server builds it, so
+// receive time minus that stamp is transit plus client queueing, and whatever remains of the
+// endpoint's delay behind the first detection was spent on the provider side. The stamp comes from
+// the provider's clock; both clocks need to be NTP-synced for the absolute values to mean anything.
+/// Receive time against provider-side timestamps: `created_at` on every update, `block_time` on blocks.
+pub struct ProviderTiming {
+    clock: Arc<dyn Clock>,
+    skews_ms: Vec<f64>,
+    block_time_lags_ms: Vec<f64>,
+}
+
+impl ProviderTiming {
+    pub fn new(clock: Arc<dyn Clock>) -> Self {
+        Self {
+            clock,
+            skews_ms: Vec::new(),
+            block_time_lags_ms: Vec::new(),
+        }
+    }
+
+    /// An update stamped by the provider at `created_at` (Unix seconds).
+    pub fn on_update(&mut self, created_at: Option<f64>) {
+        if let Some(created_at) = created_at {
+            self.skews_ms.push((self.clock.now() - created_at) * 1000.0);
+        }
+    }
+
+    /// A block produced at `block_time` (whole Unix seconds, from the leader's clock).
+    pub fn on_block_time(&mut self, block_time: i64) {
+        self.block_time_lags_ms.push((self.clock.now() - block_time as f64) * 1000.0);
+    }
+
+    /// Adds the samples to the endpoint's metrics, merging shards, connections and reconnects.
+    pub fn finish(self, endpoint_name: &str, comparator: &Mutex<Comparator>) {
+        let mut comparator = comparator.lock().unwrap();
+        let metrics = comparator.endpoint_metrics.entry(endpoint_name.to_string()).or_default();
+        metrics.provider_skews_ms.extend(self.skews_ms);
+        metrics.block_time_lags_ms.extend(self.block_time_lags_ms);
+    }
+}
+
+// AIDEV-NOTE: Server pings are answered with id 0, which is never tracked, so only pings sent on
+// the client's own timer are timed. The pong comes back on the subscription stream behind any
+// queued updates, so the minimum is the network round trip and the spread is stream queueing.
+/// Round trips of client pings, matched to their pongs by id.
+pub struct PingTracker {
+    clock: Arc<dyn Clock>,
+    next_id: i32,
+    outstanding: HashMap<i32, f64>,
+    rtts_ms: Vec<f64>,
+}
+
+impl PingTracker {
+    pub fn new(clock: Arc<dyn Clock>) -> Self {
+        Self {
+            clock,
+            next_id: 0,
+            outstanding: HashMap::new(),
+            rtts_ms: Vec::new(),
+        }
+    }
+
+    /// Id for the next client ping; its send time is recorded.
+    pub fn next_ping(&mut self) -> i32 {
+        self.next_id += 1;
+        self.outstanding.insert(self.next_id, self.clock.now());
+        self.next_id
+    }
+
+    pub fn on_pong(&mut self, id: i32) {
+        if let Some(sent_at) = self.outstanding.remove(&id) {
+            self.rtts_ms.push((self.clock.now() - sent_at) * 1000.0);
+        }
+    }
+
+    /// Adds the round trips to the endpoint's metrics; pings still in flight count as unanswered.
+    pub fn finish(self, endpoint_name: &str, comparator: &Mutex<Comparator>) {
+        let mut comparator = comparator.lock().unwrap();
+        let metrics = comparator.endpoint_metrics.entry(endpoint_name.to_string()).or_default();
+        metrics.ping_rtts_ms.extend(self.rtts_ms);
+        metrics.pings_unanswered += self.outstanding.len() as u64;
+    }
+}
+
+/// Client ping cadence for an endpoint: its own `ping_interval_secs`, else `rtt_interval_secs`.
+pub fn ping_interval_secs(endpoint: &Endpoint, config: &Config) -> Option<u64> {
+    endpoint.ping_interval_secs.or(config.rtt_interval_secs)
+}
+
+/// Records gaps between updates and stream failures for the post-run anomaly annotations, and runs
+/// the stale-stream watchdog when `watchdog` is configured.
+pub struct StreamMonitor {
+    clock: Arc<dyn Clock>,
+    stall_secs: f64,
+    watchdog_secs: Option<f64>,
+    last_update: Option<f64>,
+    last_message: f64,
+    down_since: Option<f64>,
+    events: Vec<StreamEvent>,
+    messages: u64,
+    self_profile: bool,
+}
+
+impl StreamMonitor {
+    pub fn new(clock: Arc<dyn Clock>, config: &Config) -> Self {
+        let now = clock.now();
+        Self {
+            clock,
+            stall_secs: config.stall_threshold_secs,
+            watchdog_secs: config.watchdog.as_ref().map(|watchdog| watchdog.timeout_secs as f64),
+            last_update: None,
+            last_message: now,
+            down_since: None,
+            events: Vec::new(),
+            messages: 0,
+            self_profile: config.self_profile,
+        }
+    }
+
+    /// Any message from the server, including keep-alive pings; feeds only the watchdog.
+    pub fn on_message(&mut self) {
+        let now = self.clock.now();
+        if let Some(since) = self.down_since.take() {
+            self.events.push(StreamEvent {
+                kind: StreamEventKind::Downtime,
+                at: since,
+                duration_secs: now - since,
+                detail: None,
+            });
+        }
+        self.last_message = now;
+    }
+
+    pub fn on_update(&mut self) {
+        self.messages += 1;
+        self.on_message();
+        let now = self.last_message;
+        // The wait for the first update is spin-up, which StartupTimer already covers
+        if let Some(last) = self.last_update {
+            if self.stall_secs > 0.0 && now - last >= self.stall_secs {
+                self.events.push(StreamEvent {
+                    kind: StreamEventKind::Stall,
+                    at: last,
+                    duration_secs: now - last,
+                    detail: None,
+                });
+            }
+        }
+        self.last_update = Some(now);
+    }
+
+    pub fn disconnected(&mut self, reason: String) {
+        self.events.push(StreamEvent {
+            kind: StreamEventKind::Disconnect,
+            at: self.clock.now(),
+            duration_secs: 0.0,
+            detail: Some(reason),
+        });
+    }
+
+    pub fn watchdog_enabled(&self) -> bool {
+        self.watchdog_secs.is_some()
+    }
+
+    /// Timer driving [`StreamMonitor::check_watchdog`]; ticks often enough to fire within a
+    /// second of the timeout.
+    pub fn watchdog_timer(&self) -> tokio::time::Interval {
+        let period = self.watchdog_secs.unwrap_or(1.0).clamp(0.1, 1.0);
+        let mut timer = tokio::time::interval(Duration::from_secs_f64(period));
+        timer.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+        timer
+    }
+
+    // AIDEV-NOTE: Downtime runs from the last message, not from when the watchdog noticed, and
+    // ends at the next message or when the stream is dropped (finish), so it also covers a stream
+    // that never delivered anything.
+    /// True when the stream has just gone stale; the caller may then reconnect.
+    pub fn check_watchdog(&mut self, label: &str) -> bool {
+        let Some(timeout) = self.watchdog_secs else {
+            return false;
+        };
+        if self.down_since.is_some() || self.clock.now() - self.last_message < timeout {
+            return false;
+        }
+        log::warn!("[{}] No updates for {:.0}s, stream considered down", label, timeout);
+        self.down_since = Some(self.last_message);
+        true
+    }
+
+    /// Appends the events to the endpoint's metrics; shards of one endpoint share the list.
+    pub fn finish(mut self, endpoint_name: &str, comparator: &Mutex<Comparator>) {
+        if self.self_profile {
+            profile::record_messages(comparator, endpoint_name, self.messages);
+        }
+        if let Some(since) = self.down_since.take() {
+            self.events.push(StreamEvent {
+                kind: StreamEventKind::Downtime,
+                at: since,
+                duration_secs: self.clock.now() - since,
+                detail: None,
+            });
+        }
+        if self.events.is_empty() {
+            return;
+        }
+        log::info!("[{}] {} stream events (stalls/disconnects/downtime)", endpoint_name, self.events.len());
+        comparator
+            .lock()
+            .unwrap()
+            .endpoint_metrics
+            .entry(endpoint_name.to_string())
+            .or_default()
+            .events
+            .extend(self.events);
+    }
 }
 
-pub fn create_provider(kind: &EndpointKind) -> Box<dyn GeyserProvider> {
-    match kind {
-        EndpointKind::Yellowstone => Box::new(yellowstone::YellowstoneProvider),
-        EndpointKind::YellowstoneAccounts => Box::new(yellowstone_accounts::YellowstoneAccountsProvider),
-        EndpointKind::Arpc => Box::new(arpc::ArpcProvider),
-        EndpointKind::Thor => Box::new(thor::ThorProvider),
-        EndpointKind::Shreder => Box::new(shreder::ShrederProvider),
-        EndpointKind::Jetstream => Box::new(jetstream::JetstreamProvider),
-        EndpointKind::ShredstreamProxy => Box::new(shredstream_proxy::ShredstreamProxyProvider),
+// AIDEV-NOTE: After the stop signal providers keep reading for `drain_ms` so sightings of
+// signatures other endpoints already delivered still complete their races; the Comparator
+// rejects new signatures while draining, so the window cannot grow the sample.
+/// Drain window a provider runs through between the stop signal and closing its stream.
+pub struct Drain {
+    window: Duration,
+    deadline: Option<tokio::time::Instant>,
+}
+
+impl Drain {
+    pub fn new(config: &Config) -> Self {
+        Self {
+            window: Duration::from_millis(config.drain_ms),
+            deadline: None,
+        }
+    }
+
+    /// Starts the window; false when there is none and the provider should stop right away.
+    pub fn begin(&mut self, label: &str) -> bool {
+        if self.window.is_zero() {
+            return false;
+        }
+        if self.deadline.is_none() {
+            log::info!("[{}] Draining for {} ms", label, self.window.as_millis());
+            self.deadline = Some(tokio::time::Instant::now() + self.window);
+        }
+        true
+    }
+
+    pub fn is_draining(&self) -> bool {
+        self.deadline.is_some()
+    }
+
+    pub async fn finished(&self) {
+        match self.deadline {
+            Some(deadline) => tokio::time::sleep_until(deadline).await,
+            None => std::future::pending().await,
+        }
+    }
+}
+
+// AIDEV-NOTE: tokio timers have millisecond resolution, so the per-message delay is accumulated and
+// slept off once at least a millisecond is owed. The consumer's average rate matches the setting,
+// and while it sleeps the stream is not polled, so the server sees a slow reader.
+/// Artificial processing time per message simulating a slow consumer (`consumer_delay_us`).
+pub struct ConsumerDelay {
+    delay: Duration,
+    owed: Duration,
+    messages: u64,
+    slept: Duration,
+}
+
+impl ConsumerDelay {
+    const MIN_SLEEP: Duration = Duration::from_millis(1);
+
+    pub fn new(config: &Config) -> Self {
+        Self {
+            delay: Duration::from_micros(config.consumer_delay_us),
+            owed: Duration::ZERO,
+            messages: 0,
+            slept: Duration::ZERO,
+        }
+    }
+
+    pub async fn consume(&mut self) {
+        if self.delay.is_zero() {
+            return;
+        }
+        self.messages += 1;
+        self.owed += self.delay;
+        if self.owed >= Self::MIN_SLEEP {
+            let started = tokio::time::Instant::now();
+            tokio::time::sleep(self.owed).await;
+            let elapsed = started.elapsed();
+            self.slept += elapsed;
+            self.owed = self.owed.saturating_sub(elapsed);
+        }
+    }
+
+    /// Adds the delayed messages to the endpoint's metrics; shards of one endpoint are summed.
+    pub fn finish(self, endpoint_name: &str, comparator: &Mutex<Comparator>) {
+        if self.delay.is_zero() {
+            return;
+        }
+        let mut comparator = comparator.lock().unwrap();
+        let stats = comparator
+            .endpoint_metrics
+            .entry(endpoint_name.to_string())
+            .or_default()
+            .consumer
+            .get_or_insert_with(ConsumerStats::default);
+        stats.messages += self.messages;
+        stats.delayed_secs += self.slept.as_secs_f64();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        clock::MockClock,
+        config::{ConfigFormat, ConfigToml},
+    };
+
+    fn config(extra: &str) -> Config {
+        let content = format!(
+            "endpoint = []\n[config]\ntransactions = 10\naccount = \"pubkey\"\ncommitment = \"processed\"\n{}\n",
+            extra
+        );
+        ConfigToml::parse(&content, ConfigFormat::Toml).unwrap().config
     }
-}
\ No newline at end of file
+
+    fn monitor(clock: &MockClock, extra: &str) -> StreamMonitor {
+        StreamMonitor::new(Arc::new(clock.clone()), &config(extra))
+    }
+
+    fn kinds(monitor: &StreamMonitor) -> Vec<StreamEventKind> {
+        monitor.events.iter().map(|event| event.kind).collect()
+    }
+
+    #[test]
+    fn first_update_is_not_a_stall() {
+        let clock = MockClock::new(1000.0);
+        let mut monitor = monitor(&clock, "stall_threshold_secs = 2.0");
+        clock.advance(30.0);
+        monitor.on_update();
+        assert!(monitor.events.is_empty());
+    }
+
+    #[test]
+    fn gap_at_threshold_is_a_stall_from_the_previous_update() {
+        let clock = MockClock::new(1000.0);
+        let mut monitor = monitor(&clock, "stall_threshold_secs = 2.0");
+        monitor.on_update();
+        clock.advance(1.5);
+        monitor.on_update();
+        assert!(monitor.events.is_empty());
+        clock.advance(2.0);
+        monitor.on_update();
+        assert_eq!(kinds(&monitor), [StreamEventKind::Stall]);
+        assert_eq!(monitor.events[0].at, 1001.5);
+        assert_eq!(monitor.events[0].duration_secs, 2.0);
+    }
+
+    #[test]
+    fn zero_threshold_records_no_stalls() {
+        let clock = MockClock::new(1000.0);
+        let mut monitor = monitor(&clock, "stall_threshold_secs = 0.0");
+        monitor.on_update();
+        clock.advance(60.0);
+        monitor.on_update();
+        assert!(monitor.events.is_empty());
+    }
+
+    #[test]
+    fn watchdog_is_off_without_config() {
+        let clock = MockClock::new(1000.0);
+        let mut monitor = monitor(&clock, "");
+        clock.advance(3600.0);
+        assert!(!monitor.watchdog_enabled());
+        assert!(!monitor.check_watchdog("test"));
+    }
+
+    #[test]
+    fn watchdog_fires_once_and_downtime_runs_from_the_last_message() {
+        let clock = MockClock::new(1000.0);
+        let mut monitor = monitor(&clock, "[config.watchdog]\ntimeout_secs = 5");
+        clock.advance(2.0);
+        monitor.on_message();
+        clock.advance(4.9);
+        assert!(!monitor.check_watchdog("test"));
+        clock.advance(0.1);
+        assert!(monitor.check_watchdog("test"));
+        clock.advance(10.0);
+        assert!(!monitor.check_watchdog("test"), "a stream already down is not reported again");
+
+        monitor.on_message();
+        assert_eq!(kinds(&monitor), [StreamEventKind::Downtime]);
+        assert_eq!(monitor.events[0].at, 1002.0);
+        assert_eq!(monitor.events[0].duration_secs, 15.0);
+
+        clock.advance(5.0);
+        assert!(monitor.check_watchdog("test"), "the watchdog rearms after the stream recovers");
+    }
+
+    #[test]
+    fn pings_keep_the_watchdog_quiet_but_not_the_stall_detector() {
+        let clock = MockClock::new(1000.0);
+        let mut monitor = monitor(&clock, "stall_threshold_secs = 2.0\n[config.watchdog]\ntimeout_secs = 5");
+        monitor.on_update();
+        for _ in 0..4 {
+            clock.advance(3.0);
+            monitor.on_message();
+            assert!(!monitor.check_watchdog("test"));
+        }
+        monitor.on_update();
+        assert_eq!(kinds(&monitor), [StreamEventKind::Stall]);
+        assert_eq!(monitor.events[0].duration_secs, 12.0);
+    }
+
+    #[test]
+    fn finish_closes_open_downtime() {
+        let clock = MockClock::new(1000.0);
+        let mut monitor = monitor(&clock, "[config.watchdog]\ntimeout_secs = 5");
+        clock.advance(5.0);
+        assert!(monitor.check_watchdog("test"));
+        clock.advance(7.0);
+        monitor.disconnected("reset".to_string());
+
+        let comparator = Mutex::new(Comparator::new(10));
+        monitor.finish("endpoint", &comparator);
+        let comparator = comparator.lock().unwrap();
+        let events = &comparator.endpoint_metrics["endpoint"].events;
+        let kinds: Vec<StreamEventKind> = events.iter().map(|event| event.kind).collect();
+        assert_eq!(kinds, [StreamEventKind::Disconnect, StreamEventKind::Downtime]);
+        assert_eq!(events[1].at, 1000.0);
+        assert_eq!(events[1].duration_secs, 12.0);
+    }
+}
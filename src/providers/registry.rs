@@ -0,0 +1,53 @@
+use std::{collections::BTreeMap, sync::RwLock};
+
+use crate::config::EndpointKind;
+
+use super::{
+    arpc, fumarole, jetstream, shreder, shredstream_proxy, thor, unix_socket, yellowstone, yellowstone_accounts,
+    GeyserProvider,
+};
+
+/// Builds a fresh provider for one endpoint.
+pub type ProviderFactory = fn() -> Box<dyn GeyserProvider>;
+
+// AIDEV-NOTE: One process-wide table so embedders can register their own providers before
+// `run_benchmark` and select them with `kind` in the config, exactly like the built-in ones.
+// Keys are normalized (`-` becomes `_`), so `yellowstone-accounts` and `yellowstone_accounts` are the same kind.
+lazy_static::lazy_static! {
+    static ref REGISTRY: RwLock<BTreeMap<String, ProviderFactory>> = RwLock::new(builtin());
+}
+
+fn builtin() -> BTreeMap<String, ProviderFactory> {
+    let mut factories: BTreeMap<String, ProviderFactory> = BTreeMap::new();
+    factories.insert("yellowstone".to_string(), || Box::new(yellowstone::YellowstoneProvider));
+    factories.insert("yellowstone_accounts".to_string(), || {
+        Box::new(yellowstone_accounts::YellowstoneAccountsProvider)
+    });
+    factories.insert("arpc".to_string(), || Box::new(arpc::ArpcProvider));
+    factories.insert("thor".to_string(), || Box::new(thor::ThorProvider));
+    factories.insert("shreder".to_string(), || Box::new(shreder::ShrederProvider));
+    factories.insert("jetstream".to_string(), || Box::new(jetstream::JetstreamProvider));
+    factories.insert("shredstream_proxy".to_string(), || Box::new(shredstream_proxy::ShredstreamProxyProvider));
+    factories.insert("unix_socket".to_string(), || Box::new(unix_socket::UnixSocketProvider));
+    factories.insert("fumarole".to_string(), || Box::new(fumarole::FumaroleProvider));
+    factories
+}
+
+/// Makes `kind` selectable from endpoint configs, replacing any provider registered under it.
+pub fn register_provider(kind: &str, factory: ProviderFactory) {
+    REGISTRY.write().unwrap().insert(EndpointKind::normalize(kind), factory);
+}
+
+/// Provider for `kind`, or None when nothing is registered under it.
+pub fn create_provider(kind: &EndpointKind) -> Option<Box<dyn GeyserProvider>> {
+    REGISTRY.read().unwrap().get(kind.as_str()).map(|factory| factory())
+}
+
+pub fn is_registered(kind: &EndpointKind) -> bool {
+    REGISTRY.read().unwrap().contains_key(kind.as_str())
+}
+
+/// Registered kinds in name order.
+pub fn registered_kinds() -> Vec<String> {
+    REGISTRY.read().unwrap().keys().cloned().collect()
+}
@@ -1,15 +1,21 @@
-use std::{ error::Error, sync::{ Arc, Mutex }, io::Write };
+use std::{ collections::{ HashMap, HashSet }, error::Error, sync::{ Arc, Mutex }, io::{ Cursor, Write } };
 use futures_util::StreamExt;
-use tokio::{ sync::broadcast, task };
+use serde::{ Deserialize, Serialize };
+use solana_entry::entry::Entry as SolanaEntry;
+use tokio::sync::broadcast;
 
 use crate::{
-    config::{ Config, Endpoint },
-    utils::{ Comparator, TransactionData, get_current_timestamp, open_log_file, write_log_entry },
+    clock::Clock,
+    config::{ BenchmarkMode, Config, Endpoint },
+    lookup_tables::{ LookupTables, TableLookup },
+    utils::{ Comparator, TransactionData, write_log_entry },
+    tsc,
 };
 
-use super::GeyserProvider;
+use super::{ connect_channel, entry_slot_key, start_when_ready, Capabilities, GeyserProvider, ConsumerDelay, Drain, ProviderError, ProviderStart, Ready, StartupTimer, StreamMonitor };
 
 pub mod shredstream {
+    #![allow(dead_code)]
     #![allow(clippy::clone_on_ref_ptr)]
     #![allow(clippy::missing_const_for_fn)]
 
@@ -26,8 +32,45 @@ use shredstream::{
 
 pub struct ShredstreamProxyProvider;
 
+// Slots behind the newest one whose tick counts are kept for late batches
+const TICK_HISTORY_SLOTS: u64 = 32;
+
+/// Position of a transaction's entry within its slot, from the decoded entry batch.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct EntryPosition {
+    /// Index of the entry within its batch
+    pub index: u32,
+    /// Index of the transaction within the entry
+    pub transaction_index: u32,
+    /// PoH hashes between the previous entry and this one
+    pub num_hashes: u64,
+    /// Tick entries (entries without transactions) this endpoint had delivered for the slot before this entry
+    pub ticks_before: u32,
+}
+
+/// Outcome of decoding entry batches; failures are split by cause.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct EntryDecodeStats {
+    pub batches: u64,
+    pub failed_batches: u64,
+    /// Failed batches where per-entry decoding still recovered a prefix of the entries
+    pub partial_batches: u64,
+    pub truncated: u64,
+    pub unknown_versions: u64,
+    pub malformed: u64,
+    pub recovered_entries: u64,
+    pub lost_entries: u64,
+}
+
 impl GeyserProvider for ShredstreamProxyProvider {
-    fn process(
+    fn capabilities(&self) -> Capabilities {
+        Capabilities {
+            entries: true,
+            ..Capabilities::TRANSACTIONS
+        }
+    }
+
+    fn start(
         &self,
         endpoint: Endpoint,
         config: Config,
@@ -35,16 +78,17 @@ impl GeyserProvider for ShredstreamProxyProvider {
         shutdown_rx: broadcast::Receiver<()>,
         start_time: f64,
         comparator: Arc<Mutex<Comparator>>
-    ) -> task::JoinHandle<Result<(), Box<dyn Error + Send + Sync>>> {
-        task::spawn(async move {
+    ) -> ProviderStart {
+        start_when_ready(|ready| {
             process_shreds_endpoint(
                 endpoint,
                 config,
                 shutdown_tx,
                 shutdown_rx,
                 start_time,
-                comparator
-            ).await
+                comparator,
+                ready
+            )
         })
     }
 }
@@ -55,48 +99,103 @@ async fn process_shreds_endpoint(
     shutdown_tx: broadcast::Sender<()>,
     mut shutdown_rx: broadcast::Receiver<()>,
     start_time: f64,
-    comparator: Arc<Mutex<Comparator>>
+    comparator: Arc<Mutex<Comparator>>,
+    mut ready: Ready
 ) -> Result<(), Box<dyn Error + Send + Sync>> {
     let mut transaction_count = 0;
-    let mut log_file = open_log_file(&endpoint.name)?;
+    let clock = comparator.lock().unwrap().clock.clone();
+    let lookup_tables = comparator.lock().unwrap().lookup_tables.clone();
+    let mut log_file = comparator.lock().unwrap().log_output.open(&endpoint.name)?;
 
-    log::info!("[{}] Connecting to endpoint: {}", endpoint.name, endpoint.url);
+    log::info!("[{}] Connecting to endpoint: {}", endpoint.name, endpoint.display_url());
 
-    let mut client = ShredstreamProxyClient::connect(endpoint.url.clone()).await?;
+    let mut startup = StartupTimer::start(clock.clone());
+    let mut monitor = StreamMonitor::new(clock.clone(), &config);
+    let mut watchdog_timer = monitor.watchdog_timer();
+    let mut drain = Drain::new(&config);
+    let mut consumer = ConsumerDelay::new(&config);
+    let mut client = ShredstreamProxyClient::new(connect_channel(&endpoint).await?)
+        .max_decoding_message_size(endpoint.decoding_limit());
+    startup.connected();
     log::info!("[{}] Connected successfully", endpoint.name);
 
     // AIDEV-NOTE: SubscribeEntries doesn't require filters like SubscribeTransactions
     let request = SubscribeEntriesRequest {};
     
-    let mut stream = client.subscribe_entries(request).await?.into_inner();
+    startup.subscribed();
+    let mut stream = client.subscribe_entries(request).await.map_err(ProviderError::from_status)?.into_inner();
+    ready.signal();
+    let mut seen_entry_slots = HashSet::new();
+    let mut slot_ticks = HashMap::new();
+    let mut decode_stats = EntryDecodeStats::default();
 
     'ploop: loop {
         tokio::select! {
-            _ = shutdown_rx.recv() => {
+            _ = shutdown_rx.recv(), if !drain.is_draining() => {
                 log::info!("[{}] Received stop signal...", endpoint.name);
+                if !drain.begin(&endpoint.name) {
+                    break;
+                }
+            }
+
+            _ = drain.finished(), if drain.is_draining() => {
+                log::info!("[{}] Drain window over", endpoint.name);
                 break;
             }
 
+            _ = watchdog_timer.tick(), if monitor.watchdog_enabled() => {
+                monitor.check_watchdog(&endpoint.name);
+            }
+
             message = stream.next() => {
                 if let Some(Ok(entry)) = message {
-                    // Process Entry message
-                    process_entry(
-                        entry,
-                        &endpoint,
-                        &config,
-                        &mut log_file,
-                        &mut transaction_count,
-                        start_time,
-                        &comparator,
-                        &shutdown_tx
-                    ).await?;
+                    startup.on_update();
+                    monitor.on_update();
+                    consumer.consume().await;
+                    if config.mode == BenchmarkMode::Entries {
+                        if seen_entry_slots.insert(entry.slot) {
+                            record_entry_slot(
+                                entry.slot,
+                                &endpoint,
+                                &mut log_file,
+                                &mut transaction_count,
+                                start_time,
+                                clock.as_ref(),
+                                &comparator
+                            )?;
+                        }
+                    } else {
+                        // Process Entry message
+                        process_entry(
+                            entry,
+                            &endpoint,
+                            &config,
+                            &mut log_file,
+                            &mut transaction_count,
+                            &mut decode_stats,
+                            &mut slot_ticks,
+                            lookup_tables.as_ref(),
+                            start_time,
+                            clock.as_ref(),
+                            &comparator,
+                            &shutdown_tx
+                        ).await?;
+                    }
                     
-                    let comp = comparator.lock().unwrap();
-                    if comp.get_valid_count() == config.transactions as usize {
+                    // Matches are timestamped inside the helpers; this is within microseconds
+                    if transaction_count > 0 {
+                        startup.on_match(clock.now());
+                    }
+
+                    let mut comp = comparator.lock().unwrap();
+                    if !drain.is_draining() && comp.target_reached() {
                         log::info!("Endpoint {} shutting down after {} transactions seen and {} by all workers",
                             endpoint.name, transaction_count, config.transactions);
+                        comp.begin_drain();
                         shutdown_tx.send(()).unwrap();
-                        break 'ploop;
+                        if !drain.begin(&endpoint.name) {
+                            break 'ploop;
+                        }
                     }
                 } else {
                     log::warn!("[{}] Stream ended or error occurred", endpoint.name);
@@ -106,66 +205,196 @@ async fn process_shreds_endpoint(
         }
     }
 
+    if decode_stats.batches > 0 {
+        if decode_stats.failed_batches > 0 {
+            log::warn!(
+                "[{}] {} of {} entry batches failed to decode ({} partially recovered)",
+                endpoint.name,
+                decode_stats.failed_batches,
+                decode_stats.batches,
+                decode_stats.partial_batches
+            );
+        }
+        comparator
+            .lock()
+            .unwrap()
+            .endpoint_metrics
+            .entry(endpoint.name.clone())
+            .or_default()
+            .entry_decode = Some(decode_stats);
+    }
+
+    monitor.finish(&endpoint.name, &comparator);
+    consumer.finish(&endpoint.name, &comparator);
+    startup.finish(&endpoint.name, &comparator);
     log::info!("[{}] Stream closed", endpoint.name);
     Ok(())
 }
 
+// AIDEV-NOTE: Entries mode only times the first batch of each slot, matching yellowstone's entry stream
+fn record_entry_slot(
+    slot: u64,
+    endpoint: &Endpoint,
+    log_file: &mut impl Write,
+    transaction_count: &mut usize,
+    start_time: f64,
+    clock: &dyn Clock,
+    comparator: &Arc<Mutex<Comparator>>
+) -> Result<(), Box<dyn Error + Send + Sync>> {
+    let timestamp = clock.now();
+    let tsc = tsc::read();
+    let signature = entry_slot_key(slot);
+
+    write_log_entry(log_file, timestamp, &endpoint.name, &signature, tsc)?;
+
+    comparator.lock().unwrap().add(
+        endpoint.name.clone(),
+        TransactionData {
+            timestamp,
+            signature: signature.clone(),
+            start_time,
+            tsc,
+            status: None,
+            slot: Some(slot),
+            entry: None,
+            groups: Vec::new(),
+        },
+    );
+
+    log::info!("[{:.3}] [{}] {}", timestamp, endpoint.name, signature);
+    *transaction_count += 1;
+    Ok(())
+}
+
+#[allow(clippy::too_many_arguments)]
 async fn process_entry(
     entry: Entry,
     endpoint: &Endpoint,
     config: &Config,
     log_file: &mut impl Write,
     transaction_count: &mut usize,
+    decode_stats: &mut EntryDecodeStats,
+    slot_ticks: &mut HashMap<u64, u32>,
+    lookup_tables: Option<&Arc<LookupTables>>,
     start_time: f64,
+    clock: &dyn Clock,
     comparator: &Arc<Mutex<Comparator>>,
     _shutdown_tx: &broadcast::Sender<()>
 ) -> Result<(), Box<dyn Error + Send + Sync>> {
-    // AIDEV-NOTE: Entry contains serialized Vec<Entry> - need to deserialize
-    use solana_entry::entry::Entry as SolanaEntry;
-    
     let slot = entry.slot;
-    let entries_bytes = entry.entries;
-    
-    // Deserialize the entries
-    if let Ok(entries) = bincode::deserialize::<Vec<SolanaEntry>>(&entries_bytes) {
-        for solana_entry in entries {
-            // Process transactions in each entry
-            for tx in solana_entry.transactions {
-                // Get all account keys from the transaction
-                let accounts: Vec<String> = match &tx.message {
-                    solana_sdk::message::VersionedMessage::Legacy(msg) => {
-                        msg.account_keys.iter().map(|key| key.to_string()).collect()
-                    },
-                    solana_sdk::message::VersionedMessage::V0(msg) => {
-                        msg.account_keys.iter().map(|key| key.to_string()).collect()
+    let entries = decode_entries(&endpoint.name, slot, &entry.entries, decode_stats);
+    slot_ticks.retain(|tracked, _| tracked.saturating_add(TICK_HISTORY_SLOTS) >= slot);
+    let ticks = slot_ticks.entry(slot).or_insert(0);
+
+    // AIDEV-NOTE: Ticks are counted across this endpoint's batches of the slot, so `ticks_before`
+    // places a transaction within the slot's ~400 ms; entries lost to a failed batch are not counted.
+    for (index, solana_entry) in entries.into_iter().enumerate() {
+        if solana_entry.is_tick() {
+            *ticks += 1;
+            continue;
+        }
+        let ticks_before = *ticks;
+        // Process transactions in each entry
+        for (transaction_index, tx) in solana_entry.transactions.into_iter().enumerate() {
+            // Get all account keys from the transaction
+            let accounts: Vec<String> = match &tx.message {
+                solana_sdk::message::VersionedMessage::Legacy(msg) => {
+                    msg.account_keys.iter().map(|key| key.to_string()).collect()
+                },
+                solana_sdk::message::VersionedMessage::V0(msg) => {
+                    let mut accounts: Vec<String> = msg.account_keys.iter().map(|key| key.to_string()).collect();
+                    if let Some(lookup_tables) = lookup_tables {
+                        accounts.extend(lookup_tables.resolve(msg.address_table_lookups.iter().map(|lookup| TableLookup {
+                            table: lookup.account_key.as_ref(),
+                            writable: &lookup.writable_indexes,
+                            readonly: &lookup.readonly_indexes,
+                        })));
                     }
-                };
-                
-                if accounts.contains(&config.account) {
-                    let timestamp = get_current_timestamp();
-                    let signature = tx.signatures[0].to_string();
-                    
-                    write_log_entry(log_file, timestamp, &endpoint.name, &signature)?;
-                    
-                    let mut comp = comparator.lock().unwrap();
-                    comp.add(
-                        endpoint.name.clone(),
-                        TransactionData {
-                            timestamp,
-                            signature: signature.clone(),
-                            start_time,
-                        },
-                    );
-                    
-                    log::info!("[{:.3}] [{}] Slot: {} Signature: {}", 
-                        timestamp, endpoint.name, slot, signature);
-                    *transaction_count += 1;
+                    accounts
                 }
+            };
+            
+            if config.watches_any(&accounts) && config.transaction_filter.admits(&accounts) {
+                let timestamp = clock.now();
+                let tsc = tsc::read();
+                let signature = tx.signatures[0].to_string();
+                
+                write_log_entry(log_file, timestamp, &endpoint.name, &signature, tsc)?;
+                
+                let mut comp = comparator.lock().unwrap();
+                comp.add(
+                    endpoint.name.clone(),
+                    TransactionData {
+                        timestamp,
+                        signature: signature.clone(),
+                        start_time,
+                        tsc,
+                        status: None,
+                        slot: Some(slot),
+                        entry: Some(EntryPosition {
+                            index: index as u32,
+                            transaction_index: transaction_index as u32,
+                            num_hashes: solana_entry.num_hashes,
+                            ticks_before,
+                        }),
+                        groups: config.filter_groups_for(&accounts),
+                    },
+                );
+                comp.tag_accounts(&signature, &accounts);
+                
+                log::info!("[{:.3}] [{}] Slot: {} Signature: {}", 
+                    timestamp, endpoint.name, slot, signature);
+                *transaction_count += 1;
             }
         }
-    } else {
-        log::debug!("[{}] Failed to deserialize entries for slot {}", endpoint.name, slot);
     }
     
     Ok(())
-}
\ No newline at end of file
+}
+
+// AIDEV-NOTE: Entry contains serialized Vec<Entry>. Entries carry no length framing of their own,
+// so after a failed batch only the entries before the bad one can be recovered.
+fn decode_entries(
+    endpoint_name: &str,
+    slot: u64,
+    bytes: &[u8],
+    stats: &mut EntryDecodeStats
+) -> Vec<SolanaEntry> {
+    stats.batches += 1;
+    let error = match bincode::deserialize::<Vec<SolanaEntry>>(bytes) {
+        Ok(entries) => return entries,
+        Err(error) => error,
+    };
+
+    stats.failed_batches += 1;
+    match &*error {
+        bincode::ErrorKind::Io(io) if io.kind() == std::io::ErrorKind::UnexpectedEof => {
+            stats.truncated += 1;
+        }
+        bincode::ErrorKind::Custom(message) if message.contains("message version") => {
+            stats.unknown_versions += 1;
+        }
+        _ => stats.malformed += 1,
+    }
+    log::debug!("[{}] Failed to deserialize entries for slot {}: {}", endpoint_name, slot, error);
+
+    let mut cursor = Cursor::new(bytes);
+    let Ok(expected) = bincode::deserialize_from::<_, u64>(&mut cursor) else {
+        return Vec::new();
+    };
+    let mut entries = Vec::new();
+    while (entries.len() as u64) < expected {
+        match bincode::deserialize_from::<_, SolanaEntry>(&mut cursor) {
+            Ok(entry) => entries.push(entry),
+            Err(_) => break,
+        }
+    }
+
+    if !entries.is_empty() {
+        stats.partial_batches += 1;
+        stats.recovered_entries += entries.len() as u64;
+    }
+    // A corrupt length prefix can claim billions of entries; cap by what the bytes could hold
+    stats.lost_entries += expected.saturating_sub(entries.len() as u64).min(bytes.len() as u64);
+    entries
+}
@@ -1,35 +1,38 @@
 use std::{
-    collections::HashMap,
     error::Error,
     sync::{Arc, Mutex},
 };
 
-use futures_util::{stream::StreamExt, sink::SinkExt};
-use tokio::{sync::broadcast, task};
+use futures_util::stream::StreamExt;
+use tokio::sync::broadcast;
 use publisher::{
     event_publisher_client::EventPublisherClient,
-    Empty, StreamResponse, SubscribeWalletRequest,
-    
+    Empty, StreamResponse,
 };
 use thor_streamer::types::{
-    MessageWrapper, SlotStatusEvent, TransactionEvent, TransactionEventWrapper, UpdateAccountEvent,
+    MessageWrapper,
     message_wrapper::EventMessage,
 };
 use prost::Message;
-use tonic::transport::Uri;
 use tonic::{Request, Streaming};
-use tokio_stream::Stream;
 
 use crate::{
     config::{Config, Endpoint},
-    utils::{Comparator, TransactionData, get_current_timestamp, open_log_file, write_log_entry},
+    lookup_tables::TableLookup,
+    utils::{Comparator, TransactionData, TransactionStatus, write_log_entry},
+    telemetry::PipelineTrace,
+    tsc,
 };
 
-use super::GeyserProvider;
+use super::{
+    connect_channel, start_when_ready, GeyserProvider, ConsumerDelay, ProviderError, ProviderStart, Drain, Ready, StartupTimer,
+    StreamMonitor,
+};
 
 pub mod thor_streamer {
     #![allow(clippy::clone_on_ref_ptr)]
     #![allow(clippy::missing_const_for_fn)]
+    #![allow(clippy::large_enum_variant)]
     
     pub mod types {
         include!(concat!(env!("OUT_DIR"), "/thor_streamer.types.rs"));
@@ -37,6 +40,7 @@ pub mod thor_streamer {
 }
 
 pub mod publisher {
+    #![allow(dead_code)]
     #![allow(clippy::clone_on_ref_ptr)]
     #![allow(clippy::missing_const_for_fn)]
     
@@ -49,7 +53,7 @@ pub mod publisher {
 pub struct ThorProvider;
 
 impl GeyserProvider for ThorProvider {
-    fn process(
+    fn start(
         &self,
         endpoint: Endpoint,
         config: Config,
@@ -57,8 +61,8 @@ impl GeyserProvider for ThorProvider {
         shutdown_rx: broadcast::Receiver<()>,
         start_time: f64,
         comparator: Arc<Mutex<Comparator>>,
-    ) -> task::JoinHandle<Result<(), Box<dyn Error + Send + Sync>>> {
-        task::spawn(async move {
+    ) -> ProviderStart {
+        start_when_ready(|ready| {
             process_thor_endpoint(
                 endpoint,
                 config,
@@ -66,8 +70,8 @@ impl GeyserProvider for ThorProvider {
                 shutdown_rx,
                 start_time,
                 comparator,
+                ready,
             )
-            .await
         })
     }
 }
@@ -79,22 +83,30 @@ async fn process_thor_endpoint(
     mut shutdown_rx: broadcast::Receiver<()>,
     start_time: f64,
     comparator: Arc<Mutex<Comparator>>,
+    mut ready: Ready,
 ) -> Result<(), Box<dyn Error + Send + Sync>> {
     let mut transaction_count = 0;
+    let clock = comparator.lock().unwrap().clock.clone();
+    let lookup_tables = comparator.lock().unwrap().lookup_tables.clone();
 
-    let mut log_file = open_log_file(&endpoint.name)?;
+    let mut log_file = comparator.lock().unwrap().log_output.open(&endpoint.name)?;
 
     log::info!(
         "[{}] Connecting to endpoint: {}",
         endpoint.name,
-        endpoint.url
+        endpoint.display_url()
     );
 
-    let grpc_url = &endpoint.url;
     let grpc_token = &endpoint.x_token;
     // Connect to the gRPC server
-    let uri = grpc_url.parse::<tonic::transport::Uri>()?;
-    let mut client = EventPublisherClient::connect(uri).await?;
+    let mut startup = StartupTimer::start(clock.clone());
+    let mut monitor = StreamMonitor::new(clock.clone(), &config);
+    let mut watchdog_timer = monitor.watchdog_timer();
+    let mut drain = Drain::new(&config);
+    let mut consumer = ConsumerDelay::new(&config);
+    let mut client = EventPublisherClient::new(connect_channel(&endpoint).await?)
+        .max_decoding_message_size(endpoint.decoding_limit());
+    startup.connected();
     log::info!("[{}] Connected successfully", endpoint.name);
 
     let mut request = Request::new(Empty {});
@@ -103,38 +115,79 @@ async fn process_thor_endpoint(
         .insert("authorization", grpc_token.parse()?);
     
     // Subscribe to transactions stream
+    startup.subscribed();
     let mut stream: Streaming<StreamResponse> = client
         .subscribe_to_transactions(request)
-        .await?
+        .await
+        .map_err(ProviderError::from_status)?
         .into_inner();
+    ready.signal();
 
     'ploop: loop {
         tokio::select! {
-            _ = shutdown_rx.recv() => {
+            _ = shutdown_rx.recv(), if !drain.is_draining() => {
                 log::info!("[{}] Received stop signal...", endpoint.name);
+                if !drain.begin(&endpoint.name) {
+                    break;
+                }
+            }
+
+            _ = drain.finished(), if drain.is_draining() => {
+                log::info!("[{}] Drain window over", endpoint.name);
                 break;
             }
 
+            _ = watchdog_timer.tick(), if monitor.watchdog_enabled() => {
+                monitor.check_watchdog(&endpoint.name);
+            }
+
             message = stream.next() => {
                 if let Some(Ok(msg)) = message {
+                    let mut trace = PipelineTrace::start(&endpoint.name);
+                    startup.on_update();
+                    monitor.on_update();
+                    consumer.consume().await;
                     if let Ok(message_wrapper) = MessageWrapper::decode(&*msg.data) {
                         if let Some(EventMessage::Transaction(transaction_event_wrapper)) = message_wrapper.event_message {
                             if let Some(transaction_event) = transaction_event_wrapper.transaction {
                                 if let Some(transaction) = transaction_event.transaction.as_ref() {
                                     if let Some(message) = transaction.message.as_ref() {
-                                        let accounts: Vec<String> = message.account_keys
+                                        let mut accounts: Vec<String> = message.account_keys
                                             .iter()
                                             .map(|key| bs58::encode(key).into_string())
                                             .collect();
+                                        if let Some(lookup_tables) = &lookup_tables {
+                                            // Thor sends the addresses the lookups loaded; the cache covers messages without them
+                                            match message.loaded_addresses.as_ref().filter(|loaded| !loaded.writable.is_empty() || !loaded.readonly.is_empty()) {
+                                                Some(loaded) => accounts.extend(
+                                                    loaded.writable.iter().chain(&loaded.readonly).map(|key| bs58::encode(key).into_string()),
+                                                ),
+                                                None => accounts.extend(lookup_tables.resolve(message.address_table_lookups.iter().map(|lookup| TableLookup {
+                                                    table: &lookup.account_key,
+                                                    writable: &lookup.writable_indexes,
+                                                    readonly: &lookup.readonly_indexes,
+                                                }))),
+                                            }
+                                        }
                                             
-                                        if accounts.contains(&config.account) {
-                                            let timestamp = get_current_timestamp();
+                                        if config.watches_any(&accounts) && config.transaction_filter.admits(&accounts) {
+                                            let timestamp = clock.now();
+                                            startup.on_match(timestamp);
+                                            trace.filtered();
+                                            let tsc = tsc::read();
                                             let signature = bs58::encode(&transaction_event.signature).into_string();
                                             let slot = transaction_event.slot;
+                                            let status = transaction_event.transaction_status_meta.as_ref().map(|meta| TransactionStatus {
+                                                failed: meta.is_status_err,
+                                                fee: Some(meta.fee),
+                                                compute_units: None,
+                                                error: (!meta.error_info.is_empty()).then(|| meta.error_info.clone()),
+                                            });
 
-                                            write_log_entry(&mut log_file, timestamp, &endpoint.name, &signature)?;
+                                            write_log_entry(&mut log_file, timestamp, &endpoint.name, &signature, tsc)?;
 
                                             let mut comp = comparator.lock().unwrap();
+                                            trace.locked();
 
                                             comp.add(
                                                 endpoint.name.clone(),
@@ -142,14 +195,24 @@ async fn process_thor_endpoint(
                                                     timestamp,
                                                     signature: signature.clone(),
                                                     start_time,
+                                                    tsc,
+                                                    status,
+                                                    slot: Some(slot),
+                                                    entry: None,
+                                                    groups: config.filter_groups_for(&accounts),
                                                 },
                                             );
+                                            trace.inserted(&signature);
+                                            comp.tag_accounts(&signature, &accounts);
 
-                                            if comp.get_valid_count() == config.transactions as usize {
+                                            if !drain.is_draining() && comp.target_reached() {
                                                 log::info!("Endpoint {} shutting down after {} transactions seen and {} by all workers",
                                                     endpoint.name, transaction_count, config.transactions);
+                                                comp.begin_drain();
                                                 shutdown_tx.send(()).unwrap();
-                                                break 'ploop;
+                                                if !drain.begin(&endpoint.name) {
+                                                    break 'ploop;
+                                                }
                                             }
 
                                             log::info!("[{:.3}] [{}] {}", timestamp, endpoint.name, signature);
@@ -165,6 +228,9 @@ async fn process_thor_endpoint(
         }
     }
 
+    monitor.finish(&endpoint.name, &comparator);
+    consumer.finish(&endpoint.name, &comparator);
+    startup.finish(&endpoint.name, &comparator);
     log::info!("[{}] Stream closed", endpoint.name);
     Ok(())
 }
\ No newline at end of file
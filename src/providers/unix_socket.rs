@@ -0,0 +1,283 @@
+use std::{
+    error::Error,
+    pin::Pin,
+    sync::{Arc, Mutex},
+};
+
+use bytes::{Buf, BytesMut};
+use prost::Message;
+use tokio::{
+    io::{AsyncRead, AsyncReadExt},
+    sync::broadcast,
+};
+use yellowstone_grpc_proto::geyser::{subscribe_update::UpdateOneof, SubscribeUpdate};
+
+use crate::{
+    config::{Config, Endpoint},
+    utils::{Comparator, TransactionData, TransactionStatus, write_log_entry},
+    telemetry::PipelineTrace,
+    tsc,
+};
+
+use super::{
+    yellowstone::decode_transaction_error, start_when_ready, GeyserProvider, ConsumerDelay, Drain, ProviderStart, Ready,
+    StartupTimer, StreamMonitor,
+};
+
+// Guards against a corrupt length prefix making the reader buffer without bound
+const MAX_FRAME_LEN: usize = 64 * 1024 * 1024;
+const MAX_VARINT_LEN: usize = 10;
+
+type SocketReader = Pin<Box<dyn AsyncRead + Send>>;
+
+pub struct UnixSocketProvider;
+
+impl GeyserProvider for UnixSocketProvider {
+    fn start(
+        &self,
+        endpoint: Endpoint,
+        config: Config,
+        shutdown_tx: broadcast::Sender<()>,
+        shutdown_rx: broadcast::Receiver<()>,
+        start_time: f64,
+        comparator: Arc<Mutex<Comparator>>,
+    ) -> ProviderStart {
+        start_when_ready(|ready| {
+            process_unix_socket_endpoint(
+                endpoint,
+                config,
+                shutdown_tx,
+                shutdown_rx,
+                start_time,
+                comparator,
+                ready,
+            )
+        })
+    }
+}
+
+/// Opens `url` (a path, optionally `unix://`-prefixed) as a Unix socket, or as a named pipe when
+/// the path is a FIFO.
+#[cfg(unix)]
+async fn open(url: &str) -> Result<SocketReader, Box<dyn Error + Send + Sync>> {
+    use std::os::unix::fs::FileTypeExt;
+
+    let path = url.strip_prefix("unix://").unwrap_or(url);
+    let connect_error = |e: std::io::Error| super::ProviderError::Connect(Box::new(e));
+    if tokio::fs::metadata(path).await.map_err(connect_error)?.file_type().is_fifo() {
+        Ok(Box::pin(tokio::net::unix::pipe::OpenOptions::new().open_receiver(path).map_err(connect_error)?))
+    } else {
+        let stream = tokio::net::UnixStream::connect(path).await.map_err(connect_error)?;
+        Ok(Box::pin(stream))
+    }
+}
+
+#[cfg(not(unix))]
+async fn open(_url: &str) -> Result<SocketReader, Box<dyn Error + Send + Sync>> {
+    Err("unix_socket endpoints are only supported on Unix platforms".into())
+}
+
+// AIDEV-NOTE: The plugin writes yellowstone `SubscribeUpdate` messages, each prefixed with its
+// varint length (prost's `encode_length_delimited`); there is no subscribe request, so filtering
+// happens here. Partial frames stay in `buf`, which keeps `next` safe to cancel inside select!.
+struct FrameReader {
+    reader: SocketReader,
+    buf: BytesMut,
+}
+
+impl FrameReader {
+    fn new(reader: SocketReader) -> Self {
+        Self {
+            reader,
+            buf: BytesMut::with_capacity(64 * 1024),
+        }
+    }
+
+    /// Next update, or None when the plugin closed the stream between frames.
+    async fn next(&mut self) -> Result<Option<SubscribeUpdate>, Box<dyn Error + Send + Sync>> {
+        loop {
+            if let Some((len, header_len)) = frame_header(&self.buf)? {
+                if self.buf.len() >= header_len + len {
+                    self.buf.advance(header_len);
+                    let frame = self.buf.split_to(len);
+                    return Ok(Some(SubscribeUpdate::decode(frame)?));
+                }
+            }
+            if self.reader.read_buf(&mut self.buf).await? == 0 {
+                if self.buf.is_empty() {
+                    return Ok(None);
+                }
+                return Err(format!("stream ended inside a frame ({} bytes buffered)", self.buf.len()).into());
+            }
+        }
+    }
+}
+
+/// Frame length and the size of its varint prefix; None until the prefix is complete.
+fn frame_header(buf: &[u8]) -> Result<Option<(usize, usize)>, Box<dyn Error + Send + Sync>> {
+    let mut len = 0u64;
+    for (index, byte) in buf.iter().take(MAX_VARINT_LEN).enumerate() {
+        len |= u64::from(byte & 0x7f) << (7 * index);
+        if byte & 0x80 == 0 {
+            let len = len as usize;
+            if len > MAX_FRAME_LEN {
+                return Err(format!("frame of {} bytes exceeds the {} byte limit", len, MAX_FRAME_LEN).into());
+            }
+            return Ok(Some((len, index + 1)));
+        }
+    }
+    if buf.len() >= MAX_VARINT_LEN {
+        return Err("invalid frame length prefix".into());
+    }
+    Ok(None)
+}
+
+async fn process_unix_socket_endpoint(
+    endpoint: Endpoint,
+    config: Config,
+    shutdown_tx: broadcast::Sender<()>,
+    mut shutdown_rx: broadcast::Receiver<()>,
+    start_time: f64,
+    comparator: Arc<Mutex<Comparator>>,
+    mut ready: Ready,
+) -> Result<(), Box<dyn Error + Send + Sync>> {
+    let mut transaction_count = 0;
+    let clock = comparator.lock().unwrap().clock.clone();
+
+    let mut log_file = comparator.lock().unwrap().log_output.open(&endpoint.name)?;
+
+    log::info!("[{}] Connecting to socket: {}", endpoint.name, endpoint.display_url());
+
+    let mut startup = StartupTimer::start(clock.clone());
+    let mut monitor = StreamMonitor::new(clock.clone(), &config);
+    let mut watchdog_timer = monitor.watchdog_timer();
+    let mut drain = Drain::new(&config);
+    let mut consumer = ConsumerDelay::new(&config);
+    let mut frames = FrameReader::new(open(&endpoint.url).await?);
+    startup.connected();
+    // The plugin streams unprompted, so the subscription starts with the connection
+    startup.subscribed();
+    log::info!("[{}] Connected successfully", endpoint.name);
+    ready.signal();
+
+    'ploop: loop {
+        tokio::select! {
+            _ = shutdown_rx.recv(), if !drain.is_draining() => {
+                log::info!("[{}] Received stop signal...", endpoint.name);
+                if !drain.begin(&endpoint.name) {
+                    break;
+                }
+            }
+
+            _ = drain.finished(), if drain.is_draining() => {
+                log::info!("[{}] Drain window over", endpoint.name);
+                break;
+            }
+
+            _ = watchdog_timer.tick(), if monitor.watchdog_enabled() => {
+                monitor.check_watchdog(&endpoint.name);
+            }
+
+            frame = frames.next() => {
+                let msg = match frame {
+                    Ok(Some(msg)) => msg,
+                    Ok(None) => {
+                        log::info!("[{}] Socket closed by the plugin", endpoint.name);
+                        monitor.disconnected("socket closed by the plugin".to_string());
+                        break;
+                    }
+                    Err(e) => {
+                        log::error!("[{}] Error reading frame: {}", endpoint.name, e);
+                        monitor.disconnected(e.to_string());
+                        break;
+                    }
+                };
+                monitor.on_message();
+                consumer.consume().await;
+                if matches!(msg.update_oneof, Some(UpdateOneof::Ping(_)) | Some(UpdateOneof::Pong(_))) {
+                    continue;
+                }
+                let mut trace = PipelineTrace::start(&endpoint.name);
+                startup.on_update();
+                monitor.on_update();
+                let Some(UpdateOneof::Transaction(tx_msg)) = msg.update_oneof else {
+                    continue;
+                };
+                let Some(tx) = tx_msg.transaction else {
+                    continue;
+                };
+                let Some(message) = tx.transaction.as_ref().and_then(|transaction| transaction.message.as_ref()) else {
+                    continue;
+                };
+                let mut accounts = message
+                    .account_keys
+                    .iter()
+                    .map(|key| bs58::encode(key).into_string())
+                    .collect::<Vec<String>>();
+                if let Some(meta) = tx.meta.as_ref().filter(|_| config.lookup_tables.is_some()) {
+                    accounts.extend(
+                        meta.loaded_writable_addresses
+                            .iter()
+                            .chain(&meta.loaded_readonly_addresses)
+                            .map(|key| bs58::encode(key).into_string()),
+                    );
+                }
+                if !config.watches_any(&accounts) || !config.transaction_filter.admits(&accounts) {
+                    continue;
+                }
+
+                let timestamp = clock.now();
+                startup.on_match(timestamp);
+                trace.filtered();
+                let tsc = tsc::read();
+                let status = tx.meta.as_ref().map(|meta| TransactionStatus {
+                    failed: meta.err.is_some(),
+                    fee: Some(meta.fee),
+                    compute_units: meta.compute_units_consumed,
+                    error: meta.err.as_ref().map(|err| decode_transaction_error(&err.err)),
+                });
+                let signature = bs58::encode(&tx.signature).into_string();
+
+                write_log_entry(&mut log_file, timestamp, &endpoint.name, &signature, tsc)?;
+
+                let mut comp = comparator.lock().unwrap();
+                trace.locked();
+
+                comp.add(
+                    endpoint.name.clone(),
+                    TransactionData {
+                        timestamp,
+                        signature: signature.clone(),
+                        start_time,
+                        tsc,
+                        status,
+                        slot: Some(tx_msg.slot),
+                        entry: None,
+                        groups: config.filter_groups_for(&accounts),
+                    },
+                );
+                trace.inserted(&signature);
+                comp.tag_accounts(&signature, &accounts);
+
+                if !drain.is_draining() && comp.target_reached() {
+                    log::info!("Endpoint {} shutting down after {} transactions seen and {} by all workers",
+                        endpoint.name, transaction_count, config.transactions);
+                    comp.begin_drain();
+                    shutdown_tx.send(()).unwrap();
+                    if !drain.begin(&endpoint.name) {
+                        break 'ploop;
+                    }
+                }
+
+                log::info!("[{:.3}] [{}] {}", timestamp, endpoint.name, signature);
+                transaction_count += 1;
+            }
+        }
+    }
+
+    monitor.finish(&endpoint.name, &comparator);
+    consumer.finish(&endpoint.name, &comparator);
+    startup.finish(&endpoint.name, &comparator);
+    log::info!("[{}] Stream closed", endpoint.name);
+    Ok(())
+}
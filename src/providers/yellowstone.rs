@@ -1,31 +1,66 @@
 use std::{
-    collections::HashMap,
+    any::Any,
+    collections::{HashMap, HashSet},
     error::Error,
+    pin::Pin,
     sync::{Arc, Mutex},
+    time::Duration,
 };
 
-use futures_util::{stream::StreamExt, sink::SinkExt};
-use tokio::{sync::broadcast, task};
-use yellowstone_grpc_client::GeyserGrpcClient;
+use futures::channel::mpsc;
+use solana_sdk::transaction::TransactionError;
+use futures_util::{stream::{Stream, StreamExt}, sink::{Sink, SinkExt}};
+use tokio::sync::broadcast;
 use yellowstone_grpc_proto::{
     geyser::{
-        subscribe_update::UpdateOneof, SubscribeRequest, SubscribeRequestPing,
+        geyser_client::GeyserClient, subscribe_update::UpdateOneof, GetSlotRequest, SlotStatus, SubscribeRequest,
+        SubscribeRequestFilterBlocks, SubscribeRequestFilterEntry, SubscribeRequestFilterSlots, SubscribeRequestPing,
+        SubscribeUpdate,
     },
     prelude::SubscribeRequestFilterTransactions,
-    tonic::transport::ClientTlsConfig,
+    tonic::{
+        service::interceptor::InterceptedService,
+        transport::{Channel, ClientTlsConfig},
+        Code, Status,
+    },
 };
 
 use crate::{
-    config::{Config, Endpoint},
-    utils::{Comparator, TransactionData, get_current_timestamp, open_log_file, write_log_entry},
+    config::{BenchmarkMode, Config, Endpoint},
+    frame_timing::{FrameTimingService, FrameTracker},
+    markets,
+    slot_status::slot_status_key,
+    utils::{
+        BlockSizeStats, Comparator, ReplayMetrics, ShardMetrics, TransactionData, TransactionStatus,
+        write_log_entry,
+    },
+    telemetry::PipelineTrace,
+    token_refresh, tsc,
 };
 
-use super::GeyserProvider;
+use super::{
+    apply_subscribe_overrides, block_slot_key, channel_builder, connect_yellowstone, entry_slot_key, open_channel, start_when_ready,
+    AbortOnDrop, Capabilities, GeyserProvider, spawn_provider, MetadataInterceptor, ping_interval_secs, WatchedKeys, ConsumerDelay, Drain,
+    PingTracker, ProviderError, ProviderStart, ProviderTiming, Ready, StartupTimer, StreamMonitor,
+    DEFAULT_PING_INTERVAL_SECS,
+};
 
 pub struct YellowstoneProvider;
 
 impl GeyserProvider for YellowstoneProvider {
-    fn process(
+    fn capabilities(&self) -> Capabilities {
+        Capabilities {
+            transactions: true,
+            slots: true,
+            entries: true,
+            blocks: true,
+            transaction_status: true,
+            throughput: true,
+            ..Capabilities::default()
+        }
+    }
+
+    fn start(
         &self,
         endpoint: Endpoint,
         config: Config,
@@ -33,117 +68,487 @@ impl GeyserProvider for YellowstoneProvider {
         shutdown_rx: broadcast::Receiver<()>,
         start_time: f64,
         comparator: Arc<Mutex<Comparator>>,
-    ) -> task::JoinHandle<Result<(), Box<dyn Error + Send + Sync>>> {
-        task::spawn(async move {
-            process_yellowstone_endpoint(
-                endpoint,
-                config,
-                shutdown_tx,
-                shutdown_rx,
-                start_time,
-                comparator,
-            )
-                .await
+    ) -> ProviderStart {
+        start_when_ready(|mut ready| async move {
+            let watched = config.watched_accounts();
+            let shards = match config.mode {
+                BenchmarkMode::Latency | BenchmarkMode::Status => endpoint.shards.min(watched.len()),
+                _ => 1,
+            };
+            if shards <= 1 && endpoint.connections <= 1 {
+                return run_yellowstone_endpoint(
+                    endpoint,
+                    config,
+                    watched,
+                    None,
+                    None,
+                    shutdown_tx,
+                    shutdown_rx,
+                    start_time,
+                    comparator,
+                    ready,
+                )
+                    .await;
+            }
+
+            let mut subscribed = Vec::new();
+            let handles: Vec<_> = if endpoint.connections > 1 {
+                // AIDEV-NOTE: Every connection subscribes with the full filter; load balancers may
+                // route each to a different backend, and the comparator keeps the earliest sighting.
+                log::info!(
+                    "[{}] Opening {} parallel connections",
+                    endpoint.name,
+                    endpoint.connections
+                );
+                (0..endpoint.connections)
+                    .map(|connection| {
+                        let (connection_ready, connection_subscribed) = Ready::pair();
+                        subscribed.push(connection_subscribed);
+                        spawn_provider(endpoint.name.clone(), config.self_profile, comparator.clone(), run_yellowstone_endpoint(
+                            endpoint.clone(),
+                            config.clone(),
+                            watched.clone(),
+                            None,
+                            Some(connection),
+                            shutdown_tx.clone(),
+                            shutdown_tx.subscribe(),
+                            start_time,
+                            comparator.clone(),
+                            connection_ready,
+                        ))
+                    })
+                    .collect()
+            } else {
+                // AIDEV-NOTE: Accounts are dealt round-robin; each shard is its own subscription and
+                // task, all reporting under the endpoint name so the comparator merges them.
+                let account_count = watched.len();
+                let mut shard_accounts = vec![Vec::new(); shards];
+                for (index, account) in watched.into_iter().enumerate() {
+                    shard_accounts[index % shards].push(account);
+                }
+                log::info!(
+                    "[{}] Splitting {} accounts across {} shards",
+                    endpoint.name,
+                    account_count,
+                    shards
+                );
+
+                shard_accounts
+                    .into_iter()
+                    .enumerate()
+                    .map(|(shard, accounts)| {
+                        let (shard_ready, shard_subscribed) = Ready::pair();
+                        subscribed.push(shard_subscribed);
+                        spawn_provider(endpoint.name.clone(), config.self_profile, comparator.clone(), run_yellowstone_endpoint(
+                            endpoint.clone(),
+                            config.clone(),
+                            accounts,
+                            Some(shard),
+                            None,
+                            shutdown_tx.clone(),
+                            shutdown_tx.subscribe(),
+                            start_time,
+                            comparator.clone(),
+                            shard_ready,
+                        ))
+                    })
+                    .collect()
+            };
+            drop(shutdown_rx);
+            let _abort_on_drop: Vec<_> = handles.iter().map(|handle| AbortOnDrop(handle.abort_handle())).collect();
+
+            // The endpoint is up once every shard or connection has subscribed or given up, and at
+            // least one subscribed; when none did, their errors fail the start instead
+            let results = futures::future::join_all(subscribed).await;
+            if results.iter().any(Result::is_ok) {
+                ready.signal();
+            }
+
+            let mut result = Ok(());
+            for handle in handles {
+                if let Err(e) = handle.await? {
+                    log::error!("[{}] Subscription error: {:?}", endpoint.name, e);
+                    result = Err(e);
+                }
+            }
+
+            if let Some(metrics) = comparator.lock().unwrap().endpoint_metrics.get_mut(&endpoint.name) {
+                metrics.shards.sort_by_key(|shard| shard.shard);
+            }
+            result
         })
     }
 }
 
-async fn process_yellowstone_endpoint(
-    endpoint: Endpoint,
+/// Why a connection ended and whether it should be opened again.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Restart {
+    No,
+    /// The watchdog saw the stream stall
+    Watchdog,
+    /// The server rejected the token mid-stream and `token_refresh` is configured
+    Unauthenticated,
+}
+
+// AIDEV-NOTE: A watchdog reconnect reruns the whole connect/subscribe cycle with the same shutdown
+// receiver, so a stop signal sent while reconnecting is still seen. With `token_refresh`, an
+// UNAUTHENTICATED answer at subscribe time or mid-stream fetches a new token and runs the cycle
+// again; only `max_attempts` rejections in a row without a stream in between end the endpoint.
+#[allow(clippy::too_many_arguments)]
+async fn run_yellowstone_endpoint(
+    mut endpoint: Endpoint,
     config: Config,
+    accounts: Vec<String>,
+    shard: Option<usize>,
+    connection: Option<usize>,
     shutdown_tx: broadcast::Sender<()>,
     mut shutdown_rx: broadcast::Receiver<()>,
     start_time: f64,
     comparator: Arc<Mutex<Comparator>>,
+    mut ready: Ready,
 ) -> Result<(), Box<dyn Error + Send + Sync>> {
+    if endpoint.token_refresh.as_ref().is_some_and(|refresh| refresh.on_start) {
+        token_refresh::refresh(&mut endpoint).await?;
+    }
+    let mut rejected = 0;
+    loop {
+        let result = process_yellowstone_endpoint(
+            &endpoint,
+            &config,
+            accounts.clone(),
+            shard,
+            connection,
+            &shutdown_tx,
+            &mut shutdown_rx,
+            start_time,
+            &comparator,
+            &mut ready,
+        )
+        .await;
+        match result {
+            Ok(Restart::No) => return Ok(()),
+            Ok(Restart::Watchdog) => {
+                log::warn!("[{}] Reconnecting after watchdog timeout", endpoint.name);
+                continue;
+            }
+            // The token worked until it expired, so this is a fresh start
+            Ok(Restart::Unauthenticated) => rejected = 1,
+            Err(e) if endpoint.token_refresh.is_some() && token_refresh::is_unauthenticated(&*e) => {
+                rejected += 1;
+                let max_attempts = endpoint.token_refresh.as_ref().map_or(0, |refresh| refresh.max_attempts);
+                if rejected > max_attempts {
+                    return Err(ProviderError::Auth(Status::unauthenticated(format!(
+                        "[{}] token rejected after {} refreshes in a row: {}",
+                        endpoint.name, max_attempts, e
+                    )))
+                    .into());
+                }
+            }
+            Err(e) => return Err(e),
+        }
+        log::warn!("[{}] Token rejected, refreshing and resubscribing", endpoint.name);
+        token_refresh::refresh(&mut endpoint).await?;
+        comparator
+            .lock()
+            .unwrap()
+            .endpoint_metrics
+            .entry(endpoint.name.clone())
+            .or_default()
+            .token_refreshes += 1;
+    }
+}
+
+/// Runs one connection and tells whether it should be opened again.
+#[allow(clippy::too_many_arguments)]
+async fn process_yellowstone_endpoint(
+    endpoint: &Endpoint,
+    config: &Config,
+    accounts: Vec<String>,
+    shard: Option<usize>,
+    connection: Option<usize>,
+    shutdown_tx: &broadcast::Sender<()>,
+    shutdown_rx: &mut broadcast::Receiver<()>,
+    start_time: f64,
+    comparator: &Arc<Mutex<Comparator>>,
+    ready: &mut Ready,
+) -> Result<Restart, Box<dyn Error + Send + Sync>> {
+    let reconnect_on_stall = config.watchdog.as_ref().is_some_and(|watchdog| watchdog.reconnect);
+    let mut reconnect = false;
+    let mut unauthenticated = false;
     let mut transaction_count = 0;
+    let clock = comparator.lock().unwrap().clock.clone();
+    let mut message_count = 0;
 
-    let mut log_file = open_log_file(&endpoint.name)?;
+    let mut log_file = comparator.lock().unwrap().log_output.open(&endpoint.name)?;
+    let label = match (shard, connection) {
+        (Some(shard), _) => format!("{}#{}", endpoint.name, shard),
+        (_, Some(connection)) => format!("{}@{}", endpoint.name, connection),
+        (None, None) => endpoint.name.clone(),
+    };
+    // Per-endpoint measurements come from the first shard or connection only
+    let primary = shard.unwrap_or(0) == 0 && connection.unwrap_or(0) == 0;
 
     log::info!(
         "[{}] Connecting to endpoint: {}",
-        endpoint.name,
-        endpoint.url
+        label,
+        endpoint.display_url()
     );
 
-    let mut client = GeyserGrpcClient::build_from_shared(endpoint.url)?
-        .x_token(Some(endpoint.x_token))?
-        .tls_config(ClientTlsConfig::new().with_native_roots())?
-        .connect()
-        .await?;
-
-    log::info!("[{}] Connected successfully", endpoint.name);
-
-    let (mut subscribe_tx, mut stream) = client.subscribe().await?;
+    let frame_tracker = (endpoint.frame_timing && primary).then(FrameTracker::default);
+    let mut startup = StartupTimer::start(clock.clone());
+    let mut timing = ProviderTiming::new(clock.clone());
+    let mut monitor = StreamMonitor::new(clock.clone(), config);
+    let mut watchdog_timer = monitor.watchdog_timer();
+    let mut drain = Drain::new(config);
+    let mut consumer = ConsumerDelay::new(config);
     let commitment: yellowstone_grpc_proto::geyser::CommitmentLevel = config.commitment.into();
+    // The replay is measured up to the endpoint's tip when it subscribed
+    let tip_request = (config.from_slot.is_some() && primary).then_some(GetSlotRequest {
+        commitment: Some(commitment as i32),
+    });
+    let (mut subscribe_tx, mut stream, _client, tip_slot): (SubscribeSink, SubscribeStream, Box<dyn Any + Send>, Option<u64>) =
+        match &frame_tracker {
+            Some(tracker) => {
+                let mut client = connect_with_frame_timing(endpoint, tracker.clone()).await?;
+                startup.connected();
+                log::info!("[{}] Connected successfully (frame timing enabled)", label);
+                let tip_slot = match tip_request {
+                    Some(request) => Some(client.get_slot(request).await?.into_inner().slot),
+                    None => None,
+                };
+                let (subscribe_tx, subscribe_rx) = mpsc::unbounded();
+                let stream = client.subscribe(subscribe_rx).await.map_err(ProviderError::from_status)?.into_inner();
+                (Box::pin(subscribe_tx), Box::pin(stream), Box::new(client), tip_slot)
+            }
+            None => {
+                let mut client = connect_yellowstone(endpoint).await?;
+                startup.connected();
+                log::info!("[{}] Connected successfully", label);
+                let tip_slot = match tip_request {
+                    Some(request) => Some(client.get_slot(request).await?.into_inner().slot),
+                    None => None,
+                };
+                let (subscribe_tx, subscribe_rx) = mpsc::unbounded();
+                let stream = client.subscribe(subscribe_rx).await.map_err(ProviderError::from_status)?.into_inner();
+                (Box::pin(subscribe_tx), Box::pin(stream), Box::new(client), tip_slot)
+            }
+        };
+    ready.signal();
 
+    let mut slots = HashMap::new();
     let mut transactions = HashMap::new();
-    transactions.insert(
-        "account".to_string(),
-        SubscribeRequestFilterTransactions {
-            account_include: vec![config.account.clone()],
-            account_exclude: vec![],
-            account_required: vec![],
-            ..Default::default()
-        },
-    );
+    let mut transactions_status = HashMap::new();
+    let mut entry = HashMap::new();
+    let mut blocks = HashMap::new();
+    let transaction_filter = endpoint.transaction_filter(config);
+    match config.mode {
+        BenchmarkMode::Entries => {
+            entry.insert("entries".to_string(), SubscribeRequestFilterEntry {});
+        }
+        // Every status of every slot, not just those reaching the configured commitment
+        BenchmarkMode::Slots => {
+            slots.insert(
+                "slots".to_string(),
+                SubscribeRequestFilterSlots {
+                    filter_by_commitment: Some(false),
+                    ..Default::default()
+                },
+            );
+        }
+        // Every block, not just those touching the watched accounts
+        BenchmarkMode::Blocks => {
+            blocks.insert(
+                "blocks".to_string(),
+                SubscribeRequestFilterBlocks {
+                    account_include: vec![],
+                    include_transactions: Some(config.blocks.include_transactions),
+                    include_accounts: Some(false),
+                    include_entries: Some(false),
+                },
+            );
+        }
+        BenchmarkMode::Status => {
+            transactions_status.insert(
+                "account".to_string(),
+                SubscribeRequestFilterTransactions {
+                    account_include: accounts.clone(),
+                    account_exclude: vec![],
+                    account_required: vec![],
+                    vote: transaction_filter.vote,
+                    failed: transaction_filter.failed,
+                    ..Default::default()
+                },
+            );
+        }
+        _ => {
+            transactions.insert(
+                "account".to_string(),
+                SubscribeRequestFilterTransactions {
+                    account_include: accounts.clone(),
+                    account_exclude: vec![],
+                    account_required: vec![],
+                    vote: transaction_filter.vote,
+                    failed: transaction_filter.failed,
+                    ..Default::default()
+                },
+            );
+        }
+    }
+    // AIDEV-NOTE: Entries mode keys observations on the slot so they line up with shredstream batches
+    let mut seen_entry_slots = HashSet::new();
+    let watched = WatchedKeys::from_base58(&accounts);
 
-    subscribe_tx
-        .send(SubscribeRequest {
-            slots: HashMap::default(),
-            accounts: HashMap::default(),
-            transactions,
-            transactions_status: HashMap::default(),
-            entry: HashMap::default(),
-            blocks: HashMap::default(),
-            blocks_meta: HashMap::default(),
-            commitment: Some(commitment as i32),
-            accounts_data_slice: Vec::default(),
-            ping: None,
-            from_slot: None,
-        })
-        .await?;
+    let mut request = SubscribeRequest {
+        slots,
+        accounts: HashMap::default(),
+        transactions,
+        transactions_status,
+        entry,
+        blocks,
+        blocks_meta: HashMap::default(),
+        commitment: Some(commitment as i32),
+        accounts_data_slice: Vec::default(),
+        ping: None,
+        from_slot: config.from_slot,
+    };
+    apply_subscribe_overrides(endpoint, &mut request);
+
+    startup.subscribed();
+    subscribe_tx.send(request).await?;
+    let subscribed_at = clock.now();
+    // AIDEV-NOTE: A watchdog reconnect subscribes from `from_slot` again; the first tip is kept and
+    // the replayed repeats are counted as repeat deliveries.
+    if let Some(tip_slot) = tip_slot {
+        log::info!("[{}] Replaying from slot {:?} up to tip slot {}", label, config.from_slot, tip_slot);
+        comparator
+            .lock()
+            .unwrap()
+            .endpoint_metrics
+            .entry(endpoint.name.clone())
+            .or_default()
+            .replay
+            .get_or_insert(ReplayMetrics { tip_slot, subscribed_at });
+    }
+
+    // AIDEV-NOTE: Client-initiated pings on top of answering server pings; off unless configured
+    let ping_interval = ping_interval_secs(endpoint, config);
+    let mut ping_timer = tokio::time::interval(Duration::from_secs(
+        ping_interval.unwrap_or(DEFAULT_PING_INTERVAL_SECS).max(1),
+    ));
+    let mut pings = PingTracker::new(clock.clone());
 
     'ploop: loop {
         tokio::select! {
-            _ = shutdown_rx.recv() => {
-                log::info!("[{}] Received stop signal...", endpoint.name);
+            _ = shutdown_rx.recv(), if !drain.is_draining() => {
+                log::info!("[{}] Received stop signal...", label);
+                if !drain.begin(&label) {
+                    break;
+                }
+            }
+
+            _ = drain.finished(), if drain.is_draining() => {
+                log::info!("[{}] Drain window over", label);
                 break;
             }
 
+            _ = ping_timer.tick(), if ping_interval.is_some() => {
+                subscribe_tx
+                    .send(SubscribeRequest {
+                        ping: Some(SubscribeRequestPing { id: pings.next_ping() }),
+                        ..Default::default()
+                    })
+                    .await?;
+            }
+
+            _ = watchdog_timer.tick(), if monitor.watchdog_enabled() => {
+                if monitor.check_watchdog(&label) && reconnect_on_stall {
+                    reconnect = true;
+                    break;
+                }
+            }
+
             message = stream.next() => {
                 match message {
                     Some(Ok(msg)) => {
+                        monitor.on_message();
+                        consumer.consume().await;
+                        let mut trace = PipelineTrace::start(&endpoint.name);
+                        // Server pings keep idle streams alive and say nothing about spin-up
+                        if !matches!(msg.update_oneof, Some(UpdateOneof::Ping(_)) | Some(UpdateOneof::Pong(_))) {
+                            startup.on_update();
+                            monitor.on_update();
+                            timing.on_update(created_at_secs(&msg));
+                        }
                         match msg.update_oneof {
                             Some(UpdateOneof::Transaction(tx_msg)) => {
+                                message_count += 1;
                                 if let Some(tx) = tx_msg.transaction {
-                                    let accounts = tx.transaction.clone().unwrap().message.unwrap().account_keys
-                                        .iter()
-                                        .map(|key| bs58::encode(key).into_string())
-                                        .collect::<Vec<String>>();
+                                    let status = tx.meta.as_ref().map(|meta| TransactionStatus {
+                                        failed: meta.err.is_some(),
+                                        fee: Some(meta.fee),
+                                        compute_units: meta.compute_units_consumed,
+                                        error: meta.err.as_ref().map(|err| decode_transaction_error(&err.err)),
+                                    });
+                                    let mut accounts = tx
+                                        .transaction
+                                        .as_ref()
+                                        .and_then(|transaction| transaction.message.as_ref())
+                                        .map(|message| watched.matching(&message.account_keys))
+                                        .unwrap_or_default();
+                                    // Loaded addresses come with the meta; matching them is opt-in so all endpoint kinds race the same set
+                                    if let Some(meta) = tx.meta.as_ref().filter(|_| config.lookup_tables.is_some()) {
+                                        accounts.extend(watched.matching(&meta.loaded_writable_addresses));
+                                        accounts.extend(watched.matching(&meta.loaded_readonly_addresses));
+                                    }
 
-                                    if accounts.contains(&config.account) {
-                                        let timestamp = get_current_timestamp();
-                                        let signature = bs58::encode(&tx.transaction.unwrap().signatures[0]).into_string();
+                                    if !accounts.is_empty() {
+                                        let timestamp = clock.now();
+                                        startup.on_match(timestamp);
+                                        trace.filtered();
+                                        let tsc = tsc::read();
+                                        let market = if config.decode_markets {
+                                            tx.transaction
+                                                .as_ref()
+                                                .and_then(|transaction| transaction.message.as_ref())
+                                                .and_then(|message| markets::decode_yellowstone(message, tx.meta.as_ref()))
+                                        } else {
+                                            None
+                                        };
+                                        let signature = bs58::encode(&tx.signature).into_string();
 
-                                        write_log_entry(&mut log_file, timestamp, &endpoint.name, &signature)?;
+                                        write_log_entry(&mut log_file, timestamp, &endpoint.name, &signature, tsc)?;
 
                                         let mut comp = comparator.lock().unwrap();
+                                        trace.locked();
 
-                                        comp.add(
+                                        comp.add_on_connection(
                                             endpoint.name.clone(),
+                                            connection,
                                             TransactionData {
                                                 timestamp,
                                                 signature: signature.clone(),
                                                 start_time,
+                                                tsc,
+                                                status,
+                                                slot: Some(tx_msg.slot),
+                                                entry: None,
+                                                groups: config.filter_groups_for(&accounts),
                                             },
                                         );
+                                        trace.inserted(&signature);
+                                        comp.tag_accounts(&signature, &accounts);
+                                        if let Some(market) = market {
+                                            comp.tag_market(&signature, market);
+                                        }
 
-                                        if comp.get_valid_count() == config.transactions as usize {
+                                        if !drain.is_draining() && comp.target_reached() {
                                             log::info!("Endpoint {} shutting down after {} transactions seen and {} by all workers",
                                                 endpoint.name, transaction_count, config.transactions);
+                                            comp.begin_drain();
                                             shutdown_tx.send(()).unwrap();
-                                            break 'ploop;
+                                            if !drain.begin(&endpoint.name) {
+                                                break 'ploop;
+                                            }
                                         }
 
                                         log::info!("[{:.3}] [{}] {}", timestamp, endpoint.name, signature);
@@ -151,23 +556,212 @@ async fn process_yellowstone_endpoint(
                                     }
                                 }
                             },
+                            // AIDEV-NOTE: Status updates are filtered server-side and carry no account keys
+                            Some(UpdateOneof::TransactionStatus(status_msg)) => {
+                                message_count += 1;
+                                let timestamp = clock.now();
+                                startup.on_match(timestamp);
+                                let tsc = tsc::read();
+                                let signature = bs58::encode(&status_msg.signature).into_string();
+                                let status = TransactionStatus {
+                                    failed: status_msg.err.is_some(),
+                                    fee: None,
+                                    compute_units: None,
+                                    error: status_msg.err.as_ref().map(|err| decode_transaction_error(&err.err)),
+                                };
+
+                                write_log_entry(&mut log_file, timestamp, &endpoint.name, &signature, tsc)?;
+
+                                let mut comp = comparator.lock().unwrap();
+
+                                comp.add_on_connection(
+                                    endpoint.name.clone(),
+                                    connection,
+                                    TransactionData {
+                                        timestamp,
+                                        signature: signature.clone(),
+                                        start_time,
+                                        tsc,
+                                        status: Some(status),
+                                        slot: Some(status_msg.slot),
+                                        entry: None,
+                                        groups: Vec::new(),
+                                    },
+                                );
+
+                                if !drain.is_draining() && comp.target_reached() {
+                                    log::info!("Endpoint {} shutting down after {} statuses seen and {} by all workers",
+                                        endpoint.name, transaction_count, config.transactions);
+                                    comp.begin_drain();
+                                    shutdown_tx.send(()).unwrap();
+                                    if !drain.begin(&endpoint.name) {
+                                        break 'ploop;
+                                    }
+                                }
+
+                                log::info!("[{:.3}] [{}] {} (slot {})", timestamp, endpoint.name, signature, status_msg.slot);
+                                transaction_count += 1;
+                            },
+                            Some(UpdateOneof::Entry(entry_msg))
+                                if seen_entry_slots.insert(entry_msg.slot) => {
+                                    let timestamp = clock.now();
+                                    startup.on_match(timestamp);
+                                    let tsc = tsc::read();
+                                    let signature = entry_slot_key(entry_msg.slot);
+
+                                    write_log_entry(&mut log_file, timestamp, &endpoint.name, &signature, tsc)?;
+
+                                    let mut comp = comparator.lock().unwrap();
+
+                                    comp.add_on_connection(
+                                        endpoint.name.clone(),
+                                        connection,
+                                        TransactionData {
+                                            timestamp,
+                                            signature: signature.clone(),
+                                            start_time,
+                                            tsc,
+                                            status: None,
+                                            slot: Some(entry_msg.slot),
+                                            entry: None,
+                                            groups: Vec::new(),
+                                        },
+                                    );
+
+                                    if !drain.is_draining() && comp.target_reached() {
+                                        log::info!("Endpoint {} shutting down after {} slots seen and {} by all workers",
+                                            endpoint.name, transaction_count, config.transactions);
+                                        comp.begin_drain();
+                                        shutdown_tx.send(()).unwrap();
+                                        if !drain.begin(&endpoint.name) {
+                                            break 'ploop;
+                                        }
+                                    }
+
+                                    log::info!("[{:.3}] [{}] {}", timestamp, endpoint.name, signature);
+                                    transaction_count += 1;
+                                },
+                            // AIDEV-NOTE: Only the three commitment statuses are raced; the intra-slot
+                            // ones (first shred, completed, bank created) are not sent to every plan
+                            Some(UpdateOneof::Slot(slot_msg)) => {
+                                let status = match SlotStatus::try_from(slot_msg.status) {
+                                    Ok(SlotStatus::SlotProcessed) => Some("processed"),
+                                    Ok(SlotStatus::SlotConfirmed) => Some("confirmed"),
+                                    Ok(SlotStatus::SlotFinalized) => Some("finalized"),
+                                    _ => None,
+                                };
+                                if let Some(status) = status {
+                                    let timestamp = clock.now();
+                                    startup.on_match(timestamp);
+                                    let tsc = tsc::read();
+                                    let signature = slot_status_key(slot_msg.slot, status);
+
+                                    write_log_entry(&mut log_file, timestamp, &endpoint.name, &signature, tsc)?;
+
+                                    let mut comp = comparator.lock().unwrap();
+
+                                    comp.add_on_connection(
+                                        endpoint.name.clone(),
+                                        connection,
+                                        TransactionData {
+                                            timestamp,
+                                            signature: signature.clone(),
+                                            start_time,
+                                            tsc,
+                                            status: None,
+                                            slot: Some(slot_msg.slot),
+                                            entry: None,
+                                            groups: Vec::new(),
+                                        },
+                                    );
+
+                                    if !drain.is_draining() && comp.target_reached() {
+                                        log::info!("Endpoint {} shutting down after {} slot statuses seen and {} by all workers",
+                                            endpoint.name, transaction_count, config.transactions);
+                                        comp.begin_drain();
+                                        shutdown_tx.send(()).unwrap();
+                                        if !drain.begin(&endpoint.name) {
+                                            break 'ploop;
+                                        }
+                                    }
+
+                                    log::info!("[{:.3}] [{}] {}", timestamp, endpoint.name, signature);
+                                    transaction_count += 1;
+                                }
+                            },
+                            // AIDEV-NOTE: A block update is only sent once the slot is complete, so its
+                            // arrival times full-block delivery rather than any single transaction
+                            Some(UpdateOneof::Block(block_msg)) => {
+                                message_count += 1;
+                                let timestamp = clock.now();
+                                startup.on_match(timestamp);
+                                if let Some(block_time) = &block_msg.block_time {
+                                    timing.on_block_time(block_time.timestamp);
+                                }
+                                let tsc = tsc::read();
+                                let signature = block_slot_key(block_msg.slot);
+                                let size = prost::Message::encoded_len(&block_msg);
+
+                                write_log_entry(&mut log_file, timestamp, &endpoint.name, &signature, tsc)?;
+
+                                let mut comp = comparator.lock().unwrap();
+                                comp.endpoint_metrics
+                                    .entry(endpoint.name.clone())
+                                    .or_default()
+                                    .block_sizes
+                                    .get_or_insert_with(BlockSizeStats::default)
+                                    .record(size, block_msg.executed_transaction_count);
+
+                                comp.add_on_connection(
+                                    endpoint.name.clone(),
+                                    connection,
+                                    TransactionData {
+                                        timestamp,
+                                        signature: signature.clone(),
+                                        start_time,
+                                        tsc,
+                                        status: None,
+                                        slot: Some(block_msg.slot),
+                                        entry: None,
+                                        groups: Vec::new(),
+                                    },
+                                );
+
+                                if !drain.is_draining() && comp.target_reached() {
+                                    log::info!("Endpoint {} shutting down after {} blocks seen and {} by all workers",
+                                        endpoint.name, transaction_count, config.transactions);
+                                    comp.begin_drain();
+                                    shutdown_tx.send(()).unwrap();
+                                    if !drain.begin(&endpoint.name) {
+                                        break 'ploop;
+                                    }
+                                }
+
+                                log::info!("[{:.3}] [{}] {} ({} transactions, {} bytes)",
+                                    timestamp, endpoint.name, signature, block_msg.executed_transaction_count, size);
+                                transaction_count += 1;
+                            },
                             Some(UpdateOneof::Ping(_)) => {
                                 subscribe_tx
                                     .send(SubscribeRequest {
-                                        ping: Some(SubscribeRequestPing { id: 1 }),
+                                        ping: Some(SubscribeRequestPing { id: 0 }),
                                         ..Default::default()
                                     })
                                     .await?;
                             },
+                            Some(UpdateOneof::Pong(pong)) => pings.on_pong(pong.id),
                             _ => {}
                         }
                     },
                     Some(Err(e)) => {
-                        log::error!("[{}] Error receiving message: {:?}", endpoint.name, e);
+                        log::error!("[{}] Error receiving message: {:?}", label, e);
+                        monitor.disconnected(e.to_string());
+                        unauthenticated = e.code() == Code::Unauthenticated && endpoint.token_refresh.is_some();
                         break;
                     },
                     None => {
-                        log::info!("[{}] Stream closed", endpoint.name);
+                        log::info!("[{}] Stream closed", label);
+                        monitor.disconnected("stream closed by server".to_string());
                         break;
                     }
                 }
@@ -175,6 +769,112 @@ async fn process_yellowstone_endpoint(
         }
     }
 
-    log::info!("[{}] Stream closed", endpoint.name);
-    Ok(())
+    if let Some(tracker) = frame_tracker {
+        let summary = tracker.summary();
+        log::info!(
+            "[{}] Frame timing: {} messages, {} large, avg assembly {:.2} ms",
+            endpoint.name,
+            summary.messages,
+            summary.large_messages,
+            summary.avg_assembly_ms
+        );
+        comparator
+            .lock()
+            .unwrap()
+            .endpoint_metrics
+            .entry(endpoint.name.clone())
+            .or_default()
+            .frame_timing = Some(summary);
+    }
+
+    monitor.finish(&endpoint.name, comparator);
+    consumer.finish(&endpoint.name, comparator);
+    timing.finish(&endpoint.name, comparator);
+    pings.finish(&endpoint.name, comparator);
+    // Shards and connections share the endpoint's metrics entry; the first one's spin-up stands for the endpoint
+    if primary {
+        startup.finish(&endpoint.name, comparator);
+    }
+
+    // A reconnecting shard reports only its final connection
+    if let Some(shard) = shard.filter(|_| !reconnect && !unauthenticated) {
+        let elapsed_secs = clock.now() - subscribed_at;
+        let shard_metrics = ShardMetrics {
+            shard,
+            accounts: watched.len(),
+            messages: message_count,
+            matched: transaction_count,
+            elapsed_secs,
+            messages_per_sec: if elapsed_secs > 0.0 {
+                message_count as f64 / elapsed_secs
+            } else {
+                0.0
+            },
+        };
+        log::info!(
+            "[{}] {} messages ({:.1}/s), {} matched",
+            label,
+            shard_metrics.messages,
+            shard_metrics.messages_per_sec,
+            shard_metrics.matched
+        );
+        comparator
+            .lock()
+            .unwrap()
+            .endpoint_metrics
+            .entry(endpoint.name.clone())
+            .or_default()
+            .shards
+            .push(shard_metrics);
+    }
+
+    log::info!("[{}] Stream closed", label);
+    Ok(if reconnect {
+        Restart::Watchdog
+    } else if unauthenticated {
+        Restart::Unauthenticated
+    } else {
+        Restart::No
+    })
+}
+
+/// The server's `created_at` stamp of an update, in Unix seconds.
+pub fn created_at_secs(update: &SubscribeUpdate) -> Option<f64> {
+    update
+        .created_at
+        .as_ref()
+        .map(|created_at| created_at.seconds as f64 + created_at.nanos as f64 / 1_000_000_000.0)
+}
+
+pub fn decode_transaction_error(bytes: &[u8]) -> String {
+    bincode::deserialize::<TransactionError>(bytes)
+        .map(|e| e.to_string())
+        .unwrap_or_else(|_| "undecodable error".to_string())
+}
+
+type SubscribeSink = Pin<Box<dyn Sink<SubscribeRequest, Error = mpsc::SendError> + Send>>;
+type SubscribeStream = Pin<Box<dyn Stream<Item = Result<SubscribeUpdate, Status>> + Send>>;
+
+// AIDEV-NOTE: Same as connect_yellowstone, but the frame timing layer has to sit between the
+// raw channel and the generated client.
+async fn connect_with_frame_timing(
+    endpoint: &Endpoint,
+    tracker: FrameTracker,
+) -> Result<
+    GeyserClient<
+        InterceptedService<
+            FrameTimingService<Channel>,
+            MetadataInterceptor,
+        >,
+    >,
+    Box<dyn Error + Send + Sync>,
+> {
+    let builder = channel_builder(endpoint)?.tls_config(ClientTlsConfig::new().with_native_roots())?;
+    let channel = open_channel(builder, endpoint).await?;
+
+    Ok(GeyserClient::with_interceptor(
+        FrameTimingService::new(channel, tracker),
+        MetadataInterceptor::new(endpoint)?,
+    )
+    .max_decoding_message_size(endpoint.decoding_limit()))
 }
@@ -2,26 +2,31 @@ use std::{
     collections::HashMap,
     error::Error,
     sync::{Arc, Mutex},
+    time::Duration,
 };
 
+use futures::channel::mpsc;
 use futures_util::{stream::StreamExt, sink::SinkExt};
-use tokio::{sync::broadcast, task};
-use yellowstone_grpc_client::GeyserGrpcClient;
+use tokio::sync::broadcast;
 use yellowstone_grpc_proto::{
     geyser::{
         subscribe_update::UpdateOneof, SubscribeRequest, SubscribeRequestPing,
         SubscribeRequestFilterAccounts,
     },
     prelude::SubscribeRequestFilterTransactions,
-    tonic::transport::ClientTlsConfig,
 };
 
 use crate::{
-    config::{Config, Endpoint},
-    utils::{Comparator, TransactionData, get_current_timestamp, open_log_file, write_log_entry},
+    config::{AccountsStrategy, BenchmarkMode, Config, Endpoint},
+    utils::{AccountState, Comparator, TransactionData, write_log_entry},
+    tsc,
 };
 
-use super::GeyserProvider;
+use super::{
+    account_write_key, apply_subscribe_overrides, connect_yellowstone, start_when_ready, yellowstone::created_at_secs, Capabilities,
+    GeyserProvider, ConsumerDelay, Drain, ping_interval_secs, PingTracker, ProviderError, ProviderStart, ProviderTiming, Ready,
+    StartupTimer, StreamMonitor, DEFAULT_PING_INTERVAL_SECS,
+};
 
 pub struct YellowstoneAccountsProvider;
 
@@ -31,7 +36,14 @@ lazy_static::lazy_static! {
 }
 
 impl GeyserProvider for YellowstoneAccountsProvider {
-    fn process(
+    fn capabilities(&self) -> Capabilities {
+        Capabilities {
+            accounts: true,
+            ..Capabilities::TRANSACTIONS
+        }
+    }
+
+    fn start(
         &self,
         endpoint: Endpoint,
         config: Config,
@@ -39,8 +51,8 @@ impl GeyserProvider for YellowstoneAccountsProvider {
         shutdown_rx: broadcast::Receiver<()>,
         start_time: f64,
         comparator: Arc<Mutex<Comparator>>,
-    ) -> task::JoinHandle<Result<(), Box<dyn Error + Send + Sync>>> {
-        task::spawn(async move {
+    ) -> ProviderStart {
+        start_when_ready(|ready| {
             process_yellowstone_accounts_endpoint(
                 endpoint,
                 config,
@@ -48,8 +60,8 @@ impl GeyserProvider for YellowstoneAccountsProvider {
                 shutdown_rx,
                 start_time,
                 comparator,
+                ready,
             )
-                .await
         })
     }
 }
@@ -57,7 +69,6 @@ impl GeyserProvider for YellowstoneAccountsProvider {
 // AIDEV-NOTE: Track latency differences between account and transaction streams
 #[derive(Debug, Clone)]
 struct StreamLatencyData {
-    signature: String,
     account_timestamp: Option<f64>,
     transaction_timestamp: Option<f64>,
     account_endpoint: Option<String>,  // Track which endpoint saw account first
@@ -71,30 +82,37 @@ async fn process_yellowstone_accounts_endpoint(
     mut shutdown_rx: broadcast::Receiver<()>,
     start_time: f64,
     comparator: Arc<Mutex<Comparator>>,
+    mut ready: Ready,
 ) -> Result<(), Box<dyn Error + Send + Sync>> {
     let mut transaction_count = 0;
+    let clock = comparator.lock().unwrap().clock.clone();
     let mut account_update_count = 0;
     
     // Track latencies for both streams
     let mut stream_latencies: HashMap<String, StreamLatencyData> = HashMap::new();
     
-    let mut log_file = open_log_file(&format!("{}_dual_stream", endpoint.name))?;
+    let mut log_file = comparator.lock().unwrap().log_output.open(&format!("{}_dual_stream", endpoint.name))?;
 
     log::info!(
         "[{}] Connecting to endpoint for dual stream tracking: {}",
         endpoint.name,
-        endpoint.url
+        endpoint.display_url()
     );
 
-    let mut client = GeyserGrpcClient::build_from_shared(endpoint.url)?
-        .x_token(Some(endpoint.x_token))?
-        .tls_config(ClientTlsConfig::new().with_native_roots())?
-        .connect()
-        .await?;
+    let mut startup = StartupTimer::start(clock.clone());
+    let mut timing = ProviderTiming::new(clock.clone());
+    let mut monitor = StreamMonitor::new(clock.clone(), &config);
+    let mut watchdog_timer = monitor.watchdog_timer();
+    let mut drain = Drain::new(&config);
+    let mut consumer = ConsumerDelay::new(&config);
+    let mut client = connect_yellowstone(&endpoint).await?;
+    startup.connected();
 
     log::info!("[{}] Connected successfully", endpoint.name);
 
-    let (mut subscribe_tx, mut stream) = client.subscribe().await?;
+    let (mut subscribe_tx, subscribe_rx) = mpsc::unbounded();
+    let mut stream = client.subscribe(subscribe_rx).await.map_err(ProviderError::from_status)?.into_inner();
+    ready.signal();
     let commitment: yellowstone_grpc_proto::geyser::CommitmentLevel = config.commitment.into();
     
     log::info!(
@@ -104,78 +122,116 @@ async fn process_yellowstone_accounts_endpoint(
         commitment
     );
 
-    // Subscribe to both transactions and accounts for the same account
-    let mut transactions = HashMap::new();
-    transactions.insert(
-        "account".to_string(),
-        SubscribeRequestFilterTransactions {
-            account_include: vec![config.account.clone()],
-            account_exclude: vec![],
-            account_required: vec![],
-            ..Default::default()
-        },
-    );
-
-    let mut accounts = HashMap::new();
-    // Subscribe to the specific account - try without txn_signature filter first
-    accounts.insert(
-        "account".to_string(),
-        SubscribeRequestFilterAccounts {
-            // account: vec![config.account.clone()],
-            account:vec![],
-            owner: vec![],
-            filters: vec![],
-            nonempty_txn_signature: None, // Try without filter first
-        },
-    );
-
-    let subscribe_request = SubscribeRequest {
-        slots: HashMap::default(),
-        accounts,
-        transactions,
-        transactions_status: HashMap::default(),
-        entry: HashMap::default(),
-        blocks: HashMap::default(),
-        blocks_meta: HashMap::default(),
-        commitment: Some(commitment as i32),
-        accounts_data_slice: Vec::default(),
-        ping: None,
-        from_slot: None,
-    };
-    
+    let data_sliced = !endpoint.data_slices(&config).is_empty();
+    // Only payloads sliced like the rest of the run are compared across endpoints
+    let compare_data = endpoint.data_slices(&config) == config.accounts_data_slice.as_slice();
+    let mut strategy = config.accounts_strategy;
+    let request = subscribe_request(&endpoint, &config, strategy, commitment);
     log::debug!("[{}] Sending subscribe request with {} account filters and {} transaction filters", 
         endpoint.name, 
-        subscribe_request.accounts.len(),
-        subscribe_request.transactions.len()
+        request.accounts.len(),
+        request.transactions.len()
     );
     
-    subscribe_tx.send(subscribe_request).await?;
+    let request_accounts_empty = request.accounts.is_empty();
+    startup.subscribed();
+    subscribe_tx.send(request).await?;
+
+    // AIDEV-NOTE: A filter that matches nothing (wrong owner, idle account) would leave the dual
+    // stream comparison empty; after the timeout the subscription is replaced with a looser one.
+    let fallback_after = Duration::from_secs(config.accounts_fallback_secs);
+    let fallback_timer = tokio::time::sleep(fallback_after);
+    tokio::pin!(fallback_timer);
+    // A subscription overridden to leave out the accounts filter has nothing to loosen
+    let mut fallback_armed = config.accounts_fallback_secs > 0
+        && !request_accounts_empty
+        && looser_strategy(&config, strategy).is_some();
+
+    // AIDEV-NOTE: Client-initiated pings on top of answering server pings; off unless configured
+    let ping_interval = ping_interval_secs(&endpoint, &config);
+    let mut ping_timer = tokio::time::interval(Duration::from_secs(
+        ping_interval.unwrap_or(DEFAULT_PING_INTERVAL_SECS).max(1),
+    ));
+    let mut pings = PingTracker::new(clock.clone());
 
     'ploop: loop {
         tokio::select! {
-            _ = shutdown_rx.recv() => {
+            _ = shutdown_rx.recv(), if !drain.is_draining() => {
                 log::info!("[{}] Received stop signal...", endpoint.name);
+                if !drain.begin(&endpoint.name) {
+                    break;
+                }
+            }
+
+            _ = drain.finished(), if drain.is_draining() => {
+                log::info!("[{}] Drain window over", endpoint.name);
                 break;
             }
 
+            _ = &mut fallback_timer, if fallback_armed => {
+                if let Some(next) = looser_strategy(&config, strategy) {
+                    log::warn!(
+                        "[{}] No account updates within {}s using the {:?} filter, falling back to {:?}",
+                        endpoint.name,
+                        config.accounts_fallback_secs,
+                        strategy,
+                        next
+                    );
+                    strategy = next;
+                    subscribe_tx.send(subscribe_request(&endpoint, &config, strategy, commitment)).await?;
+                    fallback_timer.as_mut().reset(tokio::time::Instant::now() + fallback_after);
+                }
+                fallback_armed = looser_strategy(&config, strategy).is_some();
+            }
+
+            _ = ping_timer.tick(), if ping_interval.is_some() => {
+                subscribe_tx
+                    .send(SubscribeRequest {
+                        ping: Some(SubscribeRequestPing { id: pings.next_ping() }),
+                        ..Default::default()
+                    })
+                    .await?;
+            }
+
+            _ = watchdog_timer.tick(), if monitor.watchdog_enabled() => {
+                monitor.check_watchdog(&endpoint.name);
+            }
+
             message = stream.next() => {
                 match message {
                     Some(Ok(msg)) => {
+                        monitor.on_message();
+                        consumer.consume().await;
+                        // Server pings keep idle streams alive and say nothing about spin-up
+                        if !matches!(msg.update_oneof, Some(UpdateOneof::Ping(_)) | Some(UpdateOneof::Pong(_))) {
+                            startup.on_update();
+                            monitor.on_update();
+                            timing.on_update(created_at_secs(&msg));
+                        }
                         match msg.update_oneof {
                             Some(UpdateOneof::Transaction(tx_msg)) => {
                                 if let Some(tx) = tx_msg.transaction {
-                                    let accounts = tx.transaction.clone().unwrap().message.unwrap().account_keys
+                                    let mut accounts = tx.transaction.clone().unwrap().message.unwrap().account_keys
                                         .iter()
                                         .map(|key| bs58::encode(key).into_string())
                                         .collect::<Vec<String>>();
+                                    if let Some(meta) = tx.meta.as_ref().filter(|_| config.lookup_tables.is_some()) {
+                                        accounts.extend(
+                                            meta.loaded_writable_addresses
+                                                .iter()
+                                                .chain(&meta.loaded_readonly_addresses)
+                                                .map(|key| bs58::encode(key).into_string()),
+                                        );
+                                    }
 
                                     if accounts.contains(&config.account) {
-                                        let timestamp = get_current_timestamp();
+                                        let timestamp = clock.now();
+                                        startup.on_match(timestamp);
+                                        let tsc = tsc::read();
                                         let signature = bs58::encode(&tx.transaction.unwrap().signatures[0]).into_string();
 
                                         // Track transaction stream timestamp locally
                                         let entry = stream_latencies.entry(signature.clone()).or_insert(StreamLatencyData {
-                                            signature: signature.clone(),
                                             account_timestamp: None,
                                             transaction_timestamp: None,
                                             account_endpoint: None,
@@ -190,7 +246,6 @@ async fn process_yellowstone_accounts_endpoint(
                                         {
                                             let mut global_tracker = GLOBAL_ACCOUNT_TRACKER.lock().unwrap();
                                             let global_entry = global_tracker.entry(signature.clone()).or_insert(StreamLatencyData {
-                                                signature: signature.clone(),
                                                 account_timestamp: None,
                                                 transaction_timestamp: None,
                                                 account_endpoint: None,
@@ -203,7 +258,7 @@ async fn process_yellowstone_accounts_endpoint(
                                         }
 
                                         // Log transaction received
-                                        write_log_entry(&mut log_file, timestamp, &format!("{}_TX", endpoint.name), &signature)?;
+                                        write_log_entry(&mut log_file, timestamp, &format!("{}_TX", endpoint.name), &signature, tsc)?;
 
                                         // Check if we have both streams for this signature
                                         if let Some(account_ts) = entry.account_timestamp {
@@ -225,18 +280,26 @@ async fn process_yellowstone_accounts_endpoint(
                                                 timestamp,
                                                 signature: signature.clone(),
                                                 start_time,
+                                                tsc,
+                                                status: None,
+                                                slot: Some(tx_msg.slot),
+                                                entry: None,
+                                                groups: Vec::new(),
                                             },
                                         );
 
-                                        if comp.get_valid_count() == config.transactions as usize {
+                                        if !drain.is_draining() && comp.target_reached() {
                                             log::info!("Endpoint {} shutting down after {} transactions seen",
                                                 endpoint.name, transaction_count);
                                             
                                             // Print final statistics
                                             print_stream_statistics(&stream_latencies, &endpoint.name);
                                             
+                                            comp.begin_drain();
                                             shutdown_tx.send(()).unwrap();
-                                            break 'ploop;
+                                            if !drain.begin(&endpoint.name) {
+                                                break 'ploop;
+                                            }
                                         }
 
                                         transaction_count += 1;
@@ -244,14 +307,67 @@ async fn process_yellowstone_accounts_endpoint(
                                 }
                             },
                             Some(UpdateOneof::Account(account_msg)) => {
-                                // AIDEV-NOTE: Process ALL account updates that have txn_signature
+                                fallback_armed = false;
+                                // AIDEV-NOTE: Process ALL account updates that have txn_signature (all of them in accounts mode)
                                 if let Some(account_info) = account_msg.account {
                                     let account_key = bs58::encode(&account_info.pubkey).into_string();
                                     account_update_count += 1;
+                                    comparator.lock().unwrap().add_account_write(
+                                        &endpoint.name,
+                                        account_key.clone(),
+                                        account_info.write_version,
+                                        AccountState {
+                                            lamports: account_info.lamports,
+                                            owner: &account_info.owner,
+                                            executable: account_info.executable,
+                                            rent_epoch: account_info.rent_epoch,
+                                            data: &account_info.data,
+                                            sliced: data_sliced,
+                                        },
+                                        compare_data,
+                                    );
+
+                                    // AIDEV-NOTE: Keyed on (pubkey, write_version) so writes without a
+                                    // transaction signature (sysvars, vote-less writes) race too
+                                    if config.mode == BenchmarkMode::Accounts {
+                                        let timestamp = clock.now();
+                                        startup.on_match(timestamp);
+                                        let tsc = tsc::read();
+                                        let key = account_write_key(&account_key, account_info.write_version);
+
+                                        write_log_entry(&mut log_file, timestamp, &endpoint.name, &key, tsc)?;
+
+                                        let mut comp = comparator.lock().unwrap();
+                                        comp.add(
+                                            endpoint.name.clone(),
+                                            TransactionData {
+                                                timestamp,
+                                                signature: key,
+                                                start_time,
+                                                tsc,
+                                                status: None,
+                                                slot: Some(account_msg.slot),
+                                                entry: None,
+                                                groups: Vec::new(),
+                                            },
+                                        );
+
+                                        if !drain.is_draining() && comp.target_reached() {
+                                            log::info!("Endpoint {} shutting down after {} account updates seen",
+                                                endpoint.name, account_update_count);
+                                            comp.begin_drain();
+                                            shutdown_tx.send(()).unwrap();
+                                            if !drain.begin(&endpoint.name) {
+                                                break 'ploop;
+                                            }
+                                        }
+                                    }
                                     
                                     // Check if account update has txn_signature
                                     if let Some(txn_sig_bytes) = account_info.txn_signature {
-                                        let timestamp = get_current_timestamp();
+                                        let timestamp = clock.now();
+                                        startup.on_match(timestamp);
+                                        let tsc = tsc::read();
                                         let signature = bs58::encode(&txn_sig_bytes).into_string();
                                         
                                         // Only log first few to avoid spam
@@ -268,7 +384,6 @@ async fn process_yellowstone_accounts_endpoint(
                                         
                                         // Track account stream timestamp locally
                                         let entry = stream_latencies.entry(signature.clone()).or_insert(StreamLatencyData {
-                                            signature: signature.clone(),
                                             account_timestamp: None,
                                             transaction_timestamp: None,
                                             account_endpoint: None,
@@ -283,7 +398,6 @@ async fn process_yellowstone_accounts_endpoint(
                                         {
                                             let mut global_tracker = GLOBAL_ACCOUNT_TRACKER.lock().unwrap();
                                             let global_entry = global_tracker.entry(signature.clone()).or_insert(StreamLatencyData {
-                                                signature: signature.clone(),
                                                 account_timestamp: None,
                                                 transaction_timestamp: None,
                                                 account_endpoint: None,
@@ -296,7 +410,7 @@ async fn process_yellowstone_accounts_endpoint(
                                         }
                                         
                                         // Log account update received
-                                        write_log_entry(&mut log_file, timestamp, &format!("{}_ACCT", endpoint.name), &signature)?;
+                                        write_log_entry(&mut log_file, timestamp, &format!("{}_ACCT", endpoint.name), &signature, tsc)?;
                                         
                                         // Check if we have both streams for this signature
                                         if let Some(tx_ts) = entry.transaction_timestamp {
@@ -317,11 +431,12 @@ async fn process_yellowstone_accounts_endpoint(
                             Some(UpdateOneof::Ping(_)) => {
                                 subscribe_tx
                                     .send(SubscribeRequest {
-                                        ping: Some(SubscribeRequestPing { id: 1 }),
+                                        ping: Some(SubscribeRequestPing { id: 0 }),
                                         ..Default::default()
                                     })
                                     .await?;
                             },
+                            Some(UpdateOneof::Pong(pong)) => pings.on_pong(pong.id),
                             Some(other) => {
                                 let update_type = match other {
                                     UpdateOneof::Slot(_) => "Slot",
@@ -340,10 +455,12 @@ async fn process_yellowstone_accounts_endpoint(
                     },
                     Some(Err(e)) => {
                         log::error!("[{}] Error receiving message: {:?}", endpoint.name, e);
+                        monitor.disconnected(e.to_string());
                         break;
                     },
                     None => {
                         log::info!("[{}] Stream closed", endpoint.name);
+                        monitor.disconnected("stream closed by server".to_string());
                         break;
                     }
                 }
@@ -351,6 +468,18 @@ async fn process_yellowstone_accounts_endpoint(
         }
     }
 
+    monitor.finish(&endpoint.name, &comparator);
+    consumer.finish(&endpoint.name, &comparator);
+    timing.finish(&endpoint.name, &comparator);
+    pings.finish(&endpoint.name, &comparator);
+    startup.finish(&endpoint.name, &comparator);
+    comparator
+        .lock()
+        .unwrap()
+        .endpoint_metrics
+        .entry(endpoint.name.clone())
+        .or_default()
+        .accounts_strategy = Some(strategy);
     log::info!(
         "[{}] Stream closed. Total transactions: {}, Account updates: {}",
         endpoint.name, transaction_count, account_update_count
@@ -365,6 +494,81 @@ async fn process_yellowstone_accounts_endpoint(
     Ok(())
 }
 
+/// Transaction filter on `account` plus the account filter the strategy selects.
+fn subscribe_request(
+    endpoint: &Endpoint,
+    config: &Config,
+    strategy: AccountsStrategy,
+    commitment: yellowstone_grpc_proto::geyser::CommitmentLevel,
+) -> SubscribeRequest {
+    let mut transactions = HashMap::new();
+    // Accounts mode races the account writes themselves; the transaction side is not needed
+    if config.mode != BenchmarkMode::Accounts {
+        let transaction_filter = endpoint.transaction_filter(config);
+        transactions.insert(
+            "account".to_string(),
+            SubscribeRequestFilterTransactions {
+                account_include: vec![config.account.clone()],
+                account_exclude: vec![],
+                account_required: vec![],
+                vote: transaction_filter.vote,
+                failed: transaction_filter.failed,
+                ..Default::default()
+            },
+        );
+    }
+
+    // AIDEV-NOTE: An explicit `accounts_filter` replaces the account and owner strategies
+    let accounts_filter = match (strategy, &config.accounts_filter) {
+        (AccountsStrategy::Unfiltered, _) => SubscribeRequestFilterAccounts::default(),
+        (_, Some(filter)) => SubscribeRequestFilterAccounts::from(filter),
+        (AccountsStrategy::Account, None) => SubscribeRequestFilterAccounts {
+            account: config.watched_accounts(),
+            ..Default::default()
+        },
+        (AccountsStrategy::Owner, None) => SubscribeRequestFilterAccounts {
+            owner: config.accounts_owner.clone(),
+            ..Default::default()
+        },
+    };
+    log::info!(
+        "[{}] Account filter ({:?}): {} accounts, {} owners, {} data filters",
+        endpoint.name,
+        strategy,
+        accounts_filter.account.len(),
+        accounts_filter.owner.len(),
+        accounts_filter.filters.len()
+    );
+    let mut accounts = HashMap::new();
+    accounts.insert("account".to_string(), accounts_filter);
+
+    let mut request = SubscribeRequest {
+        slots: HashMap::default(),
+        accounts,
+        transactions,
+        transactions_status: HashMap::default(),
+        entry: HashMap::default(),
+        blocks: HashMap::default(),
+        blocks_meta: HashMap::default(),
+        commitment: Some(commitment as i32),
+        accounts_data_slice: endpoint.data_slices(config).iter().map(Into::into).collect(),
+        ping: None,
+        from_slot: config.from_slot,
+    };
+    apply_subscribe_overrides(endpoint, &mut request);
+    request
+}
+
+fn looser_strategy(config: &Config, strategy: AccountsStrategy) -> Option<AccountsStrategy> {
+    match strategy {
+        AccountsStrategy::Account if config.accounts_filter.is_none() && !config.accounts_owner.is_empty() => {
+            Some(AccountsStrategy::Owner)
+        }
+        AccountsStrategy::Account | AccountsStrategy::Owner => Some(AccountsStrategy::Unfiltered),
+        AccountsStrategy::Unfiltered => None,
+    }
+}
+
 fn print_global_statistics() {
     let global_tracker = GLOBAL_ACCOUNT_TRACKER.lock().unwrap();
     
@@ -466,7 +670,7 @@ fn print_stream_statistics(latencies: &HashMap<String, StreamLatencyData>, endpo
     if both_received > 0 {
         diffs.sort_by(|a, b| a.partial_cmp(b).unwrap());
         let avg_diff = total_diff / both_received as f64 * 1000.0;
-        let median = if diffs.len() > 0 {
+        let median = if !diffs.is_empty() {
             diffs[diffs.len() / 2]
         } else {
             0.0
@@ -485,7 +689,7 @@ fn print_stream_statistics(latencies: &HashMap<String, StreamLatencyData>, endpo
         );
         log::info!("Average latency difference: {:.2}ms", avg_diff);
         log::info!("Median latency difference: {:.2}ms", median);
-        if diffs.len() > 0 {
+        if !diffs.is_empty() {
             log::info!("Min latency difference: {:.2}ms", diffs[0]);
             log::info!("Max latency difference: {:.2}ms", diffs[diffs.len() - 1]);
         }
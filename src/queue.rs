@@ -0,0 +1,98 @@
+use std::{
+    collections::VecDeque,
+    sync::{
+        Mutex,
+        atomic::{AtomicBool, AtomicU64, Ordering},
+    },
+};
+
+use tokio::sync::Notify;
+
+use crate::config::OverflowPolicy;
+
+// AIDEV-NOTE: tokio's mpsc cannot evict from the sending side, which drop-oldest needs, so the
+// queue is a VecDeque behind a mutex. notify_one stores a permit, so no wakeup is lost between
+// checking the queue and awaiting.
+#[derive(Debug)]
+pub struct BoundedQueue<T> {
+    items: Mutex<VecDeque<T>>,
+    capacity: usize,
+    policy: OverflowPolicy,
+    dropped: AtomicU64,
+    closed: AtomicBool,
+    not_empty: Notify,
+    not_full: Notify,
+}
+
+impl<T> BoundedQueue<T> {
+    pub fn new(capacity: usize, policy: OverflowPolicy) -> Self {
+        let capacity = capacity.max(1);
+        Self {
+            items: Mutex::new(VecDeque::with_capacity(capacity)),
+            capacity,
+            policy,
+            dropped: AtomicU64::new(0),
+            closed: AtomicBool::new(false),
+            not_empty: Notify::new(),
+            not_full: Notify::new(),
+        }
+    }
+
+    /// Enqueues `item`, waiting for room or dropping a message when full as the policy says.
+    pub async fn push(&self, item: T) {
+        loop {
+            let not_full = self.not_full.notified();
+            {
+                let mut items = self.items.lock().unwrap();
+                if items.len() < self.capacity {
+                    items.push_back(item);
+                    break;
+                }
+                match self.policy {
+                    OverflowPolicy::Block => {}
+                    OverflowPolicy::DropOldest => {
+                        items.pop_front();
+                        items.push_back(item);
+                        self.dropped.fetch_add(1, Ordering::Relaxed);
+                        break;
+                    }
+                    OverflowPolicy::DropNewest => {
+                        self.dropped.fetch_add(1, Ordering::Relaxed);
+                        return;
+                    }
+                }
+            }
+            not_full.await;
+        }
+        self.not_empty.notify_one();
+    }
+
+    /// Next item, or None once the queue is closed and drained.
+    pub async fn pop(&self) -> Option<T> {
+        loop {
+            let not_empty = self.not_empty.notified();
+            if let Some(item) = self.items.lock().unwrap().pop_front() {
+                self.not_full.notify_one();
+                return Some(item);
+            }
+            if self.closed.load(Ordering::Acquire) {
+                return None;
+            }
+            not_empty.await;
+        }
+    }
+
+    /// Lets the consumer drain what is left and then see the end of the queue.
+    pub fn close(&self) {
+        self.closed.store(true, Ordering::Release);
+        self.not_empty.notify_one();
+    }
+
+    pub fn len(&self) -> usize {
+        self.items.lock().unwrap().len()
+    }
+
+    pub fn dropped(&self) -> u64 {
+        self.dropped.load(Ordering::Relaxed)
+    }
+}
@@ -0,0 +1,201 @@
+//! Config reloading in `--continuous` mode, so endpoints and filters can change without
+//! restarting the process: endpoint changes are applied to the running benchmark, everything else
+//! from the next run.
+
+use std::{
+    collections::hash_map::DefaultHasher,
+    error::Error,
+    fs,
+    hash::{Hash, Hasher},
+};
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::config::{ConfigToml, Endpoint};
+
+/// How often the config file is checked for changes while runs are in progress
+pub const POLL_INTERVAL_SECS: u64 = 2;
+
+// Settings every subscription is built from; a change restarts all running endpoints
+const SUBSCRIPTION_KEYS: &[&str] = &[
+    "account",
+    "accounts",
+    "filter_groups",
+    "commitment",
+    "transaction_filter",
+    "accounts_filter",
+    "accounts_owner",
+    "accounts_data_slice",
+    "blocks",
+];
+
+/// A config applied by the watcher, numbered like [`ConfigWatcher::generation`].
+#[derive(Debug, Clone)]
+pub struct Generation {
+    pub number: u64,
+    pub config: ConfigToml,
+}
+
+/// An endpoint joining or leaving a run because of a config reload.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MembershipChange {
+    pub generation: u64,
+    pub endpoint: String,
+    /// False when the endpoint was stopped
+    pub joined: bool,
+    /// Comparator time of the change, in seconds since the epoch
+    pub at: f64,
+}
+
+/// What applying a reloaded config to a running benchmark takes.
+#[derive(Debug, Default)]
+pub struct ReloadPlan {
+    /// Running endpoints to stop, by name; changed endpoints are stopped and started again
+    pub stop: Vec<String>,
+    /// Endpoints to start, in config order
+    pub start: Vec<Endpoint>,
+    /// Top-level settings that changed but only take effect from the next run
+    pub deferred: Vec<String>,
+}
+
+pub struct ConfigWatcher {
+    path: String,
+    hash: Option<u64>,
+    /// Bumped on every applied change; the config the process started with is generation 1
+    pub generation: u64,
+}
+
+fn content_hash(path: &str) -> Option<u64> {
+    let content = fs::read(path).ok()?;
+    let mut hasher = DefaultHasher::new();
+    content.hash(&mut hasher);
+    Some(hasher.finish())
+}
+
+impl ConfigWatcher {
+    pub fn new(path: &str) -> Self {
+        Self {
+            path: path.to_string(),
+            hash: content_hash(path),
+            generation: 1,
+        }
+    }
+
+    // AIDEV-NOTE: The file is compared by content rather than modification time, which editors
+    // and coarse filesystem clocks make unreliable. A file that fails to load or validate, or that
+    // switches `mode`, is reported and skipped: the runs keep the last good config, and the broken
+    // file is not retried until it changes again.
+    /// The reloaded config when the file changed since the last call; `prepare` reapplies the
+    /// command line overrides. None keeps `current`.
+    pub fn poll(
+        &mut self,
+        current: &ConfigToml,
+        prepare: impl FnOnce(ConfigToml) -> Result<ConfigToml, Box<dyn Error>>,
+    ) -> Option<ConfigToml> {
+        let hash = content_hash(&self.path);
+        if hash.is_none() || hash == self.hash {
+            return None;
+        }
+        self.hash = hash;
+        let reloaded = match ConfigToml::load(&self.path).and_then(prepare) {
+            Ok(reloaded) => reloaded,
+            Err(e) => {
+                log::error!("Ignoring changed config {}: {}", self.path, e);
+                return None;
+            }
+        };
+        if reloaded.config.mode != current.config.mode {
+            log::error!(
+                "Ignoring changed config {}: mode cannot change between runs ({:?} to {:?})",
+                self.path,
+                current.config.mode,
+                reloaded.config.mode
+            );
+            return None;
+        }
+        let changes = describe_changes(current, &reloaded);
+        if changes.is_empty() {
+            return None;
+        }
+        self.generation += 1;
+        log::info!("Applying config generation {}: {}", self.generation, changes.join("; "));
+        Some(reloaded)
+    }
+}
+
+// AIDEV-NOTE: Providers get the whole shared config when they start, so an endpoint is restarted
+// (rather than patched) for any change to it or to the settings its subscription is built from.
+/// The endpoints to stop and start to move a run from `running` to `new`.
+pub fn plan(running: &ConfigToml, new: &ConfigToml) -> ReloadPlan {
+    let changed = changed_settings(running, new);
+    let (restart_all, deferred): (Vec<String>, Vec<String>) =
+        changed.into_iter().partition(|key| SUBSCRIPTION_KEYS.contains(&key.as_str()));
+    let mut plan = ReloadPlan {
+        deferred,
+        ..Default::default()
+    };
+    for endpoint in &running.endpoint {
+        let kept = new
+            .endpoint
+            .iter()
+            .find(|current| current.name == endpoint.name)
+            .is_some_and(|current| restart_all.is_empty() && same_endpoint(endpoint, current));
+        if !kept {
+            plan.stop.push(endpoint.name.clone());
+        }
+    }
+    for endpoint in &new.endpoint {
+        let kept = running
+            .endpoint
+            .iter()
+            .find(|previous| previous.name == endpoint.name)
+            .is_some_and(|previous| restart_all.is_empty() && same_endpoint(previous, endpoint));
+        if !kept {
+            plan.start.push(endpoint.clone());
+        }
+    }
+    plan
+}
+
+fn same_endpoint(a: &Endpoint, b: &Endpoint) -> bool {
+    serde_json::to_value(a).ok() == serde_json::to_value(b).ok()
+}
+
+/// Top-level settings whose value differs between the two configs.
+fn changed_settings(old: &ConfigToml, new: &ConfigToml) -> Vec<String> {
+    let (Ok(Value::Object(old)), Ok(Value::Object(new))) = (serde_json::to_value(&old.config), serde_json::to_value(&new.config)) else {
+        return Vec::new();
+    };
+    old.keys()
+        .chain(new.keys().filter(|key| !old.contains_key(*key)))
+        .filter(|key| old.get(*key) != new.get(*key))
+        .cloned()
+        .collect()
+}
+
+/// Endpoints added, removed or changed, and the top-level settings that changed. Values are not
+/// included, so tokens never reach the log.
+pub fn describe_changes(old: &ConfigToml, new: &ConfigToml) -> Vec<String> {
+    let mut changes = Vec::new();
+    for endpoint in &new.endpoint {
+        match old.endpoint.iter().find(|previous| previous.name == endpoint.name) {
+            None => changes.push(format!("added endpoint {}", endpoint.name)),
+            Some(previous) if !same_endpoint(previous, endpoint) => {
+                changes.push(format!("changed endpoint {}", endpoint.name))
+            }
+            Some(_) => {}
+        }
+    }
+    for endpoint in &old.endpoint {
+        if !new.endpoint.iter().any(|current| current.name == endpoint.name) {
+            changes.push(format!("removed endpoint {}", endpoint.name));
+        }
+    }
+
+    let changed = changed_settings(old, new);
+    if !changed.is_empty() {
+        changes.push(format!("changed {}", changed.join(", ")));
+    }
+    changes
+}
@@ -0,0 +1,210 @@
+//! Aggregated statistics over repeated runs (`--repeat`), so one noisy run does not decide a
+//! comparison.
+
+use std::collections::BTreeMap;
+
+use serde::{Deserialize, Serialize};
+
+use crate::report::BenchmarkReport;
+
+/// Mean and spread of one number across runs.
+#[derive(Debug, Clone, Copy, Default, Deserialize, Serialize)]
+pub struct Spread {
+    pub mean: f64,
+    /// Sample standard deviation across runs; zero for a single run
+    pub stddev: f64,
+    pub min: f64,
+    pub max: f64,
+}
+
+impl Spread {
+    pub fn of(values: &[f64]) -> Self {
+        if values.is_empty() {
+            return Self::default();
+        }
+        let mean = values.iter().sum::<f64>() / values.len() as f64;
+        let variance = if values.len() > 1 {
+            values.iter().map(|value| (value - mean).powi(2)).sum::<f64>() / (values.len() - 1) as f64
+        } else {
+            0.0
+        };
+        Self {
+            mean,
+            stddev: variance.sqrt(),
+            min: values.iter().copied().fold(f64::INFINITY, f64::min),
+            max: values.iter().copied().fold(f64::NEG_INFINITY, f64::max),
+        }
+    }
+}
+
+/// One endpoint's results across the runs it took part in.
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+pub struct RepeatedEndpointReport {
+    pub name: String,
+    pub runs: usize,
+    /// Runs in which this endpoint had the highest win rate
+    pub fastest_runs: usize,
+    pub win_rate: Spread,
+    /// Per-run median and p95 delay behind the first detection; zero in runs it never trailed
+    pub median_delay_ms: Spread,
+    pub p95_delay_ms: Spread,
+    pub transactions: Spread,
+    /// Sustained messages per second, in throughput mode
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sustained_rate: Option<Spread>,
+}
+
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+pub struct RepetitionReport {
+    pub runs: usize,
+    pub pause_secs: f64,
+    /// Runs that ended early, by their stop reason
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub stopped_early: Vec<String>,
+    pub endpoints: Vec<RepeatedEndpointReport>,
+}
+
+/// Runs whose reports `--continuous` keeps: in memory for the cross-run statistics, and as
+/// numbered files, which roll over to `run1` after the last
+pub const CONTINUOUS_KEPT_RUNS: usize = 100;
+
+/// Where run `index` (zero-based) of a repetition writes its own report: `report.json` becomes
+/// `report.run1.json`.
+pub fn run_report_path(path: &str, index: usize) -> String {
+    let run = format!("run{}", index + 1);
+    let file_start = path.rfind('/').map_or(0, |slash| slash + 1);
+    match path[file_start..].rfind('.') {
+        Some(dot) if dot > 0 => format!("{}.{}{}", &path[..file_start + dot], run, &path[file_start + dot..]),
+        _ => format!("{}.{}", path, run),
+    }
+}
+
+/// The parts of a run's report `aggregate` and the assertion check read, so `--continuous` does
+/// not hold every full report for the life of the process.
+pub fn summary_only(report: &BenchmarkReport) -> BenchmarkReport {
+    BenchmarkReport {
+        fastest_endpoint: report.fastest_endpoint.clone(),
+        endpoints: report.endpoints.clone(),
+        throughput: report.throughput.clone(),
+        stop_reason: report.stop_reason.clone(),
+        assertions: report.assertions.clone(),
+        config_generation: report.config_generation,
+        ..Default::default()
+    }
+}
+
+pub fn aggregate(reports: &[BenchmarkReport], pause_secs: f64) -> RepetitionReport {
+    #[derive(Default)]
+    struct Samples {
+        fastest_runs: usize,
+        win_rate: Vec<f64>,
+        median_delay_ms: Vec<f64>,
+        p95_delay_ms: Vec<f64>,
+        transactions: Vec<f64>,
+        sustained_rate: Vec<f64>,
+    }
+
+    // Endpoints keep the order they first appeared in
+    let mut order: Vec<String> = Vec::new();
+    let mut samples: BTreeMap<String, Samples> = BTreeMap::new();
+    for report in reports {
+        for endpoint in &report.endpoints {
+            if !samples.contains_key(&endpoint.name) {
+                order.push(endpoint.name.clone());
+            }
+            let entry = samples.entry(endpoint.name.clone()).or_default();
+            let delays = endpoint.delays.clone().unwrap_or_default();
+            entry.win_rate.push(endpoint.win_rate);
+            entry.median_delay_ms.push(delays.median_ms);
+            entry.p95_delay_ms.push(delays.p95_ms);
+            entry.transactions.push(endpoint.total_valid_transactions as f64);
+            if report.fastest_endpoint.as_deref() == Some(endpoint.name.as_str()) {
+                entry.fastest_runs += 1;
+            }
+        }
+        for throughput in &report.throughput {
+            if !samples.contains_key(&throughput.name) {
+                order.push(throughput.name.clone());
+            }
+            let entry = samples.entry(throughput.name.clone()).or_default();
+            entry.sustained_rate.push(throughput.sustained_rate);
+            entry.transactions.push(throughput.messages as f64);
+        }
+    }
+
+    let endpoints = order
+        .into_iter()
+        .filter_map(|name| {
+            let samples = samples.remove(&name)?;
+            Some(RepeatedEndpointReport {
+                runs: samples.transactions.len(),
+                fastest_runs: samples.fastest_runs,
+                win_rate: Spread::of(&samples.win_rate),
+                median_delay_ms: Spread::of(&samples.median_delay_ms),
+                p95_delay_ms: Spread::of(&samples.p95_delay_ms),
+                transactions: Spread::of(&samples.transactions),
+                sustained_rate: (!samples.sustained_rate.is_empty()).then(|| Spread::of(&samples.sustained_rate)),
+                name,
+            })
+        })
+        .collect();
+
+    RepetitionReport {
+        runs: reports.len(),
+        pause_secs,
+        stopped_early: reports.iter().filter_map(|report| report.stop_reason.clone()).collect(),
+        endpoints,
+    }
+}
+
+pub fn print_repetition(report: &RepetitionReport) {
+    let fmt = |spread: &Spread, unit: &str| {
+        format!(
+            "{:.2}{} ± {:.2} (min {:.2}, max {:.2})",
+            spread.mean, unit, spread.stddev, spread.min, spread.max
+        )
+    };
+
+    println!("\nAcross {} runs", report.runs);
+    println!("--------------------------------------------");
+    for endpoint in &report.endpoints {
+        println!("{} ({} runs, fastest in {}):", endpoint.name, endpoint.runs, endpoint.fastest_runs);
+        match &endpoint.sustained_rate {
+            Some(rate) => println!("  Sustained rate: {}", fmt(rate, " msg/s")),
+            None => {
+                println!("  Win rate: {}", fmt(&endpoint.win_rate, "%"));
+                println!("  Median delay: {}", fmt(&endpoint.median_delay_ms, " ms"));
+                println!("  95th percentile: {}", fmt(&endpoint.p95_delay_ms, " ms"));
+            }
+        }
+        println!("  Transactions: {}", fmt(&endpoint.transactions, ""));
+    }
+    if !report.stopped_early.is_empty() {
+        println!("Runs stopped early: {}", report.stopped_early.join(", "));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn spread_uses_the_sample_standard_deviation() {
+        let spread = Spread::of(&[2.0, 4.0, 4.0, 4.0, 5.0, 5.0, 7.0, 9.0]);
+        assert_eq!(spread.mean, 5.0);
+        assert!((spread.stddev - (32.0f64 / 7.0).sqrt()).abs() < 1e-12);
+        assert_eq!((spread.min, spread.max), (2.0, 9.0));
+    }
+
+    #[test]
+    fn spread_of_one_run_has_no_deviation() {
+        let spread = Spread::of(&[3.5]);
+        assert_eq!((spread.mean, spread.stddev, spread.min, spread.max), (3.5, 0.0, 3.5, 3.5));
+    }
+
+    #[test]
+    fn spread_of_no_runs_is_zero() {
+        let spread = Spread::of(&[]);
+        assert_eq!((spread.mean, spread.stddev, spread.min, spread.max), (0.0, 0.0, 0.0, 0.0));
+    }
+}
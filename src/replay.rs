@@ -0,0 +1,127 @@
+use std::{
+    collections::BTreeSet,
+    error::Error,
+    fs,
+    io::{self, BufRead, BufReader},
+    path::Path,
+};
+
+use crate::{
+    analysis,
+    config::Config,
+    report::{self, BenchmarkReport},
+    log_output,
+    utils::{Comparator, TransactionData, parse_log_entry},
+};
+
+/// Comparator state rebuilt from transaction logs.
+#[derive(Debug, Default)]
+pub struct Replay {
+    pub comparator: Comparator,
+    pub endpoint_names: Vec<String>,
+    pub files: usize,
+    pub entries: usize,
+    pub skipped_lines: usize,
+    /// Earliest and latest entry read
+    pub start_time: f64,
+    pub end_time: f64,
+}
+
+// AIDEV-NOTE: Logs carry only receive time, endpoint and signature, so the replay has no slots,
+// meta or market tags. TSC ticks are ignored: their calibration lived in the process that wrote
+// them. Each endpoint keeps its earliest sighting per signature, as Comparator::add does, but
+// entries are inserted directly to skip add()'s per-insert logging.
+/// Reads every transaction log in `dir` and its run subdirectories, keeping entries within `[since, until]` (Unix seconds).
+pub fn load_logs(dir: &Path, since: Option<f64>, until: Option<f64>) -> io::Result<Replay> {
+    let mut replay = Replay {
+        start_time: f64::INFINITY,
+        end_time: f64::NEG_INFINITY,
+        ..Default::default()
+    };
+    let mut endpoint_names = BTreeSet::new();
+
+    for path in log_output::log_files(dir)? {
+        replay.files += 1;
+
+        for line in BufReader::new(fs::File::open(path)?).lines() {
+            let Some(log_entry) = parse_log_entry(&line?) else {
+                replay.skipped_lines += 1;
+                continue;
+            };
+            if since.is_some_and(|since| log_entry.timestamp < since)
+                || until.is_some_and(|until| log_entry.timestamp > until)
+            {
+                continue;
+            }
+            replay.entries += 1;
+            replay.start_time = replay.start_time.min(log_entry.timestamp);
+            replay.end_time = replay.end_time.max(log_entry.timestamp);
+            endpoint_names.insert(log_entry.endpoint.clone());
+
+            let observations = replay.comparator.data.entry(log_entry.signature.clone()).or_default();
+            if observations
+                .get(&log_entry.endpoint)
+                .is_some_and(|existing| existing.timestamp <= log_entry.timestamp)
+            {
+                continue;
+            }
+            observations.insert(
+                log_entry.endpoint,
+                TransactionData {
+                    timestamp: log_entry.timestamp,
+                    signature: log_entry.signature,
+                    start_time: 0.0,
+                    tsc: log_entry.tsc,
+                    status: None,
+                    slot: None,
+                    entry: None,
+                    groups: Vec::new(),
+                },
+            );
+        }
+    }
+
+    if replay.entries == 0 {
+        replay.start_time = 0.0;
+        replay.end_time = 0.0;
+    }
+    replay.endpoint_names = endpoint_names.into_iter().collect();
+    Ok(replay)
+}
+
+/// Analysis settings when no config file is around: every option at its default.
+pub fn default_config() -> Config {
+    toml::from_str("transactions = 0\naccount = \"\"\ncommitment = \"processed\"")
+        .expect("minimal config is valid")
+}
+
+/// Rebuilds the run from the logs in `dir`, prints the statistics and optionally writes a report.
+pub fn analyze_logs(
+    dir: &Path,
+    config: &Config,
+    since: Option<f64>,
+    until: Option<f64>,
+    report_path: Option<&str>,
+) -> Result<BenchmarkReport, Box<dyn Error>> {
+    let replay = load_logs(dir, since, until)?;
+    println!(
+        "Replaying {} entries from {} transaction logs in {} ({} endpoints, {} unparseable lines)",
+        replay.entries,
+        replay.files,
+        dir.display(),
+        replay.endpoint_names.len(),
+        replay.skipped_lines
+    );
+    if replay.entries == 0 {
+        return Err(format!("no transaction log entries found in {}", dir.display()).into());
+    }
+
+    let mut report = analysis::analyze_delays(&replay.comparator, replay.endpoint_names.clone(), config);
+    report.start_time = replay.start_time;
+    report.end_time = replay.end_time;
+    report.stop_reason = Some("replayed from transaction logs".to_string());
+    if let Some(path) = report_path {
+        report::write_report(path, &report, None)?;
+    }
+    Ok(report)
+}
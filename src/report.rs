@@ -0,0 +1,699 @@
+use serde::{Deserialize, Serialize};
+use std::{collections::BTreeMap, fs, io::Write};
+
+use crate::{
+    alerts::AlertViolation,
+    assertions::AssertionResult,
+    clock::ClockOffset,
+    config::{BenchmarkMode, ConfigToml, TiePolicy},
+    leaders::LeaderReport,
+    ground_truth::GroundTruthReport,
+    runtimes::RuntimeReport,
+    slot_position::SlotPositionReport,
+    slot_status::SlotStatusReport,
+    markets::AmmProgram,
+    metadata::RunMetadata,
+    events::EventOrderReport,
+    profile::SelfProfileReport,
+    reload::MembershipChange,
+    repeat::RepetitionReport,
+    sender::SendReport,
+    signing,
+    slo::SloResult,
+    throughput::ThroughputReport,
+    timeline::EndpointTimeline,
+    utils::{EndpointMetrics, RaceResult, StreamEventKind, TransactionStatus},
+};
+
+// AIDEV-NOTE: Serialized form of the final analysis; exported as JSON when `report_path` is set
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+pub struct BenchmarkReport {
+    pub mode: BenchmarkMode,
+    pub start_time: f64,
+    pub end_time: f64,
+    pub fastest_endpoint: Option<String>,
+    pub tie_policy: TiePolicy,
+    pub tied_signatures: usize,
+    pub excluded_signatures: usize,
+    pub before_overlap: usize,
+    pub endpoints: Vec<EndpointReport>,
+    pub largest_margins: Vec<RaceResult>,
+    pub throughput: Vec<ThroughputReport>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub status_checks: Vec<StatusPairReport>,
+    /// Why the run ended early (`max_runtime_secs`, Ctrl+C); None when the transaction target was reached
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub stop_reason: Option<String>,
+    /// Transaction log lines dropped per log because the writer fell behind
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub dropped_log_entries: BTreeMap<String, u64>,
+    /// InfluxDB lines dropped because the writer fell behind; None without `influx`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub dropped_influx_lines: Option<u64>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub slots: Vec<SlotReport>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub pairs: Vec<PairReport>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub account_integrity: Option<AccountIntegrityReport>,
+    /// Bytes delivered and saved by each endpoint with `accounts_data_slice`
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub data_slices: Vec<DataSliceReport>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sampling: Option<SamplingReport>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub anomalies: Vec<AnomalyAnnotation>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub execution_gap: Option<ExecutionGapReport>,
+    /// Post-execution delays against each transaction's position in its slot, with shred endpoints
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub slot_position: Option<SlotPositionReport>,
+    /// Per-status propagation in slots mode
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub slot_status: Option<SlotStatusReport>,
+    /// Raw and handicapped race results per endpoint, when an endpoint sets `handicap_ms`
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub handicap: Vec<HandicapReport>,
+    /// Race results aggregated by endpoint tag, one entry per `group_by`
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tag_groups: Vec<TagGroupingReport>,
+    /// Busiest decoded AMM markets, most signatures first
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub markets: Vec<MarketReport>,
+    /// How completely and how fast each endpoint replayed from `from_slot`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub replay: Option<ReplayReport>,
+    /// Progress and per-endpoint numbers for each account in `account_targets`
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub account_targets: Vec<AccountTargetReport>,
+    /// Wins and delays computed separately within each of `filter_groups`
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub filter_groups: Vec<FilterGroupReport>,
+    /// Slots each endpoint trailed the highest slot delivered by any endpoint
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub slot_lag: Vec<SlotLagReport>,
+    /// Whether parallel connections to one endpoint (`connections`) saw signatures earlier
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub connection_pools: Vec<ConnectionPoolReport>,
+    /// Per-endpoint count and sample of signatures only other endpoints delivered
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub gaps: Vec<GapReport>,
+    /// Endpoints ranked by the weighted score of win rate, delays, coverage and uptime
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub scoreboard: Vec<ScoreboardEntry>,
+    /// Endpoint delays per slot leader, with `leaders`
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub leaders: Vec<LeaderReport>,
+    /// Endpoint coverage against the RPC's signatures, with `ground_truth`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub ground_truth: Option<GroundTruthReport>,
+    /// Latency budget violations found while the run went on, with `alerts`
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub alerts: Vec<AlertViolation>,
+    /// Skew between provider-side `created_at` stamps and local receive time, per endpoint
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub provider_timing: Vec<ProviderTimingReport>,
+    /// Ping round trips per endpoint, with `ping_interval_secs` or `rtt_interval_secs`
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub rtt: Vec<RttReport>,
+    /// Submit-to-stream latency of the transactions geyserbench sent itself, with `sender`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub send: Option<SendReport>,
+    /// Runner-up gaps and per-slot winner changes from the ordered event log, with `event_log`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub event_order: Option<EventOrderReport>,
+    /// Connection milestones and stream events per endpoint, in time order
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub timeline: Vec<EndpointTimeline>,
+    /// Per-endpoint behaviour under the simulated slow consumer (`consumer_delay_us`)
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub backpressure: Vec<BackpressureReport>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub clock_offset: Option<ClockOffset>,
+    /// geyserbench's own CPU, memory and per-task load, with `self_profile`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub self_profile: Option<SelfProfileReport>,
+    /// Scheduler delay of the shared runtime and each endpoint's own, with `dedicated_runtimes`
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub runtimes: Vec<RuntimeReport>,
+    /// Statistics across every run of a `--repeat`, on the last run's report
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub repetition: Option<RepetitionReport>,
+    /// Config generation the run ended on in `--continuous` mode; bumped by each applied reload
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub config_generation: Option<u64>,
+    /// Endpoints started or stopped mid-run by config reloads, with the generation that did it
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub membership: Vec<MembershipChange>,
+    /// Outcome of each `--assert` expression; any failure makes the process exit non-zero
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub assertions: Vec<AssertionResult>,
+    /// Build, host and config hash of the run that produced this report
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub metadata: Option<RunMetadata>,
+    /// Resolved configuration the run used, with endpoint tokens redacted
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub effective_config: Option<ConfigToml>,
+}
+
+/// How far behind the chain tip an endpoint's transactions were, in slots.
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+pub struct SlotLagReport {
+    pub name: String,
+    pub samples: usize,
+    pub avg_slots: f64,
+    pub p50_slots: f64,
+    pub p95_slots: f64,
+    pub max_slots: u64,
+    /// Lag over the run in fixed windows from the first sighting
+    pub windows: Vec<SlotLagWindow>,
+}
+
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+pub struct SlotLagWindow {
+    pub start: f64,
+    pub avg_slots: f64,
+    pub max_slots: u64,
+}
+
+/// First-seen race between the parallel connections of one endpoint.
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+pub struct ConnectionPoolReport {
+    pub name: String,
+    /// Signatures every connection delivered
+    pub signatures: usize,
+    pub connections: Vec<PoolConnectionReport>,
+    /// Mean of the connections' median delays: what the pool saves over a single connection
+    pub median_gain_ms: f64,
+}
+
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+pub struct PoolConnectionReport {
+    pub connection: usize,
+    /// Signatures this connection delivered first within the pool
+    pub wins: usize,
+    /// Median delay behind the pool's earliest sighting
+    pub median_delay_ms: f64,
+}
+
+/// One endpoint's place on the scoreboard; component scores are 0-100 before weighting.
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+pub struct ScoreboardEntry {
+    pub rank: usize,
+    pub name: String,
+    pub score: f64,
+    pub win_rate: f64,
+    /// Median and p99 delay behind the first detection, counting first detections as zero
+    pub median_delta_ms: f64,
+    pub p99_delta_ms: f64,
+    /// Share of all live signatures this endpoint delivered
+    pub coverage: f64,
+    /// Share of the observed run the stream was not down
+    pub uptime: f64,
+}
+
+/// Signatures an endpoint never delivered although at least one other endpoint did.
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+pub struct GapReport {
+    pub name: String,
+    pub missed: usize,
+    /// Share of all live signatures this endpoint delivered
+    pub coverage: f64,
+    /// Earliest missed signatures, by first sighting
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub examples: Vec<String>,
+}
+
+/// Receive time against the provider's own timestamps, for one endpoint.
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+pub struct ProviderTimingReport {
+    pub name: String,
+    /// Updates carrying a `created_at` stamp
+    pub updates: usize,
+    /// Receive time minus `created_at`: transit and client queueing, plus any clock difference
+    pub median_skew_ms: f64,
+    pub p95_skew_ms: f64,
+    pub min_skew_ms: f64,
+    /// Median skew above the endpoint with the lowest one
+    pub transit_excess_ms: f64,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub median_delay_ms: Option<f64>,
+    /// Blocks whose `block_time` was known
+    #[serde(default)]
+    pub blocks: usize,
+    /// Median receive time minus the block's `block_time` (whole seconds, leader clock)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub median_block_time_lag_ms: Option<f64>,
+}
+
+/// Round trips of client pings over the endpoint's subscription stream.
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+pub struct RttReport {
+    pub name: String,
+    pub pings: usize,
+    /// Pings sent without a pong by the end of the run
+    pub unanswered: u64,
+    /// Closest to the pure network round trip
+    pub min_ms: f64,
+    pub median_ms: f64,
+    pub p95_ms: f64,
+    pub max_ms: f64,
+}
+
+/// How an endpoint's stream coped with a consumer slower than its delivery rate.
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+pub struct BackpressureReport {
+    pub name: String,
+    pub messages: u64,
+    pub delayed_secs: f64,
+    pub disconnects: usize,
+    pub stalls: usize,
+    pub downtime_secs: f64,
+    /// Live signatures another endpoint delivered and this one never did
+    pub missed: usize,
+    /// Median delay in the last quarter of the run minus the first quarter; growth means the
+    /// server (or the network) is buffering for the slow reader
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub lag_growth_ms: Option<f64>,
+}
+
+/// One endpoint's race results before and after adding its `handicap_ms` to every receive time.
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+pub struct HandicapReport {
+    pub name: String,
+    /// Positive delays the endpoint, negative moves it closer
+    pub handicap_ms: f64,
+    pub signatures: usize,
+    pub raw_win_rate: f64,
+    pub win_rate: f64,
+    pub raw_median_delay_ms: f64,
+    pub median_delay_ms: f64,
+}
+
+/// Results of one `group_by` entry, one partition per value of its `within` tag.
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+pub struct TagGroupingReport {
+    pub tag: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub within: Option<String>,
+    pub partitions: Vec<TagPartitionReport>,
+}
+
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+pub struct TagPartitionReport {
+    /// Value of the `within` tag; None without `within`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub value: Option<String>,
+    /// Live signatures seen by any endpoint in the partition
+    pub signatures: usize,
+    pub groups: Vec<TagGroupReport>,
+}
+
+/// Endpoints sharing one tag value, racing as one by their fastest member.
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+pub struct TagGroupReport {
+    pub value: String,
+    pub endpoints: Vec<String>,
+    pub seen: usize,
+    pub first_detections: usize,
+    /// First detections over the partition's signatures
+    pub win_rate: f64,
+    /// Median delay of the group's fastest member behind the partition's first detection
+    pub median_delay_ms: f64,
+}
+
+/// Head start of shred-based (pre-execution) endpoints over post-execution ones, per signature
+/// taking the earliest sighting on each side.
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+pub struct ExecutionGapReport {
+    pub pre_execution: Vec<String>,
+    pub post_execution: Vec<String>,
+    /// Signatures seen on both sides
+    pub signatures: usize,
+    pub pre_execution_first: usize,
+    /// Positive when the pre-execution side was ahead
+    pub median_gap_ms: f64,
+    pub p95_gap_ms: f64,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct MarketReport {
+    pub program: AmmProgram,
+    pub market: String,
+    pub signatures: usize,
+    pub endpoints: Vec<MarketEndpointReport>,
+}
+
+/// Signatures from `from_slot` to the lowest tip slot any endpoint reported when subscribing.
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+pub struct ReplayReport {
+    pub from_slot: u64,
+    pub tip_slot: u64,
+    /// Distinct signatures in the range seen by any endpoint
+    pub signatures: usize,
+    pub endpoints: Vec<ReplayEndpointReport>,
+}
+
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+pub struct ReplayEndpointReport {
+    pub name: String,
+    pub tip_slot: u64,
+    pub signatures: usize,
+    /// Share of the range's signatures this endpoint delivered
+    pub completeness: f64,
+    /// Lowest slot delivered in the range; above the other endpoints' when the start was not honoured
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub first_slot: Option<u64>,
+    /// Subscription to the last replayed signature
+    pub duration_secs: f64,
+    pub signatures_per_sec: f64,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct AccountTargetReport {
+    pub account: String,
+    pub target: u64,
+    /// Live signatures touching the account
+    pub signatures: usize,
+    pub endpoints: Vec<MarketEndpointReport>,
+}
+
+/// Endpoint numbers over the live signatures touching one of `filter_groups`.
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+pub struct FilterGroupReport {
+    pub name: String,
+    pub accounts: Vec<String>,
+    pub signatures: usize,
+    pub endpoints: Vec<MarketEndpointReport>,
+}
+
+/// One endpoint's numbers over a subset of signatures: a market's, a targeted account's or a
+/// filter group's.
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+pub struct MarketEndpointReport {
+    pub name: String,
+    pub seen: usize,
+    pub first_detections: usize,
+    /// Median delay behind the first detection over the market's signatures this endpoint saw
+    pub median_delay_ms: f64,
+}
+
+/// A latency spike window that overlaps a stall or disconnect of the same endpoint.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct AnomalyAnnotation {
+    pub endpoint: String,
+    /// Start of the spike window
+    pub at: f64,
+    pub p99_ms: f64,
+    /// Median of the endpoint's per-window p99 delays
+    pub baseline_p99_ms: f64,
+    pub event: StreamEventKind,
+    pub event_at: f64,
+    pub message: String,
+}
+
+/// Distinct signatures kept and dropped when `sample_rate` is set.
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+pub struct SamplingReport {
+    pub rate: f64,
+    pub kept: u64,
+    pub dropped: u64,
+}
+
+/// Account data one endpoint received with `accounts_data_slice`, against the whole payloads.
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+pub struct DataSliceReport {
+    pub name: String,
+    pub updates: u64,
+    pub delivered_bytes: u64,
+    /// Updates of accounts whose whole size is known, from a `datasize` filter or an unsliced endpoint
+    pub sized_updates: u64,
+    /// Whole payload size of the sized updates
+    pub full_bytes: u64,
+    /// `full_bytes` minus what the sized updates delivered
+    pub saved_bytes: u64,
+}
+
+/// Account writes delivered by 2+ endpoints whose payloads disagreed.
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+pub struct AccountIntegrityReport {
+    /// (pubkey, write_version) pairs seen by at least two endpoints
+    pub compared: usize,
+    pub mismatches: usize,
+    /// Writes where the endpoint delivered fewer bytes than another endpoint did
+    pub short_by_endpoint: BTreeMap<String, usize>,
+    /// Mismatches where lamports, owner, executable or rent epoch disagreed
+    #[serde(default)]
+    pub meta_mismatches: usize,
+    /// Mismatches where the endpoint's content differed from what a majority of endpoints delivered
+    #[serde(default)]
+    pub outvoted_by_endpoint: BTreeMap<String, usize>,
+    pub examples: Vec<AccountMismatch>,
+}
+
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+pub struct AccountMismatch {
+    pub pubkey: String,
+    pub write_version: u64,
+    pub sizes: BTreeMap<String, usize>,
+    /// Sizes agreed but the bytes did not
+    pub same_size: bool,
+    /// Lamports, owner, executable or rent epoch disagreed
+    #[serde(default)]
+    pub meta_differs: bool,
+}
+
+/// One endpoint's copy of an account write that differed between endpoints, for `account_diff_path`.
+#[derive(Debug, Clone)]
+pub struct AccountDiffRow {
+    pub pubkey: String,
+    pub write_version: u64,
+    pub endpoint: String,
+    pub len: usize,
+    pub data_hash: u64,
+    pub meta_hash: u64,
+    /// None when no content was delivered by a majority of the endpoints
+    pub agrees_with_majority: Option<bool>,
+}
+
+/// Head-to-head timing of two endpoints over the signatures both saw; positive deltas mean `first` was ahead.
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+pub struct PairReport {
+    pub first: String,
+    pub second: String,
+    pub signatures: usize,
+    pub first_wins: usize,
+    pub median_delta_ms: f64,
+    /// 95% distribution-free confidence interval for `median_delta_ms`; None below 6 signatures
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub median_delta_ci_ms: Option<(f64, f64)>,
+    /// Two-sided Wilcoxon signed-rank p-value against a zero median delta; None when every delta is zero
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub p_value: Option<f64>,
+    /// Whether `p_value` is below 0.05; false reads as "no significant difference"
+    #[serde(default)]
+    pub significant: bool,
+    /// Median delta per `heatmap_window_secs` window, by time of the pair's first sighting
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub windows: Vec<PairWindow>,
+}
+
+/// One heatmap cell: a pair's median delta over the signatures first seen in the window.
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+pub struct PairWindow {
+    /// Unix seconds
+    pub start: f64,
+    pub signatures: usize,
+    pub median_delta_ms: f64,
+}
+
+/// First detections and average delay behind the first detection for the signatures of one slot.
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+pub struct SlotReport {
+    pub slot: u64,
+    pub signatures: usize,
+    pub first_detections: BTreeMap<String, usize>,
+    pub avg_delay_ms: BTreeMap<String, f64>,
+    pub worst_endpoint: Option<String>,
+    pub worst_avg_delay_ms: f64,
+}
+
+/// Agreement of execution status between two endpoints over the signatures both reported meta for.
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+pub struct StatusPairReport {
+    pub first: String,
+    pub second: String,
+    pub compared: usize,
+    pub disagreements: usize,
+    pub examples: Vec<StatusDisagreement>,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct StatusDisagreement {
+    pub signature: String,
+    pub first_status: TransactionStatus,
+    pub second_status: TransactionStatus,
+}
+
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+pub struct EndpointReport {
+    pub name: String,
+    pub first_detections: usize,
+    pub total_valid_transactions: usize,
+    pub old_transactions: usize,
+    pub win_rate: f64,
+    pub ties: usize,
+    pub delays: Option<DelaySummary>,
+    pub metrics: EndpointMetrics,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub slo: Vec<SloResult>,
+    /// Valid transactions that arrived with execution meta (fee, compute units)
+    #[serde(default)]
+    pub with_meta: usize,
+    #[serde(default)]
+    pub missing_meta_rate: f64,
+    /// Time the watchdog found the stream silent, shards of the endpoint merged
+    #[serde(default)]
+    pub downtime_secs: f64,
+}
+
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+pub struct DelaySummary {
+    pub average_ms: f64,
+    pub median_ms: f64,
+    pub p95_ms: f64,
+    pub min_ms: f64,
+    pub max_ms: f64,
+    pub trimmed_mean_ms: f64,
+    pub mad_ms: f64,
+}
+
+pub fn write_report(
+    path: &str,
+    report: &BenchmarkReport,
+    signing_keypair: Option<&str>,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let content = serde_json::to_vec_pretty(report)?;
+    fs::write(path, &content)?;
+    log::info!("Wrote benchmark report to {}", path);
+
+    if let Some(keypair_path) = signing_keypair {
+        let signature_path = signing::sign_file(path, &content, keypair_path)?;
+        log::info!("Wrote report signature to {}", signature_path);
+    }
+
+    Ok(())
+}
+
+/// Reads a JSON report written by [`write_report`].
+pub fn load_report(path: &str) -> Result<BenchmarkReport, Box<dyn std::error::Error>> {
+    let content = fs::read(path)?;
+    serde_json::from_slice(&content).map_err(|e| format!("Invalid report {}: {}", path, e).into())
+}
+
+pub fn write_race_table(path: &str, races: &[RaceResult]) -> std::io::Result<()> {
+    let mut file = fs::File::create(path)?;
+    writeln!(file, "signature,winner,tied_with,runner_up,margin_ms,endpoints")?;
+    for race in races {
+        writeln!(
+            file,
+            "{},{},{},{},{:.3},{}",
+            race.signature,
+            race.winner,
+            race.tied_with.join(";"),
+            race.runner_up.as_deref().unwrap_or_default(),
+            race.margin_ms,
+            race.endpoints
+        )?;
+    }
+    log::info!("Wrote race table with {} signatures to {}", races.len(), path);
+    Ok(())
+}
+
+pub fn write_account_diff(path: &str, rows: &[AccountDiffRow]) -> std::io::Result<()> {
+    let mut file = fs::File::create(path)?;
+    writeln!(file, "pubkey,write_version,endpoint,len,data_hash,meta_hash,agrees_with_majority")?;
+    for row in rows {
+        writeln!(
+            file,
+            "{},{},{},{},{:016x},{:016x},{}",
+            row.pubkey,
+            row.write_version,
+            row.endpoint,
+            row.len,
+            row.data_hash,
+            row.meta_hash,
+            row.agrees_with_majority.map_or(String::new(), |agrees| agrees.to_string())
+        )?;
+    }
+    log::info!("Wrote {} differing account writes to {}", rows.len(), path);
+    Ok(())
+}
+
+pub fn write_pair_windows(path: &str, pairs: &[PairReport]) -> std::io::Result<()> {
+    let mut file = fs::File::create(path)?;
+    writeln!(file, "first,second,window_start,signatures,median_delta_ms")?;
+    let mut rows = 0;
+    for pair in pairs {
+        for window in &pair.windows {
+            writeln!(
+                file,
+                "{},{},{:.3},{},{:.3}",
+                pair.first, pair.second, window.start, window.signatures, window.median_delta_ms
+            )?;
+            rows += 1;
+        }
+    }
+    log::info!("Wrote {} heatmap windows to {}", rows, path);
+    Ok(())
+}
+
+pub fn write_markdown_report(path: &str, report: &BenchmarkReport) -> std::io::Result<()> {
+    let mut file = fs::File::create(path)?;
+    writeln!(file, "# geyserbench report\n")?;
+    writeln!(
+        file,
+        "Mode: `{:?}`, {:.0}s run, tie policy `{:?}`\n",
+        report.mode,
+        report.end_time - report.start_time,
+        report.tie_policy
+    )?;
+    if let Some(fastest) = &report.fastest_endpoint {
+        writeln!(file, "Fastest endpoint: **{}**\n", fastest)?;
+    }
+    if let Some(metadata) = &report.metadata {
+        writeln!(file, "Run by {}\n", metadata.describe())?;
+    }
+
+    let with_slo = report.endpoints.iter().any(|endpoint| !endpoint.slo.is_empty());
+    write!(file, "| Endpoint | Win rate | First detections | Valid | Avg delay | Median | P95 |")?;
+    writeln!(file, "{}", if with_slo { " SLO |" } else { "" })?;
+    write!(file, "|---|---:|---:|---:|---:|---:|---:|")?;
+    writeln!(file, "{}", if with_slo { "---|" } else { "" })?;
+    for endpoint in &report.endpoints {
+        let delay = |value: Option<f64>| value.map_or("-".to_string(), |v| format!("{:.2} ms", v));
+        write!(
+            file,
+            "| {} | {:.2}% | {} | {} | {} | {} | {} |",
+            endpoint.name,
+            endpoint.win_rate,
+            endpoint.first_detections,
+            endpoint.total_valid_transactions,
+            delay(endpoint.delays.as_ref().map(|d| d.average_ms)),
+            delay(endpoint.delays.as_ref().map(|d| d.median_ms)),
+            delay(endpoint.delays.as_ref().map(|d| d.p95_ms)),
+        )?;
+        if with_slo {
+            let badges: Vec<String> = endpoint
+                .slo
+                .iter()
+                .map(|result| format!("{} {}", if result.pass { "✅" } else { "❌" }, result.label()))
+                .collect();
+            write!(file, " {} |", badges.join("<br>"))?;
+        }
+        writeln!(file)?;
+    }
+
+    log::info!("Wrote Markdown report to {}", path);
+    Ok(())
+}
@@ -0,0 +1,98 @@
+use std::{
+    fs,
+    io::{self, BufRead, BufReader, Write},
+    path::{Path, PathBuf},
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+use crate::log_output;
+
+const SECONDS_PER_DAY: u64 = 86_400;
+
+#[derive(Debug, Default)]
+pub struct CleanSummary {
+    pub removed_files: Vec<PathBuf>,
+    pub trimmed_files: usize,
+    pub removed_lines: usize,
+}
+
+// AIDEV-NOTE: Transaction logs are appended to across runs, so a log in daily use never ages out
+// as a file. Files untouched for `keep_days` are deleted; the rest are rewritten without the
+// entries whose `[timestamp]` prefix is older than the cutoff. Unparseable lines are kept.
+/// Prunes transaction logs in `dir` and its run subdirectories older than `keep_days`, and removes
+/// run subdirectories left empty; with `dry_run` only reports what would go.
+pub fn clean(dir: &Path, keep_days: u64, dry_run: bool) -> io::Result<CleanSummary> {
+    let cutoff = SystemTime::now() - Duration::from_secs(keep_days * SECONDS_PER_DAY);
+    let cutoff_secs = cutoff.duration_since(UNIX_EPOCH).map_or(0.0, |d| d.as_secs_f64());
+    let mut summary = CleanSummary::default();
+
+    for path in log_output::log_files(dir)? {
+        if fs::metadata(&path)?.modified()? < cutoff {
+            if !dry_run {
+                fs::remove_file(&path)?;
+            }
+            summary.removed_files.push(path);
+            continue;
+        }
+
+        let removed = trim_log(&path, cutoff_secs, dry_run)?;
+        if removed > 0 {
+            summary.trimmed_files += 1;
+            summary.removed_lines += removed;
+        }
+    }
+    if !dry_run {
+        for run_dir in log_output::run_dirs(dir)? {
+            if fs::read_dir(&run_dir)?.next().is_none() {
+                fs::remove_dir(&run_dir)?;
+            }
+        }
+    }
+    Ok(summary)
+}
+
+fn trim_log(path: &Path, cutoff_secs: f64, dry_run: bool) -> io::Result<usize> {
+    let mut kept = Vec::new();
+    let mut removed = 0;
+    for line in BufReader::new(fs::File::open(path)?).lines() {
+        let line = line?;
+        if entry_timestamp(&line).is_some_and(|timestamp| timestamp < cutoff_secs) {
+            removed += 1;
+        } else {
+            kept.push(line);
+        }
+    }
+    if removed == 0 || dry_run {
+        return Ok(removed);
+    }
+
+    // Written beside the log and renamed over it so an interrupted clean leaves the log intact
+    let tmp_path = path.with_extension("txt.tmp");
+    let mut tmp = fs::File::create(&tmp_path)?;
+    for line in &kept {
+        writeln!(tmp, "{}", line)?;
+    }
+    tmp.sync_all()?;
+    fs::rename(&tmp_path, path)?;
+    Ok(removed)
+}
+
+/// Timestamp of a `[1700000000.123] [endpoint] signature` log entry.
+fn entry_timestamp(line: &str) -> Option<f64> {
+    line.strip_prefix('[')?.split(']').next()?.parse().ok()
+}
+
+pub fn print_summary(summary: &CleanSummary, keep_days: u64, dry_run: bool) {
+    let verb = if dry_run { "Would remove" } else { "Removed" };
+    for path in &summary.removed_files {
+        println!("{} {}", verb, path.display());
+    }
+    println!(
+        "{} {} log files and {} entries older than {} days from {} other logs",
+        verb,
+        summary.removed_files.len(),
+        summary.removed_lines,
+        keep_days,
+        summary.trimmed_files
+    );
+}
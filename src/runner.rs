@@ -0,0 +1,828 @@
+use std::{
+    collections::HashMap,
+    error::Error,
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+
+use futures::{future::{join_all, BoxFuture}, stream::FuturesUnordered, StreamExt};
+use solana_sdk::signer::Signer;
+use tokio::{sync::{broadcast, watch}, task::{self, AbortHandle}};
+
+use crate::{
+    alerts::{self, AlertMonitor},
+    analysis,
+    clock::{self, Clock, ClockOffset},
+    connection::{self, ConnectionInfo},
+    coordinator,
+    config::{ArgsCommitment, BenchmarkMode, Config, ConfigToml, DedicatedRuntimesConfig, Endpoint, EndpointKind},
+    ground_truth, history, influx, leaders,
+    log_output::LogOutput,
+    lookup_tables::LookupTables,
+    metadata::RunMetadata,
+    parquet_sink,
+    profile::{self, ProfileStart},
+    providers::{self, ProviderError, ProviderRun},
+    proxy::Proxy,
+    reload::{self, Generation},
+    report::BenchmarkReport,
+    sampler::Sampler,
+    runtimes::{self, DelayProbe},
+    sender, slot_position, slot_status,
+    telemetry, throughput, timeline, token_accounts, tsc,
+    utils::Comparator,
+};
+
+const RUNTIME_GRACE_SECS: u64 = 10;
+
+/// Final report plus the raw observations it was computed from.
+#[derive(Debug, Default)]
+pub struct BenchmarkRun {
+    pub report: BenchmarkReport,
+    pub comparator: Comparator,
+    /// Endpoints that took part in the run, in config order
+    pub endpoint_names: Vec<String>,
+}
+
+/// Runs one benchmark to completion (transaction target, `max_runtime_secs` or stream end).
+pub async fn run_benchmark(config: &ConfigToml) -> Result<BenchmarkReport, Box<dyn Error + Send + Sync>> {
+    let (shutdown_tx, _) = broadcast::channel::<()>(1);
+    Ok(run_benchmark_with_shutdown(config, shutdown_tx).await?.report)
+}
+
+// AIDEV-NOTE: Checked against each provider's Capabilities before anything connects, so a mode no
+// endpoint can serve fails up front instead of producing an empty run; endpoints that cannot serve
+// it alongside ones that can are left out with a warning naming what they do support.
+fn check_mode_support(config: &ConfigToml) -> Result<(), Box<dyn Error + Send + Sync>> {
+    let mode = config.config.mode;
+    let unsupported: Vec<String> = config
+        .endpoint
+        .iter()
+        .filter(|e| !providers::supports_mode(&e.kind, mode))
+        .map(|e| {
+            let supported = providers::capabilities(&e.kind).map_or_else(|| "nothing".to_string(), |c| c.describe());
+            format!("{} ({} supports {})", e.name, e.kind, supported)
+        })
+        .collect();
+    if unsupported.is_empty() {
+        return Ok(());
+    }
+    if unsupported.len() == config.endpoint.len() {
+        return Err(format!("no endpoint supports {:?} mode: {}", mode, unsupported.join("; ")).into());
+    }
+    for endpoint in &unsupported {
+        log::warn!("{:?} mode is not supported by {}, skipping", mode, endpoint);
+    }
+    Ok(())
+}
+
+/// Like [`run_benchmark`], but stops early with partial results when `shutdown_tx` fires.
+pub async fn run_benchmark_with_shutdown(
+    config: &ConfigToml,
+    shutdown_tx: broadcast::Sender<()>,
+) -> Result<BenchmarkRun, Box<dyn Error + Send + Sync>> {
+    run_benchmark_with_clock(config, shutdown_tx, clock::system()).await
+}
+
+/// Like [`run_benchmark_with_shutdown`], with receive times, windows and report bounds taken from `clock`.
+pub async fn run_benchmark_with_clock(
+    config: &ConfigToml,
+    shutdown_tx: broadcast::Sender<()>,
+    clock: Arc<dyn Clock>,
+) -> Result<BenchmarkRun, Box<dyn Error + Send + Sync>> {
+    run(config, shutdown_tx, clock, None).await
+}
+
+/// Like [`run_benchmark_with_shutdown`], applying every config generation published on `updates`
+/// to the running benchmark (`--continuous`).
+pub async fn run_benchmark_with_reload(
+    config: &ConfigToml,
+    shutdown_tx: broadcast::Sender<()>,
+    updates: watch::Receiver<Generation>,
+) -> Result<BenchmarkRun, Box<dyn Error + Send + Sync>> {
+    run(config, shutdown_tx, clock::system(), Some(updates)).await
+}
+
+async fn run(
+    config: &ConfigToml,
+    shutdown_tx: broadcast::Sender<()>,
+    clock: Arc<dyn Clock>,
+    mut updates: Option<watch::Receiver<Generation>>,
+) -> Result<BenchmarkRun, Box<dyn Error + Send + Sync>> {
+    let unknown: Vec<String> = config
+        .endpoint
+        .iter()
+        .filter(|e| !providers::registry::is_registered(&e.kind))
+        .map(|e| format!("{} ({})", e.name, e.kind))
+        .collect();
+    if !unknown.is_empty() {
+        return Err(format!(
+            "no provider registered for {}; known kinds: {}",
+            unknown.join(", "),
+            providers::registered_kinds().join(", ")
+        )
+        .into());
+    }
+    check_mode_support(config)?;
+    let mut resolved = None;
+    if config.config.token_accounts.is_some() {
+        resolved = Some(token_accounts::resolve(config).await?);
+    }
+    let payer = match &config.config.sender {
+        Some(sender_config) => {
+            let payer = sender::load_keypair(sender_config)?;
+            resolved = Some(sender::watch_payer(resolved.as_ref().unwrap_or(config), &payer.pubkey()));
+            Some(payer)
+        }
+        None => None,
+    };
+    let config = resolved.as_ref().unwrap_or(config);
+    // Calibration sleeps and the clock check waits on UDP; neither may hold a runtime worker
+    if config.config.tsc_timestamps {
+        let _ = task::spawn_blocking(tsc::init).await;
+    }
+    let clock_offset = check_clock(config).await;
+
+    if config.config.mode == BenchmarkMode::Throughput {
+        let start_time = clock.now();
+        let report = BenchmarkReport {
+            mode: config.config.mode,
+            throughput: throughput::run(config, &shutdown_tx).await?,
+            start_time,
+            end_time: clock.now(),
+            effective_config: Some(config.redacted()),
+            clock_offset,
+            metadata: Some(RunMetadata::collect(config)),
+            ..Default::default()
+        };
+        return Ok(BenchmarkRun {
+            report,
+            ..Default::default()
+        });
+    }
+
+    if config.config.mode == BenchmarkMode::Status
+        && matches!(config.config.commitment, ArgsCommitment::Processed)
+    {
+        log::warn!("status mode at processed commitment times execution, not confirmation; use confirmed or finalized");
+    }
+
+    let telemetry = telemetry::init(config.config.otel.as_ref())?;
+    // Before the start time so the side connections neither delay streams nor count towards startup
+    let connections = if config.config.inspect_connections {
+        inspect_connections(config).await
+    } else {
+        Vec::new()
+    };
+    let start_time = clock.now();
+    let profile_start = config.config.self_profile.then(ProfileStart::take);
+    let mut comparator = Comparator::new(config.config.transactions as usize)
+        .with_clock(clock.clone())
+        .with_dedup_window(config.config.dedup_window_secs)
+        .with_account_targets(config.config.account_targets.clone())
+        .with_log_output(LogOutput::create(&config.config.logs, start_time)?);
+    for (name, connection) in connections {
+        comparator.endpoint_metrics.entry(name).or_default().connection = Some(connection);
+    }
+    if let Some(rate) = config.config.sample_rate {
+        comparator = comparator.with_sampler(Sampler::new(rate));
+    }
+    if config.config.event_log {
+        comparator = comparator.with_event_log(config.config.mode);
+    }
+    let lookup_tables = config
+        .config
+        .lookup_tables
+        .as_ref()
+        .map(|lookup_tables| Arc::new(LookupTables::new(lookup_tables, &config.config.watched_accounts())));
+    if let Some(lookup_tables) = &lookup_tables {
+        comparator = comparator.with_lookup_tables(lookup_tables.clone());
+    }
+    let influx_sink = match &config.config.influx {
+        Some(influx_config) => {
+            let sink = Arc::new(influx::InfluxSink::open(influx_config, clock.clone())?);
+            influx::spawn_window_flusher(
+                sink.clone(),
+                Duration::from_secs(influx_config.window_secs.max(1)),
+            );
+            comparator = comparator.with_influx(sink.clone());
+            Some(sink)
+        }
+        None => None,
+    };
+    let parquet_sink = parquet_sink::open(config.config.parquet.as_ref())?.map(Arc::new);
+    if let Some(sink) = &parquet_sink {
+        comparator = comparator.with_parquet(sink.clone());
+    }
+    let history_store = match &config.config.history {
+        Some(history_config) => {
+            let store = Arc::new(history::HistoryStore::open(history_config, clock.clone())?);
+            history::spawn_window_flusher(
+                store.clone(),
+                Duration::from_secs(history_config.window_secs.max(1)),
+            );
+            comparator = comparator.with_history(store.clone());
+            Some(store)
+        }
+        None => None,
+    };
+    if let Some(agent) = &config.config.agent {
+        comparator = comparator.with_forwarder(coordinator::start_agent(agent, clock_offset.as_ref()).await?);
+    }
+    // AIDEV-NOTE: With delayed endpoints only signatures after the last one joined are compared,
+    // and the warmup leaves out those seen while connections settle, whichever ends later
+    let max_start_delay = config
+        .endpoint
+        .iter()
+        .filter(|e| providers::supports_mode(&e.kind, config.config.mode))
+        .map(|e| e.start_delay_secs)
+        .max()
+        .unwrap_or(0);
+    let excluded_secs = max_start_delay.max(config.config.warmup_secs);
+    if excluded_secs > 0 {
+        comparator.measure_from = start_time + excluded_secs as f64;
+    }
+    let endpoint_names: Vec<String> = config
+        .endpoint
+        .iter()
+        .filter(|e| providers::supports_mode(&e.kind, config.config.mode))
+        .map(|e| e.name.clone())
+        .collect();
+    let (alert_monitor, alert_checker) = match &config.config.alerts {
+        Some(alert_config) => {
+            let monitor = Arc::new(AlertMonitor::new(config, alert_config, &endpoint_names, start_time, clock.clone()));
+            let checker = alerts::spawn_window_checker(
+                monitor.clone(),
+                Duration::from_secs(alert_config.window_secs),
+                config.config.notify.clone(),
+            );
+            comparator = comparator.with_alerts(monitor.clone());
+            (Some(monitor), Some(checker))
+        }
+        None => (None, None),
+    };
+    let comparator = Arc::new(Mutex::new(comparator));
+    // Stops from max_runtime_secs or the embedder's signal start the drain as well
+    let mut drain_rx = shutdown_tx.subscribe();
+    let mut stop_rx = shutdown_tx.subscribe();
+    let mut start_stop_rx = shutdown_tx.subscribe();
+    let drain_comparator = comparator.clone();
+    tokio::spawn(async move {
+        if drain_rx.recv().await.is_ok() {
+            drain_comparator.lock().unwrap().begin_drain();
+        }
+    });
+
+    let tasks = Arc::new(Mutex::new(EndpointTasks::default()));
+    let mut running = FuturesUnordered::new();
+    let mut starts = FuturesUnordered::new();
+    // The shared runtime is measured too, as the baseline the dedicated ones are compared with
+    let mut runtime_probes = Vec::new();
+    let shared_probe = config.config.dedicated_runtimes.as_ref().map(|_| {
+        let probe = DelayProbe::default();
+        runtime_probes.push((runtimes::SHARED_RUNTIME.to_string(), None, probe.clone()));
+        tokio::spawn(probe.run())
+    });
+    let mut launcher = Launcher {
+        tasks: tasks.clone(),
+        shutdown_tx: shutdown_tx.clone(),
+        comparator: comparator.clone(),
+        start_time,
+        dedicated: config.config.dedicated_runtimes.clone(),
+        runtime_probes,
+    };
+
+    for endpoint in config.endpoint.clone() {
+        // Already reported by check_mode_support
+        if !providers::supports_mode(&endpoint.kind, config.config.mode) {
+            continue;
+        }
+        match launcher.launch(endpoint, &config.config)? {
+            Launch::Running(task) => running.push(task),
+            Launch::Starting(start) => starts.push(start),
+        }
+    }
+
+    let stop_reason = Arc::new(Mutex::new(None::<String>));
+
+    // Aborted once the providers finish, so it cannot stop a later run sharing the channel
+    let runtime_timer = config.config.max_runtime_secs.map(|max_runtime_secs| {
+        let runtime_tx = shutdown_tx.clone();
+        let runtime_reason = stop_reason.clone();
+        tokio::spawn(async move {
+            tokio::time::sleep(Duration::from_secs(max_runtime_secs)).await;
+            log::warn!("Maximum run time of {}s reached, stopping with partial results", max_runtime_secs);
+            runtime_reason
+                .lock()
+                .unwrap()
+                .get_or_insert_with(|| format!("max_runtime_secs ({}s) reached", max_runtime_secs));
+            let _ = runtime_tx.send(());
+        })
+    });
+
+    let payer_pubkey = payer.as_ref().map(|payer| payer.pubkey());
+    let sender_handle = match (&config.config.sender, payer) {
+        (Some(sender_config), Some(payer)) => Some(sender::spawn(
+            sender_config.clone(),
+            payer,
+            Duration::from_secs(max_start_delay + sender_config.warmup_secs),
+            clock.clone(),
+            shutdown_tx.clone(),
+            stop_reason.clone(),
+        )),
+        _ => None,
+    };
+
+    // AIDEV-NOTE: Providers stuck connecting or reading never see the shutdown signal; once any
+    // stop (target, max_runtime_secs, Ctrl+C or SIGTERM, the embedder's signal) has had the grace
+    // period to take effect they are aborted, so the run still ends with partial results.
+    let watched_tasks = tasks.clone();
+    let stop_watch = tokio::spawn(async move {
+        let _ = stop_rx.recv().await;
+        tokio::time::sleep(Duration::from_secs(RUNTIME_GRACE_SECS)).await;
+        watched_tasks.lock().unwrap().abort_unfinished();
+    });
+
+    // AIDEV-NOTE: Each endpoint's stream is spawned as soon as its own start resolves, so one slow
+    // endpoint never holds back reading the others. Connect, auth and setup failures surface here
+    // as a ProviderError and nothing is spawned for that endpoint; endpoints still connecting when
+    // the run stops are dropped. Config reloads start and stop endpoints through the same sets
+    // until the run stops.
+    let mut live = config.clone();
+    let mut stopping = false;
+    while !starts.is_empty() || !running.is_empty() {
+        tokio::select! {
+            Some((name, id, started)) = starts.next() => match started {
+                Some(Ok(run)) if tasks.lock().unwrap().is_current(&name, id) => {
+                    let handle = providers::spawn_provider(name.clone(), config.config.self_profile, comparator.clone(), run);
+                    tasks.lock().unwrap().attach(&name, id, handle.abort_handle());
+                    running.push(joined(name, id, handle));
+                }
+                // Stopped by a reload while connecting
+                Some(Ok(_)) => {}
+                Some(Err(e)) => log::error!("[{}] Provider failed to start ({}): {}", name, e.kind(), e),
+                // Stopped during its start delay
+                None => {}
+            },
+            Some((name, id, result)) = running.next() => {
+                tasks.lock().unwrap().finish(&name, id);
+                match result {
+                    Ok(Ok(_)) => {},
+                    Ok(Err(e)) => match e.downcast_ref::<ProviderError>() {
+                        Some(error) => log::error!("[{}] Provider error ({}): {}", name, error.kind(), error),
+                        None => log::error!("[{}] Provider error: {:?}", name, e),
+                    },
+                    // Aborted past the grace period (already logged) or stopped by a reload
+                    Err(e) if e.is_cancelled() => {},
+                    Err(e) => log::error!("[{}] Task join error: {:?}", name, e),
+                }
+            }
+            Some(generation) = next_generation(&mut updates), if !stopping => {
+                if resolved.is_some() {
+                    log::warn!(
+                        "Config generation {} applies from the next run: token_accounts and sender resolve the watched accounts only when a run starts",
+                        generation.number
+                    );
+                    continue;
+                }
+                let plan = reload::plan(&live, &generation.config);
+                for setting in &plan.deferred {
+                    log::info!("Config generation {}: {} takes effect from the next run", generation.number, setting);
+                }
+                for name in &plan.stop {
+                    tasks.lock().unwrap().stop(name);
+                    log::info!("Config generation {}: stopped {}", generation.number, name);
+                }
+                let mut joined_names = Vec::new();
+                for endpoint in plan.start {
+                    if !providers::supports_mode(&endpoint.kind, generation.config.config.mode) {
+                        log::warn!("Config generation {}: {} ({}) cannot serve {:?} mode, not starting it", generation.number, endpoint.name, endpoint.kind, generation.config.config.mode);
+                        continue;
+                    }
+                    let name = endpoint.name.clone();
+                    match launcher.launch(endpoint, &generation.config.config) {
+                        Ok(Launch::Running(task)) => running.push(task),
+                        Ok(Launch::Starting(start)) => starts.push(start),
+                        Err(e) => {
+                            log::error!("Config generation {}: failed to start {}: {}", generation.number, name, e);
+                            continue;
+                        }
+                    }
+                    log::info!("Config generation {}: started {}", generation.number, name);
+                    joined_names.push(name);
+                }
+                comparator.lock().unwrap().apply_membership(generation.number, &joined_names, &plan.stop);
+                live = generation.config;
+            }
+            _ = start_stop_rx.recv(), if !stopping => {
+                stopping = true;
+                if !starts.is_empty() {
+                    log::warn!("Stopped while {} endpoints were still connecting", starts.len());
+                    starts.clear();
+                }
+            }
+        }
+    }
+    stop_watch.abort();
+    if let Some(runtime_timer) = runtime_timer {
+        runtime_timer.abort();
+    }
+    if let Some(shared_probe) = shared_probe {
+        shared_probe.abort();
+    }
+
+    let mut dropped_influx_lines = None;
+    if let Some(sink) = &influx_sink {
+        sink.flush_window();
+        let sink = sink.clone();
+        let dropped = task::spawn_blocking(move || sink.finish()).await.unwrap_or_default();
+        if dropped > 0 {
+            log::warn!("Dropped {} InfluxDB lines: the writer fell behind", dropped);
+        }
+        dropped_influx_lines = Some(dropped);
+    }
+    if let Some(store) = &history_store {
+        let store = store.clone();
+        let _ = task::spawn_blocking(move || store.flush_window()).await;
+    }
+    if let Some(sink) = &parquet_sink {
+        let sink = sink.clone();
+        let _ = task::spawn_blocking(move || sink.finish()).await;
+    }
+    if let Some(lookup_tables) = &lookup_tables {
+        let stats = lookup_tables.stats();
+        log::info!(
+            "Address lookup tables: {} fetched, {} failed, {} lookups arrived before their table was cached",
+            stats.fetched,
+            stats.failed,
+            stats.misses
+        );
+    }
+    // Only whole windows are checked; the one cut short by the end of the run is not
+    if let Some(checker) = alert_checker {
+        checker.abort();
+    }
+    if let Some(telemetry) = telemetry {
+        telemetry.shutdown();
+    }
+    let forwarder = comparator.lock().unwrap().forwarder.clone();
+    if let Some(forwarder) = forwarder {
+        forwarder.finish().await;
+    }
+
+    // Aborted providers may still hold a clone of the Arc, so take the data rather than unwrap it
+    let comparator = std::mem::take(&mut *comparator.lock().unwrap());
+    let log_output = comparator.log_output.clone();
+    let dropped_log_entries = task::spawn_blocking(move || log_output.finish()).await.unwrap_or_default();
+    for (log, dropped) in &dropped_log_entries {
+        log::warn!("Dropped {} transaction log entries for {}: the log writer fell behind", dropped, log);
+    }
+    // Reloads may have started or stopped endpoints; the results cover those running at the end
+    let endpoint_names: Vec<String> = live
+        .endpoint
+        .iter()
+        .filter(|e| providers::supports_mode(&e.kind, config.config.mode))
+        .map(|e| e.name.clone())
+        .collect();
+    let mut report = analysis::analyze_delays(&comparator, endpoint_names.clone(), &config.config);
+    report.execution_gap = analysis::summarize_execution_gap(&comparator, &live.endpoint);
+    if let Some(gap) = &report.execution_gap {
+        analysis::print_execution_gap(gap);
+    }
+    report.slot_position = slot_position::summarize_slot_position(&comparator, &endpoint_names);
+    if let Some(slot_position) = &report.slot_position {
+        slot_position::print_slot_position(slot_position);
+    }
+    if config.config.mode == BenchmarkMode::Slots {
+        report.slot_status = slot_status::summarize_slot_status(&comparator, &endpoint_names);
+        if let Some(slot_status) = &report.slot_status {
+            slot_status::print_slot_status(slot_status);
+        }
+    }
+    report.handicap = analysis::summarize_handicap(&comparator, &live.endpoint, config.config.tie_policy);
+    analysis::print_handicap(&report.handicap);
+    report.tag_groups = analysis::summarize_tag_groups(&comparator, &live.endpoint, &config.config.group_by);
+    analysis::print_tag_groups(&report.tag_groups);
+    if let Some(leaders_config) = &config.config.leaders {
+        report.leaders = leaders::analyze(&comparator, &endpoint_names, leaders_config).await;
+        leaders::print_leaders(&report.leaders, leaders_config.top_n);
+    }
+    report.ground_truth = ground_truth::analyze(&comparator, &endpoint_names, &config.config).await;
+    if let Some(ground_truth) = &report.ground_truth {
+        ground_truth::print_ground_truth(ground_truth);
+    }
+    report.timeline = timeline::summarize_timeline(&comparator, &endpoint_names, start_time);
+    timeline::print_timeline(&report.timeline, start_time, clock.now());
+    if let Some(path) = &config.config.timeline_path {
+        if let Err(e) = timeline::write_timeline(path, &report.timeline) {
+            log::error!("Failed to write run timeline to {}: {}", path, e);
+        }
+    }
+    report.self_profile = profile_start.map(|start| start.finish(&comparator, &endpoint_names));
+    report.runtimes = launcher
+        .runtime_probes
+        .iter()
+        .map(|(name, core, probe)| probe.report(name, *core))
+        .collect();
+    runtimes::print_runtimes(&report.runtimes);
+    if let Some(self_profile) = &report.self_profile {
+        profile::print_self_profile(self_profile);
+    }
+    if let Some(monitor) = &alert_monitor {
+        report.alerts = monitor.violations();
+        alerts::print_alerts(&report.alerts, start_time);
+    }
+    if let (Some(handle), Some(sender_config), Some(payer)) = (sender_handle, &config.config.sender, &payer_pubkey) {
+        // Streams can also end on their own; the signal makes the sender return what it sent so far
+        let _ = shutdown_tx.send(());
+        let sent = handle.await.unwrap_or_else(|e| {
+            log::error!("Sender task failed: {:?}", e);
+            Vec::new()
+        });
+        let send = sender::summarize_send(&comparator, &endpoint_names, sender_config, payer, sent);
+        sender::print_send(&send);
+        report.send = Some(send);
+    }
+    report.mode = config.config.mode;
+    report.start_time = start_time;
+    report.end_time = clock.now();
+    report.effective_config = Some(config.redacted());
+    report.stop_reason = stop_reason.lock().unwrap().clone();
+    report.dropped_log_entries = dropped_log_entries;
+    report.dropped_influx_lines = dropped_influx_lines;
+    report.clock_offset = clock_offset;
+    report.membership = comparator.membership.clone();
+    report.metadata = Some(RunMetadata::collect(config));
+
+    Ok(BenchmarkRun {
+        report,
+        comparator,
+        endpoint_names,
+    })
+}
+
+type ProviderResult = Result<(), Box<dyn Error + Send + Sync>>;
+type Started = (String, u64, Option<Result<ProviderRun, ProviderError>>);
+type Joined = (String, u64, Result<ProviderResult, task::JoinError>);
+
+// AIDEV-NOTE: Every launch gets a fresh id, so a task that ends (or a start that resolves) after a
+// reload restarted its endpoint cannot touch the replacement's entry.
+/// Provider tasks of the endpoints in a run, keyed by endpoint name, so a config reload can stop
+/// one mid-run; endpoints still connecting have no task yet.
+#[derive(Default)]
+struct EndpointTasks {
+    next_id: u64,
+    tasks: HashMap<String, (u64, Option<AbortHandle>)>,
+}
+
+impl EndpointTasks {
+    fn register(&mut self, name: &str) -> u64 {
+        self.next_id += 1;
+        self.tasks.insert(name.to_string(), (self.next_id, None));
+        self.next_id
+    }
+
+    fn is_current(&self, name: &str, id: u64) -> bool {
+        self.tasks.get(name).is_some_and(|(current, _)| *current == id)
+    }
+
+    fn attach(&mut self, name: &str, id: u64, handle: AbortHandle) {
+        if let Some((current, task)) = self.tasks.get_mut(name) {
+            if *current == id {
+                *task = Some(handle);
+            }
+        }
+    }
+
+    fn finish(&mut self, name: &str, id: u64) {
+        if self.is_current(name, id) {
+            self.tasks.remove(name);
+        }
+    }
+
+    /// Aborts the endpoint's task, or drops its start when it is still connecting.
+    fn stop(&mut self, name: &str) {
+        if let Some((_, Some(handle))) = self.tasks.remove(name) {
+            handle.abort();
+        }
+    }
+
+    fn abort_unfinished(&self) {
+        for (name, (_, handle)) in &self.tasks {
+            if let Some(handle) = handle.as_ref().filter(|handle| !handle.is_finished()) {
+                log::warn!("[{}] Provider did not stop within {}s of the stop signal, aborting", name, RUNTIME_GRACE_SECS);
+                handle.abort();
+            }
+        }
+    }
+}
+
+enum Launch {
+    /// Connecting and streaming on a dedicated runtime
+    Running(BoxFuture<'static, Joined>),
+    /// Connecting on the shared runtime; spawned once the start resolves
+    Starting(BoxFuture<'static, Started>),
+}
+
+/// What every provider start in a run shares, so endpoints added by a reload start like the rest.
+struct Launcher {
+    tasks: Arc<Mutex<EndpointTasks>>,
+    shutdown_tx: broadcast::Sender<()>,
+    comparator: Arc<Mutex<Comparator>>,
+    start_time: f64,
+    /// The run's setting; a reload changing it applies from the next run
+    dedicated: Option<DedicatedRuntimesConfig>,
+    /// Scheduler delay probes per runtime, with `dedicated_runtimes`
+    runtime_probes: Vec<(String, Option<usize>, DelayProbe)>,
+}
+
+impl Launcher {
+    fn launch(&mut self, endpoint: Endpoint, config: &Config) -> Result<Launch, Box<dyn Error + Send + Sync>> {
+        warn_ignored_settings(&endpoint, config);
+        let provider = providers::create_provider(&endpoint.kind)
+            .ok_or_else(|| format!("no provider registered for kind {}", endpoint.kind))?;
+        let id = self.tasks.lock().unwrap().register(&endpoint.name);
+        let shared_config = config.clone();
+        let stx = self.shutdown_tx.clone();
+        let shutdown_rx = self.shutdown_tx.subscribe();
+        let mut delay_rx = self.shutdown_tx.subscribe();
+        let shared_comparator = self.comparator.clone();
+        let start_time = self.start_time;
+        let start_delay_secs = endpoint.start_delay_secs;
+
+        if let Some(dedicated) = &self.dedicated {
+            // Endpoints take the configured cores in order; the shared runtime's probe comes first
+            let core = endpoint.core.or_else(|| dedicated.cores.get(self.runtime_probes.len() - 1).copied());
+            let probe = DelayProbe::default();
+            self.runtime_probes.push((endpoint.name.clone(), core, probe.clone()));
+            let name = endpoint.name.clone();
+            // Connecting has to happen on the dedicated runtime too, so a failed start ends that
+            // runtime's task and is reported with the other provider errors
+            let handle = runtimes::spawn_dedicated(endpoint.name.clone(), core, probe, move || async move {
+                if start_delay_secs > 0 {
+                    tokio::select! {
+                        _ = tokio::time::sleep(Duration::from_secs(start_delay_secs)) => {}
+                        _ = delay_rx.recv() => return Ok(()),
+                    }
+                    log::info!("[{}] Starting after {}s delay", endpoint.name, start_delay_secs);
+                }
+                provider
+                    .start(
+                        endpoint,
+                        shared_config,
+                        stx,
+                        shutdown_rx,
+                        start_time,
+                        shared_comparator,
+                    )
+                    .await?
+                    .await
+            });
+            self.tasks.lock().unwrap().attach(&name, id, handle.abort_handle());
+            return Ok(Launch::Running(joined(name, id, handle)));
+        }
+        Ok(Launch::Starting(Box::pin(async move {
+            let name = endpoint.name.clone();
+            if start_delay_secs > 0 {
+                tokio::select! {
+                    _ = tokio::time::sleep(Duration::from_secs(start_delay_secs)) => {}
+                    _ = delay_rx.recv() => return (name, id, None),
+                }
+                log::info!("[{}] Starting after {}s delay", name, start_delay_secs);
+            }
+            let started = provider
+                .start(
+                    endpoint,
+                    shared_config,
+                    stx,
+                    shutdown_rx,
+                    start_time,
+                    shared_comparator,
+                )
+                .await;
+            (name, id, Some(started))
+        })))
+    }
+}
+
+fn joined(name: String, id: u64, handle: task::JoinHandle<ProviderResult>) -> BoxFuture<'static, Joined> {
+    Box::pin(async move { (name, id, handle.await) })
+}
+
+/// The next config generation published for the run; never resolves without a watcher.
+async fn next_generation(updates: &mut Option<watch::Receiver<Generation>>) -> Option<Generation> {
+    let Some(receiver) = updates else {
+        return futures::future::pending().await;
+    };
+    if receiver.changed().await.is_err() {
+        // The watcher is gone; nothing more will be published this run
+        *updates = None;
+        return futures::future::pending().await;
+    }
+    let generation = receiver.borrow_and_update().clone();
+    Some(generation)
+}
+
+/// Warns about endpoint settings its provider does not apply.
+fn warn_ignored_settings(endpoint: &Endpoint, config: &Config) {
+    if endpoint.shards > 1 && endpoint.kind != EndpointKind::Yellowstone {
+        log::warn!("[{}] shards is only supported by yellowstone endpoints, ignoring", endpoint.name);
+    }
+    if endpoint.connections > 1 && endpoint.kind != EndpointKind::Yellowstone {
+        log::warn!("[{}] connections is only supported by yellowstone endpoints, ignoring", endpoint.name);
+    }
+    if !endpoint.headers.is_empty()
+        && !matches!(endpoint.kind, EndpointKind::Yellowstone | EndpointKind::YellowstoneAccounts | EndpointKind::Fumarole)
+    {
+        log::warn!("[{}] headers are only sent by yellowstone and fumarole endpoints, ignoring", endpoint.name);
+    }
+    if config.transaction_filter.failed.is_some()
+        && !matches!(endpoint.kind, EndpointKind::Yellowstone | EndpointKind::YellowstoneAccounts | EndpointKind::Fumarole)
+    {
+        log::warn!("[{}] transaction_filter.failed is only applied by yellowstone and fumarole endpoints, ignoring", endpoint.name);
+    }
+    if config.from_slot.is_some()
+        && !matches!(endpoint.kind, EndpointKind::Yellowstone | EndpointKind::YellowstoneAccounts)
+    {
+        log::warn!("[{}] from_slot is only sent by yellowstone endpoints, streaming the live tail", endpoint.name);
+    }
+    if config.watchdog.as_ref().is_some_and(|watchdog| watchdog.reconnect)
+        && endpoint.kind != EndpointKind::Yellowstone
+    {
+        log::warn!("[{}] watchdog reconnects only apply to yellowstone endpoints; downtime is still recorded", endpoint.name);
+    }
+    if endpoint.token_refresh.is_some() && endpoint.kind != EndpointKind::Yellowstone {
+        log::warn!("[{}] token_refresh only applies to yellowstone endpoints, ignoring", endpoint.name);
+    }
+    if endpoint.subscribe.is_some()
+        && !matches!(endpoint.kind, EndpointKind::Yellowstone | EndpointKind::YellowstoneAccounts)
+    {
+        log::warn!("[{}] subscribe overrides only apply to yellowstone endpoints, ignoring", endpoint.name);
+    }
+}
+
+async fn inspect_connections(config: &ConfigToml) -> Vec<(String, ConnectionInfo)> {
+    let endpoints: Vec<_> = config
+        .endpoint
+        .iter()
+        .filter(|e| providers::supports_mode(&e.kind, config.config.mode))
+        .filter(|e| e.kind != EndpointKind::UnixSocket)
+        .collect();
+    // A bad proxy setting fails the endpoint's own connect with a clearer error; inspection goes direct
+    let proxies: Vec<Option<Proxy>> = endpoints.iter().map(|e| Proxy::for_endpoint(e).ok().flatten()).collect();
+    let inspected = join_all(
+        endpoints
+            .iter()
+            .zip(&proxies)
+            .map(|(e, proxy)| connection::inspect(&e.url, proxy.as_ref())),
+    )
+    .await;
+    endpoints
+        .into_iter()
+        .zip(inspected)
+        .map(|(endpoint, info)| {
+            match &info.error {
+                Some(e) => log::warn!("[{}] Connection inspection failed: {}", endpoint.name, e),
+                None if info.downgraded() => log::warn!(
+                    "[{}] Server negotiated {} instead of h2",
+                    endpoint.name,
+                    info.alpn.as_deref().unwrap_or("no ALPN")
+                ),
+                None => {}
+            }
+            (endpoint.name.clone(), info)
+        })
+        .collect()
+}
+
+async fn check_clock(config: &ConfigToml) -> Option<ClockOffset> {
+    let clock_config = config.config.clock_check.as_ref()?;
+    let measured = {
+        let clock_config = clock_config.clone();
+        task::spawn_blocking(move || clock::measure(&clock_config))
+            .await
+            .unwrap_or_else(|e| Err(std::io::Error::other(e)))
+    };
+    match measured {
+        Ok(offset) => {
+            log::info!(
+                "Clock offset vs {}: {:+.2} ms (round trip {:.2} ms)",
+                offset.server,
+                offset.offset_ms,
+                offset.round_trip_ms
+            );
+            if offset.offset_ms.abs() > clock_config.max_offset_ms {
+                log::warn!(
+                    "Local clock is off by {:.2} ms (limit {:.2} ms); cross-machine timestamps are unreliable",
+                    offset.offset_ms,
+                    clock_config.max_offset_ms
+                );
+            }
+            Some(offset)
+        }
+        Err(e) => {
+            log::warn!("Clock check against {} failed: {}", clock_config.ntp_server, e);
+            None
+        }
+    }
+}
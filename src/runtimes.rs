@@ -0,0 +1,232 @@
+//! Dedicated provider runtimes (`dedicated_runtimes`): each endpoint on its own OS thread with a
+//! single-threaded tokio runtime, optionally pinned to a core, and the scheduler delay measured on
+//! every runtime to show whether the isolation holds.
+
+use std::{
+    future::Future,
+    sync::{Arc, Mutex},
+    thread,
+    time::Duration,
+};
+
+use futures::future::{AbortHandle, Abortable};
+use serde::{Deserialize, Serialize};
+use tokio::{sync::oneshot, task, time::Instant};
+
+/// How often each runtime's probe asks to be woken
+const PROBE_INTERVAL: Duration = Duration::from_millis(10);
+/// Name of the report entry measuring the runtime everything else runs on
+pub const SHARED_RUNTIME: &str = "shared";
+
+/// Wake-up lateness of one runtime's probe task.
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+pub struct RuntimeReport {
+    /// Endpoint name, or `shared` for the main runtime
+    pub name: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub core: Option<usize>,
+    pub samples: usize,
+    pub median_delay_us: f64,
+    pub p99_delay_us: f64,
+    pub max_delay_us: f64,
+}
+
+/// Sub-buckets per power of two of the delay histogram; delays are reported to within about 2%
+const BUCKETS_PER_OCTAVE: f64 = 16.0;
+/// Powers of two above 1 us the histogram covers; longer delays (over an hour) share the last bucket
+const OCTAVES: usize = 32;
+const BUCKETS: usize = 1 + OCTAVES * BUCKETS_PER_OCTAVE as usize;
+
+// AIDEV-NOTE: A probe records 100 samples a second for as long as the run goes on, so they are
+// counted in log-spaced buckets rather than kept: memory stays fixed however long a continuous run
+// lasts. Percentiles come out as the middle of their bucket; the maximum is exact.
+/// Scheduler delays in microseconds, counted in fixed log-spaced buckets.
+#[derive(Debug, Clone)]
+struct DelayHistogram {
+    counts: Vec<u64>,
+    samples: usize,
+    max_us: f64,
+}
+
+impl Default for DelayHistogram {
+    fn default() -> Self {
+        Self {
+            counts: vec![0; BUCKETS],
+            samples: 0,
+            max_us: 0.0,
+        }
+    }
+}
+
+impl DelayHistogram {
+    fn record(&mut self, delay_us: f64) {
+        // Bucket 0 holds sub-microsecond delays, bucket b >= 1 those with log2 in [(b-1)/16, b/16)
+        let bucket = if delay_us < 1.0 {
+            0
+        } else {
+            (1.0 + delay_us.log2() * BUCKETS_PER_OCTAVE) as usize
+        };
+        self.counts[bucket.min(BUCKETS - 1)] += 1;
+        self.samples += 1;
+        self.max_us = self.max_us.max(delay_us);
+    }
+
+    /// Delay at quantile `p`, ranked the way [`percentile`](crate::utils::percentile) ranks sorted samples.
+    fn percentile(&self, p: f64) -> f64 {
+        if self.samples == 0 {
+            return 0.0;
+        }
+        let rank = (p * (self.samples - 1) as f64).round() as u64;
+        let mut seen = 0;
+        for (bucket, count) in self.counts.iter().enumerate() {
+            seen += count;
+            if seen > rank {
+                let middle = if bucket == 0 {
+                    0.0
+                } else {
+                    2f64.powf((bucket as f64 - 0.5) / BUCKETS_PER_OCTAVE)
+                };
+                return middle.min(self.max_us);
+            }
+        }
+        self.max_us
+    }
+}
+
+/// Scheduler delay samples in microseconds, shared with the probe task.
+#[derive(Debug, Clone, Default)]
+pub struct DelayProbe {
+    histogram: Arc<Mutex<DelayHistogram>>,
+}
+
+impl DelayProbe {
+    // AIDEV-NOTE: A timer that fires late means the runtime's thread was busy (or descheduled)
+    // when it was due; a provider's message would have waited just as long to be timestamped.
+    /// Sleeps `PROBE_INTERVAL` at a time, recording how late each wake-up came, until cancelled.
+    pub async fn run(self) {
+        loop {
+            let due = Instant::now() + PROBE_INTERVAL;
+            tokio::time::sleep_until(due).await;
+            let late = Instant::now().saturating_duration_since(due);
+            self.histogram.lock().unwrap().record(late.as_secs_f64() * 1_000_000.0);
+        }
+    }
+
+    pub fn report(&self, name: &str, core: Option<usize>) -> RuntimeReport {
+        let histogram = self.histogram.lock().unwrap();
+        RuntimeReport {
+            name: name.to_string(),
+            core,
+            samples: histogram.samples,
+            median_delay_us: histogram.percentile(0.5),
+            p99_delay_us: histogram.percentile(0.99),
+            max_delay_us: histogram.max_us,
+        }
+    }
+}
+
+struct AbortOnDrop(AbortHandle);
+
+impl Drop for AbortOnDrop {
+    fn drop(&mut self) {
+        self.0.abort();
+    }
+}
+
+// AIDEV-NOTE: `start` is called inside the new runtime, so everything the provider spawns with
+// `task::spawn` (shards, pings, helpers) lands on that runtime too. The returned handle belongs to
+// the caller's runtime and behaves like the provider's own: aborting it aborts the future on the
+// dedicated thread, which then drops its runtime and every task on it.
+/// Runs the future built by `start` on a new OS thread with its own current-thread runtime,
+/// pinned to `core` when set, with `probe` measuring that runtime.
+pub fn spawn_dedicated<S, F, T>(name: String, core: Option<usize>, probe: DelayProbe, start: S) -> task::JoinHandle<T>
+where
+    S: FnOnce() -> F + Send + 'static,
+    F: Future<Output = T> + 'static,
+    T: Send + 'static,
+{
+    let (result_tx, result_rx) = oneshot::channel();
+    let (abort_handle, registration) = AbortHandle::new_pair();
+    let thread_name = name.clone();
+    let spawned = thread::Builder::new().name(format!("provider-{}", name)).spawn(move || {
+        if let Some(core) = core {
+            if !core_affinity::set_for_current(core_affinity::CoreId { id: core }) {
+                log::warn!("[{}] Failed to pin the provider runtime to core {}, running unpinned", thread_name, core);
+            }
+        }
+        let runtime = match tokio::runtime::Builder::new_current_thread().enable_all().build() {
+            Ok(runtime) => runtime,
+            Err(e) => {
+                log::error!("[{}] Failed to start a dedicated runtime: {}", thread_name, e);
+                return;
+            }
+        };
+        runtime.block_on(async move {
+            let probe_task = tokio::spawn(probe.run());
+            let result = Abortable::new(start(), registration).await;
+            probe_task.abort();
+            if let Ok(output) = result {
+                let _ = result_tx.send(output);
+            }
+        });
+    });
+    if let Err(e) = &spawned {
+        log::error!("[{}] Failed to start a dedicated runtime thread: {}", name, e);
+    }
+
+    task::spawn(async move {
+        let _abort = AbortOnDrop(abort_handle);
+        match result_rx.await {
+            Ok(output) => output,
+            Err(_) => panic!("[{}] dedicated provider runtime stopped without a result", name),
+        }
+    })
+}
+
+pub fn print_runtimes(reports: &[RuntimeReport]) {
+    if reports.is_empty() {
+        return;
+    }
+
+    println!("\nProvider runtimes");
+    println!("--------------------------------------------");
+    for report in reports {
+        let core = report.core.map(|core| format!(" on core {}", core)).unwrap_or_default();
+        println!(
+            "{}{}: scheduler delay median {:.0} us, p99 {:.0} us, max {:.0} us ({} samples)",
+            report.name, core, report.median_delay_us, report.p99_delay_us, report.max_delay_us, report.samples
+        );
+    }
+    println!("A runtime's delay is how late it could have timestamped a message; isolated runtimes stay low when another endpoint bursts");
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn histogram_percentiles_are_within_a_bucket() {
+        let mut histogram = DelayHistogram::default();
+        for delay_us in 1..=1000 {
+            histogram.record(delay_us as f64);
+        }
+        assert_eq!(histogram.samples, 1000);
+        assert_eq!(histogram.max_us, 1000.0);
+        for (p, exact) in [(0.5, 501.0), (0.99, 990.0)] {
+            let estimate = histogram.percentile(p);
+            assert!((estimate - exact).abs() / exact < 0.025, "p{} {} vs {}", p, estimate, exact);
+        }
+    }
+
+    #[test]
+    fn histogram_keeps_tiny_and_huge_delays() {
+        let mut histogram = DelayHistogram::default();
+        assert_eq!(histogram.percentile(0.5), 0.0);
+        histogram.record(0.2);
+        histogram.record(0.4);
+        histogram.record(1e12);
+        assert_eq!(histogram.percentile(0.5), 0.0);
+        assert_eq!(histogram.max_us, 1e12);
+        assert!(histogram.percentile(1.0) <= 1e12);
+    }
+}
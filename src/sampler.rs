@@ -0,0 +1,106 @@
+use std::{
+    collections::hash_map::DefaultHasher,
+    hash::{Hash, Hasher},
+};
+
+// 2^24 bits (2 MiB) per generation with 4 probes stays under ~0.2% false positives at
+// GENERATION_CAPACITY entries
+const FILTER_BITS: usize = 1 << 24;
+const FILTER_HASHES: u64 = 4;
+const GENERATION_CAPACITY: usize = 1_000_000;
+
+/// Decides once per signature whether it is kept, so every endpoint's sighting shares the decision.
+// AIDEV-NOTE: Kept signatures are recognised exactly through the Comparator's own map; only the
+// dropped ones go into the bloom filter. A false positive can therefore only drop a new
+// signature (slightly lowering the effective rate), never admit a late sighting of a dropped
+// one, which would credit the win to whichever endpoint happened to be second.
+#[derive(Debug, Clone)]
+pub struct Sampler {
+    rate: f64,
+    credit: f64,
+    current: BloomFilter,
+    previous: BloomFilter,
+    pub kept: u64,
+    pub dropped: u64,
+}
+
+impl Sampler {
+    /// Keeps roughly `rate` (0, 1] of the distinct signatures.
+    pub fn new(rate: f64) -> Self {
+        Self {
+            rate,
+            credit: 0.0,
+            current: BloomFilter::new(),
+            previous: BloomFilter::new(),
+            kept: 0,
+            dropped: 0,
+        }
+    }
+
+    pub fn rate(&self) -> f64 {
+        self.rate
+    }
+
+    /// Whether a sighting of `signature` should be recorded; `already_kept` is true when another
+    /// endpoint's sighting of it is already in the Comparator.
+    pub fn admit(&mut self, signature: &str, already_kept: bool) -> bool {
+        if already_kept {
+            return true;
+        }
+        if self.current.contains(signature) || self.previous.contains(signature) {
+            return false;
+        }
+
+        // Systematic selection by arrival order: which endpoint saw the signature first plays no part
+        self.credit += self.rate;
+        if self.credit >= 1.0 {
+            self.credit -= 1.0;
+            self.kept += 1;
+            return true;
+        }
+
+        // Two generations bound memory on long runs while still covering every in-flight race
+        if self.current.len >= GENERATION_CAPACITY {
+            self.previous = std::mem::replace(&mut self.current, BloomFilter::new());
+        }
+        self.current.insert(signature);
+        self.dropped += 1;
+        false
+    }
+}
+
+#[derive(Debug, Clone)]
+struct BloomFilter {
+    bits: Vec<u64>,
+    len: usize,
+}
+
+impl BloomFilter {
+    fn new() -> Self {
+        Self {
+            bits: vec![0; FILTER_BITS / 64],
+            len: 0,
+        }
+    }
+
+    fn insert(&mut self, item: &str) {
+        for index in Self::indexes(item) {
+            self.bits[index / 64] |= 1 << (index % 64);
+        }
+        self.len += 1;
+    }
+
+    fn contains(&self, item: &str) -> bool {
+        Self::indexes(item).all(|index| self.bits[index / 64] & (1 << (index % 64)) != 0)
+    }
+
+    // Double hashing: probe i lands on h1 + i * h2
+    fn indexes(item: &str) -> impl Iterator<Item = usize> {
+        let mut hasher = DefaultHasher::new();
+        item.hash(&mut hasher);
+        let h1 = hasher.finish();
+        0xa5u8.hash(&mut hasher);
+        let h2 = hasher.finish() | 1;
+        (0..FILTER_HASHES).map(move |i| (h1.wrapping_add(i.wrapping_mul(h2)) % FILTER_BITS as u64) as usize)
+    }
+}
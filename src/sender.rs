@@ -0,0 +1,341 @@
+use std::{
+    collections::HashMap,
+    error::Error,
+    str::FromStr,
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use solana_sdk::{
+    hash::Hash,
+    instruction::{AccountMeta, Instruction},
+    pubkey::Pubkey,
+    signature::{read_keypair_file, Keypair},
+    signer::Signer,
+    transaction::Transaction,
+};
+use tokio::sync::broadcast;
+
+use crate::{
+    clock::Clock,
+    config::{ConfigToml, SenderConfig},
+    utils::{percentile, Comparator},
+};
+
+pub const MEMO_PROGRAM_ID: &str = "MemoSq4gqABAXKb96qnH8TysNcWxMyWCqXgDLGmfcHr";
+const SYSTEM_PROGRAM_ID: &str = "11111111111111111111111111111111";
+// Index of the system program's Transfer instruction
+const SYSTEM_TRANSFER: u32 = 2;
+
+/// Jito's mainnet tip accounts; bundles tip one of them, round robin.
+pub const JITO_TIP_ACCOUNTS: [&str; 8] = [
+    "96gYZGLnJYVFmbjzopPSU6QiEV5fGqZNyN9nmNhvrZU5",
+    "HFqU5x63VTqvQss8hp11i4wVV8bD44PvwucfZ2bU7gRe",
+    "Cw8CFyM9FkoMi7K7Crf6HNQqf4uEMzpKw6QNghXLvLkY",
+    "ADaUMid9yfUytqMBgopwjb2DTLSokTSzL1zt6iGPaS49",
+    "DfXygSm4jCyNCybVYYK6DwvWqjKee8pbDmJGcLWNDXjh",
+    "ADuUkR4vqLUMWXxW9gh6D6L8pMSawimctcNZ5pGwDcEt",
+    "DttWaMuVvTiduZRnguLF7jNxTgiMBZ1hyAumKUiL2KRL",
+    "3AVi9Tg9Uo68tJfuvoKvqKNWKkC5wPdSSdeBnizKZ6jT",
+];
+
+const REQUEST_TIMEOUT_SECS: u64 = 10;
+// Blockhashes stay valid for about 60 seconds; refreshing at half that keeps every send landable
+const BLOCKHASH_REFRESH_SECS: f64 = 30.0;
+
+/// One transaction geyserbench submitted itself.
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+pub struct SentTransaction {
+    pub signature: String,
+    /// Just before the request went out
+    pub sent_at: f64,
+    /// When the RPC node or block engine answered
+    pub acknowledged_at: f64,
+    /// Submission error; the transaction is then not expected on any stream
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+/// Submit-to-stream latency of one endpoint over the sent transactions it delivered.
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+pub struct SendEndpointReport {
+    pub name: String,
+    pub seen: usize,
+    /// Sent transactions this endpoint delivered before any other
+    pub first: usize,
+    pub min_ms: f64,
+    pub median_ms: f64,
+    pub p95_ms: f64,
+    pub max_ms: f64,
+}
+
+/// Send-side run: what was submitted, how it was acknowledged and how fast each stream showed it.
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+pub struct SendReport {
+    /// `rpc` or `jito`
+    pub via: String,
+    pub payer: String,
+    pub sent: usize,
+    pub failed: usize,
+    /// Accepted transactions seen by at least one endpoint
+    pub landed: usize,
+    pub median_ack_ms: f64,
+    pub endpoints: Vec<SendEndpointReport>,
+    pub transactions: Vec<SentTransaction>,
+}
+
+pub fn load_keypair(sender: &SenderConfig) -> Result<Keypair, Box<dyn Error + Send + Sync>> {
+    read_keypair_file(&sender.keypair)
+        .map_err(|e| format!("Failed to read sender keypair {}: {}", sender.keypair, e).into())
+}
+
+/// Copy of `config` that also watches the payer, so every stream delivers the sent transactions.
+pub fn watch_payer(config: &ConfigToml, payer: &Pubkey) -> ConfigToml {
+    let mut resolved = config.clone();
+    let payer = payer.to_string();
+    if !resolved.config.watched_accounts().contains(&payer) {
+        resolved.config.accounts.push(payer);
+    }
+    resolved
+}
+
+async fn rpc_call(
+    client: &reqwest::Client,
+    url: &str,
+    method: &str,
+    params: Value,
+) -> Result<Value, Box<dyn Error + Send + Sync>> {
+    let request = json!({ "jsonrpc": "2.0", "id": 1, "method": method, "params": params });
+    let response: Value = client
+        .post(url)
+        .timeout(Duration::from_secs(REQUEST_TIMEOUT_SECS))
+        .json(&request)
+        .send()
+        .await?
+        .error_for_status()?
+        .json()
+        .await?;
+    if let Some(error) = response.get("error") {
+        return Err(format!("{} failed: {}", method, error).into());
+    }
+    Ok(response["result"].clone())
+}
+
+async fn latest_blockhash(client: &reqwest::Client, rpc_url: &str) -> Result<Hash, Box<dyn Error + Send + Sync>> {
+    let result = rpc_call(client, rpc_url, "getLatestBlockhash", json!([{ "commitment": "confirmed" }])).await?;
+    let blockhash = result["value"]["blockhash"]
+        .as_str()
+        .ok_or("getLatestBlockhash returned no blockhash")?;
+    Ok(Hash::from_str(blockhash)?)
+}
+
+// AIDEV-NOTE: The memo text carries the run start and a sequence number, so every transaction
+// has its own signature even when several are built on the same blockhash. The memo program
+// needs the payer as signer, which is what puts the payer's account on every sent transaction.
+fn build_transaction(payer: &Keypair, blockhash: Hash, memo: &str, tip: Option<(Pubkey, u64)>) -> Transaction {
+    let mut instructions = vec![Instruction::new_with_bytes(
+        Pubkey::from_str(MEMO_PROGRAM_ID).expect("valid program id"),
+        memo.as_bytes(),
+        vec![AccountMeta::new_readonly(payer.pubkey(), true)],
+    )];
+    if let Some((tip_account, lamports)) = tip {
+        let mut data = SYSTEM_TRANSFER.to_le_bytes().to_vec();
+        data.extend_from_slice(&lamports.to_le_bytes());
+        instructions.push(Instruction::new_with_bytes(
+            Pubkey::from_str(SYSTEM_PROGRAM_ID).expect("valid program id"),
+            &data,
+            vec![AccountMeta::new(payer.pubkey(), true), AccountMeta::new(tip_account, false)],
+        ));
+    }
+    Transaction::new_signed_with_payer(&instructions, Some(&payer.pubkey()), &[payer], blockhash)
+}
+
+async fn submit(
+    client: &reqwest::Client,
+    sender: &SenderConfig,
+    transaction: &Transaction,
+) -> Result<(), Box<dyn Error + Send + Sync>> {
+    let encoded = bs58::encode(bincode::serialize(transaction)?).into_string();
+    match &sender.jito_url {
+        Some(jito_url) => rpc_call(client, jito_url, "sendBundle", json!([[encoded]])).await?,
+        None => rpc_call(
+            client,
+            &sender.rpc_url,
+            "sendTransaction",
+            json!([encoded, { "skipPreflight": true }]),
+        )
+        .await?,
+    };
+    Ok(())
+}
+
+/// Sends `count` memo transactions after `start_delay`, then stops the run once `settle_secs`
+/// have passed, recording `stop_reason` unless something else stopped it first.
+pub fn spawn(
+    sender: SenderConfig,
+    payer: Keypair,
+    start_delay: Duration,
+    clock: Arc<dyn Clock>,
+    shutdown_tx: broadcast::Sender<()>,
+    stop_reason: Arc<Mutex<Option<String>>>,
+) -> tokio::task::JoinHandle<Vec<SentTransaction>> {
+    let mut shutdown_rx = shutdown_tx.subscribe();
+    tokio::spawn(async move {
+        let mut sent = Vec::new();
+        tokio::select! {
+            _ = tokio::time::sleep(start_delay) => {}
+            _ = shutdown_rx.recv() => return sent,
+        }
+
+        let client = reqwest::Client::new();
+        let run_id = clock.now() as u64;
+        let mut blockhash: Option<(Hash, f64)> = None;
+        let mut interval = tokio::time::interval(Duration::from_millis(sender.interval_ms.max(1)));
+        log::info!(
+            "Sending {} memo transactions from {} via {}",
+            sender.count,
+            payer.pubkey(),
+            if sender.jito_url.is_some() { "Jito bundles" } else { "RPC" }
+        );
+        for sequence in 0..sender.count {
+            tokio::select! {
+                _ = interval.tick() => {}
+                _ = shutdown_rx.recv() => return sent,
+            }
+            if !blockhash.is_some_and(|(_, fetched_at)| clock.now() - fetched_at <= BLOCKHASH_REFRESH_SECS) {
+                match latest_blockhash(&client, &sender.rpc_url).await {
+                    Ok(hash) => blockhash = Some((hash, clock.now())),
+                    Err(e) => log::warn!("Failed to fetch a blockhash: {}", e),
+                }
+            }
+            let Some((hash, _)) = blockhash else {
+                continue;
+            };
+
+            let tip = sender.jito_url.as_ref().map(|_| {
+                let account = JITO_TIP_ACCOUNTS[sequence as usize % JITO_TIP_ACCOUNTS.len()];
+                (Pubkey::from_str(account).expect("valid tip account"), sender.jito_tip_lamports)
+            });
+            let transaction = build_transaction(&payer, hash, &format!("geyserbench {} {}", run_id, sequence), tip);
+            let sent_at = clock.now();
+            let result = submit(&client, &sender, &transaction).await;
+            let acknowledged_at = clock.now();
+            if let Err(e) = &result {
+                log::warn!("Failed to send transaction {}: {}", sequence, e);
+            }
+            sent.push(SentTransaction {
+                signature: transaction.signatures[0].to_string(),
+                sent_at,
+                acknowledged_at,
+                error: result.err().map(|e| e.to_string()),
+            });
+        }
+
+        tokio::select! {
+            _ = tokio::time::sleep(Duration::from_secs(sender.settle_secs)) => {}
+            _ = shutdown_rx.recv() => return sent,
+        }
+        log::info!("All transactions sent and {}s passed, stopping", sender.settle_secs);
+        stop_reason
+            .lock()
+            .unwrap()
+            .get_or_insert_with(|| format!("sender sent {} transactions", sent.len()));
+        let _ = shutdown_tx.send(());
+        sent
+    })
+}
+
+/// Submit-to-stream latency per endpoint, measured from `sent_at` to the receive time.
+pub fn summarize_send(
+    comparator: &Comparator,
+    endpoint_names: &[String],
+    sender: &SenderConfig,
+    payer: &Pubkey,
+    sent: Vec<SentTransaction>,
+) -> SendReport {
+    let mut delays: HashMap<&str, Vec<f64>> = HashMap::new();
+    let mut firsts: HashMap<&str, usize> = HashMap::new();
+    let mut landed = 0;
+    for transaction in sent.iter().filter(|transaction| transaction.error.is_none()) {
+        let Some(sig_data) = comparator.data.get(&transaction.signature) else {
+            continue;
+        };
+        landed += 1;
+        let first = sig_data
+            .iter()
+            .min_by(|(_, a), (_, b)| a.timestamp.total_cmp(&b.timestamp))
+            .map(|(name, _)| name.as_str());
+        for name in endpoint_names {
+            if let Some(tx) = sig_data.get(name) {
+                delays
+                    .entry(name.as_str())
+                    .or_default()
+                    .push((tx.timestamp - transaction.sent_at) * 1000.0);
+                if first == Some(name.as_str()) {
+                    *firsts.entry(name.as_str()).or_default() += 1;
+                }
+            }
+        }
+    }
+
+    let endpoints = endpoint_names
+        .iter()
+        .map(|name| {
+            let mut endpoint_delays = delays.remove(name.as_str()).unwrap_or_default();
+            endpoint_delays.sort_by(|a, b| a.total_cmp(b));
+            SendEndpointReport {
+                name: name.clone(),
+                seen: endpoint_delays.len(),
+                first: firsts.get(name.as_str()).copied().unwrap_or(0),
+                min_ms: endpoint_delays.first().copied().unwrap_or(0.0),
+                median_ms: percentile(&endpoint_delays, 0.5),
+                p95_ms: percentile(&endpoint_delays, 0.95),
+                max_ms: endpoint_delays.last().copied().unwrap_or(0.0),
+            }
+        })
+        .collect();
+    let mut acks: Vec<f64> = sent
+        .iter()
+        .filter(|transaction| transaction.error.is_none())
+        .map(|transaction| (transaction.acknowledged_at - transaction.sent_at) * 1000.0)
+        .collect();
+    acks.sort_by(|a, b| a.total_cmp(b));
+
+    SendReport {
+        via: if sender.jito_url.is_some() { "jito" } else { "rpc" }.to_string(),
+        payer: payer.to_string(),
+        sent: sent.len(),
+        failed: sent.iter().filter(|transaction| transaction.error.is_some()).count(),
+        landed,
+        median_ack_ms: percentile(&acks, 0.5),
+        endpoints,
+        transactions: sent,
+    }
+}
+
+pub fn print_send(report: &SendReport) {
+    println!("\nSubmit to stream latency");
+    println!("--------------------------------------------");
+    println!(
+        "{} sent via {} from {} ({} failed, {} seen on a stream), median acknowledgement {:.2} ms",
+        report.sent, report.via, report.payer, report.failed, report.landed, report.median_ack_ms
+    );
+    for endpoint in &report.endpoints {
+        if endpoint.seen == 0 {
+            println!("{}: none of the sent transactions seen", endpoint.name);
+            continue;
+        }
+        println!(
+            "{}: min {:.2} ms, median {:.2} ms, p95 {:.2} ms, max {:.2} ms over {} transactions ({} first)",
+            endpoint.name,
+            endpoint.min_ms,
+            endpoint.median_ms,
+            endpoint.p95_ms,
+            endpoint.max_ms,
+            endpoint.seen,
+            endpoint.first
+        );
+    }
+}
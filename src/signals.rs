@@ -0,0 +1,47 @@
+//! Stop signals for interactive and supervised runs: Ctrl+C, and SIGTERM from `kill`, systemd or
+//! container runtimes.
+
+use tokio::signal::ctrl_c;
+
+/// Exit status after a second signal, as a shell reports a SIGINT-terminated process.
+pub const FORCED_EXIT_CODE: i32 = 130;
+
+/// Waits for Ctrl+C or, on Unix, SIGTERM, and returns the signal's name.
+pub async fn stop_signal() -> &'static str {
+    #[cfg(unix)]
+    {
+        use tokio::signal::unix::{signal, SignalKind};
+
+        match signal(SignalKind::terminate()) {
+            Ok(mut terminate) => tokio::select! {
+                _ = ctrl_c() => "Ctrl+C",
+                _ = terminate.recv() => "SIGTERM",
+            },
+            Err(e) => {
+                log::warn!("Failed to install the SIGTERM handler: {}", e);
+                let _ = ctrl_c().await;
+                "Ctrl+C"
+            }
+        }
+    }
+    #[cfg(not(unix))]
+    {
+        let _ = ctrl_c().await;
+        "Ctrl+C"
+    }
+}
+
+// AIDEV-NOTE: The first signal stops the run gracefully: providers drain and every statistic and
+// report is still produced from what was collected. Draining or a stuck provider can hold that up,
+// so a second signal exits at once without results.
+/// Calls `on_stop` on the first stop signal and exits the process on the second.
+pub fn spawn_handler(on_stop: impl FnOnce(&'static str) + Send + 'static) {
+    tokio::spawn(async move {
+        let name = stop_signal().await;
+        println!("\nReceived {} signal. Stopping and writing partial results (repeat to exit immediately)...", name);
+        on_stop(name);
+        let name = stop_signal().await;
+        eprintln!("Received {} again, exiting without results", name);
+        std::process::exit(FORCED_EXIT_CODE);
+    });
+}
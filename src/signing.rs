@@ -0,0 +1,67 @@
+use serde::{Deserialize, Serialize};
+use solana_sdk::{
+    pubkey::Pubkey,
+    signature::{read_keypair_file, Signature},
+    signer::Signer,
+};
+use std::{fs, str::FromStr};
+
+// AIDEV-NOTE: Detached ed25519 signature over the exact report bytes, stored next to it as `<report>.sig`
+#[derive(Debug, Deserialize, Serialize)]
+pub struct ReportSignature {
+    pub pubkey: String,
+    pub signature: String,
+}
+
+pub fn signature_path(report_path: &str) -> String {
+    format!("{}.sig", report_path)
+}
+
+pub fn sign_file(
+    report_path: &str,
+    content: &[u8],
+    keypair_path: &str,
+) -> Result<String, Box<dyn std::error::Error>> {
+    let keypair = read_keypair_file(keypair_path)
+        .map_err(|e| format!("Failed to read signing keypair {}: {}", keypair_path, e))?;
+    let report_signature = ReportSignature {
+        pubkey: keypair.pubkey().to_string(),
+        signature: keypair.sign_message(content).to_string(),
+    };
+
+    let path = signature_path(report_path);
+    fs::write(&path, serde_json::to_vec_pretty(&report_signature)?)?;
+    Ok(path)
+}
+
+pub fn verify_file(
+    report_path: &str,
+    expected_pubkey: Option<&str>,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let content = fs::read(report_path)?;
+    let sig_path = signature_path(report_path);
+    let report_signature: ReportSignature = serde_json::from_slice(&fs::read(&sig_path)?)
+        .map_err(|e| format!("Invalid signature file {}: {}", sig_path, e))?;
+
+    let pubkey = Pubkey::from_str(&report_signature.pubkey)?;
+    if let Some(expected) = expected_pubkey {
+        if Pubkey::from_str(expected)? != pubkey {
+            return Err(format!(
+                "Report was signed by {}, expected {}",
+                pubkey, expected
+            )
+            .into());
+        }
+    }
+
+    let signature = Signature::from_str(&report_signature.signature)?;
+    if !signature.verify(pubkey.as_ref(), &content) {
+        return Err(format!("Signature verification failed for {}", report_path).into());
+    }
+
+    println!("{}: valid signature by {}", report_path, pubkey);
+    if expected_pubkey.is_none() {
+        println!("Note: pass --pubkey to also check who signed the report");
+    }
+    Ok(())
+}
@@ -0,0 +1,55 @@
+use serde::{Deserialize, Serialize};
+
+use crate::{config::SloConfig, report::EndpointReport};
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct SloResult {
+    pub metric: String,
+    pub target: f64,
+    /// None when the endpoint was never behind, which passes every delay target
+    pub value: Option<f64>,
+    pub pass: bool,
+}
+
+impl SloResult {
+    /// Short human readable form, e.g. `p95 <= 50.00 ms`.
+    pub fn label(&self) -> String {
+        match self.metric.as_str() {
+            "win_rate" => format!("win rate >= {:.2}%", self.target),
+            metric => format!("{} <= {:.2} ms", metric, self.target),
+        }
+    }
+}
+
+pub fn evaluate(slo: &SloConfig, endpoint: &EndpointReport) -> Vec<SloResult> {
+    let delays = endpoint.delays.as_ref();
+    let delay_targets = [
+        ("average", slo.average_ms, delays.map(|d| d.average_ms)),
+        ("median", slo.median_ms, delays.map(|d| d.median_ms)),
+        ("p95", slo.p95_ms, delays.map(|d| d.p95_ms)),
+        ("max", slo.max_ms, delays.map(|d| d.max_ms)),
+    ];
+
+    let mut results: Vec<SloResult> = delay_targets
+        .into_iter()
+        .filter_map(|(metric, target, value)| {
+            let target = target?;
+            Some(SloResult {
+                metric: metric.to_string(),
+                target,
+                value,
+                pass: value.is_none_or(|value| value <= target),
+            })
+        })
+        .collect();
+
+    if let Some(target) = slo.min_win_rate {
+        results.push(SloResult {
+            metric: "win_rate".to_string(),
+            target,
+            value: Some(endpoint.win_rate),
+            pass: endpoint.win_rate >= target,
+        });
+    }
+    results
+}
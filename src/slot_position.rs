@@ -0,0 +1,151 @@
+//! Whether an endpoint's delivery latency depends on where in the slot a transaction landed.
+//!
+//! Shred-based endpoints record each transaction's entry position; post-execution endpoints
+//! delivering later in a way that shrinks towards the end of the slot are batching per slot
+//! rather than being uniformly slow.
+
+use std::collections::HashMap;
+
+use serde::{Deserialize, Serialize};
+
+use crate::utils::{percentile, Comparator};
+
+/// A slot is 64 ticks; buckets are eighths of it
+const TICKS_PER_BUCKET: u32 = 8;
+const BUCKETS: u32 = 8;
+/// Fewer paired samples than this give no correlation
+const MIN_SAMPLES: usize = 10;
+
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+pub struct SlotPositionBucket {
+    /// First tick of the bucket; the last bucket also holds anything past the 64th tick
+    pub ticks_from: u32,
+    pub samples: usize,
+    pub median_delay_ms: f64,
+}
+
+/// One post-execution endpoint's delay behind the shred sighting against the transaction's
+/// position in its slot.
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+pub struct SlotPositionEndpointReport {
+    pub name: String,
+    pub samples: usize,
+    /// Pearson correlation of delay with `ticks_before`; negative when later transactions wait less
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub correlation: Option<f64>,
+    /// Least-squares change in delay per tick
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub slope_ms_per_tick: Option<f64>,
+    pub buckets: Vec<SlotPositionBucket>,
+}
+
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+pub struct SlotPositionReport {
+    /// Live signatures with a recorded entry position
+    pub signatures: usize,
+    pub endpoints: Vec<SlotPositionEndpointReport>,
+}
+
+/// Correlation and least-squares slope of `y` on `x`; None without enough spread in either.
+fn regression(samples: &[(f64, f64)]) -> Option<(f64, f64)> {
+    let n = samples.len() as f64;
+    let mean_x = samples.iter().map(|(x, _)| x).sum::<f64>() / n;
+    let mean_y = samples.iter().map(|(_, y)| y).sum::<f64>() / n;
+    let (mut sxx, mut syy, mut sxy) = (0.0, 0.0, 0.0);
+    for (x, y) in samples {
+        sxx += (x - mean_x).powi(2);
+        syy += (y - mean_y).powi(2);
+        sxy += (x - mean_x) * (y - mean_y);
+    }
+    if sxx == 0.0 || syy == 0.0 {
+        return None;
+    }
+    Some((sxy / (sxx * syy).sqrt(), sxy / sxx))
+}
+
+// AIDEV-NOTE: Delays are measured from the earliest sighting that carries an entry position, not
+// from the first detection overall, so they are the time from the transaction appearing in shreds
+// to its delivery after execution. Endpoints that themselves record positions are the reference
+// side and are not reported.
+pub fn summarize_slot_position(comparator: &Comparator, endpoint_names: &[String]) -> Option<SlotPositionReport> {
+    let mut signatures = 0;
+    let mut samples: HashMap<&str, Vec<(f64, f64)>> = HashMap::new();
+    for sig_data in comparator.data.values().filter(|sig_data| comparator.is_live(sig_data)) {
+        let Some((shred_time, position)) = sig_data
+            .values()
+            .filter_map(|tx| Some((tx.precise_timestamp(), tx.entry?)))
+            .min_by(|a, b| a.0.total_cmp(&b.0))
+        else {
+            continue;
+        };
+        signatures += 1;
+        for (endpoint, tx) in sig_data.iter().filter(|(_, tx)| tx.entry.is_none()) {
+            let delay = (tx.precise_timestamp() - shred_time) * 1000.0;
+            samples.entry(endpoint.as_str()).or_default().push((position.ticks_before as f64, delay));
+        }
+    }
+    if samples.is_empty() {
+        return None;
+    }
+
+    let endpoints = endpoint_names
+        .iter()
+        .filter_map(|name| {
+            let samples = samples.remove(name.as_str())?;
+            let fit = (samples.len() >= MIN_SAMPLES).then(|| regression(&samples)).flatten();
+            let mut by_bucket: Vec<Vec<f64>> = vec![Vec::new(); BUCKETS as usize];
+            for (ticks, delay) in &samples {
+                let bucket = ((*ticks as u32) / TICKS_PER_BUCKET).min(BUCKETS - 1);
+                by_bucket[bucket as usize].push(*delay);
+            }
+            let buckets = by_bucket
+                .into_iter()
+                .enumerate()
+                .map(|(index, mut delays)| {
+                    delays.sort_by(|a, b| a.total_cmp(b));
+                    SlotPositionBucket {
+                        ticks_from: index as u32 * TICKS_PER_BUCKET,
+                        samples: delays.len(),
+                        median_delay_ms: percentile(&delays, 0.5),
+                    }
+                })
+                .collect();
+            Some(SlotPositionEndpointReport {
+                name: name.clone(),
+                samples: samples.len(),
+                correlation: fit.map(|(correlation, _)| correlation),
+                slope_ms_per_tick: fit.map(|(_, slope)| slope),
+                buckets,
+            })
+        })
+        .collect();
+    Some(SlotPositionReport { signatures, endpoints })
+}
+
+pub fn print_slot_position(report: &SlotPositionReport) {
+    println!("\nDelay by position in slot");
+    println!("--------------------------------------------");
+    println!("{} signatures with a shred entry position; delays are behind the shred sighting", report.signatures);
+    for endpoint in &report.endpoints {
+        match (endpoint.correlation, endpoint.slope_ms_per_tick) {
+            (Some(correlation), Some(slope)) => println!(
+                "{} ({} samples): correlation {:+.2}, {:+.3} ms per tick",
+                endpoint.name, endpoint.samples, correlation, slope
+            ),
+            _ => println!("{} ({} samples): too few samples for a correlation", endpoint.name, endpoint.samples),
+        }
+        let medians: Vec<String> = endpoint
+            .buckets
+            .iter()
+            .map(|bucket| {
+                if bucket.samples == 0 {
+                    "-".to_string()
+                } else {
+                    format!("{:.1}", bucket.median_delay_ms)
+                }
+            })
+            .collect();
+        println!("  median ms per eighth of the slot: {}", medians.join(" | "));
+    }
+    println!("Strongly negative correlation means delivery waits for the end of the slot; near zero means a steady delay");
+}
@@ -0,0 +1,145 @@
+//! Slots mode: when each endpoint reported each slot reaching processed, confirmed and finalized.
+//!
+//! Every (slot, status) pair is raced on its own, so propagation latency is reported per status;
+//! providers that keep up at processed can still trail at finalized.
+
+use std::collections::HashMap;
+
+use serde::{Deserialize, Serialize};
+
+use crate::utils::{percentile, Comparator, TransactionData};
+
+/// Statuses raced in slots mode, in the order a slot reaches them.
+pub const SLOT_STATUSES: [&str; 3] = ["processed", "confirmed", "finalized"];
+
+/// Comparator key for one slot reaching one status.
+pub fn slot_status_key(slot: u64, status: &str) -> String {
+    format!("slot:{}:{}", slot, status)
+}
+
+/// One endpoint's delivery of one status.
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+pub struct SlotStatusEndpointReport {
+    pub name: String,
+    pub seen: usize,
+    pub first_detections: usize,
+    /// Delay behind the first endpoint reporting the same slot at the same status
+    pub median_delay_ms: f64,
+    pub p95_delay_ms: f64,
+    /// Time from the slot's first processed report by any endpoint to this endpoint's report of
+    /// this status; absent for processed itself
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub median_since_processed_ms: Option<f64>,
+}
+
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+pub struct SlotStatusLevelReport {
+    pub status: String,
+    /// Live slots reported at this status by at least one endpoint
+    pub slots: usize,
+    pub endpoints: Vec<SlotStatusEndpointReport>,
+}
+
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+pub struct SlotStatusReport {
+    pub statuses: Vec<SlotStatusLevelReport>,
+}
+
+type SlotSightings<'a> = Vec<(u64, &'a HashMap<String, TransactionData>)>;
+
+// AIDEV-NOTE: "Since processed" is measured from the earliest processed report across all
+// endpoints, so it includes the cluster's own confirmation time; the per-status delay is what
+// separates the providers.
+pub fn summarize_slot_status(comparator: &Comparator, endpoint_names: &[String]) -> Option<SlotStatusReport> {
+    let mut by_status: HashMap<&str, SlotSightings> = HashMap::new();
+    let mut processed_at: HashMap<u64, f64> = HashMap::new();
+    for (key, sig_data) in comparator.data.iter().filter(|(_, sig_data)| comparator.is_live(sig_data)) {
+        let Some((slot, status)) = key
+            .strip_prefix("slot:")
+            .and_then(|rest| rest.split_once(':'))
+            .and_then(|(slot, status)| Some((slot.parse::<u64>().ok()?, status)))
+        else {
+            continue;
+        };
+        let Some(status) = SLOT_STATUSES.iter().copied().find(|known| *known == status) else {
+            continue;
+        };
+        if status == SLOT_STATUSES[0] {
+            let first = sig_data.values().map(|tx| tx.precise_timestamp()).fold(f64::INFINITY, f64::min);
+            processed_at.insert(slot, first);
+        }
+        by_status.entry(status).or_default().push((slot, sig_data));
+    }
+    if by_status.is_empty() {
+        return None;
+    }
+
+    let statuses = SLOT_STATUSES
+        .iter()
+        .filter_map(|status| {
+            let slots = by_status.remove(status)?;
+            let endpoints = endpoint_names
+                .iter()
+                .map(|name| {
+                    let mut report = SlotStatusEndpointReport {
+                        name: name.clone(),
+                        ..Default::default()
+                    };
+                    let mut delays = Vec::new();
+                    let mut since_processed = Vec::new();
+                    for (slot, sig_data) in &slots {
+                        let Some(own) = sig_data.get(name) else {
+                            continue;
+                        };
+                        let first = sig_data.values().map(|tx| tx.precise_timestamp()).fold(f64::INFINITY, f64::min);
+                        let delay = (own.precise_timestamp() - first) * 1000.0;
+                        report.seen += 1;
+                        if delay == 0.0 {
+                            report.first_detections += 1;
+                        }
+                        delays.push(delay);
+                        if let Some(processed) = processed_at.get(slot).filter(|_| *status != SLOT_STATUSES[0]) {
+                            since_processed.push((own.precise_timestamp() - processed) * 1000.0);
+                        }
+                    }
+                    delays.sort_by(|a, b| a.total_cmp(b));
+                    since_processed.sort_by(|a, b| a.total_cmp(b));
+                    report.median_delay_ms = percentile(&delays, 0.5);
+                    report.p95_delay_ms = percentile(&delays, 0.95);
+                    report.median_since_processed_ms =
+                        (!since_processed.is_empty()).then(|| percentile(&since_processed, 0.5));
+                    report
+                })
+                .collect();
+            Some(SlotStatusLevelReport {
+                status: status.to_string(),
+                slots: slots.len(),
+                endpoints,
+            })
+        })
+        .collect();
+    Some(SlotStatusReport { statuses })
+}
+
+pub fn print_slot_status(report: &SlotStatusReport) {
+    println!("\nSlot status propagation");
+    println!("--------------------------------------------");
+    for level in &report.statuses {
+        println!("{} ({} slots):", level.status, level.slots);
+        for endpoint in &level.endpoints {
+            let since_processed = endpoint
+                .median_since_processed_ms
+                .map(|ms| format!(", {:.1} ms after processed", ms))
+                .unwrap_or_default();
+            println!(
+                "  {}: {}/{} first, median delay {:.2} ms, p95 {:.2} ms{}",
+                endpoint.name,
+                endpoint.first_detections,
+                endpoint.seen,
+                endpoint.median_delay_ms,
+                endpoint.p95_delay_ms,
+                since_processed
+            );
+        }
+    }
+}
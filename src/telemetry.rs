@@ -0,0 +1,220 @@
+//! OpenTelemetry spans and histograms for the per-message pipeline (stream receipt, account
+//! filtering, Comparator lock wait and insertion), exported over OTLP.
+//!
+//! Only compiled in with the `otel` feature; without it [`PipelineTrace`] is a no-op so providers
+//! instrument unconditionally.
+
+use std::error::Error;
+
+use crate::config::OtelConfig;
+
+#[cfg(feature = "otel")]
+mod otlp {
+    use std::{
+        error::Error,
+        sync::OnceLock,
+        time::{Instant, SystemTime},
+    };
+
+    use opentelemetry::{
+        global,
+        metrics::Histogram,
+        trace::{Span, TraceContextExt, Tracer},
+        Context, KeyValue,
+    };
+    use opentelemetry_otlp::WithExportConfig;
+    use opentelemetry_sdk::{
+        metrics::{PeriodicReader, SdkMeterProvider},
+        runtime,
+        trace::TracerProvider,
+        Resource,
+    };
+
+    use crate::config::OtelConfig;
+
+    struct Instruments {
+        filter_ms: Histogram<f64>,
+        lock_wait_ms: Histogram<f64>,
+        insert_ms: Histogram<f64>,
+    }
+
+    static INSTRUMENTS: OnceLock<Instruments> = OnceLock::new();
+
+    pub struct Telemetry {
+        tracer_provider: TracerProvider,
+        meter_provider: SdkMeterProvider,
+    }
+
+    impl Telemetry {
+        pub fn init(config: &OtelConfig) -> Result<Self, Box<dyn Error + Send + Sync>> {
+            let resource = Resource::new([KeyValue::new("service.name", config.service_name.clone())]);
+
+            let span_exporter = opentelemetry_otlp::SpanExporter::builder()
+                .with_tonic()
+                .with_endpoint(config.endpoint.clone())
+                .build()?;
+            let tracer_provider = TracerProvider::builder()
+                .with_batch_exporter(span_exporter, runtime::Tokio)
+                .with_resource(resource.clone())
+                .build();
+            global::set_tracer_provider(tracer_provider.clone());
+
+            let metric_exporter = opentelemetry_otlp::MetricExporter::builder()
+                .with_tonic()
+                .with_endpoint(config.endpoint.clone())
+                .build()?;
+            let meter_provider = SdkMeterProvider::builder()
+                .with_reader(PeriodicReader::builder(metric_exporter, runtime::Tokio).build())
+                .with_resource(resource)
+                .build();
+            global::set_meter_provider(meter_provider.clone());
+
+            let meter = global::meter("geyserbench");
+            let histogram = |name: &'static str, description: &'static str| {
+                meter.f64_histogram(name).with_unit("ms").with_description(description).build()
+            };
+            let _ = INSTRUMENTS.set(Instruments {
+                filter_ms: histogram("geyserbench.pipeline.filter", "Stream receipt to account filter match"),
+                lock_wait_ms: histogram("geyserbench.pipeline.lock_wait", "Filter match to Comparator lock acquired"),
+                insert_ms: histogram("geyserbench.pipeline.insert", "Comparator lock acquired to insertion done"),
+            });
+            Ok(Self {
+                tracer_provider,
+                meter_provider,
+            })
+        }
+
+        /// Flushes pending spans and metrics.
+        pub fn shutdown(self) {
+            if let Err(e) = self.tracer_provider.shutdown() {
+                log::warn!("Failed to flush OpenTelemetry spans: {}", e);
+            }
+            if let Err(e) = self.meter_provider.shutdown() {
+                log::warn!("Failed to flush OpenTelemetry metrics: {}", e);
+            }
+        }
+    }
+
+    pub struct PipelineTrace<'a> {
+        endpoint: &'a str,
+        received: Option<(Instant, SystemTime)>,
+        filtered: Option<Instant>,
+        locked: Option<Instant>,
+    }
+
+    impl<'a> PipelineTrace<'a> {
+        pub fn start(endpoint: &'a str) -> Self {
+            Self {
+                endpoint,
+                // Nothing is timed unless telemetry was initialised for this run
+                received: INSTRUMENTS.get().map(|_| (Instant::now(), SystemTime::now())),
+                filtered: None,
+                locked: None,
+            }
+        }
+
+        pub fn filtered(&mut self) {
+            if self.received.is_some() {
+                self.filtered = Some(Instant::now());
+            }
+        }
+
+        pub fn locked(&mut self) {
+            if self.received.is_some() {
+                self.locked = Some(Instant::now());
+            }
+        }
+
+        // AIDEV-NOTE: Spans are built after the fact from the recorded instants so the hot path
+        // only reads the clock; wall times are derived from the receipt instant to stay monotonic.
+        pub fn inserted(self, signature: &str) {
+            let (Some(instruments), Some((received, received_wall))) = (INSTRUMENTS.get(), self.received) else {
+                return;
+            };
+            let inserted = Instant::now();
+            let filtered = self.filtered.unwrap_or(received);
+            let locked = self.locked.unwrap_or(filtered);
+            let ms = |from: Instant, to: Instant| (to - from).as_secs_f64() * 1000.0;
+            let wall = |at: Instant| received_wall + (at - received);
+
+            let attributes = [KeyValue::new("endpoint", self.endpoint.to_string())];
+            instruments.filter_ms.record(ms(received, filtered), &attributes);
+            instruments.lock_wait_ms.record(ms(filtered, locked), &attributes);
+            instruments.insert_ms.record(ms(locked, inserted), &attributes);
+
+            let tracer = global::tracer("geyserbench");
+            let message = tracer
+                .span_builder("message")
+                .with_start_time(received_wall)
+                .with_attributes([
+                    KeyValue::new("endpoint", self.endpoint.to_string()),
+                    KeyValue::new("signature", signature.to_string()),
+                ])
+                .start(&tracer);
+            let cx = Context::current_with_span(message);
+            for (name, from, to) in [
+                ("filter", received, filtered),
+                ("lock_wait", filtered, locked),
+                ("insert", locked, inserted),
+            ] {
+                let mut stage = tracer
+                    .span_builder(name)
+                    .with_start_time(wall(from))
+                    .start_with_context(&tracer, &cx);
+                stage.end_with_timestamp(wall(to));
+            }
+            cx.span().end_with_timestamp(wall(inserted));
+        }
+    }
+}
+
+#[cfg(feature = "otel")]
+pub use otlp::{PipelineTrace, Telemetry};
+
+/// Starts OTLP export when `otel` is configured; warns and returns None when built without it.
+pub fn init(config: Option<&OtelConfig>) -> Result<Option<Telemetry>, Box<dyn Error + Send + Sync>> {
+    let Some(config) = config else {
+        return Ok(None);
+    };
+    #[cfg(feature = "otel")]
+    {
+        log::info!("Exporting OpenTelemetry pipeline traces to {}", config.endpoint);
+        Ok(Some(Telemetry::init(config)?))
+    }
+    #[cfg(not(feature = "otel"))]
+    {
+        log::warn!(
+            "otel is configured for {} but geyserbench was built without the `otel` feature",
+            config.endpoint
+        );
+        Ok(None)
+    }
+}
+
+#[cfg(not(feature = "otel"))]
+pub struct Telemetry;
+
+#[cfg(not(feature = "otel"))]
+impl Telemetry {
+    pub fn shutdown(self) {}
+}
+
+#[cfg(not(feature = "otel"))]
+pub struct PipelineTrace<'a>(std::marker::PhantomData<&'a str>);
+
+#[cfg(not(feature = "otel"))]
+impl<'a> PipelineTrace<'a> {
+    #[inline]
+    pub fn start(_endpoint: &'a str) -> Self {
+        Self(std::marker::PhantomData)
+    }
+
+    #[inline]
+    pub fn filtered(&mut self) {}
+
+    #[inline]
+    pub fn locked(&mut self) {}
+
+    #[inline]
+    pub fn inserted(self, _signature: &str) {}
+}
@@ -0,0 +1,356 @@
+use std::{collections::HashMap, error::Error, sync::Arc, time::Duration};
+
+use futures_util::{sink::SinkExt, stream::StreamExt};
+use serde::{Deserialize, Serialize};
+use tokio::{sync::broadcast, task};
+use yellowstone_grpc_proto::{
+    geyser::{subscribe_update::UpdateOneof, SubscribeRequest, SubscribeRequestPing, SubscribeUpdate},
+    prelude::SubscribeRequestFilterTransactions,
+};
+
+use crate::{
+    config::{BenchmarkMode, Config, ConfigToml, Endpoint, OverflowPolicy},
+    providers::{self, connect_yellowstone, yellowstone::created_at_secs},
+    queue::BoundedQueue,
+    utils::{get_current_timestamp, percentile},
+};
+
+/// A server-bound endpoint below this share of the fastest endpoint's sustained rate is flagged
+const THROTTLE_RATE_SHARE: f64 = 0.9;
+/// A server-bound endpoint whose lag grows by more than this over the run is flagged
+const THROTTLE_LAG_GROWTH_MS: f64 = 1000.0;
+
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+pub struct ThroughputReport {
+    pub name: String,
+    pub messages: u64,
+    pub bytes: u64,
+    pub duration_secs: f64,
+    pub max_rate: f64,
+    pub sustained_rate: f64,
+    pub avg_queue_depth: f64,
+    pub max_queue_depth: usize,
+    pub queue_capacity: usize,
+    pub overflow: OverflowPolicy,
+    /// Messages discarded by a drop-oldest or drop-newest overflow policy
+    pub dropped: u64,
+    pub bottleneck: String,
+    /// Mean receive time minus the update's `created_at`, over messages carrying one
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub avg_lag_ms: Option<f64>,
+    /// Mean lag in the last quarter of the run minus the first quarter; growth means the stream
+    /// is falling behind the chain
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub lag_growth_ms: Option<f64>,
+    /// Why the stream ended before the run did, e.g. a `RESOURCE_EXHAUSTED` status
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub stream_error: Option<String>,
+    /// Why the provider looks like it is throttling this client; None when it does not
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub throttled: Option<String>,
+}
+
+#[derive(Default)]
+struct ThroughputStats {
+    messages: u64,
+    bytes: u64,
+    per_second: HashMap<u64, u64>,
+    queue_depth_sum: u64,
+    max_queue_depth: usize,
+    /// Sum and count of `created_at` lags per second of the run
+    lag_per_second: HashMap<u64, (f64, u64)>,
+    stream_error: Option<tonic::Status>,
+}
+
+pub async fn run(
+    config: &ConfigToml,
+    shutdown_tx: &broadcast::Sender<()>,
+) -> Result<Vec<ThroughputReport>, Box<dyn Error + Send + Sync>> {
+    let mut handles = Vec::new();
+
+    for endpoint in config.endpoint.clone() {
+        // Already reported by the runner's mode check
+        if !providers::supports_mode(&endpoint.kind, BenchmarkMode::Throughput) {
+            continue;
+        }
+        let shared_config = config.config.clone();
+        let shutdown_rx = shutdown_tx.subscribe();
+        handles.push(task::spawn(run_endpoint(endpoint, shared_config, shutdown_rx)));
+    }
+
+    let mut reports = Vec::new();
+    for handle in handles {
+        match handle.await {
+            Ok(Ok(report)) => reports.push(report),
+            Ok(Err(e)) => log::error!("Provider error: {:?}", e),
+            Err(e) => log::error!("Task join error: {:?}", e),
+        }
+    }
+
+    flag_throttling(&mut reports);
+    print_throughput(&reports);
+    Ok(reports)
+}
+
+// AIDEV-NOTE: Reader and consumer are split by a bounded queue; a full queue means this client
+// can't keep up (client-bound), an empty queue at a plateaued rate means the server/network is.
+async fn run_endpoint(
+    endpoint: Endpoint,
+    config: Config,
+    mut shutdown_rx: broadcast::Receiver<()>,
+) -> Result<ThroughputReport, Box<dyn Error + Send + Sync>> {
+    let queue_capacity = config.throughput.queue_capacity.max(1);
+    let overflow = config.throughput.overflow;
+
+    log::info!("[{}] Connecting to endpoint: {}", endpoint.name, endpoint.display_url());
+    let mut client = connect_yellowstone(&endpoint).await?;
+    log::info!("[{}] Connected successfully", endpoint.name);
+
+    let (mut subscribe_tx, subscribe_rx) = futures::channel::mpsc::unbounded();
+    let mut stream = client.subscribe(subscribe_rx).await?.into_inner();
+    let commitment: yellowstone_grpc_proto::geyser::CommitmentLevel = config.commitment.into();
+
+    let mut transactions = HashMap::new();
+    transactions.insert("all".to_string(), SubscribeRequestFilterTransactions::default());
+    subscribe_tx
+        .send(SubscribeRequest {
+            transactions,
+            commitment: Some(commitment as i32),
+            ..Default::default()
+        })
+        .await?;
+
+    let queue = Arc::new(BoundedQueue::<SubscribeUpdate>::new(queue_capacity, overflow));
+    let consumer_queue = queue.clone();
+    let consumer = task::spawn(async move {
+        let mut processed = 0u64;
+        while let Some(update) = consumer_queue.pop().await {
+            if let Some(UpdateOneof::Transaction(tx_msg)) = update.update_oneof {
+                if let Some(message) = tx_msg
+                    .transaction
+                    .and_then(|tx| tx.transaction)
+                    .and_then(|tx| tx.message)
+                {
+                    // Same per-message work as the latency providers' account matching
+                    let _accounts: Vec<String> = message
+                        .account_keys
+                        .iter()
+                        .map(|key| bs58::encode(key).into_string())
+                        .collect();
+                }
+            }
+            processed += 1;
+        }
+        processed
+    });
+
+    let mut stats = ThroughputStats::default();
+    let start = get_current_timestamp();
+    let deadline = tokio::time::sleep(Duration::from_secs(config.throughput.duration_secs));
+    tokio::pin!(deadline);
+
+    loop {
+        tokio::select! {
+            _ = shutdown_rx.recv() => {
+                log::info!("[{}] Received stop signal...", endpoint.name);
+                break;
+            }
+            _ = &mut deadline => {
+                log::info!("[{}] Throughput run finished", endpoint.name);
+                break;
+            }
+            message = stream.next() => {
+                match message {
+                    Some(Ok(msg)) => {
+                        if let Some(UpdateOneof::Ping(_)) = msg.update_oneof {
+                            subscribe_tx
+                                .send(SubscribeRequest {
+                                    ping: Some(SubscribeRequestPing { id: 1 }),
+                                    ..Default::default()
+                                })
+                                .await?;
+                            continue;
+                        }
+
+                        let now = get_current_timestamp();
+                        let second = (now - start) as u64;
+                        *stats.per_second.entry(second).or_insert(0) += 1;
+                        if let Some(created_at) = created_at_secs(&msg) {
+                            let lag = stats.lag_per_second.entry(second).or_default();
+                            lag.0 += (now - created_at) * 1000.0;
+                            lag.1 += 1;
+                        }
+                        stats.messages += 1;
+                        stats.bytes += prost::Message::encoded_len(&msg) as u64;
+
+                        let depth = queue.len();
+                        stats.queue_depth_sum += depth as u64;
+                        stats.max_queue_depth = stats.max_queue_depth.max(depth);
+
+                        queue.push(msg).await;
+                    }
+                    Some(Err(e)) => {
+                        log::error!("[{}] Error receiving message: {:?}", endpoint.name, e);
+                        stats.stream_error = Some(e);
+                        break;
+                    }
+                    None => {
+                        log::info!("[{}] Stream closed", endpoint.name);
+                        break;
+                    }
+                }
+            }
+        }
+    }
+
+    let duration_secs = get_current_timestamp() - start;
+    queue.close();
+    let processed = consumer.await.unwrap_or_default();
+    let dropped = queue.dropped();
+    log::info!(
+        "[{}] Processed {} of {} messages ({} dropped)",
+        endpoint.name,
+        processed,
+        stats.messages,
+        dropped
+    );
+
+    Ok(build_report(&endpoint.name, stats, duration_secs, queue_capacity, overflow, dropped))
+}
+
+fn build_report(
+    name: &str,
+    stats: ThroughputStats,
+    duration_secs: f64,
+    queue_capacity: usize,
+    overflow: OverflowPolicy,
+    dropped: u64,
+) -> ThroughputReport {
+    // The first and last seconds are partial and would drag the rates down
+    let last_second = duration_secs as u64;
+    let mut rates: Vec<f64> = stats
+        .per_second
+        .iter()
+        .filter(|(second, _)| **second > 0 && **second < last_second)
+        .map(|(_, count)| *count as f64)
+        .collect();
+    rates.sort_by(|a, b| a.partial_cmp(b).unwrap());
+
+    let avg_queue_depth = if stats.messages > 0 {
+        stats.queue_depth_sum as f64 / stats.messages as f64
+    } else {
+        0.0
+    };
+    // Any drop means the consumer fell a whole queue behind
+    let bottleneck = if dropped > 0 || avg_queue_depth >= queue_capacity as f64 * 0.5 {
+        "client"
+    } else {
+        "server"
+    };
+
+    let lag_messages: u64 = stats.lag_per_second.values().map(|(_, count)| count).sum();
+    let avg_lag_ms = (lag_messages > 0)
+        .then(|| stats.lag_per_second.values().map(|(sum, _)| sum).sum::<f64>() / lag_messages as f64);
+    let quarter = (duration_secs / 4.0) as u64;
+    let mean_lag = |range: std::ops::Range<u64>| {
+        let (sum, count) = range
+            .filter_map(|second| stats.lag_per_second.get(&second))
+            .fold((0.0, 0u64), |(sum, count), (second_sum, second_count)| (sum + second_sum, count + second_count));
+        (count > 0).then(|| sum / count as f64)
+    };
+    let lag_growth_ms = match (mean_lag(0..quarter), mean_lag(last_second.saturating_sub(quarter)..last_second + 1)) {
+        (Some(first), Some(last)) if quarter > 0 => Some(last - first),
+        _ => None,
+    };
+
+    ThroughputReport {
+        name: name.to_string(),
+        messages: stats.messages,
+        bytes: stats.bytes,
+        duration_secs,
+        max_rate: rates.last().copied().unwrap_or(0.0),
+        sustained_rate: percentile(&rates, 0.5),
+        avg_queue_depth,
+        max_queue_depth: stats.max_queue_depth,
+        queue_capacity,
+        overflow,
+        dropped,
+        bottleneck: bottleneck.to_string(),
+        avg_lag_ms,
+        lag_growth_ms,
+        stream_error: stats.stream_error.map(|status| format!("{:?}: {}", status.code(), status.message())),
+        throttled: None,
+    }
+}
+
+// AIDEV-NOTE: Every endpoint streams the same firehose, so a server-bound endpoint well below the
+// fastest one's rate is holding messages back or dropping them, and one whose lag keeps growing is
+// buffering. Client-bound endpoints are never flagged: their shortfall is geyserbench's own.
+fn flag_throttling(reports: &mut [ThroughputReport]) {
+    let best_rate = reports.iter().map(|report| report.sustained_rate).fold(0.0, f64::max);
+    let endpoints = reports.len();
+    for report in reports.iter_mut() {
+        let exhausted = report.stream_error.as_deref().is_some_and(|error| error.starts_with("ResourceExhausted"));
+        report.throttled = if exhausted {
+            Some("stream ended with RESOURCE_EXHAUSTED".to_string())
+        } else if report.bottleneck != "server" {
+            None
+        } else if endpoints > 1 && best_rate > 0.0 && report.sustained_rate < best_rate * THROTTLE_RATE_SHARE {
+            Some(format!(
+                "sustained rate is {:.0}% of the fastest endpoint's",
+                report.sustained_rate / best_rate * 100.0
+            ))
+        } else {
+            report
+                .lag_growth_ms
+                .filter(|growth| *growth > THROTTLE_LAG_GROWTH_MS)
+                .map(|growth| format!("lag grew by {:.0} ms over the run", growth))
+        };
+    }
+}
+
+fn print_throughput(reports: &[ThroughputReport]) {
+    println!("\nThroughput test results");
+    println!("--------------------------------------------");
+    if reports.is_empty() {
+        println!("Not enough data");
+        return;
+    }
+
+    for report in reports {
+        println!("\n{}:", report.name);
+        println!(
+            "  Messages: {} ({:.2} MB) in {:.1}s",
+            report.messages,
+            report.bytes as f64 / 1_000_000.0,
+            report.duration_secs
+        );
+        println!("  Max rate: {:.0} msg/s", report.max_rate);
+        println!("  Sustained (median) rate: {:.0} msg/s", report.sustained_rate);
+        println!(
+            "  Queue depth avg/max: {:.1}/{} of {}",
+            report.avg_queue_depth, report.max_queue_depth, report.queue_capacity
+        );
+        if report.dropped > 0 {
+            println!(
+                "  Dropped: {} ({:.2}%, {:?})",
+                report.dropped,
+                report.dropped as f64 / report.messages.max(1) as f64 * 100.0,
+                report.overflow
+            );
+        }
+        println!("  Bottleneck: {}", report.bottleneck);
+        if let Some(avg_lag_ms) = report.avg_lag_ms {
+            match report.lag_growth_ms {
+                Some(growth) => println!("  Lag behind created_at: {:.1} ms avg, {:+.1} ms over the run", avg_lag_ms, growth),
+                None => println!("  Lag behind created_at: {:.1} ms avg", avg_lag_ms),
+            }
+        }
+        if let Some(error) = &report.stream_error {
+            println!("  Stream ended early: {}", error);
+        }
+        if let Some(reason) = &report.throttled {
+            println!("  Possibly throttled: {}", reason);
+        }
+    }
+}
@@ -0,0 +1,62 @@
+//! Formatting of the Unix timestamps (f64 seconds) used throughout geyserbench.
+
+const SECONDS_PER_DAY: i64 = 86_400;
+
+/// Year, month and day of a count of days since 1970-01-01 (proleptic Gregorian calendar).
+fn civil_from_days(days: i64) -> (i64, u32, u32) {
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let day_of_era = z.rem_euclid(146_097);
+    let year_of_era = (day_of_era - day_of_era / 1460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let shifted_month = (5 * day_of_year + 2) / 153;
+    let day = (day_of_year - (153 * shifted_month + 2) / 5 + 1) as u32;
+    let month = (if shifted_month < 10 { shifted_month + 3 } else { shifted_month - 9 }) as u32;
+    let year = year_of_era + era * 400 + i64::from(month <= 2);
+    (year, month, day)
+}
+
+/// `HH:MM:SS` UTC of a Unix timestamp.
+pub fn utc_time(timestamp: f64) -> String {
+    let secs = timestamp.max(0.0) as u64 % 86_400;
+    format!("{:02}:{:02}:{:02}", secs / 3600, secs / 60 % 60, secs % 60)
+}
+
+/// RFC 3339 UTC with milliseconds, e.g. `2026-10-16T09:30:00.125Z`.
+pub fn utc_datetime(timestamp: f64) -> String {
+    let millis = (timestamp * 1000.0).round() as i64;
+    let secs = millis.div_euclid(1000);
+    let (year, month, day) = civil_from_days(secs.div_euclid(SECONDS_PER_DAY));
+    let time_of_day = secs.rem_euclid(SECONDS_PER_DAY);
+    format!(
+        "{:04}-{:02}-{:02}T{:02}:{:02}:{:02}.{:03}Z",
+        year,
+        month,
+        day,
+        time_of_day / 3600,
+        time_of_day / 60 % 60,
+        time_of_day % 60,
+        millis.rem_euclid(1000)
+    )
+}
+
+/// Compact UTC time for file and directory names, e.g. `20261016T093000Z`.
+pub fn utc_compact(timestamp: f64) -> String {
+    let secs = timestamp.max(0.0) as i64;
+    let (year, month, day) = civil_from_days(secs.div_euclid(SECONDS_PER_DAY));
+    let time_of_day = secs.rem_euclid(SECONDS_PER_DAY);
+    format!(
+        "{:04}{:02}{:02}T{:02}{:02}{:02}Z",
+        year,
+        month,
+        day,
+        time_of_day / 3600,
+        time_of_day / 60 % 60,
+        time_of_day % 60
+    )
+}
+
+/// Signed offset from the run start, e.g. `+1.234s`.
+pub fn offset(secs: f64) -> String {
+    format!("{:+.3}s", secs)
+}
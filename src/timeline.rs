@@ -0,0 +1,210 @@
+use std::{error::Error, fs};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    timefmt,
+    utils::{Comparator, StreamEventKind},
+};
+
+const GANTT_WIDTH: usize = 60;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TimelineEventKind {
+    /// Started opening the channel
+    Connect,
+    /// Started opening the channel again after an earlier connection ended
+    Reconnect,
+    Connected,
+    Subscribed,
+    FirstMatch,
+    Stall,
+    Disconnect,
+    Downtime,
+    /// The connection's stream ended, on the stop signal or otherwise
+    Closed,
+}
+
+/// One point in an endpoint's life, as recorded by its provider.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TimelineEvent {
+    pub kind: TimelineEventKind,
+    pub at: f64,
+}
+
+/// One timeline entry as exported, with the run-relative and wall-clock time spelled out.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TimelineEntry {
+    pub kind: TimelineEventKind,
+    pub at: f64,
+    /// Seconds since the run started
+    pub offset_secs: f64,
+    pub utc: String,
+    /// Length of stalls, disconnects and downtime
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub duration_secs: Option<f64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub detail: Option<String>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct EndpointTimeline {
+    pub name: String,
+    pub entries: Vec<TimelineEntry>,
+}
+
+impl EndpointTimeline {
+    fn first(&self, kind: TimelineEventKind) -> Option<&TimelineEntry> {
+        self.entries.iter().find(|entry| entry.kind == kind)
+    }
+
+    fn count(&self, kind: TimelineEventKind) -> usize {
+        self.entries.iter().filter(|entry| entry.kind == kind).count()
+    }
+}
+
+fn entry(kind: TimelineEventKind, at: f64, start_time: f64) -> TimelineEntry {
+    TimelineEntry {
+        kind,
+        at,
+        offset_secs: at - start_time,
+        utc: timefmt::utc_datetime(at),
+        duration_secs: None,
+        detail: None,
+    }
+}
+
+// AIDEV-NOTE: Connection milestones come from each provider's StartupTimer (the primary shard or
+// connection only), stalls and disconnects from its StreamMonitor; both are merged here by time.
+/// Every endpoint's connection milestones and stream events, in time order.
+pub fn summarize_timeline(comparator: &Comparator, endpoint_names: &[String], start_time: f64) -> Vec<EndpointTimeline> {
+    endpoint_names
+        .iter()
+        .map(|name| {
+            let mut entries = Vec::new();
+            if let Some(metrics) = comparator.endpoint_metrics.get(name) {
+                entries.extend(metrics.timeline.iter().map(|event| entry(event.kind, event.at, start_time)));
+                entries.extend(metrics.events.iter().map(|event| {
+                    let kind = match event.kind {
+                        StreamEventKind::Stall => TimelineEventKind::Stall,
+                        StreamEventKind::Disconnect => TimelineEventKind::Disconnect,
+                        StreamEventKind::Downtime => TimelineEventKind::Downtime,
+                    };
+                    TimelineEntry {
+                        duration_secs: Some(event.duration_secs),
+                        detail: event.detail.clone(),
+                        ..entry(kind, event.at, start_time)
+                    }
+                }));
+            }
+            entries.sort_by(|a, b| a.at.total_cmp(&b.at));
+            EndpointTimeline {
+                name: name.clone(),
+                entries,
+            }
+        })
+        .collect()
+}
+
+pub fn write_timeline(path: &str, timelines: &[EndpointTimeline]) -> Result<(), Box<dyn Error>> {
+    fs::write(path, serde_json::to_vec_pretty(timelines)?)?;
+    Ok(())
+}
+
+/// What an endpoint was doing at each point of the run, as one character per Gantt column.
+fn gantt_row(timeline: &EndpointTimeline, start_time: f64, end_time: f64) -> String {
+    let span = (end_time - start_time).max(f64::EPSILON);
+    let column = |at: f64| (((at - start_time) / span * GANTT_WIDTH as f64) as usize).min(GANTT_WIDTH - 1);
+    let mut row = vec![' '; GANTT_WIDTH];
+    let fill = |from: f64, to: f64, symbol: char, row: &mut [char]| {
+        for cell in &mut row[column(from)..=column(to.max(from))] {
+            *cell = symbol;
+        }
+    };
+
+    let mut connecting_since = None;
+    let mut streaming_since = None;
+    for entry in &timeline.entries {
+        match entry.kind {
+            TimelineEventKind::Connect | TimelineEventKind::Reconnect => connecting_since = Some(entry.at),
+            TimelineEventKind::Subscribed => {
+                if let Some(since) = connecting_since.take() {
+                    fill(since, entry.at, '.', &mut row);
+                }
+                streaming_since = Some(entry.at);
+            }
+            TimelineEventKind::Closed => {
+                if let Some(since) = streaming_since.take() {
+                    fill(since, entry.at, '=', &mut row);
+                } else if let Some(since) = connecting_since.take() {
+                    fill(since, entry.at, '.', &mut row);
+                }
+            }
+            _ => {}
+        }
+    }
+    if let Some(since) = streaming_since {
+        fill(since, end_time, '=', &mut row);
+    }
+    // Problems and the first match are drawn over the stream so they stay visible
+    for entry in &timeline.entries {
+        let symbol = match entry.kind {
+            TimelineEventKind::Stall => '~',
+            TimelineEventKind::Disconnect | TimelineEventKind::Downtime => 'x',
+            _ => continue,
+        };
+        fill(entry.at, entry.at + entry.duration_secs.unwrap_or(0.0), symbol, &mut row);
+    }
+    if let Some(first_match) = timeline.first(TimelineEventKind::FirstMatch) {
+        row[column(first_match.at)] = '*';
+    }
+    row.into_iter().collect()
+}
+
+pub fn print_timeline(timelines: &[EndpointTimeline], start_time: f64, end_time: f64) {
+    if timelines.iter().all(|timeline| timeline.entries.is_empty()) {
+        return;
+    }
+
+    println!("\nRun timeline");
+    println!("--------------------------------------------");
+    let name_width = timelines.iter().map(|timeline| timeline.name.len()).max().unwrap_or(0);
+    let start = timefmt::utc_time(start_time);
+    println!(
+        "{:width$}  {}{:>rest$} ({:.1}s)",
+        "",
+        start,
+        timefmt::utc_time(end_time),
+        end_time - start_time,
+        width = name_width,
+        rest = GANTT_WIDTH - start.len()
+    );
+    for timeline in timelines {
+        println!(
+            "{:width$} |{}|",
+            timeline.name,
+            gantt_row(timeline, start_time, end_time),
+            width = name_width
+        );
+    }
+    println!(". connecting  = streaming  * first match  ~ stall  x disconnected");
+
+    for timeline in timelines {
+        let at = |kind| {
+            timeline
+                .first(kind)
+                .map_or("-".to_string(), |entry| timefmt::offset(entry.offset_secs))
+        };
+        println!(
+            "{}: connected {}, subscribed {}, first match {}, {} reconnects, {} disconnects, {} stalls",
+            timeline.name,
+            at(TimelineEventKind::Connected),
+            at(TimelineEventKind::Subscribed),
+            at(TimelineEventKind::FirstMatch),
+            timeline.count(TimelineEventKind::Reconnect),
+            timeline.count(TimelineEventKind::Disconnect),
+            timeline.count(TimelineEventKind::Stall)
+        );
+    }
+}
@@ -0,0 +1,126 @@
+use std::{collections::HashSet, error::Error, time::Duration};
+
+use serde_json::{json, Value};
+
+use crate::config::{ConfigToml, TokenAccountsConfig};
+
+pub const TOKEN_PROGRAM_ID: &str = "TokenkegQfeZyiNwAJbNbGKPFXCWuBvf9Ss623VQ5DA";
+pub const TOKEN_2022_PROGRAM_ID: &str = "TokenzQdBNbLqP5VEhdkAS6EPFLC1PE1DmHqYW7yKc5K";
+
+const REQUEST_TIMEOUT_SECS: u64 = 10;
+
+/// Token accounts found for one wallet.
+#[derive(Debug, Clone, Default)]
+pub struct WalletTokenAccounts {
+    pub wallet: String,
+    pub token: Vec<String>,
+    pub token_2022: Vec<String>,
+}
+
+// AIDEV-NOTE: `getTokenAccountsByOwner` with a zero-length data slice returns just the keys, so
+// wallets holding many tokens stay cheap to look up. Any token account the wallet owns counts,
+// the associated one and any other; accounts opened after startup are not picked up.
+async fn token_accounts_by_owner(
+    client: &reqwest::Client,
+    rpc_url: &str,
+    owner: &str,
+    program_id: &str,
+) -> Result<Vec<String>, Box<dyn Error + Send + Sync>> {
+    let request = json!({
+        "jsonrpc": "2.0",
+        "id": 1,
+        "method": "getTokenAccountsByOwner",
+        "params": [
+            owner,
+            { "programId": program_id },
+            { "encoding": "base64", "dataSlice": { "offset": 0, "length": 0 } }
+        ]
+    });
+    let response: Value = client
+        .post(rpc_url)
+        .timeout(Duration::from_secs(REQUEST_TIMEOUT_SECS))
+        .json(&request)
+        .send()
+        .await?
+        .error_for_status()?
+        .json()
+        .await?;
+    if let Some(error) = response.get("error") {
+        return Err(format!("getTokenAccountsByOwner for {} failed: {}", owner, error).into());
+    }
+    let accounts = response["result"]["value"]
+        .as_array()
+        .ok_or_else(|| format!("getTokenAccountsByOwner for {} returned no account list", owner))?;
+    Ok(accounts
+        .iter()
+        .filter_map(|account| account["pubkey"].as_str().map(str::to_string))
+        .collect())
+}
+
+/// Looks up the token accounts of every wallet in `token_accounts.wallets` (by default `account`
+/// and `accounts`).
+pub async fn lookup(
+    config: &ConfigToml,
+    token_accounts: &TokenAccountsConfig,
+) -> Result<Vec<WalletTokenAccounts>, Box<dyn Error + Send + Sync>> {
+    let wallets = if token_accounts.wallets.is_empty() {
+        let mut wallets = vec![config.config.account.clone()];
+        wallets.extend(config.config.accounts.iter().cloned());
+        wallets
+    } else {
+        token_accounts.wallets.clone()
+    };
+
+    let client = reqwest::Client::new();
+    let mut found = Vec::new();
+    for wallet in wallets {
+        let token = token_accounts_by_owner(&client, &token_accounts.rpc_url, &wallet, TOKEN_PROGRAM_ID).await?;
+        let token_2022 = if token_accounts.token_2022 {
+            token_accounts_by_owner(&client, &token_accounts.rpc_url, &wallet, TOKEN_2022_PROGRAM_ID).await?
+        } else {
+            Vec::new()
+        };
+        found.push(WalletTokenAccounts {
+            wallet,
+            token,
+            token_2022,
+        });
+    }
+    Ok(found)
+}
+
+/// Copy of `config` whose `accounts` also list the wallets' token accounts, up to `max_accounts`.
+pub async fn resolve(config: &ConfigToml) -> Result<ConfigToml, Box<dyn Error + Send + Sync>> {
+    let mut resolved = config.clone();
+    let Some(token_accounts) = &config.config.token_accounts else {
+        return Ok(resolved);
+    };
+
+    let mut watched: HashSet<String> = resolved.config.watched_accounts().into_iter().collect();
+    let mut added = 0;
+    for wallet in lookup(config, token_accounts).await? {
+        log::info!(
+            "Wallet {} owns {} token and {} Token-2022 accounts",
+            wallet.wallet,
+            wallet.token.len(),
+            wallet.token_2022.len()
+        );
+        for account in wallet.token.into_iter().chain(wallet.token_2022) {
+            if watched.contains(&account) {
+                continue;
+            }
+            if added == token_accounts.max_accounts {
+                log::warn!(
+                    "Watching only the first {} token accounts (token_accounts.max_accounts)",
+                    token_accounts.max_accounts
+                );
+                return Ok(resolved);
+            }
+            watched.insert(account.clone());
+            resolved.config.accounts.push(account);
+            added += 1;
+        }
+    }
+    log::info!("Added {} token accounts to the watched accounts", added);
+    Ok(resolved)
+}
@@ -0,0 +1,107 @@
+use std::{error::Error, time::Duration};
+
+use serde_json::Value;
+use yellowstone_grpc_proto::tonic::{Code, Status};
+
+use crate::{
+    config::{Endpoint, TokenRefresh},
+    providers::ProviderError,
+};
+
+const REQUEST_TIMEOUT_SECS: u64 = 10;
+// Fields read from a JSON token response, first match wins
+const TOKEN_FIELDS: [&str; 3] = ["token", "access_token", "id_token"];
+
+/// Whether a connect or subscribe error is the server rejecting the credentials.
+pub fn is_unauthenticated(error: &(dyn Error + Send + Sync + 'static)) -> bool {
+    let status = match error.downcast_ref::<ProviderError>() {
+        Some(ProviderError::Auth(status)) => Some(status),
+        Some(_) => None,
+        None => error.downcast_ref::<Status>(),
+    };
+    status.is_some_and(|status| status.code() == Code::Unauthenticated)
+}
+
+/// The platform shell running `command`; killed if the command outlives its timeout.
+fn shell(command: &str) -> tokio::process::Command {
+    let mut shell = if cfg!(windows) {
+        let mut shell = tokio::process::Command::new("cmd");
+        shell.arg("/C");
+        shell
+    } else {
+        let mut shell = tokio::process::Command::new("sh");
+        shell.arg("-c");
+        shell
+    };
+    shell.arg(command).kill_on_drop(true);
+    shell
+}
+
+async fn run_command(command: &str) -> Result<String, Box<dyn Error + Send + Sync>> {
+    let output = tokio::time::timeout(Duration::from_secs(REQUEST_TIMEOUT_SECS), shell(command).output())
+    .await
+    .map_err(|_| format!("token command did not finish within {}s", REQUEST_TIMEOUT_SECS))??;
+    if !output.status.success() {
+        return Err(format!(
+            "token command exited with {}: {}",
+            output.status,
+            String::from_utf8_lossy(&output.stderr).trim()
+        )
+        .into());
+    }
+    Ok(String::from_utf8(output.stdout)?)
+}
+
+async fn fetch_url(url: &str) -> Result<String, Box<dyn Error + Send + Sync>> {
+    let body = reqwest::Client::new()
+        .get(url)
+        .timeout(Duration::from_secs(REQUEST_TIMEOUT_SECS))
+        .send()
+        .await?
+        .error_for_status()?
+        .text()
+        .await?;
+    // JSON responses carry the token in a field; anything else is the token itself
+    match serde_json::from_str::<Value>(&body) {
+        Ok(Value::Object(fields)) => TOKEN_FIELDS
+            .iter()
+            .find_map(|field| fields.get(*field).and_then(Value::as_str))
+            .map(str::to_string)
+            .ok_or_else(|| format!("token response has none of the fields {}", TOKEN_FIELDS.join(", ")).into()),
+        _ => Ok(body),
+    }
+}
+
+/// Fresh token from the configured command or URL, trimmed.
+pub async fn fetch_token(refresh: &TokenRefresh) -> Result<String, Box<dyn Error + Send + Sync>> {
+    let raw = match (&refresh.command, &refresh.url) {
+        (Some(command), _) => run_command(command).await?,
+        (None, Some(url)) => fetch_url(url).await?,
+        (None, None) => return Err("token_refresh needs a command or a url".into()),
+    };
+    let token = raw.trim().to_string();
+    if token.is_empty() || token.chars().any(|c| c.is_whitespace() || c.is_control()) {
+        return Err("token refresh returned an empty token or one with whitespace".into());
+    }
+    Ok(token)
+}
+
+// AIDEV-NOTE: The token lands where the static credential would have been (`x_token`, or the
+// header named by `token_refresh.header`), so the next connection's MetadataInterceptor sends it.
+/// Replaces the endpoint's credential with a fresh token.
+pub async fn refresh(endpoint: &mut Endpoint) -> Result<(), Box<dyn Error + Send + Sync>> {
+    let Some(refresh) = endpoint.token_refresh.clone() else {
+        return Err(format!("[{}] no token_refresh configured", endpoint.name).into());
+    };
+    let token = fetch_token(&refresh)
+        .await
+        .map_err(|e| format!("[{}] token refresh failed: {}", endpoint.name, e))?;
+    match &refresh.header {
+        Some(header) => {
+            endpoint.headers.insert(header.clone(), format!("{}{}", refresh.prefix, token));
+        }
+        None => endpoint.x_token = format!("{}{}", refresh.prefix, token),
+    }
+    log::info!("[{}] Refreshed the access token", endpoint.name);
+    Ok(())
+}
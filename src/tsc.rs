@@ -0,0 +1,84 @@
+use std::{
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        OnceLock,
+    },
+    thread,
+    time::Duration,
+};
+
+use crate::utils::get_current_timestamp;
+
+// AIDEV-NOTE: rdtsc is only comparable across cores with an invariant TSC (any modern x86_64).
+// Calibration maps ticks onto the wall clock once at startup; readings stay None elsewhere.
+const CALIBRATION_PERIOD: Duration = Duration::from_millis(200);
+
+static ENABLED: AtomicBool = AtomicBool::new(false);
+static CALIBRATION: OnceLock<TscCalibration> = OnceLock::new();
+
+#[derive(Debug, Clone, Copy)]
+pub struct TscCalibration {
+    pub ticks_per_second: f64,
+    pub base_ticks: u64,
+    pub base_timestamp: f64,
+}
+
+#[cfg(target_arch = "x86_64")]
+fn rdtsc() -> Option<u64> {
+    // SAFETY: rdtsc is available on every x86_64 CPU and has no side effects.
+    Some(unsafe { core::arch::x86_64::_rdtsc() })
+}
+
+#[cfg(not(target_arch = "x86_64"))]
+fn rdtsc() -> Option<u64> {
+    None
+}
+
+pub fn init() {
+    let Some(start_ticks) = rdtsc() else {
+        log::warn!("TSC timestamps requested but not supported on this architecture");
+        return;
+    };
+    let start_timestamp = get_current_timestamp();
+    thread::sleep(CALIBRATION_PERIOD);
+    let end_ticks = rdtsc().unwrap_or(start_ticks);
+    let end_timestamp = get_current_timestamp();
+
+    let elapsed = end_timestamp - start_timestamp;
+    if end_ticks <= start_ticks || elapsed <= 0.0 {
+        log::warn!("TSC calibration failed, falling back to wall-clock timestamps only");
+        return;
+    }
+
+    let calibration = TscCalibration {
+        ticks_per_second: (end_ticks - start_ticks) as f64 / elapsed,
+        base_ticks: end_ticks,
+        base_timestamp: end_timestamp,
+    };
+    log::info!(
+        "TSC calibrated at {:.3} GHz",
+        calibration.ticks_per_second / 1_000_000_000.0
+    );
+
+    let _ = CALIBRATION.set(calibration);
+    ENABLED.store(true, Ordering::Relaxed);
+}
+
+pub fn read() -> Option<u64> {
+    if ENABLED.load(Ordering::Relaxed) {
+        rdtsc()
+    } else {
+        None
+    }
+}
+
+pub fn calibration() -> Option<TscCalibration> {
+    CALIBRATION.get().copied()
+}
+
+/// Converts a raw tick count into a wall-clock aligned timestamp in seconds.
+pub fn to_timestamp(ticks: u64) -> Option<f64> {
+    let calibration = calibration()?;
+    let delta = ticks as f64 - calibration.base_ticks as f64;
+    Some(calibration.base_timestamp + delta / calibration.ticks_per_second)
+}
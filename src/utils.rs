@@ -1,24 +1,329 @@
 use std::{
-    collections::HashMap,
-    fs::OpenOptions,
+    collections::{BTreeMap, HashMap, HashSet, hash_map::DefaultHasher},
+    hash::{Hash, Hasher},
     io::Write,
-    sync::{Arc, Mutex},
+    sync::Arc,
     time::{SystemTime, UNIX_EPOCH},
 };
 
-use crate::config::ConfigToml;
+use serde::{Deserialize, Serialize};
 
-#[derive(Debug, Clone)]
+use crate::{
+    alerts::AlertMonitor, clock::{self, Clock}, connection::ConnectionInfo, coordinator::AgentForwarder,
+    config::{AccountsStrategy, BenchmarkMode, TiePolicy}, events::EventLog, frame_timing::FrameTimingSummary, history::HistoryStore, influx::InfluxSink, log_output::LogOutput, lookup_tables::LookupTables, parquet_sink::ParquetSink,
+    markets::MarketTag, profile::TaskProfile, reload::MembershipChange, providers::shredstream_proxy::{EntryDecodeStats, EntryPosition}, sampler::Sampler, timeline::TimelineEvent,
+};
+
+#[derive(Debug, Clone, Default)]
 pub struct TransactionData {
     pub timestamp: f64,
     pub signature: String,
     pub start_time: f64,
+    pub tsc: Option<u64>,
+    pub status: Option<TransactionStatus>,
+    pub slot: Option<u64>,
+    /// Where the transaction sat in its slot, for endpoints streaming raw entries
+    pub entry: Option<EntryPosition>,
+    /// `filter_groups` whose accounts the transaction touched; empty without groups
+    pub groups: Vec<String>,
 }
 
-#[derive(Debug, Clone, Default)]
+/// Execution result as served by an endpoint whose stream carries transaction meta.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TransactionStatus {
+    pub failed: bool,
+    pub fee: Option<u64>,
+    pub compute_units: Option<u64>,
+    /// Provider specific rendering, kept for context but not compared
+    pub error: Option<String>,
+}
+
+impl TransactionStatus {
+    pub fn agrees_with(&self, other: &TransactionStatus) -> bool {
+        fn same(a: Option<u64>, b: Option<u64>) -> bool {
+            a.is_none() || b.is_none() || a == b
+        }
+        self.failed == other.failed
+            && same(self.fee, other.fee)
+            && same(self.compute_units, other.compute_units)
+    }
+
+    /// Whether this came with execution meta (fee etc.) rather than a bare status.
+    pub fn has_meta(&self) -> bool {
+        self.fee.is_some()
+    }
+}
+
+impl TransactionData {
+    /// Receive time in seconds, preferring the calibrated TSC reading when one was captured.
+    pub fn precise_timestamp(&self) -> f64 {
+        self.tsc
+            .and_then(crate::tsc::to_timestamp)
+            .unwrap_or(self.timestamp)
+    }
+}
+
+#[derive(Debug, Clone)]
 pub struct Comparator {
     pub data: HashMap<String, HashMap<String, TransactionData>>,
     pub worker_count: usize,
+    pub influx: Option<Arc<InfluxSink>>,
+    pub history: Option<Arc<HistoryStore>>,
+    pub alerts: Option<Arc<AlertMonitor>>,
+    pub parquet: Option<Arc<ParquetSink>>,
+    pub endpoint_metrics: HashMap<String, EndpointMetrics>,
+    /// Signatures first seen before this time are left out of the statistics.
+    pub measure_from: f64,
+    /// Timestamp source shared with the providers feeding this comparator
+    pub clock: Arc<dyn Clock>,
+    /// Payload per endpoint for each (pubkey, write_version) seen on an account stream
+    pub account_writes: HashMap<(String, u64), HashMap<String, AccountWrite>>,
+    /// Updates and bytes delivered per endpoint and pubkey, for endpoints with `accounts_data_slice`
+    pub sliced_account_updates: HashMap<String, HashMap<String, (u64, u64)>>,
+    /// Whole data size of each account, as last delivered by an endpoint without data slices
+    pub account_full_sizes: HashMap<String, usize>,
+    /// Keeps a fraction of signatures when `sample_rate` is set
+    pub sampler: Option<Sampler>,
+    /// AMM market each decoded signature traded on, when `decode_markets` is set
+    pub markets: HashMap<String, MarketTag>,
+    /// Per-account targets from `account_targets`; empty stops at `worker_count` signatures instead
+    pub account_targets: BTreeMap<String, u64>,
+    /// Targeted accounts each live signature touched, and how many signatures each account has
+    pub signature_accounts: HashMap<String, Vec<String>>,
+    pub account_counts: HashMap<String, u64>,
+    /// Set once the run is stopping; only sightings of already known signatures are accepted
+    pub draining: bool,
+    /// Every connection's sighting per signature, for endpoints with `connections` > 1
+    pub pool_sightings: HashMap<String, HashMap<String, Vec<(usize, f64)>>>,
+    /// Repeats of an endpoint's signature later than this after its first sighting count as
+    /// redeliveries rather than duplicates; None counts every repeat as a duplicate
+    pub dedup_window_secs: Option<f64>,
+    /// Sends every accepted sighting on to a coordinator when running as an agent
+    pub forwarder: Option<Arc<AgentForwarder>>,
+    /// Every accepted delivery per signature in receive order, with `event_log`
+    pub events: Option<EventLog>,
+    /// Address lookup tables shared by the providers, with `lookup_tables`
+    pub lookup_tables: Option<Arc<LookupTables>>,
+    /// Directory and rotation of the providers' transaction logs
+    pub log_output: Arc<LogOutput>,
+    /// Endpoints stopped by a config reload and not started again; their sightings are refused
+    pub removed: HashSet<String>,
+    /// Endpoints joining and leaving the run through config reloads, in the order applied
+    pub membership: Vec<MembershipChange>,
+}
+
+// Unfiltered account streams would otherwise grow the integrity map without bound
+const MAX_TRACKED_ACCOUNT_WRITES: usize = 1_000_000;
+
+/// Size and content hash of one account write as delivered by an endpoint.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct AccountWrite {
+    pub len: usize,
+    pub hash: u64,
+    /// Hash of lamports, owner, executable and rent epoch
+    pub meta_hash: u64,
+}
+
+/// What an account update carried, borrowed from the stream's message.
+#[derive(Debug, Clone, Copy)]
+pub struct AccountState<'a> {
+    pub lamports: u64,
+    pub owner: &'a [u8],
+    pub executable: bool,
+    pub rent_epoch: u64,
+    pub data: &'a [u8],
+    /// `data` holds only the endpoint's `accounts_data_slice` ranges
+    pub sliced: bool,
+}
+
+/// Account update payload sizes seen by one endpoint.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct AccountSizeStats {
+    pub updates: u64,
+    pub total_bytes: u64,
+    pub min_bytes: usize,
+    pub max_bytes: usize,
+    /// Update counts keyed by power-of-two size bucket upper bound, in bytes
+    pub buckets: BTreeMap<usize, u64>,
+}
+
+impl AccountSizeStats {
+    pub fn record(&mut self, len: usize) {
+        self.min_bytes = if self.updates == 0 { len } else { self.min_bytes.min(len) };
+        self.max_bytes = self.max_bytes.max(len);
+        self.updates += 1;
+        self.total_bytes += len as u64;
+        *self.buckets.entry(len.next_power_of_two()).or_default() += 1;
+    }
+
+    pub fn avg_bytes(&self) -> f64 {
+        if self.updates == 0 {
+            return 0.0;
+        }
+        self.total_bytes as f64 / self.updates as f64
+    }
+}
+
+/// Messages held back by the simulated slow consumer (`consumer_delay_us`).
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ConsumerStats {
+    pub messages: u64,
+    /// Time spent sleeping instead of reading the stream
+    pub delayed_secs: f64,
+}
+
+/// Complete blocks seen by one endpoint in blocks mode.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct BlockSizeStats {
+    pub blocks: u64,
+    /// Encoded size of the `blocks` update, so it depends on `include_transactions`
+    pub total_bytes: u64,
+    pub min_bytes: usize,
+    pub max_bytes: usize,
+    /// Executed transactions per block, as reported by the block header
+    pub total_transactions: u64,
+    pub max_transactions: u64,
+}
+
+impl BlockSizeStats {
+    pub fn record(&mut self, len: usize, transactions: u64) {
+        self.min_bytes = if self.blocks == 0 { len } else { self.min_bytes.min(len) };
+        self.max_bytes = self.max_bytes.max(len);
+        self.max_transactions = self.max_transactions.max(transactions);
+        self.blocks += 1;
+        self.total_bytes += len as u64;
+        self.total_transactions += transactions;
+    }
+
+    pub fn avg_bytes(&self) -> f64 {
+        if self.blocks == 0 {
+            return 0.0;
+        }
+        self.total_bytes as f64 / self.blocks as f64
+    }
+
+    pub fn avg_transactions(&self) -> f64 {
+        if self.blocks == 0 {
+            return 0.0;
+        }
+        self.total_transactions as f64 / self.blocks as f64
+    }
+}
+
+impl Default for Comparator {
+    fn default() -> Self {
+        Self::new(0)
+    }
+}
+
+/// Per-endpoint measurements reported by providers alongside their observations.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct EndpointMetrics {
+    pub frame_timing: Option<FrameTimingSummary>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub shards: Vec<ShardMetrics>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub entry_decode: Option<EntryDecodeStats>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub startup: Option<StartupMetrics>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub account_sizes: Option<AccountSizeStats>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub block_sizes: Option<BlockSizeStats>,
+    /// Account filter in force when a `yellowstone_accounts` stream ended
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub accounts_strategy: Option<AccountsStrategy>,
+    /// Stalls and disconnects, correlated with latency spikes after the run
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub events: Vec<StreamEvent>,
+    /// Sightings accepted during the drain window after the stop signal
+    #[serde(default)]
+    pub late_arrivals: u64,
+    /// Repeat deliveries of a signature this endpoint had already sent, within `dedup_window_secs`
+    #[serde(default)]
+    pub duplicates: u64,
+    /// Repeats later than `dedup_window_secs` after the first sighting, e.g. replays after a reconnect
+    #[serde(default)]
+    pub redeliveries: u64,
+    /// Tokens fetched with `token_refresh` after the server rejected the previous one
+    #[serde(default)]
+    pub token_refreshes: u64,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub consumer: Option<ConsumerStats>,
+    /// Peer address and negotiated protocol, with `inspect_connections`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub connection: Option<ConnectionInfo>,
+    /// Poll time and update count of the endpoint's tasks, with `self_profile`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub profile: Option<TaskProfile>,
+    /// Replay range end and subscription time, with `from_slot`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub replay: Option<ReplayMetrics>,
+    /// Receive time minus the provider's `created_at` stamp, per update; summarized into `provider_timing`
+    #[serde(skip)]
+    pub provider_skews_ms: Vec<f64>,
+    /// Receive time minus the block's `block_time`, per block update
+    #[serde(skip)]
+    pub block_time_lags_ms: Vec<f64>,
+    /// Round trip of each answered client ping; summarized into `rtt`
+    #[serde(skip)]
+    pub ping_rtts_ms: Vec<f64>,
+    #[serde(skip)]
+    pub pings_unanswered: u64,
+    /// Connect, subscribe, first match and close of each connection; exported as the run timeline
+    #[serde(skip)]
+    pub timeline: Vec<TimelineEvent>,
+}
+
+/// Where an endpoint's `from_slot` replay catches up with the live tail.
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize)]
+pub struct ReplayMetrics {
+    /// The endpoint's current slot just before subscribing
+    pub tip_slot: u64,
+    pub subscribed_at: f64,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum StreamEventKind {
+    /// No update for at least `stall_threshold_secs`
+    Stall,
+    /// The stream failed or was closed by the server
+    Disconnect,
+    /// The watchdog found no message of any kind for `watchdog.timeout_secs`
+    Downtime,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StreamEvent {
+    pub kind: StreamEventKind,
+    /// Start of the stall, or when the stream went away
+    pub at: f64,
+    pub duration_secs: f64,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub detail: Option<String>,
+}
+
+/// How long an endpoint's stream took to spin up.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct StartupMetrics {
+    /// Channel establishment (TCP, TLS and HTTP/2 handshake)
+    pub connect_ms: f64,
+    /// Subscribe request to the first update of any kind
+    pub first_update_ms: Option<f64>,
+    /// Subscribe request to the first update matching the watched accounts
+    pub first_match_ms: Option<f64>,
+}
+
+/// Throughput of one subscription shard of a sharded endpoint.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ShardMetrics {
+    pub shard: usize,
+    pub accounts: usize,
+    pub messages: u64,
+    pub matched: u64,
+    pub elapsed_secs: f64,
+    pub messages_per_sec: f64,
 }
 
 impl Comparator {
@@ -26,14 +331,160 @@ impl Comparator {
         Self {
             data: HashMap::new(),
             worker_count,
+            influx: None,
+            history: None,
+            alerts: None,
+            parquet: None,
+            endpoint_metrics: HashMap::new(),
+            measure_from: 0.0,
+            clock: clock::system(),
+            account_writes: HashMap::new(),
+            sliced_account_updates: HashMap::new(),
+            account_full_sizes: HashMap::new(),
+            sampler: None,
+            markets: HashMap::new(),
+            account_targets: BTreeMap::new(),
+            signature_accounts: HashMap::new(),
+            account_counts: HashMap::new(),
+            draining: false,
+            pool_sightings: HashMap::new(),
+            dedup_window_secs: None,
+            forwarder: None,
+            events: None,
+            lookup_tables: None,
+            log_output: Arc::new(LogOutput::default()),
+            removed: HashSet::new(),
+            membership: Vec::new(),
         }
     }
 
+    pub fn with_clock(mut self, clock: Arc<dyn Clock>) -> Self {
+        self.clock = clock;
+        self
+    }
+
+    pub fn with_sampler(mut self, sampler: Sampler) -> Self {
+        self.sampler = Some(sampler);
+        self
+    }
+
+    pub fn with_influx(mut self, sink: Arc<InfluxSink>) -> Self {
+        self.influx = Some(sink);
+        self
+    }
+
+    pub fn with_history(mut self, store: Arc<HistoryStore>) -> Self {
+        self.history = Some(store);
+        self
+    }
+
+    pub fn with_parquet(mut self, sink: Arc<ParquetSink>) -> Self {
+        self.parquet = Some(sink);
+        self
+    }
+
+    pub fn with_alerts(mut self, monitor: Arc<AlertMonitor>) -> Self {
+        self.alerts = Some(monitor);
+        self
+    }
+
+    pub fn with_dedup_window(mut self, window_secs: Option<f64>) -> Self {
+        self.dedup_window_secs = window_secs;
+        self
+    }
+
+    pub fn with_account_targets(mut self, targets: BTreeMap<String, u64>) -> Self {
+        self.account_targets = targets;
+        self
+    }
+
+    pub fn with_event_log(mut self, mode: BenchmarkMode) -> Self {
+        self.events = Some(EventLog::new(mode));
+        self
+    }
+
+    pub fn with_log_output(mut self, log_output: LogOutput) -> Self {
+        self.log_output = Arc::new(log_output);
+        self
+    }
+
+    pub fn with_lookup_tables(mut self, lookup_tables: Arc<LookupTables>) -> Self {
+        self.lookup_tables = Some(lookup_tables);
+        self
+    }
+
+    pub fn with_forwarder(mut self, forwarder: Arc<AgentForwarder>) -> Self {
+        self.forwarder = Some(forwarder);
+        self
+    }
+
     pub fn add(&mut self, from: String, data: TransactionData) {
-        self.data
+        self.insert(from, data, true);
+    }
+
+    // AIDEV-NOTE: Repeats never change the result (the earliest sighting is kept either way); they
+    // are only counted, so endpoints redelivering after reconnects or on two streams show up.
+    fn insert(&mut self, from: String, data: TransactionData, count_repeats: bool) {
+        if self.removed.contains(&from) {
+            return;
+        }
+        if self.draining && !self.data.contains_key(&data.signature) {
+            return;
+        }
+        if let Some(sampler) = &mut self.sampler {
+            if !sampler.admit(&data.signature, self.data.contains_key(&data.signature)) {
+                return;
+            }
+        }
+        if let Some(events) = &mut self.events {
+            events.record(&data.signature, &from, data.precise_timestamp(), data.slot);
+        }
+        let previous = self
+            .data
+            .get(&data.signature)
+            .and_then(|observations| observations.get(&from))
+            .map(|existing| existing.precise_timestamp());
+        if let Some(previous) = previous.filter(|_| count_repeats) {
+            self.record_repeat(&from, (data.precise_timestamp() - previous).abs());
+        }
+        let observations = self.data
             .entry(data.signature.clone())
-            .or_insert_with(HashMap::new)
-            .insert(from.clone(), data.clone());
+            .or_default();
+        // AIDEV-NOTE: Shards of one endpoint can both see a transaction; keep that endpoint's earliest sighting
+        if observations
+            .get(&from)
+            .is_some_and(|existing| existing.precise_timestamp() <= data.precise_timestamp())
+        {
+            return;
+        }
+        let is_new = !observations.contains_key(&from);
+        observations.insert(from.clone(), data.clone());
+        if let Some(forwarder) = &self.forwarder {
+            forwarder.forward(&from, &data);
+        }
+        if self.draining && is_new {
+            self.endpoint_metrics.entry(from.clone()).or_default().late_arrivals += 1;
+        }
+
+        if self.influx.is_some() || self.history.is_some() || self.alerts.is_some() || self.parquet.is_some() {
+            let first_timestamp = observations
+                .values()
+                .map(|tx| tx.precise_timestamp())
+                .fold(f64::INFINITY, f64::min);
+            let delay_ms = (data.precise_timestamp() - first_timestamp) * 1000.0;
+            if let Some(sink) = &self.influx {
+                sink.record_observation(&from, "transaction", &data, delay_ms);
+            }
+            if let Some(store) = &self.history {
+                store.record_observation(&from, delay_ms);
+            }
+            if let Some(monitor) = &self.alerts {
+                monitor.record_observation(&from, data.timestamp, delay_ms);
+            }
+            if let Some(sink) = &self.parquet {
+                sink.record_observation(&from, &data, delay_ms);
+            }
+        }
 
         let valid_count = self.get_valid_count();
         log::info!(
@@ -43,9 +494,283 @@ impl Comparator {
         );
     }
 
+    pub fn begin_drain(&mut self) {
+        self.draining = true;
+    }
+
+    // AIDEV-NOTE: Comparisons need one endpoint set for every signature they cover. A stopped
+    // endpoint's sightings are dropped, so they no longer set first-detection times for the rest;
+    // a started one (or a restarted one, which is both) moves `measure_from` to now, the same way
+    // a start delay does, so signatures from before it joined are left out.
+    /// Applies the endpoint changes of config generation `generation` to the run.
+    pub fn apply_membership(&mut self, generation: u64, joined: &[String], left: &[String]) {
+        let at = self.clock.now();
+        for endpoint in left {
+            for observations in self.data.values_mut() {
+                observations.remove(endpoint);
+            }
+            self.removed.insert(endpoint.clone());
+            self.membership.push(MembershipChange { generation, endpoint: endpoint.clone(), joined: false, at });
+        }
+        self.data.retain(|_, observations| !observations.is_empty());
+        for endpoint in joined {
+            self.removed.remove(endpoint);
+            self.membership.push(MembershipChange { generation, endpoint: endpoint.clone(), joined: true, at });
+        }
+        if !joined.is_empty() {
+            self.measure_from = self.measure_from.max(at);
+        }
+    }
+
+    fn record_repeat(&mut self, from: &str, gap_secs: f64) {
+        let metrics = self.endpoint_metrics.entry(from.to_string()).or_default();
+        if self.dedup_window_secs.is_some_and(|window| gap_secs > window) {
+            metrics.redeliveries += 1;
+        } else {
+            metrics.duplicates += 1;
+        }
+    }
+
+    /// Like [`Comparator::add`], also recording which of the endpoint's parallel connections saw it.
+    pub fn add_on_connection(&mut self, from: String, connection: Option<usize>, data: TransactionData) {
+        let Some(connection) = connection else {
+            return self.add(from, data);
+        };
+        let signature = data.signature.clone();
+        let timestamp = data.precise_timestamp();
+        // Sibling connections seeing the same signature is the point of the pool, not a repeat
+        self.insert(from.clone(), data, false);
+        // Signatures the drain or the sampler turned away are not compared at all
+        if self.data.get(&signature).is_some_and(|observations| observations.contains_key(&from)) {
+            let seen = self.pool_sightings.entry(from.clone()).or_default().entry(signature).or_default();
+            // A reconnected connection may deliver a signature again; its first sighting counts
+            let previous = seen
+                .iter()
+                .find(|(seen_on, _)| *seen_on == connection)
+                .map(|(_, previous)| *previous);
+            match previous {
+                Some(previous) => self.record_repeat(&from, (timestamp - previous).abs()),
+                None => seen.push((connection, timestamp)),
+            }
+        }
+    }
+
+    /// Tags a signature with its market; the first endpoint to decode it wins, as the tag is the same.
+    pub fn tag_market(&mut self, signature: &str, tag: MarketTag) {
+        if self.data.contains_key(signature) && !self.markets.contains_key(signature) {
+            self.markets.insert(signature.to_string(), tag);
+        }
+    }
+
+    // AIDEV-NOTE: Only the first sighting of a live signature is tagged, so a hot account's
+    // signatures seen by every endpoint count once and pre-run backlog never fills a target.
+    /// Counts a signature towards each targeted account among its account keys.
+    pub fn tag_accounts(&mut self, signature: &str, account_keys: &[String]) {
+        if self.account_targets.is_empty() || self.signature_accounts.contains_key(signature) {
+            return;
+        }
+        if !self.data.get(signature).is_some_and(|sig_data| self.is_live(sig_data)) {
+            return;
+        }
+        let touched: Vec<String> = self
+            .account_targets
+            .keys()
+            .filter(|account| account_keys.contains(account))
+            .cloned()
+            .collect();
+        if touched.is_empty() {
+            return;
+        }
+        for account in &touched {
+            *self.account_counts.entry(account.clone()).or_default() += 1;
+        }
+        self.signature_accounts.insert(signature.to_string(), touched);
+    }
+
+    /// Whether the run has enough signatures: every account's own target with `account_targets`,
+    /// otherwise `worker_count` in total.
+    pub fn target_reached(&self) -> bool {
+        if self.account_targets.is_empty() {
+            return self.get_valid_count() == self.worker_count;
+        }
+        self.account_targets
+            .iter()
+            .all(|(account, target)| self.account_counts.get(account).is_some_and(|count| count >= target))
+    }
+
+    // AIDEV-NOTE: `compare` is false for endpoints slicing account data differently from the run-wide
+    // `accounts_data_slice`, whose payloads would otherwise all show up as integrity mismatches.
+    /// Records an account update's payload for size statistics, data slice savings and
+    /// cross-endpoint integrity checks.
+    pub fn add_account_write(
+        &mut self,
+        from: &str,
+        pubkey: String,
+        write_version: u64,
+        state: AccountState<'_>,
+        compare: bool,
+    ) {
+        self.endpoint_metrics
+            .entry(from.to_string())
+            .or_default()
+            .account_sizes
+            .get_or_insert_with(AccountSizeStats::default)
+            .record(state.data.len());
+
+        if state.sliced {
+            let accounts = self.sliced_account_updates.entry(from.to_string()).or_default();
+            if accounts.contains_key(&pubkey) || accounts.len() < MAX_TRACKED_ACCOUNT_WRITES {
+                let (updates, bytes) = accounts.entry(pubkey.clone()).or_default();
+                *updates += 1;
+                *bytes += state.data.len() as u64;
+            }
+        } else if self.account_full_sizes.contains_key(&pubkey) || self.account_full_sizes.len() < MAX_TRACKED_ACCOUNT_WRITES {
+            self.account_full_sizes.insert(pubkey.clone(), state.data.len());
+        }
+        if !compare {
+            return;
+        }
+
+        let key = (pubkey, write_version);
+        if !self.account_writes.contains_key(&key) && self.account_writes.len() >= MAX_TRACKED_ACCOUNT_WRITES {
+            return;
+        }
+        let mut hasher = DefaultHasher::new();
+        state.data.hash(&mut hasher);
+        let hash = hasher.finish();
+        let mut hasher = DefaultHasher::new();
+        (state.lamports, state.owner, state.executable, state.rent_epoch).hash(&mut hasher);
+        self.account_writes.entry(key).or_default().insert(
+            from.to_string(),
+            AccountWrite {
+                len: state.data.len(),
+                hash,
+                meta_hash: hasher.finish(),
+            },
+        );
+    }
+
     pub fn get_valid_count(&self) -> usize {
         self.data.len()
     }
+
+    /// Whether a signature was first seen before every endpoint had joined the run.
+    pub fn before_overlap(&self, sig_data: &HashMap<String, TransactionData>) -> bool {
+        sig_data.values().any(|tx| tx.timestamp < self.measure_from)
+    }
+
+    /// Whether a signature counts towards the statistics: live and seen after every endpoint joined.
+    pub fn is_live(&self, sig_data: &HashMap<String, TransactionData>) -> bool {
+        !sig_data.values().any(|tx| tx.timestamp < tx.start_time) && !self.before_overlap(sig_data)
+    }
+
+    /// Live signatures another endpoint delivered and `endpoint` never did, with their first
+    /// sighting time, earliest first.
+    pub fn missed_by(&self, endpoint: &str) -> Vec<(&str, f64)> {
+        let mut missed: Vec<(&str, f64)> = self
+            .data
+            .iter()
+            .filter(|(_, sig_data)| !sig_data.contains_key(endpoint) && self.is_live(sig_data))
+            .map(|(signature, sig_data)| {
+                let first = sig_data
+                    .values()
+                    .map(|tx| tx.precise_timestamp())
+                    .fold(f64::INFINITY, f64::min);
+                (signature.as_str(), first)
+            })
+            .collect();
+        missed.sort_by(|a, b| a.1.partial_cmp(&b.1).unwrap());
+        missed
+    }
+
+    /// Status comparison for every endpoint pair that both reported meta for a live signature.
+    pub fn status_checks(&self) -> Vec<StatusCheck> {
+        let mut checks = Vec::new();
+        for (signature, sig_data) in &self.data {
+            if !self.is_live(sig_data) {
+                continue;
+            }
+            let mut reported: Vec<(&String, &TransactionStatus)> = sig_data
+                .iter()
+                .filter_map(|(endpoint, tx)| Some((endpoint, tx.status.as_ref()?)))
+                .collect();
+            reported.sort_by(|a, b| a.0.cmp(b.0));
+
+            for (i, (first, first_status)) in reported.iter().enumerate() {
+                for (second, second_status) in &reported[i + 1..] {
+                    checks.push(StatusCheck {
+                        signature: signature.clone(),
+                        first: (*first).clone(),
+                        second: (*second).clone(),
+                        agrees: first_status.agrees_with(second_status),
+                        first_status: (*first_status).clone(),
+                        second_status: (*second_status).clone(),
+                    });
+                }
+            }
+        }
+        checks
+    }
+
+    // AIDEV-NOTE: Ties follow `tie_policy` like the win counts: excluded signatures are left out,
+    // otherwise every endpoint sharing the first timestamp is named and the margin is measured to
+    // the first endpoint strictly behind them. Names are sorted so a tie never depends on map order.
+    /// Winner and margin to second place for every live signature seen by 2+ endpoints.
+    pub fn races(&self, tie_policy: TiePolicy) -> Vec<RaceResult> {
+        let mut races = Vec::new();
+        for (signature, sig_data) in &self.data {
+            if sig_data.len() < 2 || !self.is_live(sig_data) {
+                continue;
+            }
+
+            let mut ordered: Vec<(&String, f64)> = sig_data
+                .iter()
+                .map(|(endpoint, tx)| (endpoint, tx.precise_timestamp()))
+                .collect();
+            ordered.sort_by(|a, b| a.1.total_cmp(&b.1).then_with(|| a.0.cmp(b.0)));
+
+            let first = ordered[0].1;
+            let winners = ordered.iter().take_while(|(_, timestamp)| *timestamp == first).count();
+            if winners > 1 && tie_policy == TiePolicy::Exclude {
+                continue;
+            }
+            let runner_up = ordered.get(winners);
+            races.push(RaceResult {
+                signature: signature.clone(),
+                winner: ordered[0].0.clone(),
+                tied_with: ordered[1..winners].iter().map(|(endpoint, _)| (*endpoint).clone()).collect(),
+                runner_up: runner_up.map(|(endpoint, _)| (*endpoint).clone()),
+                margin_ms: runner_up.map_or(0.0, |(_, timestamp)| (timestamp - first) * 1000.0),
+                endpoints: ordered.len(),
+            });
+        }
+        races.sort_by(|a, b| b.margin_ms.total_cmp(&a.margin_ms));
+        races
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RaceResult {
+    pub signature: String,
+    pub winner: String,
+    /// Endpoints that saw the signature at the same instant as `winner`
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tied_with: Vec<String>,
+    /// First endpoint behind the winners; None when every endpoint tied
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub runner_up: Option<String>,
+    pub margin_ms: f64,
+    pub endpoints: usize,
+}
+
+#[derive(Debug, Clone)]
+pub struct StatusCheck {
+    pub signature: String,
+    pub first: String,
+    pub second: String,
+    pub agrees: bool,
+    pub first_status: TransactionStatus,
+    pub second_status: TransactionStatus,
 }
 
 pub fn get_current_timestamp() -> f64 {
@@ -62,12 +787,148 @@ pub fn percentile(sorted_data: &[f64], p: f64) -> f64 {
     sorted_data[index]
 }
 
-pub fn open_log_file(name: &str) -> std::io::Result<impl Write> {
-    let log_filename = format!("transaction_log_{}.txt", name);
-    OpenOptions::new()
-        .create(true)
-        .append(true)
-        .open(log_filename)
+/// Mean of `sorted_data` after dropping `fraction` of the samples from each tail.
+pub fn trimmed_mean(sorted_data: &[f64], fraction: f64) -> f64 {
+    if sorted_data.is_empty() {
+        return 0.0;
+    }
+    let trim = ((sorted_data.len() as f64 * fraction).floor() as usize)
+        .min((sorted_data.len() - 1) / 2);
+    let kept = &sorted_data[trim..sorted_data.len() - trim];
+    kept.iter().sum::<f64>() / kept.len() as f64
+}
+
+pub fn median_absolute_deviation(sorted_data: &[f64]) -> f64 {
+    if sorted_data.is_empty() {
+        return 0.0;
+    }
+    let median = percentile(sorted_data, 0.5);
+    let mut deviations: Vec<f64> = sorted_data.iter().map(|v| (v - median).abs()).collect();
+    deviations.sort_by(|a, b| a.partial_cmp(b).unwrap());
+    percentile(&deviations, 0.5)
+}
+
+/// Two-sided level used for pair significance tests and confidence intervals
+pub const SIGNIFICANCE_LEVEL: f64 = 0.05;
+// Without ties, the exact null distribution is cheap up to here; above it the normal approximation is close
+const WILCOXON_EXACT_MAX_SAMPLES: usize = 50;
+
+// AIDEV-NOTE: Zero differences are dropped (Wilcoxon's method), so ties between endpoints neither
+// help nor hurt either side. Exact p-values need untied ranks; with ties or many samples the normal
+// approximation with tie and continuity corrections is used.
+/// Two-sided Wilcoxon signed-rank p-value for paired differences having a zero median; None when all are zero.
+pub fn wilcoxon_signed_rank(differences: &[f64]) -> Option<f64> {
+    let mut nonzero: Vec<f64> = differences.iter().copied().filter(|difference| *difference != 0.0).collect();
+    let n = nonzero.len();
+    if n == 0 {
+        return None;
+    }
+    nonzero.sort_by(|a, b| a.abs().partial_cmp(&b.abs()).unwrap());
+
+    let mut w_plus = 0.0;
+    let mut tie_correction = 0.0;
+    let mut start = 0;
+    while start < n {
+        let mut end = start;
+        while end + 1 < n && nonzero[end + 1].abs() == nonzero[start].abs() {
+            end += 1;
+        }
+        // Tied magnitudes share the average of their ranks
+        let rank = (start + end) as f64 / 2.0 + 1.0;
+        w_plus += rank * nonzero[start..=end].iter().filter(|difference| **difference > 0.0).count() as f64;
+        let tied = (end - start + 1) as f64;
+        tie_correction += tied.powi(3) - tied;
+        start = end + 1;
+    }
+
+    if tie_correction == 0.0 && n <= WILCOXON_EXACT_MAX_SAMPLES {
+        return Some(wilcoxon_exact_p_value(n, w_plus.round() as usize));
+    }
+    let n = n as f64;
+    let mean = n * (n + 1.0) / 4.0;
+    let variance = n * (n + 1.0) * (2.0 * n + 1.0) / 24.0 - tie_correction / 48.0;
+    if variance <= 0.0 {
+        return Some(1.0);
+    }
+    let z = ((w_plus - mean).abs() - 0.5).max(0.0) / variance.sqrt();
+    Some((2.0 * (1.0 - normal_cdf(z))).min(1.0))
+}
+
+fn wilcoxon_exact_p_value(n: usize, w_plus: usize) -> f64 {
+    let max_sum = n * (n + 1) / 2;
+    // Number of subsets of the ranks 1..=n summing to each value, i.e. the null distribution of W+
+    let mut counts = vec![0.0f64; max_sum + 1];
+    counts[0] = 1.0;
+    for rank in 1..=n {
+        for sum in (rank..=max_sum).rev() {
+            counts[sum] += counts[sum - rank];
+        }
+    }
+    let total = 2f64.powi(n as i32);
+    let lower = counts[..=w_plus].iter().sum::<f64>() / total;
+    let upper = counts[w_plus..].iter().sum::<f64>() / total;
+    (2.0 * lower.min(upper)).min(1.0)
+}
+
+/// Standard normal CDF, via the Abramowitz-Stegun 7.1.26 erfc approximation (error below 1.5e-7).
+fn normal_cdf(z: f64) -> f64 {
+    let x = z.abs() / std::f64::consts::SQRT_2;
+    let t = 1.0 / (1.0 + 0.327_591_1 * x);
+    let poly = t * (0.254_829_592 + t * (-0.284_496_736 + t * (1.421_413_741 + t * (-1.453_152_027 + t * 1.061_405_429))));
+    let upper_tail = 0.5 * poly * (-x * x).exp();
+    if z >= 0.0 {
+        1.0 - upper_tail
+    } else {
+        upper_tail
+    }
+}
+
+/// Distribution-free confidence interval at [`SIGNIFICANCE_LEVEL`] for the median of sorted samples,
+/// from binomial order statistics; None below 6 samples, where no such interval exists.
+pub fn median_confidence_interval(sorted_data: &[f64]) -> Option<(f64, f64)> {
+    let n = sorted_data.len();
+    // Largest k with P(Binomial(n, 1/2) < k) <= level / 2; the interval is the k-th smallest to the
+    // k-th largest sample. The pmf is stepped in log space so large n does not underflow.
+    let mut ln_pmf = -(n as f64) * std::f64::consts::LN_2;
+    let mut cumulative = 0.0;
+    let mut k = 0;
+    while k < n {
+        cumulative += ln_pmf.exp();
+        if cumulative > SIGNIFICANCE_LEVEL / 2.0 {
+            break;
+        }
+        k += 1;
+        ln_pmf += ((n - k + 1) as f64).ln() - (k as f64).ln();
+    }
+    (k > 0).then(|| (sorted_data[k - 1], sorted_data[n - k]))
+}
+
+pub const LOG_FILE_PREFIX: &str = "transaction_log_";
+pub const LOG_FILE_SUFFIX: &str = ".txt";
+
+/// One line written by [`write_log_entry`].
+#[derive(Debug, Clone)]
+pub struct LogEntry {
+    pub timestamp: f64,
+    pub endpoint: String,
+    pub signature: String,
+    pub tsc: Option<u64>,
+}
+
+/// Parses `[1700000000.123] [endpoint] signature` with an optional trailing `[tsc:ticks]`.
+pub fn parse_log_entry(line: &str) -> Option<LogEntry> {
+    let (timestamp, rest) = line.strip_prefix('[')?.split_once("] [")?;
+    let (endpoint, rest) = rest.split_once("] ")?;
+    let (signature, tsc) = match rest.split_once(" [tsc:") {
+        Some((signature, ticks)) => (signature, Some(ticks.strip_suffix(']')?.parse().ok()?)),
+        None => (rest, None),
+    };
+    Some(LogEntry {
+        timestamp: timestamp.parse().ok()?,
+        endpoint: endpoint.to_string(),
+        signature: signature.trim().to_string(),
+        tsc,
+    })
 }
 
 pub fn write_log_entry(
@@ -75,12 +936,197 @@ pub fn write_log_entry(
     timestamp: f64,
     endpoint_name: &str,
     signature: &str,
+    tsc: Option<u64>,
 ) -> std::io::Result<()> {
-    let log_entry = format!(
-        "[{:.3}] [{}] {}\n",
-        timestamp,
-        endpoint_name,
-        signature
-    );
+    let log_entry = match tsc {
+        Some(ticks) => format!(
+            "[{:.3}] [{}] {} [tsc:{}]\n",
+            timestamp,
+            endpoint_name,
+            signature,
+            ticks
+        ),
+        None => format!(
+            "[{:.3}] [{}] {}\n",
+            timestamp,
+            endpoint_name,
+            signature
+        ),
+    };
     file.write_all(log_entry.as_bytes())
-}
\ No newline at end of file
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::clock::MockClock;
+
+    fn sighting(signature: &str, timestamp: f64) -> TransactionData {
+        TransactionData {
+            timestamp,
+            signature: signature.to_string(),
+            ..Default::default()
+        }
+    }
+
+    fn comparator(clock: &MockClock) -> Comparator {
+        Comparator::new(100).with_clock(Arc::new(clock.clone()))
+    }
+
+    #[test]
+    fn signatures_before_measure_from_are_not_live() {
+        let clock = MockClock::new(1000.0);
+        let mut comparator = comparator(&clock);
+        comparator.measure_from = 1005.0;
+        comparator.add("a".to_string(), sighting("early", 1004.9));
+        comparator.add("b".to_string(), sighting("early", 1006.0));
+        comparator.add("a".to_string(), sighting("late", 1005.0));
+        assert!(!comparator.is_live(&comparator.data["early"]));
+        assert!(comparator.is_live(&comparator.data["late"]));
+    }
+
+    #[test]
+    fn joining_endpoint_moves_measure_from_to_now() {
+        let clock = MockClock::new(1000.0);
+        let mut comparator = comparator(&clock);
+        comparator.measure_from = 1002.0;
+        comparator.add("a".to_string(), sighting("before", 1010.0));
+        clock.set(1020.0);
+        comparator.apply_membership(1, &["b".to_string()], &[]);
+        assert_eq!(comparator.measure_from, 1020.0);
+        comparator.add("b".to_string(), sighting("before", 1021.0));
+        comparator.add("a".to_string(), sighting("after", 1021.0));
+        assert!(!comparator.is_live(&comparator.data["before"]));
+        assert!(comparator.is_live(&comparator.data["after"]));
+
+        // A join never moves the window back
+        clock.set(1015.0);
+        comparator.apply_membership(2, &["c".to_string()], &[]);
+        assert_eq!(comparator.measure_from, 1020.0);
+        assert_eq!(comparator.membership.last().unwrap().at, 1015.0);
+    }
+
+    #[test]
+    fn leaving_endpoint_is_dropped_and_refused() {
+        let clock = MockClock::new(1000.0);
+        let mut comparator = comparator(&clock);
+        comparator.add("a".to_string(), sighting("shared", 1001.0));
+        comparator.add("b".to_string(), sighting("shared", 1002.0));
+        comparator.add("b".to_string(), sighting("only_b", 1003.0));
+        clock.set(1010.0);
+        comparator.apply_membership(1, &[], &["b".to_string()]);
+        assert_eq!(comparator.measure_from, 0.0, "only joins move the window");
+        assert!(!comparator.data.contains_key("only_b"));
+        assert!(!comparator.data["shared"].contains_key("b"));
+
+        comparator.add("b".to_string(), sighting("new", 1011.0));
+        assert!(!comparator.data.contains_key("new"));
+
+        let change = &comparator.membership[0];
+        assert_eq!((change.generation, change.endpoint.as_str(), change.joined, change.at), (1, "b", false, 1010.0));
+    }
+
+    #[test]
+    fn repeats_within_the_dedup_window_are_duplicates() {
+        let clock = MockClock::new(1000.0);
+        let mut comparator = comparator(&clock).with_dedup_window(Some(2.0));
+        comparator.add("a".to_string(), sighting("sig", 1000.0));
+        comparator.add("a".to_string(), sighting("sig", 1001.5));
+        comparator.add("a".to_string(), sighting("sig", 1003.0));
+        let metrics = &comparator.endpoint_metrics["a"];
+        assert_eq!((metrics.duplicates, metrics.redeliveries), (1, 1));
+        assert_eq!(comparator.data["sig"]["a"].timestamp, 1000.0, "the earliest sighting is kept");
+    }
+
+    #[test]
+    fn without_dedup_window_every_repeat_is_a_duplicate() {
+        let clock = MockClock::new(1000.0);
+        let mut comparator = comparator(&clock);
+        comparator.add("a".to_string(), sighting("sig", 1000.0));
+        comparator.add("a".to_string(), sighting("sig", 1100.0));
+        let metrics = &comparator.endpoint_metrics["a"];
+        assert_eq!((metrics.duplicates, metrics.redeliveries), (1, 0));
+    }
+
+    #[test]
+    fn draining_accepts_only_known_signatures() {
+        let clock = MockClock::new(1000.0);
+        let mut comparator = comparator(&clock);
+        comparator.add("a".to_string(), sighting("known", 1000.0));
+        comparator.begin_drain();
+        comparator.add("b".to_string(), sighting("known", 1001.0));
+        comparator.add("b".to_string(), sighting("unknown", 1001.0));
+        assert!(comparator.data["known"].contains_key("b"));
+        assert!(!comparator.data.contains_key("unknown"));
+        assert_eq!(comparator.endpoint_metrics["b"].late_arrivals, 1);
+    }
+
+    fn assert_close(actual: f64, expected: f64) {
+        assert!((actual - expected).abs() < 1e-6, "{} != {}", actual, expected);
+    }
+
+    #[test]
+    fn wilcoxon_exact_p_values_match_the_table() {
+        // (differences, two-sided p) with W+ at the tabulated 0.05 critical values
+        let cases: [(&[f64], f64); 6] = [
+            (&[1.0, 2.0, 3.0, 4.0, 5.0], 0.0625),
+            (&[1.0, 2.0, 3.0, 4.0, 5.0, 6.0], 0.03125),
+            (&[-1.0, -2.0, -3.0, -4.0, -5.0, -6.0], 0.03125),
+            (&[1.0, 2.0, -3.0, -4.0, -5.0, -6.0, -7.0, -8.0], 0.0390625),
+            (&[1.0, -2.0, 3.0, 4.0, -5.0, -6.0, -7.0, -8.0, -9.0, -10.0], 0.048828125),
+            (&[1.0, 2.0, -3.0, 4.0, -5.0, 6.0, -7.0, -8.0, -9.0, -10.0, -11.0, -12.0], 0.04248046875),
+        ];
+        for (differences, expected) in cases {
+            assert_close(wilcoxon_signed_rank(differences).unwrap(), expected);
+        }
+    }
+
+    #[test]
+    fn wilcoxon_balanced_differences_are_not_significant() {
+        // W+ = 27, next to the mean of 27.5
+        let differences = [1.0, 2.0, 3.0, 4.0, -5.0, -6.0, 7.0, -8.0, -9.0, 10.0];
+        assert_close(wilcoxon_signed_rank(&differences).unwrap(), 1.0);
+    }
+
+    #[test]
+    fn wilcoxon_drops_zero_differences() {
+        assert_eq!(wilcoxon_signed_rank(&[0.0, 0.0]), None);
+        assert_eq!(wilcoxon_signed_rank(&[]), None);
+        let with_zeros = [0.0, 1.0, 0.0, 2.0, 3.0, 4.0, 5.0, 6.0];
+        assert_close(wilcoxon_signed_rank(&with_zeros).unwrap(), 0.03125);
+    }
+
+    #[test]
+    fn wilcoxon_tied_magnitudes_use_the_corrected_normal_approximation() {
+        // Ranks 1.5, 1.5, 3.5, 3.5, 5, 6, 7, 8: W+ = 27.5, tie correction 12, variance 50.75
+        let differences = [1.0, -1.0, 2.0, 2.0, 3.0, 4.0, -5.0, 6.0];
+        assert_close(wilcoxon_signed_rank(&differences).unwrap(), 0.206_462_587);
+    }
+
+    #[test]
+    fn median_confidence_interval_uses_binomial_ranks() {
+        let samples = |n: usize| (1..=n).map(|value| value as f64).collect::<Vec<f64>>();
+        assert_eq!(median_confidence_interval(&samples(5)), None);
+        // k = 1, 2 and 40: the k-th smallest to the k-th largest sample
+        assert_eq!(median_confidence_interval(&samples(6)), Some((1.0, 6.0)));
+        assert_eq!(median_confidence_interval(&samples(10)), Some((2.0, 9.0)));
+        assert_eq!(median_confidence_interval(&samples(100)), Some((40.0, 61.0)));
+    }
+
+    #[test]
+    fn trimmed_mean_drops_each_tail() {
+        let samples = [1.0, 2.0, 3.0, 4.0, 100.0];
+        assert_eq!(trimmed_mean(&samples, 0.0), 22.0);
+        assert_eq!(trimmed_mean(&samples, 0.2), 3.0);
+        // Never trims past the median
+        assert_eq!(trimmed_mean(&samples, 0.5), 3.0);
+        assert_eq!(trimmed_mean(&[1.0, 2.0], 0.5), 1.5);
+        assert_eq!(trimmed_mean(&[], 0.1), 0.0);
+    }
+
+    #[test]
+    fn median_absolute_deviation_ignores_outliers() {
+        assert_eq!(median_absolute_deviation(&[1.0, 2.0, 3.0, 4.0, 100.0]), 1.0);
+        assert_eq!(median_absolute_deviation(&[5.0, 5.0, 5.0]), 0.0);
+        assert_eq!(median_absolute_deviation(&[]), 0.0);
+    }
+}